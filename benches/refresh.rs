@@ -0,0 +1,97 @@
+//! Per-tick cost of refreshing metrics under different refresh plans.
+//!
+//! Run with `cargo bench --bench refresh`. Every scenario performs the same
+//! number of ticks at the same cadence, so collectors with a longer
+//! interval than the cadence are skipped on some ticks just like in the
+//! TUI. Only the time spent inside the refresh itself is measured.
+//!
+//! The `refresh_all` scenario reproduces the previous behaviour of
+//! rescanning everything on every tick and serves as the baseline.
+
+use std::time::{Duration, Instant};
+
+use sysinfo::{System, SystemExt};
+use system_monitor::{App, Collector, RefreshPlan, SysinfoSource};
+
+/// Number of measured ticks per scenario.
+const TICKS: u32 = 12;
+
+/// Time between two ticks, as with a fast TUI sample interval.
+const TICK_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Timing summary of one scenario.
+struct Measurement {
+    name: &'static str,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+}
+
+impl Measurement {
+    fn mean(&self) -> Duration {
+        self.total / TICKS
+    }
+}
+
+/// Runs `tick` `TICKS` times at `TICK_INTERVAL` and times each call.
+fn measure(name: &'static str, mut tick: impl FnMut()) -> Measurement {
+    let mut measurement = Measurement {
+        name,
+        total: Duration::ZERO,
+        min: Duration::MAX,
+        max: Duration::ZERO,
+    };
+    for _ in 0..TICKS {
+        std::thread::sleep(TICK_INTERVAL);
+        let start = Instant::now();
+        tick();
+        let elapsed = start.elapsed();
+        measurement.total += elapsed;
+        measurement.min = measurement.min.min(elapsed);
+        measurement.max = measurement.max.max(elapsed);
+    }
+    measurement
+}
+
+/// Measures `App::update` backed by a sysinfo source following `plan`.
+fn measure_plan(name: &'static str, plan: RefreshPlan) -> Measurement {
+    let mut app = App::with_source(SysinfoSource::with_plan(plan), 300);
+    measure(name, || {
+        app.update();
+    })
+}
+
+fn main() {
+    let mut system = System::new_all();
+    system.refresh_all();
+
+    let measurements = [
+        measure("refresh_all (baseline)", || system.refresh_all()),
+        measure_plan("every collector, every tick", RefreshPlan::every_tick()),
+        measure_plan("default plan", RefreshPlan::default()),
+        measure_plan(
+            "cpu + memory only",
+            RefreshPlan::none()
+                .with_interval(Collector::Cpu, Duration::ZERO)
+                .with_interval(Collector::Memory, Duration::ZERO),
+        ),
+    ];
+
+    println!("{} ticks every {:?}, time per tick:", TICKS, TICK_INTERVAL);
+    println!(
+        "{:<30} {:>12} {:>12} {:>12}",
+        "scenario", "mean", "min", "max"
+    );
+    let baseline = measurements[0].mean().as_secs_f64();
+    for measurement in &measurements {
+        let mean = measurement.mean();
+        println!(
+            "{:<30} {:>12?} {:>12?} {:>12?}  ({:.0}% of baseline)",
+            measurement.name,
+            mean,
+            measurement.min,
+            measurement.max,
+            mean.as_secs_f64() / baseline * 100.0
+        );
+    }
+}
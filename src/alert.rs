@@ -0,0 +1,820 @@
+//! Threshold alerting.
+//!
+//! An [`AlertRule`] such as `cpu > 90% for 30s` is evaluated against every
+//! snapshot by an [`AlertEngine`]. Each rule moves through these states:
+//!
+//! * **Inactive** - the condition does not hold
+//! * **Pending** - the condition holds, but not yet for the rule's duration
+//! * **Firing** - the condition has held for the whole duration
+//! * **Resolved** - the value dropped back past the threshold by more than
+//!   the rule's hysteresis margin; the next snapshot starts over
+//!
+//! A firing alert only resolves once the value moves past the threshold by
+//! the hysteresis margin, so a value hovering around the threshold does not
+//! make the alert flap. Time is taken from the snapshot timestamps rather
+//! than the wall clock, so rules evaluate identically on live and recorded
+//! data.
+//!
+//! # Example
+//! ```
+//! use std::time::{Duration, UNIX_EPOCH};
+//! use system_monitor::alert::{AlertEngine, AlertState, Transition};
+//! use system_monitor::{CoreUsage, MetricsSnapshot};
+//!
+//! let snapshot = |seconds, usage| MetricsSnapshot {
+//!     timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+//!     cores: vec![CoreUsage { id: 0, name: "cpu0".to_string(), usage }],
+//!     ..MetricsSnapshot::default()
+//! };
+//!
+//! let mut engine = AlertEngine::new(vec!["cpu > 90% for 30s".parse().unwrap()]);
+//! let recorded = [snapshot(0, 95.0), snapshot(15, 97.0), snapshot(30, 99.0)];
+//! let events = engine.evaluate_all(&recorded);
+//! let transitions: Vec<_> = events.iter().map(|event| event.transition).collect();
+//! assert_eq!(transitions, vec![Transition::Pending, Transition::Firing]);
+//! assert!(matches!(engine.alerts()[0].state, AlertState::Firing { .. }));
+//! ```
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::pressure::Resource;
+use crate::refresh::Collector;
+use crate::snapshot::{Metric, MetricsSnapshot};
+
+/// Default margin, in percentage points, by which a firing value must drop
+/// back past the threshold before the alert resolves.
+pub const DEFAULT_HYSTERESIS: f32 = 5.0;
+
+/// Value an alert rule watches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AlertMetric {
+    /// Average CPU usage across all cores
+    Cpu,
+    /// Physical memory usage
+    Memory,
+    /// Swap usage
+    Swap,
+    /// Usage of the fullest filesystem, pseudo filesystems excluded
+    Disk,
+    /// Share of the last 10 seconds in which tasks waited for a CPU
+    CpuPressure,
+    /// Share of the last 10 seconds in which tasks waited for memory
+    MemoryPressure,
+    /// Share of the last 10 seconds in which tasks waited for I/O
+    IoPressure,
+}
+
+impl AlertMetric {
+    /// All alert metrics.
+    pub const ALL: [AlertMetric; 7] = [
+        AlertMetric::Cpu,
+        AlertMetric::Memory,
+        AlertMetric::Swap,
+        AlertMetric::Disk,
+        AlertMetric::CpuPressure,
+        AlertMetric::MemoryPressure,
+        AlertMetric::IoPressure,
+    ];
+
+    /// Returns the lowercase identifier used in rules.
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertMetric::Cpu => "cpu",
+            AlertMetric::Memory => "memory",
+            AlertMetric::Swap => "swap",
+            AlertMetric::Disk => "disk",
+            AlertMetric::CpuPressure => "cpu_pressure",
+            AlertMetric::MemoryPressure => "memory_pressure",
+            AlertMetric::IoPressure => "io_pressure",
+        }
+    }
+
+    /// Returns the charted metric this alert metric corresponds to, if any.
+    pub fn metric(self) -> Option<Metric> {
+        match self {
+            AlertMetric::Cpu => Some(Metric::Cpu),
+            AlertMetric::Memory => Some(Metric::Memory),
+            AlertMetric::Swap => Some(Metric::Swap),
+            AlertMetric::Disk
+            | AlertMetric::CpuPressure
+            | AlertMetric::MemoryPressure
+            | AlertMetric::IoPressure => None,
+        }
+    }
+
+    /// Returns the pressure resource this alert metric watches, if any.
+    pub fn resource(self) -> Option<Resource> {
+        match self {
+            AlertMetric::CpuPressure => Some(Resource::Cpu),
+            AlertMetric::MemoryPressure => Some(Resource::Memory),
+            AlertMetric::IoPressure => Some(Resource::Io),
+            _ => None,
+        }
+    }
+
+    /// Returns the collector that refreshes this metric.
+    ///
+    /// Pressure is read along with the CPU and memory collectors.
+    pub fn collector(self) -> Collector {
+        match self {
+            AlertMetric::Cpu | AlertMetric::CpuPressure | AlertMetric::IoPressure => Collector::Cpu,
+            AlertMetric::Memory | AlertMetric::Swap | AlertMetric::MemoryPressure => {
+                Collector::Memory
+            }
+            AlertMetric::Disk => Collector::Disks,
+        }
+    }
+
+    /// Reads the value from a snapshot.
+    ///
+    /// # Returns
+    /// * `Option<f32>` - The value as a percentage, or `None` if the host
+    ///   does not report it
+    pub fn value(self, snapshot: &MetricsSnapshot) -> Option<f32> {
+        match self {
+            AlertMetric::Cpu => Metric::Cpu.value(snapshot),
+            AlertMetric::Memory => Metric::Memory.value(snapshot),
+            AlertMetric::Swap => Metric::Swap.value(snapshot),
+            AlertMetric::Disk => snapshot
+                .disks
+                .iter()
+                .filter(|disk| !disk.is_pseudo() && disk.total > 0)
+                .map(|disk| disk.used_percent())
+                .reduce(f32::max),
+            AlertMetric::CpuPressure | AlertMetric::MemoryPressure | AlertMetric::IoPressure => {
+                let resource = self.resource()?;
+                Some(snapshot.pressure?.get(resource)?.some.avg10)
+            }
+        }
+    }
+}
+
+impl FromStr for AlertMetric {
+    type Err = AlertRuleError;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        AlertMetric::ALL
+            .into_iter()
+            .find(|metric| metric.name().eq_ignore_ascii_case(name))
+            .ok_or_else(|| AlertRuleError::UnknownMetric(name.to_string()))
+    }
+}
+
+/// Direction in which a value crosses the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Comparison {
+    /// The condition holds while the value is above the threshold
+    Above,
+    /// The condition holds while the value is below the threshold
+    Below,
+}
+
+impl Comparison {
+    /// Returns the operator used in rules, `>` or `<`.
+    pub fn symbol(self) -> &'static str {
+        match self {
+            Comparison::Above => ">",
+            Comparison::Below => "<",
+        }
+    }
+}
+
+/// Condition on a metric that raises an alert.
+///
+/// Rules are usually parsed from text of the form
+/// `METRIC (>|<) THRESHOLD[%] [for DURATION]`, where `METRIC` is one of
+/// `cpu`, `memory`, `swap`, `disk`, `cpu_pressure`, `memory_pressure` or
+/// `io_pressure` and `DURATION` is a number of seconds with an optional
+/// `s`, `m` or `h` suffix. The text is also the rule's default name.
+///
+/// # Fields
+/// * `name` - Name shown in the UI and in notifications
+/// * `metric` - Watched value
+/// * `comparison` - Whether the alert is raised above or below the
+///   threshold
+/// * `threshold` - Percentage at which the condition holds
+/// * `duration` - How long the condition must hold before firing; zero
+///   fires on the first matching snapshot
+/// * `hysteresis` - Percentage points the value must move back past the
+///   threshold before a firing alert resolves
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use system_monitor::alert::{AlertMetric, AlertRule, Comparison};
+///
+/// let rule: AlertRule = "memory > 85% for 2m".parse().unwrap();
+/// assert_eq!(rule.metric, AlertMetric::Memory);
+/// assert_eq!(rule.comparison, Comparison::Above);
+/// assert_eq!(rule.threshold, 85.0);
+/// assert_eq!(rule.duration, Duration::from_secs(120));
+/// assert_eq!(rule.name, "memory > 85% for 2m");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertRule {
+    pub name: String,
+    pub metric: AlertMetric,
+    pub comparison: Comparison,
+    pub threshold: f32,
+    pub duration: Duration,
+    pub hysteresis: f32,
+}
+
+impl AlertRule {
+    /// Creates a rule that fires as soon as the condition holds.
+    ///
+    /// The rule is named after its condition and uses the
+    /// [`DEFAULT_HYSTERESIS`].
+    pub fn new(metric: AlertMetric, comparison: Comparison, threshold: f32) -> Self {
+        let mut rule = Self {
+            name: String::new(),
+            metric,
+            comparison,
+            threshold,
+            duration: Duration::ZERO,
+            hysteresis: DEFAULT_HYSTERESIS,
+        };
+        rule.name = rule.condition();
+        rule
+    }
+
+    /// Requires the condition to hold for `duration` before firing.
+    pub fn for_duration(mut self, duration: Duration) -> Self {
+        let default_name = self.name == self.condition();
+        self.duration = duration;
+        if default_name {
+            self.name = self.condition();
+        }
+        self
+    }
+
+    /// Sets the margin by which the value must move back before resolving.
+    pub fn with_hysteresis(mut self, hysteresis: f32) -> Self {
+        self.hysteresis = hysteresis.max(0.0);
+        self
+    }
+
+    /// Renames the rule.
+    pub fn named(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Returns the rule's condition in rule syntax, e.g. `cpu > 90% for 30s`.
+    pub fn condition(&self) -> String {
+        let mut condition = format!(
+            "{} {} {}%",
+            self.metric.name(),
+            self.comparison.symbol(),
+            self.threshold
+        );
+        if !self.duration.is_zero() {
+            condition.push_str(" for ");
+            condition.push_str(&format_duration(self.duration));
+        }
+        condition
+    }
+
+    /// Returns `true` if `value` meets the condition.
+    pub fn holds(&self, value: f32) -> bool {
+        match self.comparison {
+            Comparison::Above => value > self.threshold,
+            Comparison::Below => value < self.threshold,
+        }
+    }
+
+    /// Returns `true` if `value` is back past the threshold by at least the
+    /// hysteresis margin.
+    fn cleared(&self, value: f32) -> bool {
+        match self.comparison {
+            Comparison::Above => value <= self.threshold - self.hysteresis,
+            Comparison::Below => value >= self.threshold + self.hysteresis,
+        }
+    }
+}
+
+impl FromStr for AlertRule {
+    type Err = AlertRuleError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let text = text.trim();
+        let (condition, duration) = match text.split_once(" for ") {
+            Some((condition, duration)) => (condition, Some(duration.trim())),
+            None => (text, None),
+        };
+
+        let (index, comparison) = condition
+            .char_indices()
+            .find_map(|(i, c)| match c {
+                '>' => Some((i, Comparison::Above)),
+                '<' => Some((i, Comparison::Below)),
+                _ => None,
+            })
+            .ok_or_else(|| AlertRuleError::Syntax(text.to_string()))?;
+        let metric: AlertMetric = condition[..index].trim().parse()?;
+        let threshold = condition[index + 1..].trim();
+        let threshold = threshold
+            .strip_suffix('%')
+            .unwrap_or(threshold)
+            .trim()
+            .parse::<f32>()
+            .ok()
+            .filter(|threshold| (0.0..=100.0).contains(threshold))
+            .ok_or_else(|| AlertRuleError::InvalidThreshold(threshold.to_string()))?;
+
+        let mut rule = AlertRule::new(metric, comparison, threshold);
+        if let Some(duration) = duration {
+            rule = rule.for_duration(parse_duration(duration)?);
+        }
+        Ok(rule)
+    }
+}
+
+impl fmt::Display for AlertRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// Error parsing an [`AlertRule`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum AlertRuleError {
+    /// The text is not of the form `METRIC > THRESHOLD [for DURATION]`
+    Syntax(String),
+    /// The metric is not one of the known alert metrics
+    UnknownMetric(String),
+    /// The threshold is not a percentage between 0 and 100
+    InvalidThreshold(String),
+    /// The duration is not a non-negative number of seconds, minutes or
+    /// hours
+    InvalidDuration(String),
+}
+
+impl fmt::Display for AlertRuleError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            AlertRuleError::Syntax(text) => write!(
+                f,
+                "invalid rule '{}' (expected e.g. 'cpu > 90% for 30s')",
+                text
+            ),
+            AlertRuleError::UnknownMetric(name) => write!(
+                f,
+                "unknown metric '{}' (expected cpu, memory, swap, disk, \
+                 cpu_pressure, memory_pressure or io_pressure)",
+                name
+            ),
+            AlertRuleError::InvalidThreshold(value) => {
+                write!(f, "invalid threshold '{}' (expected 0-100%)", value)
+            }
+            AlertRuleError::InvalidDuration(value) => {
+                write!(f, "invalid duration '{}' (expected e.g. 30s or 5m)", value)
+            }
+        }
+    }
+}
+
+impl Error for AlertRuleError {}
+
+/// Parses a duration such as `30s`, `1.5m`, `2h` or `45` (seconds).
+fn parse_duration(text: &str) -> Result<Duration, AlertRuleError> {
+    let (number, unit) = match text.char_indices().last() {
+        Some((i, 's')) => (&text[..i], 1.0),
+        Some((i, 'm')) => (&text[..i], 60.0),
+        Some((i, 'h')) => (&text[..i], 3600.0),
+        _ => (text, 1.0),
+    };
+    number
+        .trim()
+        .parse::<f64>()
+        .ok()
+        .and_then(|number| Duration::try_from_secs_f64(number * unit).ok())
+        .ok_or_else(|| AlertRuleError::InvalidDuration(text.to_string()))
+}
+
+/// Formats a duration in the largest unit that represents it exactly.
+fn format_duration(duration: Duration) -> String {
+    let seconds = duration.as_secs_f64();
+    if duration.subsec_nanos() == 0 && seconds >= 3600.0 && seconds % 3600.0 == 0.0 {
+        format!("{}h", seconds / 3600.0)
+    } else if duration.subsec_nanos() == 0 && seconds >= 60.0 && seconds % 60.0 == 0.0 {
+        format!("{}m", seconds / 60.0)
+    } else {
+        format!("{}s", seconds)
+    }
+}
+
+/// State of an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum AlertState {
+    /// The condition does not hold
+    #[default]
+    Inactive,
+    /// The condition has held since `since`, not yet for the rule's duration
+    Pending { since: SystemTime },
+    /// The condition has held for the rule's duration; firing since `since`
+    Firing { since: SystemTime },
+    /// The alert stopped firing at `at`
+    Resolved { at: SystemTime },
+}
+
+impl AlertState {
+    /// Returns the lowercase name of the state.
+    pub fn name(self) -> &'static str {
+        match self {
+            AlertState::Inactive => "inactive",
+            AlertState::Pending { .. } => "pending",
+            AlertState::Firing { .. } => "firing",
+            AlertState::Resolved { .. } => "resolved",
+        }
+    }
+
+    /// Returns `true` if the alert is firing.
+    pub fn is_firing(self) -> bool {
+        matches!(self, AlertState::Firing { .. })
+    }
+}
+
+/// A rule and its current state.
+///
+/// # Fields
+/// * `rule` - The evaluated rule
+/// * `state` - State after the latest evaluation
+/// * `value` - Value of the metric in the latest snapshot that reported it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Alert {
+    pub rule: AlertRule,
+    pub state: AlertState,
+    pub value: Option<f32>,
+}
+
+/// State change of an alert.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Transition {
+    /// The condition started to hold, but the rule has a duration
+    Pending,
+    /// The alert started firing
+    Firing,
+    /// The alert stopped firing
+    Resolved,
+}
+
+impl Transition {
+    /// Returns the lowercase name of the transition.
+    pub fn name(self) -> &'static str {
+        match self {
+            Transition::Pending => "pending",
+            Transition::Firing => "firing",
+            Transition::Resolved => "resolved",
+        }
+    }
+}
+
+/// Alert state change produced by an evaluation.
+///
+/// # Fields
+/// * `rule` - The rule whose alert changed state
+/// * `transition` - The state the alert entered
+/// * `value` - Value of the metric that caused the change
+/// * `timestamp` - Timestamp of the snapshot that caused the change
+#[derive(Debug, Clone, PartialEq)]
+pub struct AlertEvent {
+    pub rule: AlertRule,
+    pub transition: Transition,
+    pub value: f32,
+    pub timestamp: SystemTime,
+}
+
+/// Evaluates alert rules against successive snapshots.
+///
+/// Snapshots must be passed in timestamp order. A snapshot in which a
+/// rule's metric is unavailable leaves that rule's state unchanged.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AlertEngine {
+    alerts: Vec<Alert>,
+}
+
+impl AlertEngine {
+    /// Creates an engine with every rule inactive.
+    pub fn new(rules: Vec<AlertRule>) -> Self {
+        let alerts = rules
+            .into_iter()
+            .map(|rule| Alert {
+                rule,
+                state: AlertState::Inactive,
+                value: None,
+            })
+            .collect();
+        Self { alerts }
+    }
+
+    /// Returns every rule with its current state, in rule order.
+    pub fn alerts(&self) -> &[Alert] {
+        &self.alerts
+    }
+
+    /// Iterates over the firing alerts.
+    pub fn firing(&self) -> impl Iterator<Item = &Alert> {
+        self.alerts.iter().filter(|alert| alert.state.is_firing())
+    }
+
+    /// Returns `true` if an alert on `metric` is firing.
+    pub fn is_firing(&self, metric: AlertMetric) -> bool {
+        self.firing().any(|alert| alert.rule.metric == metric)
+    }
+
+    /// Evaluates every rule against `snapshot`.
+    ///
+    /// # Returns
+    /// * `Vec<AlertEvent>` - The state changes, in rule order
+    pub fn evaluate(&mut self, snapshot: &MetricsSnapshot) -> Vec<AlertEvent> {
+        let now = snapshot.timestamp;
+        let mut events = Vec::new();
+        for alert in &mut self.alerts {
+            let Some(value) = alert.rule.metric.value(snapshot).filter(|v| v.is_finite()) else {
+                continue;
+            };
+            alert.value = Some(value);
+            let rule = &alert.rule;
+            let (state, transition) = match alert.state {
+                AlertState::Inactive | AlertState::Resolved { .. } if rule.holds(value) => {
+                    if rule.duration.is_zero() {
+                        (AlertState::Firing { since: now }, Some(Transition::Firing))
+                    } else {
+                        (
+                            AlertState::Pending { since: now },
+                            Some(Transition::Pending),
+                        )
+                    }
+                }
+                AlertState::Inactive | AlertState::Resolved { .. } => (AlertState::Inactive, None),
+                AlertState::Pending { .. } if !rule.holds(value) => (AlertState::Inactive, None),
+                AlertState::Pending { since } => {
+                    let held = now.duration_since(since).unwrap_or_default();
+                    if held >= rule.duration {
+                        (AlertState::Firing { since: now }, Some(Transition::Firing))
+                    } else {
+                        (alert.state, None)
+                    }
+                }
+                AlertState::Firing { .. } if rule.cleared(value) => {
+                    (AlertState::Resolved { at: now }, Some(Transition::Resolved))
+                }
+                AlertState::Firing { .. } => (alert.state, None),
+            };
+            alert.state = state;
+            if let Some(transition) = transition {
+                events.push(AlertEvent {
+                    rule: rule.clone(),
+                    transition,
+                    value,
+                    timestamp: now,
+                });
+            }
+        }
+        events
+    }
+
+    /// Evaluates every rule against each of `snapshots` in turn, e.g. a
+    /// recorded session.
+    ///
+    /// # Returns
+    /// * `Vec<AlertEvent>` - All state changes, in order
+    pub fn evaluate_all<'a>(
+        &mut self,
+        snapshots: impl IntoIterator<Item = &'a MetricsSnapshot>,
+    ) -> Vec<AlertEvent> {
+        snapshots
+            .into_iter()
+            .flat_map(|snapshot| self.evaluate(snapshot))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::disk::DiskUsage;
+    use crate::pressure::{Pressure, ResourcePressure, StallStats};
+    use crate::snapshot::CoreUsage;
+    use std::time::UNIX_EPOCH;
+
+    fn snapshot(seconds: u64, cpu: f32) -> MetricsSnapshot {
+        MetricsSnapshot {
+            timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+            cores: vec![CoreUsage {
+                id: 0,
+                name: "cpu0".to_string(),
+                usage: cpu,
+            }],
+            total_memory: 100,
+            used_memory: 50,
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    /// Runs `rule` over CPU values one second apart and returns the
+    /// transitions with the second at which they happened.
+    fn transitions(rule: &str, values: &[f32]) -> Vec<(u64, Transition)> {
+        let snapshots: Vec<_> = values
+            .iter()
+            .enumerate()
+            .map(|(second, &value)| snapshot(second as u64, value))
+            .collect();
+        let mut engine = AlertEngine::new(vec![rule.parse().unwrap()]);
+        engine
+            .evaluate_all(&snapshots)
+            .into_iter()
+            .map(|event| {
+                let second = event.timestamp.duration_since(UNIX_EPOCH).unwrap();
+                (second.as_secs(), event.transition)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_rules() {
+        let rule: AlertRule = "cpu > 90".parse().unwrap();
+        assert_eq!(
+            rule,
+            AlertRule::new(AlertMetric::Cpu, Comparison::Above, 90.0)
+        );
+        assert_eq!(rule.name, "cpu > 90%");
+
+        let rule: AlertRule = " Swap<10.5% for 90 ".parse().unwrap();
+        assert_eq!(rule.metric, AlertMetric::Swap);
+        assert_eq!(rule.comparison, Comparison::Below);
+        assert_eq!(rule.threshold, 10.5);
+        assert_eq!(rule.duration, Duration::from_secs(90));
+        assert_eq!(rule.name, "swap < 10.5% for 90s");
+
+        let rule: AlertRule = "disk > 95% for 1h".parse().unwrap();
+        assert_eq!(rule.to_string(), "disk > 95% for 1h");
+        let rule: AlertRule = "memory > 85 for 0.5m".parse().unwrap();
+        assert_eq!(rule.duration, Duration::from_secs(30));
+    }
+
+    #[test]
+    fn test_invalid_rules() {
+        let parse = |text: &str| text.parse::<AlertRule>().unwrap_err();
+        assert_eq!(
+            parse("cpu = 90"),
+            AlertRuleError::Syntax("cpu = 90".to_string())
+        );
+        assert_eq!(
+            parse("gpu > 90"),
+            AlertRuleError::UnknownMetric("gpu".to_string())
+        );
+        assert_eq!(
+            parse("cpu > 190%"),
+            AlertRuleError::InvalidThreshold("190%".to_string())
+        );
+        assert_eq!(
+            parse("cpu > 90 for soon"),
+            AlertRuleError::InvalidDuration("soon".to_string())
+        );
+        assert_eq!(
+            parse("cpu > 90 for -5s"),
+            AlertRuleError::InvalidDuration("-5s".to_string())
+        );
+    }
+
+    #[test]
+    fn test_builder_keeps_custom_names() {
+        let rule = AlertRule::new(AlertMetric::Memory, Comparison::Above, 80.0)
+            .for_duration(Duration::from_secs(300));
+        assert_eq!(rule.name, "memory > 80% for 5m");
+        let rule = rule.named("Memory pressure").for_duration(Duration::ZERO);
+        assert_eq!(rule.name, "Memory pressure");
+        assert_eq!(rule.condition(), "memory > 80%");
+    }
+
+    #[test]
+    fn test_fires_immediately_without_duration() {
+        assert_eq!(
+            transitions("cpu > 90", &[50.0, 95.0, 96.0, 40.0]),
+            vec![(1, Transition::Firing), (3, Transition::Resolved)]
+        );
+    }
+
+    #[test]
+    fn test_pending_until_duration_elapsed() {
+        assert_eq!(
+            transitions("cpu > 90 for 3s", &[95.0, 95.0, 95.0, 95.0, 95.0]),
+            vec![(0, Transition::Pending), (3, Transition::Firing)]
+        );
+    }
+
+    #[test]
+    fn test_pending_is_cancelled_when_condition_stops() {
+        // The dip at second 2 restarts the wait
+        assert_eq!(
+            transitions("cpu > 90 for 2s", &[95.0, 95.0, 89.0, 95.0, 95.0, 95.0]),
+            vec![
+                (0, Transition::Pending),
+                (3, Transition::Pending),
+                (5, Transition::Firing)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_hysteresis_prevents_flapping() {
+        // Hovering between 86% and 91% stays firing with a 5 point margin
+        assert_eq!(
+            transitions("cpu > 90", &[91.0, 86.0, 91.0, 86.0, 85.0, 91.0]),
+            vec![
+                (0, Transition::Firing),
+                (4, Transition::Resolved),
+                (5, Transition::Firing)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_below_rules() {
+        let mut engine = AlertEngine::new(vec![AlertRule::new(
+            AlertMetric::Cpu,
+            Comparison::Below,
+            10.0,
+        )
+        .with_hysteresis(2.0)]);
+        let events = engine.evaluate_all(&[snapshot(0, 5.0), snapshot(1, 11.0), snapshot(2, 12.0)]);
+        let transitions: Vec<_> = events.iter().map(|e| e.transition).collect();
+        assert_eq!(transitions, vec![Transition::Firing, Transition::Resolved]);
+        assert_eq!(events[1].value, 12.0);
+    }
+
+    #[test]
+    fn test_unavailable_metric_keeps_state() {
+        let mut engine = AlertEngine::new(vec!["cpu > 90".parse().unwrap()]);
+        engine.evaluate(&snapshot(0, 95.0));
+        let mut no_cores = snapshot(1, 0.0);
+        no_cores.cores.clear();
+        assert!(engine.evaluate(&no_cores).is_empty());
+        assert!(engine.is_firing(AlertMetric::Cpu));
+        assert_eq!(engine.alerts()[0].value, Some(95.0));
+    }
+
+    #[test]
+    fn test_resolved_is_transient() {
+        let mut engine = AlertEngine::new(vec!["cpu > 90".parse().unwrap()]);
+        engine.evaluate_all(&[snapshot(0, 95.0), snapshot(1, 10.0)]);
+        assert_eq!(
+            engine.alerts()[0].state,
+            AlertState::Resolved {
+                at: UNIX_EPOCH + Duration::from_secs(1)
+            }
+        );
+        engine.evaluate(&snapshot(2, 10.0));
+        assert_eq!(engine.alerts()[0].state, AlertState::Inactive);
+        assert_eq!(engine.firing().count(), 0);
+    }
+
+    #[test]
+    fn test_disk_uses_fullest_real_filesystem() {
+        let disk = |mount: &str, file_system: &str, available| DiskUsage {
+            name: mount.to_string(),
+            mount_point: mount.to_string(),
+            file_system: file_system.to_string(),
+            total: 100,
+            available,
+        };
+        let mut snapshot = snapshot(0, 0.0);
+        assert_eq!(AlertMetric::Disk.value(&snapshot), None);
+        snapshot.disks = vec![
+            disk("/", "ext4", 40),
+            disk("/data", "xfs", 10),
+            disk("/run", "tmpfs", 0),
+        ];
+        assert_eq!(AlertMetric::Disk.value(&snapshot), Some(90.0));
+    }
+
+    #[test]
+    fn test_pressure_uses_some_avg10() {
+        let rule: AlertRule = "io_pressure > 20% for 30s".parse().unwrap();
+        assert_eq!(rule.metric, AlertMetric::IoPressure);
+        assert_eq!(rule.metric.collector(), Collector::Cpu);
+
+        let mut snapshot = snapshot(0, 0.0);
+        assert_eq!(AlertMetric::IoPressure.value(&snapshot), None);
+        let stalled = |avg10| ResourcePressure {
+            some: StallStats {
+                avg10,
+                ..StallStats::default()
+            },
+            full: None,
+        };
+        snapshot.pressure = Some(Pressure {
+            cpu: Some(stalled(5.0)),
+            memory: None,
+            io: Some(stalled(35.5)),
+        });
+        assert_eq!(AlertMetric::IoPressure.value(&snapshot), Some(35.5));
+        assert_eq!(AlertMetric::CpuPressure.value(&snapshot), Some(5.0));
+        assert_eq!(AlertMetric::MemoryPressure.value(&snapshot), None);
+    }
+}
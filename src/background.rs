@@ -0,0 +1,458 @@
+//! Metric collection on a dedicated thread.
+//!
+//! Reading a [`MetricSource`] can take a while (e.g. rescanning every
+//! process). A [`BackgroundCollector`] moves an [`App`] behind a mutex and
+//! reads its source on a separate thread, so callers such as a render loop
+//! or a service's request handlers never block on a refresh. The mutex is
+//! only held while a finished reading is ingested, never while reading.
+//!
+//! New snapshots are published over a bounded channel; the latest
+//! snapshot, the histories and the statistics stay available through
+//! [`BackgroundCollector::with_app`]. A collector started with
+//! [`BackgroundCollector::spawn`] drops the snapshots a slow consumer has
+//! no room for, which suits a display that only shows the latest values.
+//! Recorders use [`BackgroundCollector::spawn_lossless`], which waits for
+//! the consumer instead.
+
+use std::io;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant};
+
+use crate::snapshot::MetricsSnapshot;
+use crate::source::MetricSource;
+use crate::App;
+
+/// Number of published snapshots buffered for a slow consumer.
+///
+/// Once the buffer is full, a collector started with
+/// [`BackgroundCollector::spawn`] still ingests new snapshots into the app
+/// but drops them instead of publishing them, and counts them in
+/// [`BackgroundCollector::dropped`]. A collector started with
+/// [`BackgroundCollector::spawn_lossless`] stops reading until the
+/// consumer has made room.
+pub const SNAPSHOT_BUFFER: usize = 16;
+
+/// Runs an [`App`] on its own thread and publishes its snapshots.
+///
+/// The collector stops when [`BackgroundCollector::stop`] is called or
+/// when it is dropped.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use system_monitor::{App, BackgroundCollector, Reading, ScriptedSource};
+///
+/// let source = ScriptedSource::new(vec![
+///     Reading::default(),
+///     Reading { total_memory: 100, used_memory: 25, ..Reading::default() },
+/// ]);
+/// let app = App::with_source(source, 10);
+/// let collector = BackgroundCollector::spawn(app, Duration::from_millis(10)).unwrap();
+///
+/// let snapshot = collector.recv_timeout(Duration::from_secs(5)).unwrap();
+/// assert_eq!(snapshot.memory_usage(), Ok(25.0));
+/// let app = collector.stop();
+/// assert_eq!(app.memory_stats().min(), Some(25.0));
+/// ```
+#[derive(Debug)]
+pub struct BackgroundCollector {
+    app: Arc<Mutex<App>>,
+    snapshots: Option<Receiver<MetricsSnapshot>>,
+    paused: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+    stop: Option<Sender<()>>,
+    handle: Option<JoinHandle<Option<Box<dyn MetricSource>>>>,
+}
+
+impl BackgroundCollector {
+    /// Starts collecting from `app`'s source every `interval`.
+    ///
+    /// The first reading is taken one `interval` after spawning. An app
+    /// whose source is already lent out is never updated. Snapshots
+    /// published while the buffer is full are dropped.
+    ///
+    /// # Arguments
+    /// * `app` - App whose source is read and into which readings are
+    ///   ingested
+    /// * `interval` - Time between the starts of two readings
+    ///
+    /// # Returns
+    /// * `io::Result<Self>` - The running collector, or the error raised
+    ///   when the thread could not be spawned
+    pub fn spawn(app: App, interval: Duration) -> io::Result<Self> {
+        Self::start(app, interval, false)
+    }
+
+    /// Starts collecting like [`BackgroundCollector::spawn`], but never
+    /// drops a snapshot.
+    ///
+    /// While the buffer is full, the thread waits for the consumer before
+    /// taking the next reading, so a stalled consumer delays the samples
+    /// instead of losing them.
+    ///
+    /// # Arguments
+    /// * `app` - App whose source is read and into which readings are
+    ///   ingested
+    /// * `interval` - Time between the starts of two readings
+    ///
+    /// # Returns
+    /// * `io::Result<Self>` - The running collector, or the error raised
+    ///   when the thread could not be spawned
+    pub fn spawn_lossless(app: App, interval: Duration) -> io::Result<Self> {
+        Self::start(app, interval, true)
+    }
+
+    /// Spawns the collector thread.
+    ///
+    /// # Arguments
+    /// * `app` - App whose source is read
+    /// * `interval` - Time between the starts of two readings
+    /// * `lossless` - Whether publishing waits for room in the buffer
+    fn start(mut app: App, interval: Duration, lossless: bool) -> io::Result<Self> {
+        let source = app.take_source();
+        let app = Arc::new(Mutex::new(app));
+        let paused = Arc::new(AtomicBool::new(false));
+        let dropped = Arc::new(AtomicU64::new(0));
+        let (stop, stop_rx) = mpsc::channel();
+        let (snapshot_tx, snapshots) = mpsc::sync_channel(SNAPSHOT_BUFFER);
+
+        let worker = Worker {
+            app: Arc::clone(&app),
+            paused: Arc::clone(&paused),
+            dropped: Arc::clone(&dropped),
+            stop: stop_rx,
+            snapshots: snapshot_tx,
+            interval,
+            lossless,
+        };
+        let handle = thread::Builder::new()
+            .name("metric-collector".to_string())
+            .spawn(move || worker.run(source))?;
+
+        Ok(Self {
+            app,
+            snapshots: Some(snapshots),
+            paused,
+            dropped,
+            stop: Some(stop),
+            handle: Some(handle),
+        })
+    }
+
+    /// Returns the next published snapshot, if one is waiting.
+    pub fn try_recv(&self) -> Option<MetricsSnapshot> {
+        self.snapshots.as_ref()?.try_recv().ok()
+    }
+
+    /// Waits for the next published snapshot.
+    ///
+    /// # Returns
+    /// * `Option<MetricsSnapshot>` - The snapshot, or `None` if the
+    ///   collector thread has exited
+    pub fn recv(&self) -> Option<MetricsSnapshot> {
+        self.snapshots.as_ref()?.recv().ok()
+    }
+
+    /// Waits up to `timeout` for the next published snapshot.
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<MetricsSnapshot> {
+        self.snapshots.as_ref()?.recv_timeout(timeout).ok()
+    }
+
+    /// Iterates over the published snapshots waiting, without blocking.
+    pub fn try_iter(&self) -> impl Iterator<Item = MetricsSnapshot> + '_ {
+        self.snapshots.iter().flat_map(Receiver::try_iter)
+    }
+
+    /// Returns the number of snapshots dropped because the buffer was
+    /// full; always zero for a lossless collector.
+    pub fn dropped(&self) -> u64 {
+        self.dropped.load(Ordering::Relaxed)
+    }
+
+    /// Returns a copy of the most recently ingested snapshot.
+    pub fn latest(&self) -> MetricsSnapshot {
+        self.with_app(|app| app.snapshot().clone())
+    }
+
+    /// Runs `f` with exclusive access to the app.
+    ///
+    /// The collector cannot ingest a reading while `f` runs, so `f` should
+    /// be short (e.g. rendering a frame or copying values out).
+    pub fn with_app<R>(&self, f: impl FnOnce(&mut App) -> R) -> R {
+        f(&mut lock(&self.app))
+    }
+
+    /// Suspends or resumes reading the source.
+    ///
+    /// While paused, the source is not read at all.
+    pub fn set_paused(&self, paused: bool) {
+        self.paused.store(paused, Ordering::Relaxed);
+    }
+
+    /// Returns `true` if reading is suspended.
+    pub fn is_paused(&self) -> bool {
+        self.paused.load(Ordering::Relaxed)
+    }
+
+    /// Stops the collector thread and hands the app back.
+    ///
+    /// Waits for an in-progress reading to finish. The app gets its source
+    /// back and can be updated directly again.
+    pub fn stop(mut self) -> App {
+        let source = self.shutdown();
+        let app = Arc::clone(&self.app);
+        drop(self);
+        let mut app = match Arc::try_unwrap(app) {
+            Ok(app) => app.into_inner().unwrap_or_else(PoisonError::into_inner),
+            Err(_) => unreachable!("the collector thread has been joined"),
+        };
+        app.restore_source(source);
+        app
+    }
+
+    /// Signals the thread to stop and waits for it.
+    ///
+    /// # Returns
+    /// * `Option<Box<dyn MetricSource>>` - The source the thread was
+    ///   reading, or `None` if it was already shut down or panicked
+    fn shutdown(&mut self) -> Option<Box<dyn MetricSource>> {
+        // Dropping the sender wakes the thread from its wait, dropping the
+        // receiver from a lossless publish into a full buffer
+        self.stop.take();
+        self.snapshots.take();
+        self.handle
+            .take()
+            .and_then(|handle| handle.join().ok())
+            .flatten()
+    }
+}
+
+impl Drop for BackgroundCollector {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+/// State moved to the collector thread.
+struct Worker {
+    app: Arc<Mutex<App>>,
+    paused: Arc<AtomicBool>,
+    dropped: Arc<AtomicU64>,
+    stop: Receiver<()>,
+    snapshots: SyncSender<MetricsSnapshot>,
+    interval: Duration,
+    lossless: bool,
+}
+
+impl Worker {
+    /// Reads `source` every interval until asked to stop.
+    ///
+    /// # Returns
+    /// * `Option<Box<dyn MetricSource>>` - `source`, handed back to the app
+    fn run(self, mut source: Option<Box<dyn MetricSource>>) -> Option<Box<dyn MetricSource>> {
+        let mut next = Instant::now() + self.interval;
+        loop {
+            let timeout = next.saturating_duration_since(Instant::now());
+            match self.stop.recv_timeout(timeout) {
+                Err(RecvTimeoutError::Timeout) => {}
+                Ok(()) | Err(RecvTimeoutError::Disconnected) => return source,
+            }
+            // Skip missed ticks after a slow reading instead of bursting
+            next = (next + self.interval).max(Instant::now());
+
+            let Some(reader) = source.as_mut() else {
+                continue;
+            };
+            if self.paused.load(Ordering::Relaxed) {
+                continue;
+            }
+            let reading = reader.read();
+            let snapshot = lock(&self.app).ingest(reading);
+            self.publish(snapshot);
+        }
+    }
+
+    /// Hands `snapshot` to the consumer.
+    ///
+    /// A full buffer means the consumer is behind. A lossless worker waits
+    /// for it; otherwise the snapshot is counted as dropped, and the
+    /// consumer can still read the latest snapshot from the app.
+    fn publish(&self, snapshot: MetricsSnapshot) {
+        if self.lossless {
+            // Fails only once the collector is shutting down
+            let _ = self.snapshots.send(snapshot);
+        } else if let Err(TrySendError::Full(_)) = self.snapshots.try_send(snapshot) {
+            self.dropped.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+}
+
+/// Locks the app, recovering it if a previous holder panicked.
+fn lock(app: &Mutex<App>) -> MutexGuard<'_, App> {
+    app.lock().unwrap_or_else(PoisonError::into_inner)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::Metric;
+    use crate::source::{Reading, ScriptedSource};
+    use std::time::SystemTime;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn reading(seconds: u64, used_memory: u64) -> Reading {
+        Reading {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            total_memory: 100,
+            used_memory,
+            ..Reading::default()
+        }
+    }
+
+    /// Source that takes a long time to read, like a full process scan.
+    #[derive(Debug)]
+    struct SlowSource {
+        delay: Duration,
+        reads: u64,
+    }
+
+    impl MetricSource for SlowSource {
+        fn read(&mut self) -> Reading {
+            thread::sleep(self.delay);
+            self.reads += 1;
+            reading(self.reads, 10)
+        }
+    }
+
+    #[test]
+    fn test_publishes_snapshots_in_order() {
+        let source = ScriptedSource::new(vec![reading(0, 0), reading(1, 10), reading(2, 20)]);
+        let app = App::with_source(source, 10);
+        let collector = BackgroundCollector::spawn(app, Duration::from_millis(1)).unwrap();
+
+        let first = collector.recv_timeout(TIMEOUT).unwrap();
+        let second = collector.recv_timeout(TIMEOUT).unwrap();
+        assert_eq!(first.memory_usage(), Ok(10.0));
+        assert_eq!(second.memory_usage(), Ok(20.0));
+    }
+
+    #[test]
+    fn test_stop_returns_app_with_source() {
+        let source = ScriptedSource::new(vec![reading(0, 0), reading(1, 10), reading(2, 50)]);
+        let app = App::with_source(source, 10);
+        let collector = BackgroundCollector::spawn(app, Duration::from_millis(1)).unwrap();
+        collector.recv_timeout(TIMEOUT).unwrap();
+
+        let mut app = collector.stop();
+        assert!(!app.history(Metric::Memory).is_empty());
+        // The source is back: the script continues where the thread left off
+        app.update();
+        assert_eq!(app.get_memory_usage(), Ok(50.0));
+    }
+
+    #[test]
+    fn test_slow_source_does_not_block_readers() {
+        let source = SlowSource {
+            delay: Duration::from_millis(300),
+            reads: 0,
+        };
+        let app = App::with_source(source, 10);
+        let collector = BackgroundCollector::spawn(app, Duration::ZERO).unwrap();
+        thread::sleep(Duration::from_millis(50));
+
+        // The thread is now in the middle of a slow read
+        let start = Instant::now();
+        collector.latest();
+        assert!(
+            start.elapsed() < Duration::from_millis(150),
+            "Reading the latest snapshot should not wait for the source"
+        );
+    }
+
+    #[test]
+    fn test_paused_collector_does_not_read() {
+        let source = ScriptedSource::new(vec![reading(0, 0), reading(1, 10)]);
+        let app = App::with_source(source, 10);
+        let collector = BackgroundCollector::spawn(app, Duration::from_millis(1)).unwrap();
+        collector.set_paused(true);
+        assert!(collector.is_paused());
+        // Drop anything published before the pause took effect
+        thread::sleep(Duration::from_millis(20));
+        collector.try_iter().for_each(drop);
+
+        assert!(collector.recv_timeout(Duration::from_millis(50)).is_none());
+        collector.set_paused(false);
+        assert!(collector.recv_timeout(TIMEOUT).is_some());
+    }
+
+    #[test]
+    fn test_slow_consumer_does_not_grow_buffer() {
+        let app = App::with_source(ScriptedSource::new(vec![reading(0, 0)]), 10);
+        let collector = BackgroundCollector::spawn(app, Duration::ZERO).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        assert!(collector.try_iter().count() <= SNAPSHOT_BUFFER);
+    }
+
+    #[test]
+    fn test_slow_consumer_counts_dropped_snapshots() {
+        let app = App::with_source(ScriptedSource::new(vec![reading(0, 0)]), 10);
+        let collector = BackgroundCollector::spawn(app, Duration::ZERO).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        collector.set_paused(true);
+        thread::sleep(Duration::from_millis(20));
+
+        let published = collector.try_iter().count() as u64;
+        let ingested = collector.with_app(|app| app.memory_stats().count());
+        assert!(collector.dropped() > 0);
+        assert_eq!(published + collector.dropped(), ingested);
+    }
+
+    #[test]
+    fn test_lossless_collector_publishes_every_snapshot() {
+        let readings = (0..SNAPSHOT_BUFFER as u64 * 3)
+            .map(|seconds| reading(seconds, seconds % 100))
+            .collect();
+        let app = App::with_source(ScriptedSource::new(readings), 10);
+        let collector = BackgroundCollector::spawn_lossless(app, Duration::ZERO).unwrap();
+        // Let the buffer fill up before consuming
+        thread::sleep(Duration::from_millis(50));
+
+        let timestamps: Vec<_> = (0..SNAPSHOT_BUFFER * 2)
+            .map(|_| collector.recv_timeout(TIMEOUT).unwrap().timestamp)
+            .collect();
+        let expected: Vec<_> = (1..=SNAPSHOT_BUFFER as u64 * 2)
+            .map(|seconds| SystemTime::UNIX_EPOCH + Duration::from_secs(seconds))
+            .collect();
+        assert_eq!(timestamps, expected);
+        assert_eq!(collector.dropped(), 0);
+    }
+
+    #[test]
+    fn test_lossless_collector_stops_with_full_buffer() {
+        let app = App::with_source(ScriptedSource::new(vec![reading(0, 0)]), 10);
+        let collector = BackgroundCollector::spawn_lossless(app, Duration::ZERO).unwrap();
+        thread::sleep(Duration::from_millis(50));
+        let start = Instant::now();
+        collector.stop();
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "Stopping should not wait for the consumer"
+        );
+    }
+
+    #[test]
+    fn test_drop_stops_thread() {
+        let app = App::with_source(ScriptedSource::default(), 10);
+        let collector = BackgroundCollector::spawn(app, Duration::from_secs(3600)).unwrap();
+        let start = Instant::now();
+        drop(collector);
+        assert!(
+            start.elapsed() < Duration::from_secs(1),
+            "Dropping should not wait for the next interval"
+        );
+    }
+}
@@ -0,0 +1,686 @@
+//! Container-aware limits and usage from cgroup v2.
+//!
+//! Inside a container the host-wide figures reported by `sysinfo` describe
+//! the machine, not the container: memory is compared against the host's
+//! RAM and CPU usage against the host's cores. When the monitor runs in a
+//! cgroup v2 hierarchy, [`Cgroup`] reads the container's own accounting
+//! instead:
+//! * `memory.current` and `memory.max` - Memory charged to the cgroup and
+//!   its hard limit
+//! * `cpu.max` - CPU bandwidth quota and period
+//! * `cpu.stat` - Cumulative CPU time and throttling counters
+//!
+//! CPU time is cumulative; [`ContainerTracker`] turns successive readings
+//! into a usage percentage of the quota.
+
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use serde::{Deserialize, Serialize};
+
+/// Mount point of the unified cgroup v2 hierarchy on most systems.
+pub const DEFAULT_CGROUP_ROOT: &str = "/sys/fs/cgroup";
+
+/// File listing the cgroup membership of the current process.
+pub const SELF_CGROUP: &str = "/proc/self/cgroup";
+
+/// CPU bandwidth limit from `cpu.max`.
+///
+/// # Fields
+/// * `quota_usec` - CPU time the cgroup may use per period, in microseconds
+/// * `period_usec` - Length of an accounting period, in microseconds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuQuota {
+    pub quota_usec: u64,
+    pub period_usec: u64,
+}
+
+impl CpuQuota {
+    /// Returns the limit as a number of CPUs (e.g. 1.5 for a quota of
+    /// 150ms per 100ms period).
+    pub fn cpus(&self) -> f64 {
+        if self.period_usec == 0 {
+            return 0.0;
+        }
+        self.quota_usec as f64 / self.period_usec as f64
+    }
+}
+
+/// Cumulative CPU counters from `cpu.stat`.
+///
+/// # Fields
+/// * `usage_usec` - Total CPU time consumed, in microseconds
+/// * `user_usec` - CPU time spent in user mode, in microseconds
+/// * `system_usec` - CPU time spent in kernel mode, in microseconds
+/// * `nr_periods` - Number of elapsed enforcement periods
+/// * `nr_throttled` - Number of periods in which the cgroup was throttled
+/// * `throttled_usec` - Total time the cgroup spent throttled, in
+///   microseconds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CpuStat {
+    pub usage_usec: u64,
+    pub user_usec: u64,
+    pub system_usec: u64,
+    pub nr_periods: u64,
+    pub nr_throttled: u64,
+    pub throttled_usec: u64,
+}
+
+/// Usage and limits of the cgroup the monitor runs in.
+///
+/// # Fields
+/// * `cgroup` - Path of the cgroup within the hierarchy (e.g. `/docker/abc`)
+/// * `memory_used` - Memory charged to the cgroup in bytes
+/// * `memory_limit` - Hard memory limit in bytes, `None` if unlimited
+/// * `cpu_quota` - CPU bandwidth limit, `None` if unlimited
+/// * `cpu` - Cumulative CPU counters
+/// * `cpu_usage` - CPU usage as a percentage of the quota (or of all host
+///   cores when unlimited) since the previous reading, `None` until two
+///   readings were taken
+///
+/// # Example
+/// ```
+/// use system_monitor::cgroup::{ContainerUsage, CpuQuota};
+///
+/// let usage = ContainerUsage {
+///     memory_used: 256 * 1024 * 1024,
+///     memory_limit: Some(512 * 1024 * 1024),
+///     cpu_quota: Some(CpuQuota { quota_usec: 200_000, period_usec: 100_000 }),
+///     ..ContainerUsage::default()
+/// };
+/// assert!(usage.has_limits());
+/// assert_eq!(usage.memory_usage(), Some(50.0));
+/// assert_eq!(usage.cpu_limit(), Some(2.0));
+/// ```
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ContainerUsage {
+    pub cgroup: String,
+    pub memory_used: u64,
+    pub memory_limit: Option<u64>,
+    pub cpu_quota: Option<CpuQuota>,
+    pub cpu: CpuStat,
+    pub cpu_usage: Option<f32>,
+}
+
+impl ContainerUsage {
+    /// Returns `true` if the cgroup has a memory or CPU limit.
+    pub fn has_limits(&self) -> bool {
+        self.memory_limit.is_some() || self.cpu_quota.is_some()
+    }
+
+    /// Returns the CPU limit as a number of CPUs, if any.
+    pub fn cpu_limit(&self) -> Option<f64> {
+        self.cpu_quota.map(|quota| quota.cpus())
+    }
+
+    /// Returns the memory usage percentage.
+    ///
+    /// # Returns
+    /// * `Option<f32>` - Used memory as a percentage of `memory.max`, or
+    ///   `None` if the cgroup has no memory limit
+    pub fn memory_usage(&self) -> Option<f32> {
+        self.memory_limit
+            .filter(|&limit| limit > 0)
+            .map(|limit| (self.memory_used as f32 / limit as f32) * 100.0)
+    }
+
+    /// Computes [`cpu_usage`](Self::cpu_usage) from the CPU time consumed
+    /// since `previous`.
+    ///
+    /// The usage is relative to the CPU quota; without a quota it is
+    /// relative to all `host_cpus`, like the host-wide average. A counter
+    /// that went backwards (the cgroup was recreated) or a zero `elapsed`
+    /// leaves the usage unknown.
+    ///
+    /// # Arguments
+    /// * `previous` - CPU counters read `elapsed` before this reading
+    /// * `elapsed` - Wall-clock time between the two readings
+    /// * `host_cpus` - Number of logical cores of the host
+    pub fn derive_cpu_usage(&mut self, previous: &CpuStat, elapsed: Duration, host_cpus: usize) {
+        let cpus = self.cpu_limit().unwrap_or(host_cpus as f64);
+        let available = elapsed.as_micros() as f64 * cpus;
+        self.cpu_usage = match self.cpu.usage_usec.checked_sub(previous.usage_usec) {
+            Some(used) if available > 0.0 => Some((used as f64 / available * 100.0) as f32),
+            _ => None,
+        };
+    }
+}
+
+/// Turns successive cgroup readings into CPU usage percentages.
+///
+/// The collector may skip the cgroup on some refreshes, so the tracker
+/// remembers when the counters were last read rather than relying on the
+/// previous snapshot's timestamp.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use system_monitor::cgroup::{ContainerTracker, ContainerUsage, CpuStat};
+///
+/// let at = |usage_usec| ContainerUsage {
+///     cpu: CpuStat { usage_usec, ..CpuStat::default() },
+///     ..ContainerUsage::default()
+/// };
+/// let mut tracker = ContainerTracker::default();
+/// assert_eq!(tracker.sample(at(0), UNIX_EPOCH, 4).cpu_usage, None);
+///
+/// let later = UNIX_EPOCH + Duration::from_secs(2);
+/// assert_eq!(tracker.sample(at(4_000_000), later, 4).cpu_usage, Some(50.0));
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct ContainerTracker {
+    previous: Option<(CpuStat, SystemTime)>,
+    usage: Option<f32>,
+}
+
+impl ContainerTracker {
+    /// Records a new reading and fills in its CPU usage.
+    ///
+    /// A reading taken no later than the previous one (a repeated reading
+    /// or a clock going backwards) keeps the last computed usage.
+    ///
+    /// # Arguments
+    /// * `usage` - Usage as read from the cgroup
+    /// * `now` - Wall-clock time at which the cgroup was read
+    /// * `host_cpus` - Number of logical cores of the host
+    pub fn sample(
+        &mut self,
+        mut usage: ContainerUsage,
+        now: SystemTime,
+        host_cpus: usize,
+    ) -> ContainerUsage {
+        match self.previous {
+            Some((_, then)) if now <= then => {
+                usage.cpu_usage = self.usage;
+                return usage;
+            }
+            Some((previous, then)) => {
+                let elapsed = now.duration_since(then).unwrap_or_default();
+                usage.derive_cpu_usage(&previous, elapsed, host_cpus);
+            }
+            None => {}
+        }
+        self.previous = Some((usage.cpu, now));
+        self.usage = usage.cpu_usage;
+        usage
+    }
+}
+
+/// Reason why a cgroup could not be read.
+#[derive(Debug)]
+pub enum CgroupError {
+    /// A file of the cgroup could not be read
+    Io(PathBuf, io::Error),
+    /// A file of the cgroup does not have the expected format
+    Malformed(PathBuf, String),
+}
+
+impl fmt::Display for CgroupError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CgroupError::Io(path, err) => write!(f, "cannot read {}: {}", path.display(), err),
+            CgroupError::Malformed(path, text) => {
+                write!(f, "unexpected content in {}: '{}'", path.display(), text)
+            }
+        }
+    }
+}
+
+impl Error for CgroupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            CgroupError::Io(_, err) => Some(err),
+            CgroupError::Malformed(..) => None,
+        }
+    }
+}
+
+/// A cgroup v2 directory the monitor reads its container limits from.
+///
+/// # Example
+/// ```no_run
+/// use system_monitor::cgroup::Cgroup;
+///
+/// if let Some(cgroup) = Cgroup::detect() {
+///     let usage = cgroup.read().expect("cgroup files are readable");
+///     println!("{} uses {} bytes", usage.cgroup, usage.memory_used);
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Cgroup {
+    path: String,
+    dir: PathBuf,
+}
+
+impl Cgroup {
+    /// Detects the cgroup of the current process under
+    /// [`DEFAULT_CGROUP_ROOT`].
+    ///
+    /// # Returns
+    /// * `Option<Cgroup>` - The cgroup, or `None` if the host does not use
+    ///   the unified cgroup v2 hierarchy
+    pub fn detect() -> Option<Self> {
+        let membership = fs::read_to_string(SELF_CGROUP).ok()?;
+        Self::detect_in(Path::new(DEFAULT_CGROUP_ROOT), &membership)
+    }
+
+    /// Detects a cgroup under an arbitrary sysfs root, such as a fixture
+    /// directory instead of [`DEFAULT_CGROUP_ROOT`].
+    ///
+    /// The root must be a cgroup v2 mount (it contains
+    /// `cgroup.controllers`). If the cgroup's directory is not visible
+    /// under the root, as when a container has its own cgroup namespace
+    /// but sees the host's path, the root itself is used.
+    ///
+    /// # Arguments
+    /// * `root` - Mount point of the cgroup v2 hierarchy
+    /// * `membership` - Contents of `/proc/self/cgroup`
+    ///
+    /// # Returns
+    /// * `Option<Cgroup>` - The cgroup, or `None` if `root` is not a cgroup
+    ///   v2 mount or `membership` has no cgroup v2 entry
+    pub fn detect_in(root: &Path, membership: &str) -> Option<Self> {
+        if !root.join("cgroup.controllers").is_file() {
+            return None;
+        }
+        let path = parse_membership(membership)?;
+        let dir = root.join(path.trim_start_matches('/'));
+        let dir = if dir.is_dir() {
+            dir
+        } else {
+            root.to_path_buf()
+        };
+        Some(Self {
+            path: path.to_string(),
+            dir,
+        })
+    }
+
+    /// Returns the path of the cgroup within the hierarchy.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the directory the cgroup's files are read from.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Reads the current usage and limits.
+    ///
+    /// Files of controllers that are not enabled for the cgroup are
+    /// treated as unlimited or zero usage. The returned
+    /// [`ContainerUsage::cpu_usage`] is always `None`.
+    pub fn read(&self) -> Result<ContainerUsage, CgroupError> {
+        let memory_used = match self.read_file("memory.current")? {
+            Some(text) => {
+                parse_limit(&text).ok_or_else(|| self.malformed("memory.current", &text))?
+            }
+            None => None,
+        };
+        let memory_limit = match self.read_file("memory.max")? {
+            Some(text) => parse_limit(&text).ok_or_else(|| self.malformed("memory.max", &text))?,
+            None => None,
+        };
+        let cpu_quota = match self.read_file("cpu.max")? {
+            Some(text) => parse_cpu_max(&text).ok_or_else(|| self.malformed("cpu.max", &text))?,
+            None => None,
+        };
+        let cpu = match self.read_file("cpu.stat")? {
+            Some(text) => parse_cpu_stat(&text).ok_or_else(|| self.malformed("cpu.stat", &text))?,
+            None => CpuStat::default(),
+        };
+        Ok(ContainerUsage {
+            cgroup: self.path.clone(),
+            memory_used: memory_used.unwrap_or(0),
+            memory_limit,
+            cpu_quota,
+            cpu,
+            cpu_usage: None,
+        })
+    }
+
+    /// Reads a file of the cgroup, `None` if it does not exist.
+    fn read_file(&self, name: &str) -> Result<Option<String>, CgroupError> {
+        let path = self.dir.join(name);
+        match fs::read_to_string(&path) {
+            Ok(text) => Ok(Some(text)),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => Ok(None),
+            Err(err) => Err(CgroupError::Io(path, err)),
+        }
+    }
+
+    fn malformed(&self, name: &str, text: &str) -> CgroupError {
+        CgroupError::Malformed(self.dir.join(name), text.trim().to_string())
+    }
+}
+
+/// Extracts the cgroup v2 path from the contents of `/proc/self/cgroup`.
+///
+/// The unified hierarchy is listed as `0::<path>`; cgroup v1 controllers
+/// use other hierarchy ids and are ignored.
+///
+/// # Example
+/// ```
+/// use system_monitor::cgroup::parse_membership;
+///
+/// assert_eq!(parse_membership("0::/docker/abc\n"), Some("/docker/abc"));
+/// assert_eq!(parse_membership("4:memory:/docker/abc\n"), None);
+/// ```
+pub fn parse_membership(contents: &str) -> Option<&str> {
+    contents
+        .lines()
+        .find_map(|line| line.strip_prefix("0::"))
+        .map(str::trim)
+        .filter(|path| path.starts_with('/'))
+}
+
+/// Parses a single-value limit file such as `memory.max`.
+///
+/// # Returns
+/// * `Some(Some(bytes))` - A finite limit
+/// * `Some(None)` - The limit is `max` (unlimited)
+/// * `None` - The content is not a limit
+pub fn parse_limit(text: &str) -> Option<Option<u64>> {
+    match text.trim() {
+        "max" => Some(None),
+        value => value.parse().ok().map(Some),
+    }
+}
+
+/// Parses `cpu.max`, which holds `<quota> <period>` with a quota of `max`
+/// when unlimited.
+///
+/// # Returns
+/// * `Some(Some(quota))` - A finite quota
+/// * `Some(None)` - The quota is `max` (unlimited)
+/// * `None` - The content is not a CPU quota
+///
+/// # Example
+/// ```
+/// use system_monitor::cgroup::parse_cpu_max;
+///
+/// let quota = parse_cpu_max("50000 100000\n").unwrap().unwrap();
+/// assert_eq!(quota.cpus(), 0.5);
+/// assert_eq!(parse_cpu_max("max 100000\n"), Some(None));
+/// ```
+pub fn parse_cpu_max(text: &str) -> Option<Option<CpuQuota>> {
+    let mut fields = text.split_whitespace();
+    let quota = fields.next()?;
+    let period_usec = fields.next()?.parse().ok()?;
+    if fields.next().is_some() {
+        return None;
+    }
+    match quota {
+        "max" => Some(None),
+        quota => Some(Some(CpuQuota {
+            quota_usec: quota.parse().ok()?,
+            period_usec,
+        })),
+    }
+}
+
+/// Parses the `<key> <value>` lines of `cpu.stat`.
+///
+/// Unknown keys are ignored so newer kernels keep working; counters
+/// missing from the file are zero.
+///
+/// # Returns
+/// * `Option<CpuStat>` - The counters, or `None` if a line is not a
+///   key and a number
+pub fn parse_cpu_stat(text: &str) -> Option<CpuStat> {
+    let mut stat = CpuStat::default();
+    for line in text.lines().filter(|line| !line.trim().is_empty()) {
+        let (key, value) = line.split_once(' ')?;
+        let value: u64 = value.trim().parse().ok()?;
+        match key {
+            "usage_usec" => stat.usage_usec = value,
+            "user_usec" => stat.user_usec = value,
+            "system_usec" => stat.system_usec = value,
+            "nr_periods" => stat.nr_periods = value,
+            "nr_throttled" => stat.nr_throttled = value,
+            "throttled_usec" => stat.throttled_usec = value,
+            _ => {}
+        }
+    }
+    Some(stat)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    const CPU_STAT: &str = "usage_usec 2500000\nuser_usec 2000000\nsystem_usec 500000\n\
+        core_sched.force_idle_usec 0\nnr_periods 40\nnr_throttled 3\nthrottled_usec 120000\n";
+
+    /// Fixture sysfs root, removed when dropped.
+    struct Fixture(PathBuf);
+
+    impl Fixture {
+        /// Creates an empty cgroup v2 root.
+        fn new() -> Self {
+            static NEXT: AtomicUsize = AtomicUsize::new(0);
+            let dir = env::temp_dir().join(format!(
+                "system-monitor-cgroup-{}-{}",
+                process::id(),
+                NEXT.fetch_add(1, Ordering::Relaxed)
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            fs::write(
+                dir.join("cgroup.controllers"),
+                "cpuset cpu io memory pids\n",
+            )
+            .unwrap();
+            Fixture(dir)
+        }
+
+        /// Writes `files` into the cgroup directory `path`.
+        fn cgroup(&self, path: &str, files: &[(&str, &str)]) -> &Self {
+            let dir = self.0.join(path.trim_start_matches('/'));
+            fs::create_dir_all(&dir).unwrap();
+            for (name, contents) in files {
+                fs::write(dir.join(name), contents).unwrap();
+            }
+            self
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn test_parse_membership() {
+        let hybrid = "4:memory:/user.slice\n1:cpu:/\n0::/user.slice/session-1.scope\n";
+        assert_eq!(
+            parse_membership(hybrid),
+            Some("/user.slice/session-1.scope")
+        );
+        assert_eq!(parse_membership("0::/\n"), Some("/"));
+        assert_eq!(parse_membership("1:name=systemd:/\n"), None);
+        assert_eq!(parse_membership(""), None);
+    }
+
+    #[test]
+    fn test_parse_limits() {
+        assert_eq!(parse_limit("536870912\n"), Some(Some(536_870_912)));
+        assert_eq!(parse_limit("max\n"), Some(None));
+        assert_eq!(parse_limit("lots"), None);
+
+        assert_eq!(
+            parse_cpu_max("150000 100000\n"),
+            Some(Some(CpuQuota {
+                quota_usec: 150_000,
+                period_usec: 100_000
+            }))
+        );
+        assert_eq!(parse_cpu_max("max 100000\n"), Some(None));
+        assert_eq!(parse_cpu_max("max\n"), None);
+        assert_eq!(parse_cpu_max("1 2 3"), None);
+    }
+
+    #[test]
+    fn test_parse_cpu_stat() {
+        let stat = parse_cpu_stat(CPU_STAT).unwrap();
+        assert_eq!(
+            stat,
+            CpuStat {
+                usage_usec: 2_500_000,
+                user_usec: 2_000_000,
+                system_usec: 500_000,
+                nr_periods: 40,
+                nr_throttled: 3,
+                throttled_usec: 120_000,
+            }
+        );
+        assert_eq!(parse_cpu_stat("usage_usec many\n"), None);
+    }
+
+    #[test]
+    fn test_detect_requires_cgroup_v2() {
+        let fixture = Fixture::new();
+        fs::remove_file(fixture.0.join("cgroup.controllers")).unwrap();
+        assert_eq!(Cgroup::detect_in(&fixture.0, "0::/\n"), None);
+    }
+
+    #[test]
+    fn test_read_container_limits() {
+        let fixture = Fixture::new();
+        fixture.cgroup(
+            "/docker/abc",
+            &[
+                ("memory.current", "268435456\n"),
+                ("memory.max", "536870912\n"),
+                ("cpu.max", "200000 100000\n"),
+                ("cpu.stat", CPU_STAT),
+            ],
+        );
+
+        let cgroup = Cgroup::detect_in(&fixture.0, "0::/docker/abc\n").unwrap();
+        assert_eq!(cgroup.path(), "/docker/abc");
+        assert_eq!(cgroup.dir(), fixture.0.join("docker/abc"));
+
+        let usage = cgroup.read().unwrap();
+        assert_eq!(usage.cgroup, "/docker/abc");
+        assert_eq!(usage.memory_used, 268_435_456);
+        assert_eq!(usage.memory_limit, Some(536_870_912));
+        assert_eq!(usage.memory_usage(), Some(50.0));
+        assert_eq!(usage.cpu_limit(), Some(2.0));
+        assert_eq!(usage.cpu.throttled_usec, 120_000);
+        assert_eq!(usage.cpu_usage, None);
+    }
+
+    #[test]
+    fn test_read_unlimited_cgroup() {
+        let fixture = Fixture::new();
+        fixture.cgroup(
+            "/user.slice",
+            &[
+                ("memory.current", "1024\n"),
+                ("memory.max", "max\n"),
+                ("cpu.max", "max 100000\n"),
+            ],
+        );
+
+        let usage = Cgroup::detect_in(&fixture.0, "0::/user.slice\n")
+            .unwrap()
+            .read()
+            .unwrap();
+        assert!(!usage.has_limits());
+        assert_eq!(usage.memory_usage(), None);
+        assert_eq!(usage.cpu, CpuStat::default(), "cpu.stat is missing");
+    }
+
+    #[test]
+    fn test_namespaced_cgroup_falls_back_to_root() {
+        // With a cgroup namespace the container sees its own cgroup
+        // mounted at the root but may still report the host's path
+        let fixture = Fixture::new();
+        fixture.cgroup("/", &[("memory.max", "1048576\n")]);
+
+        let cgroup = Cgroup::detect_in(&fixture.0, "0::/kubepods/pod1\n").unwrap();
+        assert_eq!(cgroup.dir(), fixture.0);
+        assert_eq!(cgroup.read().unwrap().memory_limit, Some(1_048_576));
+    }
+
+    #[test]
+    fn test_malformed_file_is_reported() {
+        let fixture = Fixture::new();
+        fixture.cgroup("/", &[("cpu.max", "unlimited\n")]);
+
+        let err = Cgroup::detect_in(&fixture.0, "0::/\n")
+            .unwrap()
+            .read()
+            .unwrap_err();
+        assert!(matches!(err, CgroupError::Malformed(..)));
+        assert!(err.to_string().contains("cpu.max"));
+        assert!(err.to_string().ends_with("'unlimited'"));
+    }
+
+    #[test]
+    fn test_cpu_usage_against_quota() {
+        let quota = CpuQuota {
+            quota_usec: 50_000,
+            period_usec: 100_000,
+        };
+        let at = |usage_usec| ContainerUsage {
+            cpu_quota: Some(quota),
+            cpu: CpuStat {
+                usage_usec,
+                ..CpuStat::default()
+            },
+            ..ContainerUsage::default()
+        };
+
+        // 250ms of CPU time in one second against half a CPU
+        let mut usage = at(1_250_000);
+        usage.derive_cpu_usage(&at(1_000_000).cpu, Duration::from_secs(1), 8);
+        assert_eq!(usage.cpu_usage, Some(50.0));
+
+        // Without a quota the host's cores are the limit
+        let mut unlimited = at(1_250_000);
+        unlimited.cpu_quota = None;
+        unlimited.derive_cpu_usage(&at(1_000_000).cpu, Duration::from_secs(1), 2);
+        assert_eq!(unlimited.cpu_usage, Some(12.5));
+
+        // A recreated cgroup or no elapsed time leaves the usage unknown
+        let mut reset = at(100);
+        reset.derive_cpu_usage(&at(1_000_000).cpu, Duration::from_secs(1), 8);
+        assert_eq!(reset.cpu_usage, None);
+        let mut instant = at(1_250_000);
+        instant.derive_cpu_usage(&at(1_000_000).cpu, Duration::ZERO, 8);
+        assert_eq!(instant.cpu_usage, None);
+    }
+
+    #[test]
+    fn test_tracker_keeps_usage_on_repeated_reading() {
+        let at = |usage_usec| ContainerUsage {
+            cpu: CpuStat {
+                usage_usec,
+                ..CpuStat::default()
+            },
+            ..ContainerUsage::default()
+        };
+        let start = SystemTime::UNIX_EPOCH;
+        let later = start + Duration::from_secs(1);
+
+        let mut tracker = ContainerTracker::default();
+        tracker.sample(at(0), start, 1);
+        assert_eq!(tracker.sample(at(500_000), later, 1).cpu_usage, Some(50.0));
+        assert_eq!(tracker.sample(at(500_000), later, 1).cpu_usage, Some(50.0));
+        assert_eq!(tracker.sample(at(900_000), start, 1).cpu_usage, Some(50.0));
+
+        // The baseline is still the reading taken at `later`
+        let next = later + Duration::from_secs(1);
+        assert_eq!(tracker.sample(at(750_000), next, 1).cpu_usage, Some(25.0));
+    }
+}
@@ -0,0 +1,372 @@
+//! Command-line interface of the `system-monitor` binary.
+//!
+//! Arguments are parsed and validated into a [`Cli`] before the terminal
+//! is switched to raw mode, so mistakes are reported on a normal screen
+//! together with a usage hint. Without a command the interactive terminal
+//! UI is started; every other output mode is a subcommand.
+//!
+//! Options that can also be set in the configuration file are optional here
+//! so that they only override the file when given.
+
+use std::ffi::OsString;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{Args, CommandFactory, FromArgMatches, Parser, Subcommand};
+use system_monitor::csv::{MetricGroup, Rotation, DEFAULT_KEEP};
+use system_monitor::exporter::DEFAULT_LISTEN_ADDR;
+
+use crate::config::seconds_to_duration;
+use crate::View;
+
+/// Terminal-based system resource monitor.
+///
+/// Without a command, starts the interactive terminal UI.
+// The doc comment above doubles as the `--help` text, so it describes the
+// program rather than the fields.
+#[derive(Debug, Parser)]
+#[command(name = "system-monitor", version)]
+pub struct Cli {
+    /// Configuration file (default: $XDG_CONFIG_HOME/system-monitor/config.toml)
+    #[arg(long, global = true, value_name = "PATH")]
+    pub config: Option<PathBuf>,
+
+    #[command(flatten)]
+    pub tui: TuiArgs,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+impl Cli {
+    /// Parses the process arguments, exiting with a usage message on error.
+    pub fn parse_args() -> Self {
+        Self::try_parse_args(std::env::args_os()).unwrap_or_else(|e| e.exit())
+    }
+
+    /// Parses `args`, rejecting terminal UI options given with a command.
+    ///
+    /// clap's `args_conflicts_with_subcommands` cannot be used for this as
+    /// it would also reject the global `--config` before a command.
+    ///
+    /// # Errors
+    /// * `clap::Error` - The arguments are invalid, or help or the version
+    ///   was requested
+    pub fn try_parse_args<I, T>(args: I) -> Result<Self, clap::Error>
+    where
+        I: IntoIterator<Item = T>,
+        T: Into<OsString> + Clone,
+    {
+        let mut command = Self::command();
+        let matches = command.try_get_matches_from_mut(args)?;
+        if let Some((name, _)) = matches.subcommand() {
+            let tui_args = TuiArgs::augment_args(clap::Command::new("tui"));
+            let given = tui_args.get_arguments().find(|arg| {
+                matches.value_source(arg.get_id().as_str()) == Some(ValueSource::CommandLine)
+            });
+            if let Some(arg) = given {
+                let message = format!(
+                    "the argument '--{}' cannot be used with the '{}' command",
+                    arg.get_long().unwrap_or_default(),
+                    name
+                );
+                return Err(command.error(ErrorKind::ArgumentConflict, message));
+            }
+        }
+        Self::from_arg_matches(&matches).map_err(|e| e.format(&mut command))
+    }
+
+    /// Splits the arguments into the configuration file and the command to
+    /// run, defaulting to the terminal UI.
+    pub fn into_parts(self) -> (Option<PathBuf>, Command) {
+        (self.config, self.command.unwrap_or(Command::Tui(self.tui)))
+    }
+}
+
+/// Output mode selected on the command line.
+#[derive(Debug, Subcommand)]
+pub enum Command {
+    /// Interactive terminal UI (the default)
+    Tui(TuiArgs),
+
+    /// Serve metrics to Prometheus on http://ADDR/metrics
+    Serve {
+        /// Address to listen on
+        #[arg(default_value = DEFAULT_LISTEN_ADDR)]
+        addr: String,
+
+        #[command(flatten)]
+        sampling: Sampling,
+    },
+
+    /// Print one JSON object per sample to stdout
+    Json {
+        #[command(flatten)]
+        sampling: Sampling,
+
+        /// Exit after N samples instead of running until interrupted
+        #[arg(
+            short = 'n',
+            long,
+            value_name = "N",
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        count: Option<u64>,
+
+        /// Include the process list
+        #[arg(long)]
+        processes: bool,
+    },
+
+    /// Append one CSV row per sample to a rotating file
+    #[command(visible_alias = "csv")]
+    Record {
+        /// File to write; rotated files are named PATH.1, PATH.2, ...
+        path: PathBuf,
+
+        #[command(flatten)]
+        sampling: Sampling,
+
+        /// Comma-separated metric groups to record (default: cpu, memory,
+        /// swap, disks and networks)
+        #[arg(long, value_name = "LIST", value_delimiter = ',')]
+        groups: Vec<MetricGroup>,
+
+        /// Rotate once the file reaches this many bytes
+        #[arg(
+            long,
+            value_name = "BYTES",
+            value_parser = clap::value_parser!(u64).range(1..)
+        )]
+        max_size: Option<u64>,
+
+        /// Rotate once the file spans this many seconds
+        #[arg(long, value_name = "SECS", value_parser = parse_seconds)]
+        max_age: Option<Duration>,
+
+        /// Number of rotated files to keep
+        #[arg(long, value_name = "N", default_value_t = DEFAULT_KEEP)]
+        keep: usize,
+    },
+
+    /// Play a recorded session back in the terminal UI
+    Replay {
+        /// Session file written with --session or by `json --processes`
+        path: PathBuf,
+    },
+
+    /// Print a one-shot summary of the current resource usage and exit
+    Summary {
+        #[command(flatten)]
+        sampling: Sampling,
+
+        /// Number of processes listed, busiest first
+        #[arg(long, value_name = "N", default_value_t = 5)]
+        top: usize,
+    },
+}
+
+/// Options of the interactive terminal UI.
+///
+/// # Fields
+/// * `sampling` - Interval between two samples
+/// * `views` - Views shown in the tab bar, if overridden
+/// * `session` - Session file every sample is recorded to, if any
+#[derive(Debug, Clone, Args)]
+pub struct TuiArgs {
+    #[command(flatten)]
+    pub sampling: Sampling,
+
+    /// Comma-separated views to show, in tab order (default:
+    /// overview,processes,disks,network,alerts,pressure)
+    #[arg(long, value_name = "LIST", value_delimiter = ',')]
+    pub views: Option<Vec<View>>,
+
+    /// Record every sample to this file for later replay
+    #[arg(long, value_name = "PATH")]
+    pub session: Option<PathBuf>,
+}
+
+/// Sampling options shared by every live output mode.
+///
+/// # Fields
+/// * `interval` - Interval between two samples, if overridden
+#[derive(Debug, Clone, Copy, Args)]
+pub struct Sampling {
+    /// Seconds between two samples, e.g. 2 or 0.5 (default: 1)
+    #[arg(short, long, value_name = "SECS", value_parser = parse_seconds)]
+    pub interval: Option<Duration>,
+}
+
+impl Sampling {
+    /// Returns the interval given on the command line, else `configured`.
+    pub fn interval_or(&self, configured: Duration) -> Duration {
+        self.interval.unwrap_or(configured)
+    }
+}
+
+impl Command {
+    /// Returns the rotation limits of the `record` command.
+    ///
+    /// # Returns
+    /// * `Option<Rotation>` - The limits, or `None` for any other command
+    pub fn rotation(&self) -> Option<Rotation> {
+        match *self {
+            Command::Record {
+                max_size,
+                max_age,
+                keep,
+                ..
+            } => Some(Rotation {
+                max_bytes: max_size,
+                max_age,
+                keep,
+            }),
+            _ => None,
+        }
+    }
+}
+
+/// Parses a positive number of seconds such as `2` or `0.5`.
+///
+/// # Returns
+/// * `Ok(Duration)` - The parsed duration
+/// * `Err(String)` - The value is not a number, or not a positive finite one
+fn parse_seconds(value: &str) -> Result<Duration, String> {
+    let seconds: f64 = value
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not a number of seconds", value))?;
+    seconds_to_duration(seconds)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(args: &[&str]) -> Result<Command, clap::Error> {
+        let args = std::iter::once("system-monitor").chain(args.iter().copied());
+        Cli::try_parse_args(args).map(|cli| cli.into_parts().1)
+    }
+
+    #[test]
+    fn test_definition_is_consistent() {
+        Cli::command().debug_assert();
+    }
+
+    #[test]
+    fn test_no_arguments_starts_tui_with_defaults() {
+        match parse(&[]).unwrap() {
+            Command::Tui(args) => {
+                assert_eq!(args.sampling.interval, None);
+                assert_eq!(args.views, None);
+                assert_eq!(args.session, None);
+            }
+            other => panic!("expected the TUI, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_tui_options() {
+        let command = parse(&["-i", "0.5", "--views", "network,overview"]).unwrap();
+        match command {
+            Command::Tui(args) => {
+                assert_eq!(args.sampling.interval, Some(Duration::from_millis(500)));
+                assert_eq!(args.views, Some(vec![View::Network, View::Overview]));
+            }
+            other => panic!("expected the TUI, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_config_is_accepted_before_and_after_the_command() {
+        for args in [
+            &["--config", "monitor.toml", "serve"][..],
+            &["serve", "--config", "monitor.toml"],
+            &["--config", "monitor.toml"],
+        ] {
+            let args = std::iter::once("system-monitor").chain(args.iter().copied());
+            let (config, _) = Cli::try_parse_args(args).unwrap().into_parts();
+            assert_eq!(config, Some(PathBuf::from("monitor.toml")));
+        }
+    }
+
+    #[test]
+    fn test_record_options() {
+        let command = parse(&[
+            "csv",
+            "out.csv",
+            "--groups",
+            "memory,cpu",
+            "--max-size",
+            "1000",
+            "--max-age",
+            "60",
+        ])
+        .unwrap();
+        assert_eq!(
+            command.rotation(),
+            Some(Rotation {
+                max_bytes: Some(1000),
+                max_age: Some(Duration::from_secs(60)),
+                keep: DEFAULT_KEEP,
+            })
+        );
+        match command {
+            Command::Record { path, groups, .. } => {
+                assert_eq!(path, PathBuf::from("out.csv"));
+                assert_eq!(groups, vec![MetricGroup::Memory, MetricGroup::Cpu]);
+            }
+            other => panic!("expected record, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_invalid_values_are_rejected() {
+        for args in [
+            &["--interval", "0"][..],
+            &["--interval", "soon"],
+            &["--views", "gpu"],
+            &["json", "--count", "0"],
+            &["record", "out.csv", "--groups", "cpu,gpu"],
+            &["record", "out.csv", "--max-age=-5"],
+        ] {
+            let kind = parse(args).unwrap_err().kind();
+            assert!(
+                matches!(kind, ErrorKind::ValueValidation | ErrorKind::InvalidValue),
+                "{:?} gave {:?}",
+                args,
+                kind
+            );
+        }
+    }
+
+    #[test]
+    fn test_usage_errors() {
+        let kind = |args: &[&str]| parse(args).unwrap_err().kind();
+        assert_eq!(kind(&["record"]), ErrorKind::MissingRequiredArgument);
+        assert_eq!(kind(&["top"]), ErrorKind::InvalidSubcommand);
+        assert_eq!(
+            kind(&["--views", "disks", "serve"]),
+            ErrorKind::ArgumentConflict
+        );
+        assert_eq!(kind(&["--version"]), ErrorKind::DisplayVersion);
+        assert_eq!(kind(&["--help"]), ErrorKind::DisplayHelp);
+    }
+
+    #[test]
+    fn test_parse_seconds_messages() {
+        assert_eq!(parse_seconds("2"), Ok(Duration::from_secs(2)));
+        assert_eq!(
+            parse_seconds("x"),
+            Err("'x' is not a number of seconds".to_string())
+        );
+        assert_eq!(
+            parse_seconds("NaN"),
+            Err("must be greater than zero".to_string())
+        );
+        assert!(parse_seconds("inf").is_err());
+    }
+}
@@ -0,0 +1,1236 @@
+//! Layered configuration of the `system-monitor` binary.
+//!
+//! Settings are resolved from, in increasing order of precedence:
+//! 1. Built-in defaults
+//! 2. The configuration file: `--config PATH`, else `$SYSTEM_MONITOR_CONFIG`,
+//!    else `$XDG_CONFIG_HOME/system-monitor/config.toml` (by default
+//!    `~/.config/system-monitor/config.toml`) if it exists
+//! 3. Environment variables: `SYSTEM_MONITOR_INTERVAL` and
+//!    `SYSTEM_MONITOR_VIEWS`
+//! 4. Command-line flags, applied by the caller
+//!
+//! Mistakes in the file are reported with their line and column instead of
+//! being ignored or causing a panic.
+//!
+//! # Example
+//! ```toml
+//! interval = 2
+//! views = ["overview", "processes"]
+//!
+//! [layout]
+//! cpu_percent = 60
+//!
+//! [colors]
+//! cpu = "#00afff"
+//!
+//! [thresholds]
+//! memory = { warning = 70, critical = 90 }
+//!
+//! [keys]
+//! toggle_pause = ["space"]
+//!
+//! [[alerts]]
+//! rule = "cpu > 90% for 30s"
+//!
+//! [[actions]]
+//! webhook = "http://127.0.0.1:9000/alerts"
+//! ```
+
+use std::collections::BTreeMap;
+use std::env;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::num::NonZeroUsize;
+use std::ops::Range;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::Deserialize;
+use system_monitor::cputime::CpuMode;
+use system_monitor::notify::{ActionKind, WebhookUrl};
+use system_monitor::{AlertAction, AlertRule, DEFAULT_HISTORY_LEN};
+use toml::Spanned;
+use tui::style::Color;
+
+use crate::keymap::{KeyMap, KeySpec};
+use crate::{Action, View};
+
+/// Environment variable naming the configuration file.
+pub const CONFIG_VAR: &str = "SYSTEM_MONITOR_CONFIG";
+
+/// Environment variable overriding the sample interval, in seconds.
+pub const INTERVAL_VAR: &str = "SYSTEM_MONITOR_INTERVAL";
+
+/// Environment variable overriding the shown views, comma-separated.
+pub const VIEWS_VAR: &str = "SYSTEM_MONITOR_VIEWS";
+
+/// Default upper bound on how long a single loop iteration waits for input.
+const DEFAULT_TICK_RATE: Duration = Duration::from_millis(50);
+
+/// Default interval between two redraws when nothing else changed.
+const DEFAULT_RENDER_INTERVAL: Duration = Duration::from_millis(250);
+
+/// Default interval between two system metric samples.
+const DEFAULT_SAMPLE_INTERVAL: Duration = Duration::from_millis(1000);
+
+/// Resolved configuration of the application.
+///
+/// Rendering and sampling run on independent cadences, and on separate
+/// threads: the UI can be redrawn several times per sample (e.g. on
+/// resize) without forcing an expensive refresh of the system metrics.
+///
+/// # Fields
+/// * `tick_rate` - Maximum time a single loop iteration blocks waiting for
+///   input
+/// * `render_interval` - Interval between two periodic redraws
+/// * `sample_interval` - Interval between two samples taken by the
+///   background collector
+/// * `history_len` - Number of samples kept per metric for the charts
+/// * `views` - Views shown in the tab bar, in tab order
+/// * `layout` - Proportions of the overview
+/// * `theme` - Colors of the panels
+/// * `thresholds` - Usage levels at which gauges change color
+/// * `keys` - Key bindings
+/// * `alerts` - Alert rules evaluated against every sample
+/// * `actions` - Actions run when an alert fires or resolves
+#[derive(Debug, Clone, PartialEq)]
+pub struct Config {
+    pub tick_rate: Duration,
+    pub render_interval: Duration,
+    pub sample_interval: Duration,
+    pub history_len: usize,
+    pub views: Vec<View>,
+    pub layout: LayoutConfig,
+    pub theme: Theme,
+    pub thresholds: Thresholds,
+    pub keys: KeyMap,
+    pub alerts: Vec<AlertRule>,
+    pub actions: Vec<AlertAction>,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            tick_rate: DEFAULT_TICK_RATE,
+            render_interval: DEFAULT_RENDER_INTERVAL,
+            sample_interval: DEFAULT_SAMPLE_INTERVAL,
+            history_len: DEFAULT_HISTORY_LEN,
+            views: View::ALL.to_vec(),
+            layout: LayoutConfig::default(),
+            theme: Theme::default(),
+            thresholds: Thresholds::default(),
+            keys: KeyMap::default(),
+            alerts: Vec::new(),
+            actions: Vec::new(),
+        }
+    }
+}
+
+/// Proportions of the overview.
+///
+/// # Fields
+/// * `cpu_percent` - Share of the height given to the CPU section; the
+///   memory section gets the rest
+/// * `chart_percent` - Share of the width given to the history charts
+/// * `min_chart_width` - Narrowest terminal on which charts are shown
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct LayoutConfig {
+    pub cpu_percent: u16,
+    pub chart_percent: u16,
+    pub min_chart_width: u16,
+}
+
+impl Default for LayoutConfig {
+    fn default() -> Self {
+        Self {
+            cpu_percent: 50,
+            chart_percent: 50,
+            min_chart_width: 80,
+        }
+    }
+}
+
+/// Colors of the user interface.
+///
+/// # Fields
+/// * `title` - Application name in the title bar
+/// * `highlight` - Selected tab, sort column and the paused marker
+/// * `replay` - Playback position in the title bar
+/// * `cpu` - CPU gauge, per-core bars and CPU chart
+/// * `memory` - Memory gauge and chart
+/// * `swap` - Swap gauge and chart
+/// * `disk` - Disk usage bars
+/// * `receive` - Network receive rates
+/// * `transmit` - Network transmit rates
+/// * `sparkline` - Network throughput sparklines
+/// * `unavailable` - Panels whose metric the host cannot report
+/// * `warning` - Gauges above their warning threshold
+/// * `critical` - Gauges above their critical threshold
+/// * `cpu_user`, `cpu_nice`, `cpu_system`, `cpu_steal`, `cpu_irq`,
+///   `cpu_softirq`, `cpu_guest` - CPU modes in the CPU time bars
+/// * `memory_buffers` - Block device buffers in the memory bar
+/// * `memory_shared` - Shared memory in the memory bar
+/// * `memory_cache` - Reclaimable caches in the memory bar
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Theme {
+    pub title: Color,
+    pub highlight: Color,
+    pub replay: Color,
+    pub cpu: Color,
+    pub memory: Color,
+    pub swap: Color,
+    pub disk: Color,
+    pub receive: Color,
+    pub transmit: Color,
+    pub sparkline: Color,
+    pub unavailable: Color,
+    pub warning: Color,
+    pub critical: Color,
+    pub cpu_user: Color,
+    pub cpu_nice: Color,
+    pub cpu_system: Color,
+    pub cpu_steal: Color,
+    pub cpu_irq: Color,
+    pub cpu_softirq: Color,
+    pub cpu_guest: Color,
+    pub memory_buffers: Color,
+    pub memory_shared: Color,
+    pub memory_cache: Color,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            title: Color::Green,
+            highlight: Color::Yellow,
+            replay: Color::Cyan,
+            cpu: Color::Cyan,
+            memory: Color::Magenta,
+            swap: Color::Blue,
+            disk: Color::Green,
+            receive: Color::Green,
+            transmit: Color::Blue,
+            sparkline: Color::Cyan,
+            unavailable: Color::DarkGray,
+            warning: Color::Yellow,
+            critical: Color::Red,
+            cpu_user: Color::Green,
+            cpu_nice: Color::Blue,
+            cpu_system: Color::Red,
+            cpu_steal: Color::Cyan,
+            cpu_irq: Color::Yellow,
+            cpu_softirq: Color::Magenta,
+            cpu_guest: Color::LightCyan,
+            memory_buffers: Color::Blue,
+            memory_shared: Color::LightMagenta,
+            memory_cache: Color::Gray,
+        }
+    }
+}
+
+impl Theme {
+    /// Returns the color of a gauge whose value is at `level`.
+    ///
+    /// # Arguments
+    /// * `normal` - Color of the gauge below its warning threshold
+    /// * `level` - Level of the displayed value
+    pub fn level_color(&self, normal: Color, level: Level) -> Color {
+        match level {
+            Level::Normal => normal,
+            Level::Warning => self.warning,
+            Level::Critical => self.critical,
+        }
+    }
+
+    /// Returns the color of a CPU mode in the CPU time bars.
+    ///
+    /// Idle and iowait time have no color: they make up the empty rest of
+    /// the bar, so the filled part matches the busy share shown next to it.
+    pub fn cpu_mode_color(&self, mode: CpuMode) -> Option<Color> {
+        match mode {
+            CpuMode::User => Some(self.cpu_user),
+            CpuMode::Nice => Some(self.cpu_nice),
+            CpuMode::System => Some(self.cpu_system),
+            CpuMode::Steal => Some(self.cpu_steal),
+            CpuMode::Irq => Some(self.cpu_irq),
+            CpuMode::Softirq => Some(self.cpu_softirq),
+            CpuMode::Guest => Some(self.cpu_guest),
+            CpuMode::Iowait | CpuMode::Idle => None,
+        }
+    }
+}
+
+/// Severity of a value relative to its [`Threshold`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    /// Below the warning threshold
+    Normal,
+    /// At or above the warning threshold
+    Warning,
+    /// At or above the critical threshold
+    Critical,
+}
+
+/// Warning and critical levels of a percentage metric.
+///
+/// # Fields
+/// * `warning` - Percentage from which the value is a warning
+/// * `critical` - Percentage from which the value is critical, at least
+///   `warning`
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+#[serde(try_from = "RawThreshold")]
+pub struct Threshold {
+    pub warning: f32,
+    pub critical: f32,
+}
+
+impl Threshold {
+    /// Classifies a percentage.
+    ///
+    /// `NaN`, reported for unavailable metrics, is normal.
+    pub fn level(&self, percent: f32) -> Level {
+        if percent >= self.critical {
+            Level::Critical
+        } else if percent >= self.warning {
+            Level::Warning
+        } else {
+            Level::Normal
+        }
+    }
+}
+
+/// Threshold as written in the file, validated into a [`Threshold`].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawThreshold {
+    warning: f32,
+    critical: f32,
+}
+
+impl TryFrom<RawThreshold> for Threshold {
+    type Error = String;
+
+    fn try_from(raw: RawThreshold) -> Result<Self, Self::Error> {
+        let valid = |value: f32| (0.0..=100.0).contains(&value);
+        if !valid(raw.warning) || !valid(raw.critical) {
+            return Err("thresholds must be percentages between 0 and 100".to_string());
+        }
+        if raw.warning > raw.critical {
+            return Err(format!(
+                "warning threshold {} is above the critical threshold {}",
+                raw.warning, raw.critical
+            ));
+        }
+        Ok(Self {
+            warning: raw.warning,
+            critical: raw.critical,
+        })
+    }
+}
+
+/// Thresholds of every percentage shown in a gauge.
+///
+/// # Fields
+/// * `cpu` - Aggregate and per-core CPU usage
+/// * `memory` - Physical memory usage
+/// * `swap` - Swap usage
+/// * `disk` - Usage of each filesystem
+/// * `pressure` - Share of time tasks were stalled on a resource
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Thresholds {
+    pub cpu: Threshold,
+    pub memory: Threshold,
+    pub swap: Threshold,
+    pub disk: Threshold,
+    pub pressure: Threshold,
+}
+
+impl Default for Thresholds {
+    fn default() -> Self {
+        let threshold = |warning, critical| Threshold { warning, critical };
+        Self {
+            cpu: threshold(75.0, 90.0),
+            memory: threshold(80.0, 95.0),
+            swap: threshold(50.0, 80.0),
+            disk: threshold(85.0, 95.0),
+            pressure: threshold(10.0, 40.0),
+        }
+    }
+}
+
+/// Error resolving the configuration.
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read
+    Io { path: PathBuf, source: io::Error },
+    /// The configuration file is not valid; `position` is the line and
+    /// column of the offending text, if known
+    Invalid {
+        path: PathBuf,
+        position: Option<(usize, usize)>,
+        message: String,
+    },
+    /// An environment variable holds an invalid value
+    Env { name: &'static str, message: String },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            ConfigError::Invalid {
+                path,
+                position: Some((line, column)),
+                message,
+            } => write!(f, "{}:{}:{}: {}", path.display(), line, column, message),
+            ConfigError::Invalid {
+                path,
+                position: None,
+                message,
+            } => write!(f, "{}: {}", path.display(), message),
+            ConfigError::Env { name, message } => write!(f, "{}: {}", name, message),
+        }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            ConfigError::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+/// Error in a TOML document, located by byte range.
+#[derive(Debug)]
+struct FileError {
+    span: Option<Range<usize>>,
+    message: String,
+}
+
+impl From<toml::de::Error> for FileError {
+    fn from(err: toml::de::Error) -> Self {
+        Self {
+            span: err.span(),
+            message: err.message().to_string(),
+        }
+    }
+}
+
+/// Contents of the configuration file; unset keys keep their value.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigFile {
+    interval: Option<Seconds>,
+    render_interval: Option<Seconds>,
+    tick_rate: Option<Seconds>,
+    history: Option<NonZeroUsize>,
+    views: Option<Spanned<Vec<View>>>,
+    layout: LayoutFile,
+    colors: ColorsFile,
+    thresholds: ThresholdsFile,
+    keys: BTreeMap<Action, Spanned<Vec<Spanned<KeySpec>>>>,
+    alerts: Vec<AlertFile>,
+    actions: Vec<Spanned<ActionFile>>,
+}
+
+/// `[[alerts]]` entry of the configuration file.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct AlertFile {
+    rule: RuleText,
+    name: Option<String>,
+    hysteresis: Option<Hysteresis>,
+}
+
+/// Alert rule such as `cpu > 90% for 30s`.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+struct RuleText(AlertRule);
+
+impl TryFrom<String> for RuleText {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse().map(RuleText).map_err(|err| err.to_string())
+    }
+}
+
+/// Non-negative number of percentage points.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "f32")]
+struct Hysteresis(f32);
+
+impl TryFrom<f32> for Hysteresis {
+    type Error = String;
+
+    fn try_from(points: f32) -> Result<Self, Self::Error> {
+        if points >= 0.0 && points.is_finite() {
+            Ok(Hysteresis(points))
+        } else {
+            Err(format!("hysteresis {} must not be negative", points))
+        }
+    }
+}
+
+/// `[[actions]]` entry of the configuration file; exactly one of `exec`,
+/// `log` and `webhook` must be given.
+#[derive(Debug, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ActionFile {
+    exec: Option<Spanned<Vec<String>>>,
+    log: Option<PathBuf>,
+    webhook: Option<UrlText>,
+    timeout: Option<Seconds>,
+    retries: Option<u32>,
+    retry_delay: Option<Seconds>,
+    cooldown: Option<Cooldown>,
+}
+
+/// Webhook URL such as `http://127.0.0.1:9000/alerts`.
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+struct UrlText(WebhookUrl);
+
+impl TryFrom<String> for UrlText {
+    type Error = String;
+
+    fn try_from(text: String) -> Result<Self, Self::Error> {
+        text.parse().map(UrlText).map_err(|err| err.to_string())
+    }
+}
+
+/// Non-negative number of seconds; zero disables the cooldown.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "f64")]
+struct Cooldown(Duration);
+
+impl TryFrom<f64> for Cooldown {
+    type Error = String;
+
+    fn try_from(seconds: f64) -> Result<Self, Self::Error> {
+        if seconds == 0.0 {
+            Ok(Cooldown(Duration::ZERO))
+        } else {
+            seconds_to_duration(seconds)
+                .map(Cooldown)
+                .map_err(|_| format!("cooldown {} must not be negative", seconds))
+        }
+    }
+}
+
+/// `[layout]` table of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LayoutFile {
+    cpu_percent: Option<Percent>,
+    chart_percent: Option<Percent>,
+    min_chart_width: Option<u16>,
+}
+
+/// `[colors]` table of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ColorsFile {
+    title: Option<ThemeColor>,
+    highlight: Option<ThemeColor>,
+    replay: Option<ThemeColor>,
+    cpu: Option<ThemeColor>,
+    memory: Option<ThemeColor>,
+    swap: Option<ThemeColor>,
+    disk: Option<ThemeColor>,
+    receive: Option<ThemeColor>,
+    transmit: Option<ThemeColor>,
+    sparkline: Option<ThemeColor>,
+    unavailable: Option<ThemeColor>,
+    warning: Option<ThemeColor>,
+    critical: Option<ThemeColor>,
+    cpu_user: Option<ThemeColor>,
+    cpu_nice: Option<ThemeColor>,
+    cpu_system: Option<ThemeColor>,
+    cpu_steal: Option<ThemeColor>,
+    cpu_irq: Option<ThemeColor>,
+    cpu_softirq: Option<ThemeColor>,
+    cpu_guest: Option<ThemeColor>,
+    memory_buffers: Option<ThemeColor>,
+    memory_shared: Option<ThemeColor>,
+    memory_cache: Option<ThemeColor>,
+}
+
+/// `[thresholds]` table of the configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ThresholdsFile {
+    cpu: Option<Threshold>,
+    memory: Option<Threshold>,
+    swap: Option<Threshold>,
+    disk: Option<Threshold>,
+    pressure: Option<Threshold>,
+}
+
+/// Positive number of seconds, such as `2` or `0.5`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "f64")]
+struct Seconds(Duration);
+
+impl TryFrom<f64> for Seconds {
+    type Error = String;
+
+    fn try_from(seconds: f64) -> Result<Self, Self::Error> {
+        seconds_to_duration(seconds).map(Seconds)
+    }
+}
+
+/// Percentage strictly between 0 and 100.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "u16")]
+struct Percent(u16);
+
+impl TryFrom<u16> for Percent {
+    type Error = String;
+
+    fn try_from(percent: u16) -> Result<Self, Self::Error> {
+        if (1..100).contains(&percent) {
+            Ok(Percent(percent))
+        } else {
+            Err(format!("{} is not a percentage between 1 and 99", percent))
+        }
+    }
+}
+
+/// Color given by name (e.g. `cyan`, `dark_gray`) or as `#rrggbb`.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+struct ThemeColor(Color);
+
+impl TryFrom<String> for ThemeColor {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        if let Some(hex) = name.strip_prefix('#') {
+            let channel = |i: usize| {
+                hex.get(i..i + 2)
+                    .and_then(|c| u8::from_str_radix(c, 16).ok())
+            };
+            return match (hex.len(), channel(0), channel(2), channel(4)) {
+                (6, Some(r), Some(g), Some(b)) => Ok(ThemeColor(Color::Rgb(r, g, b))),
+                _ => Err(format!("invalid color '{}' (expected #rrggbb)", name)),
+            };
+        }
+        let color = match name.to_ascii_lowercase().replace(['-', ' '], "_").as_str() {
+            "reset" | "default" => Color::Reset,
+            "black" => Color::Black,
+            "red" => Color::Red,
+            "green" => Color::Green,
+            "yellow" => Color::Yellow,
+            "blue" => Color::Blue,
+            "magenta" => Color::Magenta,
+            "cyan" => Color::Cyan,
+            "gray" | "grey" => Color::Gray,
+            "dark_gray" | "dark_grey" | "darkgray" | "darkgrey" => Color::DarkGray,
+            "light_red" => Color::LightRed,
+            "light_green" => Color::LightGreen,
+            "light_yellow" => Color::LightYellow,
+            "light_blue" => Color::LightBlue,
+            "light_magenta" => Color::LightMagenta,
+            "light_cyan" => Color::LightCyan,
+            "white" => Color::White,
+            _ => return Err(format!("unknown color '{}'", name)),
+        };
+        Ok(ThemeColor(color))
+    }
+}
+
+/// Builds an alert action from its `[[actions]]` entry.
+fn action_from_file(entry: Spanned<ActionFile>) -> Result<AlertAction, FileError> {
+    let span = entry.span();
+    let file = entry.into_inner();
+    let kind = match (file.exec, file.log, file.webhook) {
+        (Some(command), None, None) => {
+            if command.get_ref().is_empty() {
+                return Err(FileError {
+                    span: Some(command.span()),
+                    message: "exec needs at least the program to run".to_string(),
+                });
+            }
+            ActionKind::Exec(command.into_inner())
+        }
+        (None, Some(path), None) => ActionKind::Log(path),
+        (None, None, Some(UrlText(url))) => ActionKind::Webhook(url),
+        _ => {
+            return Err(FileError {
+                span: Some(span),
+                message: "an action needs exactly one of exec, log or webhook".to_string(),
+            })
+        }
+    };
+    let mut action = AlertAction::new(kind);
+    if let Some(Seconds(timeout)) = file.timeout {
+        action = action.with_timeout(timeout);
+    }
+    if let Some(retries) = file.retries {
+        action = action.with_retries(retries);
+    }
+    if let Some(Seconds(delay)) = file.retry_delay {
+        action = action.with_retry_delay(delay);
+    }
+    if let Some(Cooldown(cooldown)) = file.cooldown {
+        action = action.with_cooldown(cooldown);
+    }
+    Ok(action)
+}
+
+/// Converts a number of seconds into a duration.
+///
+/// # Returns
+/// * `Ok(Duration)` - The duration
+/// * `Err(String)` - The value is not a positive, finite number
+pub fn seconds_to_duration(seconds: f64) -> Result<Duration, String> {
+    if seconds.is_nan() || seconds <= 0.0 {
+        return Err("must be greater than zero".to_string());
+    }
+    Duration::try_from_secs_f64(seconds).map_err(|_| format!("{} seconds is out of range", seconds))
+}
+
+/// Returns the configuration file used when none is given explicitly.
+///
+/// # Arguments
+/// * `env` - Reads an environment variable
+fn default_path(env: &impl Fn(&str) -> Option<String>) -> Option<PathBuf> {
+    let absolute = |name| {
+        env(name)
+            .map(PathBuf::from)
+            .filter(|path| path.is_absolute())
+    };
+    let config_home = absolute("XDG_CONFIG_HOME")
+        .or_else(|| absolute("HOME").map(|home| home.join(".config")))?;
+    Some(config_home.join("system-monitor").join("config.toml"))
+}
+
+/// Converts a byte offset in `text` into a 1-based line and column.
+fn line_column(text: &str, offset: usize) -> (usize, usize) {
+    let before = text.get(..offset).unwrap_or(text);
+    let line = before.matches('\n').count() + 1;
+    let column = before.rsplit('\n').next().map_or(0, |l| l.chars().count()) + 1;
+    (line, column)
+}
+
+impl Config {
+    /// Resolves the configuration from the defaults, the configuration file
+    /// and the environment.
+    ///
+    /// # Arguments
+    /// * `path` - File given with `--config`; it must exist. Without it,
+    ///   `$SYSTEM_MONITOR_CONFIG` or the XDG default is read if present.
+    ///
+    /// # Errors
+    /// * The file cannot be read or is not valid
+    /// * An environment variable holds an invalid value
+    pub fn load(path: Option<&Path>) -> Result<Self, ConfigError> {
+        Self::resolve(path, |name| env::var(name).ok())
+    }
+
+    /// Resolves the configuration, reading variables through `env`.
+    fn resolve(
+        path: Option<&Path>,
+        env: impl Fn(&str) -> Option<String>,
+    ) -> Result<Self, ConfigError> {
+        let explicit = path
+            .map(Path::to_path_buf)
+            .or_else(|| env(CONFIG_VAR).map(PathBuf::from));
+        let file = explicit.or_else(|| default_path(&env).filter(|path| path.is_file()));
+
+        let mut config = Config::default();
+        if let Some(path) = file {
+            let text = fs::read_to_string(&path).map_err(|source| ConfigError::Io {
+                path: path.clone(),
+                source,
+            })?;
+            config
+                .merge_toml(&text)
+                .map_err(|err| ConfigError::Invalid {
+                    position: err.span.map(|span| line_column(&text, span.start)),
+                    message: err.message,
+                    path,
+                })?;
+        }
+        config.merge_env(&env)?;
+        Ok(config)
+    }
+
+    /// Applies the settings of a TOML document on top of this configuration.
+    fn merge_toml(&mut self, text: &str) -> Result<(), FileError> {
+        let file: ConfigFile = toml::from_str(text)?;
+
+        if let Some(Seconds(interval)) = file.interval {
+            self.sample_interval = interval;
+        }
+        if let Some(Seconds(interval)) = file.render_interval {
+            self.render_interval = interval;
+        }
+        if let Some(Seconds(tick_rate)) = file.tick_rate {
+            self.tick_rate = tick_rate;
+        }
+        if let Some(history) = file.history {
+            self.history_len = history.get();
+        }
+        if let Some(views) = file.views {
+            if views.get_ref().is_empty() {
+                return Err(FileError {
+                    span: Some(views.span()),
+                    message: "at least one view must be shown".to_string(),
+                });
+            }
+            self.set_views(views.into_inner());
+        }
+
+        let layout = file.layout;
+        if let Some(Percent(percent)) = layout.cpu_percent {
+            self.layout.cpu_percent = percent;
+        }
+        if let Some(Percent(percent)) = layout.chart_percent {
+            self.layout.chart_percent = percent;
+        }
+        if let Some(width) = layout.min_chart_width {
+            self.layout.min_chart_width = width;
+        }
+
+        let colors = file.colors;
+        let theme = &mut self.theme;
+        for (color, value) in [
+            (&mut theme.title, colors.title),
+            (&mut theme.highlight, colors.highlight),
+            (&mut theme.replay, colors.replay),
+            (&mut theme.cpu, colors.cpu),
+            (&mut theme.memory, colors.memory),
+            (&mut theme.swap, colors.swap),
+            (&mut theme.disk, colors.disk),
+            (&mut theme.receive, colors.receive),
+            (&mut theme.transmit, colors.transmit),
+            (&mut theme.sparkline, colors.sparkline),
+            (&mut theme.unavailable, colors.unavailable),
+            (&mut theme.warning, colors.warning),
+            (&mut theme.critical, colors.critical),
+            (&mut theme.cpu_user, colors.cpu_user),
+            (&mut theme.cpu_nice, colors.cpu_nice),
+            (&mut theme.cpu_system, colors.cpu_system),
+            (&mut theme.cpu_steal, colors.cpu_steal),
+            (&mut theme.cpu_irq, colors.cpu_irq),
+            (&mut theme.cpu_softirq, colors.cpu_softirq),
+            (&mut theme.cpu_guest, colors.cpu_guest),
+            (&mut theme.memory_buffers, colors.memory_buffers),
+            (&mut theme.memory_shared, colors.memory_shared),
+            (&mut theme.memory_cache, colors.memory_cache),
+        ] {
+            if let Some(ThemeColor(value)) = value {
+                *color = value;
+            }
+        }
+
+        let thresholds = file.thresholds;
+        let current = &mut self.thresholds;
+        for (threshold, value) in [
+            (&mut current.cpu, thresholds.cpu),
+            (&mut current.memory, thresholds.memory),
+            (&mut current.swap, thresholds.swap),
+            (&mut current.disk, thresholds.disk),
+            (&mut current.pressure, thresholds.pressure),
+        ] {
+            if let Some(value) = value {
+                *threshold = value;
+            }
+        }
+
+        if !file.alerts.is_empty() {
+            self.alerts = file
+                .alerts
+                .into_iter()
+                .map(|alert| {
+                    let mut rule = alert.rule.0;
+                    if let Some(Hysteresis(points)) = alert.hysteresis {
+                        rule = rule.with_hysteresis(points);
+                    }
+                    match alert.name {
+                        Some(name) => rule.named(name),
+                        None => rule,
+                    }
+                })
+                .collect();
+        }
+
+        if !file.actions.is_empty() {
+            self.actions = file
+                .actions
+                .into_iter()
+                .map(action_from_file)
+                .collect::<Result<_, _>>()?;
+        }
+
+        self.merge_keys(file.keys)
+    }
+
+    /// Rebinds the actions listed in the `[keys]` table.
+    ///
+    /// A key may be given to only one of the listed actions; keys of
+    /// actions that are not listed are taken over silently.
+    fn merge_keys(
+        &mut self,
+        keys: BTreeMap<Action, Spanned<Vec<Spanned<KeySpec>>>>,
+    ) -> Result<(), FileError> {
+        // Check in file order so the error points at the second use
+        let mut entries: Vec<_> = keys.into_iter().collect();
+        entries.sort_by_key(|(_, keys)| keys.span().start);
+
+        let mut bound: Vec<(KeySpec, Action)> = Vec::new();
+        for (action, keys) in entries {
+            for key in keys.get_ref() {
+                if let Some(&(_, other)) = bound.iter().find(|(spec, _)| spec == key.get_ref()) {
+                    return Err(FileError {
+                        span: Some(key.span()),
+                        message: format!(
+                            "key '{}' is bound to both '{}' and '{}'",
+                            key.get_ref(),
+                            other.name(),
+                            action.name()
+                        ),
+                    });
+                }
+                bound.push((*key.get_ref(), action));
+            }
+            let keys = keys
+                .into_inner()
+                .into_iter()
+                .map(Spanned::into_inner)
+                .collect();
+            self.keys.bind(action, keys);
+        }
+        Ok(())
+    }
+
+    /// Applies the environment variable overrides.
+    fn merge_env(&mut self, env: &impl Fn(&str) -> Option<String>) -> Result<(), ConfigError> {
+        if let Some(value) = env(INTERVAL_VAR) {
+            let seconds = value.trim().parse::<f64>().map_err(|_| ConfigError::Env {
+                name: INTERVAL_VAR,
+                message: format!("'{}' is not a number of seconds", value),
+            })?;
+            self.sample_interval =
+                seconds_to_duration(seconds).map_err(|message| ConfigError::Env {
+                    name: INTERVAL_VAR,
+                    message,
+                })?;
+        }
+        if let Some(value) = env(VIEWS_VAR) {
+            let views = value
+                .split(',')
+                .map(|name| {
+                    View::from_str(name.trim(), true)
+                        .map_err(|_| format!("unknown view '{}'", name.trim()))
+                })
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|message| ConfigError::Env {
+                    name: VIEWS_VAR,
+                    message,
+                })?;
+            self.set_views(views);
+        }
+        Ok(())
+    }
+
+    /// Shows `views` in the tab bar, dropping repeated entries.
+    ///
+    /// An empty list leaves the shown views unchanged.
+    pub fn set_views(&mut self, views: Vec<View>) {
+        if views.is_empty() {
+            return;
+        }
+        self.views.clear();
+        for view in views {
+            if !self.views.contains(&view) {
+                self.views.push(view);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Scroll;
+    use crossterm::event::KeyCode;
+
+    fn parse(text: &str) -> Result<Config, FileError> {
+        let mut config = Config::default();
+        config.merge_toml(text).map(|_| config)
+    }
+
+    /// Parses `text` and returns the line, column and message of the error.
+    fn error(text: &str) -> (usize, usize, String) {
+        let err = parse(text).expect_err("document should be rejected");
+        let (line, column) = line_column(text, err.span.expect("error should be located").start);
+        (line, column, err.message)
+    }
+
+    #[test]
+    fn test_empty_file_keeps_defaults() {
+        assert_eq!(parse("").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_full_file() {
+        let config = parse(
+            r##"
+interval = 2
+render_interval = 0.5
+history = 60
+views = ["network", "overview", "network"]
+
+[layout]
+cpu_percent = 70
+min_chart_width = 100
+
+[colors]
+cpu = "light_blue"
+memory = "#ff8000"
+cpu_steal = "light_red"
+memory_cache = "#808080"
+
+[thresholds]
+cpu = { warning = 50, critical = 60.5 }
+pressure = { warning = 5, critical = 20 }
+
+[keys]
+toggle_pause = ["space", "p"]
+scroll_down = ["n"]
+
+[[alerts]]
+rule = "cpu > 90% for 30s"
+
+[[alerts]]
+rule = "disk > 95"
+name = "Disk almost full"
+hysteresis = 1
+
+[[actions]]
+exec = ["notify-send", "System Monitor"]
+
+[[actions]]
+webhook = "http://localhost:9000/alerts"
+timeout = 2
+retries = 5
+retry_delay = 0.5
+cooldown = 0
+"##,
+        )
+        .unwrap();
+        assert_eq!(config.sample_interval, Duration::from_secs(2));
+        assert_eq!(config.render_interval, Duration::from_millis(500));
+        assert_eq!(config.tick_rate, DEFAULT_TICK_RATE);
+        assert_eq!(config.history_len, 60);
+        assert_eq!(config.views, vec![View::Network, View::Overview]);
+        assert_eq!(config.layout.cpu_percent, 70);
+        assert_eq!(config.layout.chart_percent, 50);
+        assert_eq!(config.layout.min_chart_width, 100);
+        assert_eq!(config.theme.cpu, Color::LightBlue);
+        assert_eq!(config.theme.memory, Color::Rgb(255, 128, 0));
+        assert_eq!(config.theme.cpu_steal, Color::LightRed);
+        assert_eq!(config.theme.cpu_user, Theme::default().cpu_user);
+        assert_eq!(config.theme.memory_cache, Color::Rgb(128, 128, 128));
+        assert_eq!(config.theme.swap, Theme::default().swap);
+        assert_eq!(config.thresholds.cpu.critical, 60.5);
+        assert_eq!(config.thresholds.memory, Thresholds::default().memory);
+        assert_eq!(config.thresholds.pressure.warning, 5.0);
+        assert_eq!(
+            config.keys.keys(Action::TogglePause),
+            &[
+                KeySpec::new(KeyCode::Char(' ')),
+                KeySpec::new(KeyCode::Char('p'))
+            ]
+        );
+        assert_eq!(
+            config.keys.keys(Action::Scroll(Scroll::Down)),
+            &[KeySpec::new(KeyCode::Char('n'))]
+        );
+        assert_eq!(
+            config.alerts,
+            vec![
+                "cpu > 90% for 30s".parse().unwrap(),
+                "disk > 95"
+                    .parse::<AlertRule>()
+                    .unwrap()
+                    .named("Disk almost full")
+                    .with_hysteresis(1.0),
+            ]
+        );
+        assert_eq!(
+            config.actions,
+            vec![
+                AlertAction::new(ActionKind::Exec(vec![
+                    "notify-send".to_string(),
+                    "System Monitor".to_string()
+                ])),
+                AlertAction::new(ActionKind::Webhook(
+                    "http://localhost:9000/alerts".parse().unwrap()
+                ))
+                .with_timeout(Duration::from_secs(2))
+                .with_retries(5)
+                .with_retry_delay(Duration::from_millis(500))
+                .with_cooldown(Duration::ZERO),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syntax_error_is_located() {
+        let (line, column, _) = error("interval = 1\nviews = [\"overview\" \"disks\"]\n");
+        assert_eq!((line, column), (2, 21));
+    }
+
+    #[test]
+    fn test_invalid_values_are_located() {
+        assert_eq!(
+            error("interval = 1\n\n[colors]\ncpu = \"purple\"\n"),
+            (4, 7, "unknown color 'purple'".to_string())
+        );
+        let (line, column, message) = error("interval = -1\n");
+        assert_eq!((line, column), (1, 12));
+        assert_eq!(message, "must be greater than zero");
+        let (line, _, message) = error("[thresholds]\nswap = { warning = 90, critical = 80 }\n");
+        assert_eq!(line, 2);
+        assert!(
+            message.contains("above the critical threshold"),
+            "{}",
+            message
+        );
+        let (line, _, message) = error("[layout]\ncpu_percent = 100\n");
+        assert_eq!(line, 2);
+        assert!(message.contains("between 1 and 99"), "{}", message);
+        assert_eq!(error("views = []\n").2, "at least one view must be shown");
+        let (line, column, message) = error("[[alerts]]\nrule = \"gpu > 90\"\n");
+        assert_eq!((line, column), (2, 8));
+        assert!(message.contains("unknown metric 'gpu'"), "{}", message);
+        let (line, _, message) = error("[[alerts]]\nrule = \"cpu > 90\"\nhysteresis = -1\n");
+        assert_eq!(line, 3);
+        assert!(message.contains("must not be negative"), "{}", message);
+    }
+
+    #[test]
+    fn test_invalid_actions_are_located() {
+        let (line, column, message) = error("[[actions]]\nwebhook = \"https://example.com\"\n");
+        assert_eq!((line, column), (2, 11));
+        assert!(message.contains("invalid webhook URL"), "{}", message);
+        let (line, column, message) = error("[[actions]]\nexec = []\n");
+        assert_eq!((line, column), (2, 8));
+        assert_eq!(message, "exec needs at least the program to run");
+        let (line, _, message) = error("\n[[actions]]\nlog = \"a.log\"\nwebhook = \"http://x/\"\n");
+        assert_eq!(line, 2);
+        assert_eq!(
+            message,
+            "an action needs exactly one of exec, log or webhook"
+        );
+        let (line, _, message) = error("[[actions]]\nlog = \"a.log\"\ncooldown = -1\n");
+        assert_eq!(line, 3);
+        assert!(message.contains("must not be negative"), "{}", message);
+    }
+
+    #[test]
+    fn test_unknown_names_are_located() {
+        let (line, _, message) = error("interval = 1\nintervall = 2\n");
+        assert_eq!(line, 2);
+        assert!(message.contains("unknown field `intervall`"), "{}", message);
+        let (line, _, message) = error("[keys]\nquit = [\"q\"]\nexplode = [\"x\"]\n");
+        assert_eq!(line, 3);
+        assert!(message.contains("unknown action 'explode'"), "{}", message);
+        let (line, column, message) = error("[keys]\nquit = [\"ctrl+hyper+q\"]\n");
+        assert_eq!((line, column), (2, 8));
+        assert!(message.contains("unknown modifier"), "{}", message);
+    }
+
+    #[test]
+    fn test_key_bound_twice_is_rejected() {
+        let (line, column, message) =
+            error("[keys]\nreset_stats = [\"x\"]\nquit = [\"q\", \"x\"]\n");
+        assert_eq!((line, column), (3, 14));
+        assert_eq!(message, "key 'x' is bound to both 'reset_stats' and 'quit'");
+    }
+
+    #[test]
+    fn test_layering() {
+        let dir = env::temp_dir().join(format!("system-monitor-config-{}", std::process::id()));
+        fs::create_dir_all(dir.join("system-monitor")).unwrap();
+        let path = dir.join("system-monitor").join("config.toml");
+        fs::write(&path, "interval = 5\nviews = [\"disks\"]\nhistory = 10\n").unwrap();
+
+        // XDG default, with the environment overriding the file
+        let xdg = dir.to_string_lossy().into_owned();
+        let config = Config::resolve(None, |name| match name {
+            "XDG_CONFIG_HOME" => Some(xdg.clone()),
+            INTERVAL_VAR => Some("0.5".to_string()),
+            _ => None,
+        })
+        .unwrap();
+        assert_eq!(config.sample_interval, Duration::from_millis(500));
+        assert_eq!(config.views, vec![View::Disks]);
+        assert_eq!(config.history_len, 10);
+
+        // An explicit path must exist
+        let missing = dir.join("missing.toml");
+        assert!(matches!(
+            Config::resolve(Some(&missing), |_| None),
+            Err(ConfigError::Io { .. })
+        ));
+
+        // No file at all keeps the defaults
+        let config = Config::resolve(None, |_| None).unwrap();
+        assert_eq!(config, Config::default());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_invalid_environment() {
+        let err = Config::resolve(None, |name| {
+            (name == VIEWS_VAR).then(|| "overview,graphs".to_string())
+        })
+        .unwrap_err();
+        assert!(matches!(
+            err,
+            ConfigError::Env {
+                name: VIEWS_VAR,
+                ..
+            }
+        ));
+        let err = Config::resolve(None, |name| (name == INTERVAL_VAR).then(|| "0".to_string()))
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "SYSTEM_MONITOR_INTERVAL: must be greater than zero"
+        );
+    }
+
+    #[test]
+    fn test_error_display_includes_position() {
+        let err = ConfigError::Invalid {
+            path: PathBuf::from("config.toml"),
+            position: Some((3, 7)),
+            message: "unknown color 'purple'".to_string(),
+        };
+        assert_eq!(err.to_string(), "config.toml:3:7: unknown color 'purple'");
+    }
+
+    #[test]
+    fn test_threshold_levels() {
+        let threshold = Threshold {
+            warning: 50.0,
+            critical: 90.0,
+        };
+        assert_eq!(threshold.level(10.0), Level::Normal);
+        assert_eq!(threshold.level(50.0), Level::Warning);
+        assert_eq!(threshold.level(95.0), Level::Critical);
+        assert_eq!(threshold.level(f32::NAN), Level::Normal);
+    }
+}
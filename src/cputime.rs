@@ -0,0 +1,455 @@
+//! CPU time breakdown from `/proc/stat`.
+//!
+//! A single busy percentage hides where the time goes. Linux counts, per
+//! core and for all cores together, the time spent in each mode since boot
+//! (in clock ticks):
+//!
+//! ```text
+//! cpu  2200 20 700 15800 200 10 70 300 50 0
+//! cpu0 1000 20 300 8000 50 5 25 100 0 0
+//! ```
+//!
+//! The columns are `user`, `nice`, `system`, `idle`, `iowait`, `irq`,
+//! `softirq`, `steal`, `guest` and `guest_nice`. [`CpuTimeTracker`] turns
+//! two successive readings into [`CpuShares`]: the percentage of the
+//! elapsed time spent in each mode. Steal time, the time a virtual machine
+//! was ready to run but the hypervisor ran something else, is only visible
+//! here.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Cumulative time spent in each mode, in clock ticks.
+///
+/// `user` and `nice` include the time spent running guests (`guest` and
+/// `guest_nice`), as reported by the kernel.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CpuTimes {
+    pub user: u64,
+    pub nice: u64,
+    pub system: u64,
+    pub idle: u64,
+    pub iowait: u64,
+    pub irq: u64,
+    pub softirq: u64,
+    pub steal: u64,
+    pub guest: u64,
+    pub guest_nice: u64,
+}
+
+impl CpuTimes {
+    /// Returns the total time, counting guest time once.
+    pub fn total(&self) -> u64 {
+        self.user
+            + self.nice
+            + self.system
+            + self.idle
+            + self.iowait
+            + self.irq
+            + self.softirq
+            + self.steal
+    }
+
+    /// Returns the increase from `earlier` to `self`.
+    ///
+    /// A counter that went backwards (a core was brought back online) is
+    /// treated as unchanged.
+    fn since(&self, earlier: &CpuTimes) -> CpuTimes {
+        CpuTimes {
+            user: self.user.saturating_sub(earlier.user),
+            nice: self.nice.saturating_sub(earlier.nice),
+            system: self.system.saturating_sub(earlier.system),
+            idle: self.idle.saturating_sub(earlier.idle),
+            iowait: self.iowait.saturating_sub(earlier.iowait),
+            irq: self.irq.saturating_sub(earlier.irq),
+            softirq: self.softirq.saturating_sub(earlier.softirq),
+            steal: self.steal.saturating_sub(earlier.steal),
+            guest: self.guest.saturating_sub(earlier.guest),
+            guest_nice: self.guest_nice.saturating_sub(earlier.guest_nice),
+        }
+    }
+}
+
+/// Counters of all cores together and of every core, as read from
+/// `/proc/stat`.
+///
+/// # Fields
+/// * `total` - Counters of all cores together (the `cpu` line)
+/// * `cores` - Counters of every online core with its id (the `cpuN`
+///   lines), ordered by id
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct ProcStat {
+    pub total: CpuTimes,
+    pub cores: Vec<(usize, CpuTimes)>,
+}
+
+impl ProcStat {
+    /// Reads `stat` under `proc_root`.
+    ///
+    /// # Returns
+    /// * `Option<ProcStat>` - The counters, or `None` if the file is
+    ///   missing (other platforms) or malformed
+    pub fn read(proc_root: &Path) -> Option<Self> {
+        let text = fs::read_to_string(proc_root.join("stat")).ok()?;
+        parse_stat(&text)
+    }
+}
+
+/// Parses the CPU lines of `/proc/stat`.
+///
+/// Other lines are ignored. Kernels before 2.6.33 report fewer columns;
+/// the missing ones are zero.
+///
+/// # Returns
+/// * `Option<ProcStat>` - The counters, or `None` if there is no `cpu`
+///   line or a CPU line has a non-numeric column
+///
+/// # Example
+/// ```
+/// use system_monitor::cputime::parse_stat;
+///
+/// let stat = parse_stat("cpu  10 0 5 85 0 0 0 0 0 0\ncpu0 10 0 5 85 0 0 0 0 0 0\n").unwrap();
+/// assert_eq!(stat.total.idle, 85);
+/// assert_eq!(stat.cores[0].0, 0);
+/// ```
+pub fn parse_stat(text: &str) -> Option<ProcStat> {
+    let mut total = None;
+    let mut cores = Vec::new();
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let Some(id) = fields.next().and_then(|name| name.strip_prefix("cpu")) else {
+            continue;
+        };
+        let mut columns = [0u64; 10];
+        for (column, field) in columns.iter_mut().zip(fields) {
+            *column = field.parse().ok()?;
+        }
+        let [user, nice, system, idle, iowait, irq, softirq, steal, guest, guest_nice] = columns;
+        let times = CpuTimes {
+            user,
+            nice,
+            system,
+            idle,
+            iowait,
+            irq,
+            softirq,
+            steal,
+            guest,
+            guest_nice,
+        };
+        if id.is_empty() {
+            total = Some(times);
+        } else {
+            cores.push((id.parse().ok()?, times));
+        }
+    }
+    cores.sort_by_key(|&(id, _)| id);
+    Some(ProcStat {
+        total: total?,
+        cores,
+    })
+}
+
+/// Mode a CPU spends its time in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CpuMode {
+    /// Running user-space code, guests excluded
+    User,
+    /// Running niced user-space code, guests excluded
+    Nice,
+    /// Running kernel code
+    System,
+    /// Ready to run while the hypervisor ran another virtual machine
+    Steal,
+    /// Servicing hardware interrupts
+    Irq,
+    /// Servicing software interrupts
+    Softirq,
+    /// Running a virtual machine guest
+    Guest,
+    /// Waiting for I/O while otherwise idle
+    Iowait,
+    /// Idle
+    Idle,
+}
+
+impl CpuMode {
+    /// All modes in the order they are stacked: busy modes with steal time
+    /// early so it stays visible on narrow legends, then waiting and idle.
+    pub const ALL: [CpuMode; 9] = [
+        CpuMode::User,
+        CpuMode::Nice,
+        CpuMode::System,
+        CpuMode::Steal,
+        CpuMode::Irq,
+        CpuMode::Softirq,
+        CpuMode::Guest,
+        CpuMode::Iowait,
+        CpuMode::Idle,
+    ];
+
+    /// Returns the lowercase name used by the kernel.
+    pub fn name(self) -> &'static str {
+        match self {
+            CpuMode::User => "user",
+            CpuMode::Nice => "nice",
+            CpuMode::System => "system",
+            CpuMode::Iowait => "iowait",
+            CpuMode::Irq => "irq",
+            CpuMode::Softirq => "softirq",
+            CpuMode::Steal => "steal",
+            CpuMode::Guest => "guest",
+            CpuMode::Idle => "idle",
+        }
+    }
+}
+
+/// Percentage of the elapsed time spent in each mode.
+///
+/// The shares add up to 100. Guest time is only counted in `guest`, not in
+/// `user` and `nice` as in the raw counters.
+///
+/// # Example
+/// ```
+/// use system_monitor::cputime::{CpuMode, CpuShares, CpuTimes};
+///
+/// let earlier = CpuTimes::default();
+/// let later = CpuTimes { user: 30, system: 10, idle: 50, steal: 10, ..CpuTimes::default() };
+/// let shares = CpuShares::between(&earlier, &later).unwrap();
+/// assert_eq!(shares.get(CpuMode::Steal), 10.0);
+/// assert_eq!(shares.busy(), 50.0);
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CpuShares {
+    pub user: f32,
+    pub nice: f32,
+    pub system: f32,
+    pub idle: f32,
+    pub iowait: f32,
+    pub irq: f32,
+    pub softirq: f32,
+    pub steal: f32,
+    pub guest: f32,
+}
+
+impl CpuShares {
+    /// Computes the shares of the time elapsed between two readings.
+    ///
+    /// # Returns
+    /// * `Option<CpuShares>` - The shares, or `None` if no time was counted
+    ///   in between
+    pub fn between(earlier: &CpuTimes, later: &CpuTimes) -> Option<Self> {
+        let delta = later.since(earlier);
+        let total = delta.total();
+        if total == 0 {
+            return None;
+        }
+        let share = |ticks: u64| (ticks as f64 / total as f64 * 100.0) as f32;
+        Some(Self {
+            user: share(delta.user.saturating_sub(delta.guest)),
+            nice: share(delta.nice.saturating_sub(delta.guest_nice)),
+            system: share(delta.system),
+            idle: share(delta.idle),
+            iowait: share(delta.iowait),
+            irq: share(delta.irq),
+            softirq: share(delta.softirq),
+            steal: share(delta.steal),
+            guest: share(delta.guest + delta.guest_nice),
+        })
+    }
+
+    /// Returns the share of one mode.
+    pub fn get(&self, mode: CpuMode) -> f32 {
+        match mode {
+            CpuMode::User => self.user,
+            CpuMode::Nice => self.nice,
+            CpuMode::System => self.system,
+            CpuMode::Iowait => self.iowait,
+            CpuMode::Irq => self.irq,
+            CpuMode::Softirq => self.softirq,
+            CpuMode::Steal => self.steal,
+            CpuMode::Guest => self.guest,
+            CpuMode::Idle => self.idle,
+        }
+    }
+
+    /// Returns the share of time the CPU was neither idle nor waiting for
+    /// I/O.
+    pub fn busy(&self) -> f32 {
+        (100.0 - self.idle - self.iowait).max(0.0)
+    }
+}
+
+/// Time shares of one core.
+///
+/// # Fields
+/// * `id` - Number of the core in its `cpuN` name, which matches
+///   [`CoreUsage::name`](crate::CoreUsage) rather than its index when some
+///   CPUs are offline
+/// * `shares` - Time spent in each mode
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct CoreShares {
+    pub id: usize,
+    #[serde(flatten)]
+    pub shares: CpuShares,
+}
+
+/// Time shares of all cores together and of every core.
+///
+/// # Fields
+/// * `total` - Shares of all cores together
+/// * `cores` - Shares of every core, ordered by id
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CpuTimeUsage {
+    pub total: CpuShares,
+    pub cores: Vec<CoreShares>,
+}
+
+/// Turns successive `/proc/stat` readings into time shares.
+///
+/// # Example
+/// ```
+/// use system_monitor::cputime::{parse_stat, CpuTimeTracker};
+///
+/// let mut tracker = CpuTimeTracker::default();
+/// assert_eq!(tracker.sample(parse_stat("cpu 0 0 0 0\n").unwrap()), None);
+///
+/// let usage = tracker.sample(parse_stat("cpu 60 0 20 20\n").unwrap()).unwrap();
+/// assert_eq!(usage.total.user, 60.0);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct CpuTimeTracker {
+    previous: Option<ProcStat>,
+    usage: Option<CpuTimeUsage>,
+}
+
+impl CpuTimeTracker {
+    /// Records a new reading.
+    ///
+    /// A reading in which no time was counted since the previous one (a
+    /// repeated reading) keeps the last computed shares.
+    ///
+    /// # Returns
+    /// * `Option<CpuTimeUsage>` - The shares since the previous reading,
+    ///   `None` until two readings were taken
+    pub fn sample(&mut self, stat: ProcStat) -> Option<CpuTimeUsage> {
+        let Some(previous) = &self.previous else {
+            self.previous = Some(stat);
+            return None;
+        };
+        let Some(total) = CpuShares::between(&previous.total, &stat.total) else {
+            return self.usage.clone();
+        };
+        let cores = stat
+            .cores
+            .iter()
+            .filter_map(|&(id, times)| {
+                let (_, earlier) = previous.cores.iter().find(|(core, _)| *core == id)?;
+                let shares = CpuShares::between(earlier, &times)?;
+                Some(CoreShares { id, shares })
+            })
+            .collect();
+        self.previous = Some(stat);
+        self.usage = Some(CpuTimeUsage { total, cores });
+        self.usage.clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::fixture_proc_root;
+
+    #[test]
+    fn test_read_fixture_file() {
+        let stat = ProcStat::read(&fixture_proc_root()).unwrap();
+        assert_eq!(
+            stat.total,
+            CpuTimes {
+                user: 2200,
+                nice: 20,
+                system: 700,
+                idle: 15800,
+                iowait: 200,
+                irq: 10,
+                softirq: 70,
+                steal: 300,
+                guest: 50,
+                guest_nice: 0,
+            }
+        );
+        assert_eq!(stat.cores.len(), 2);
+        assert_eq!(stat.cores[1].0, 1);
+        assert_eq!(stat.cores[1].1.steal, 200);
+        assert_eq!(stat.total.total(), 19_300);
+    }
+
+    #[test]
+    fn test_missing_stat_file() {
+        assert_eq!(ProcStat::read(Path::new("/nonexistent/proc")), None);
+    }
+
+    #[test]
+    fn test_parse_old_kernel_and_malformed_lines() {
+        // Before Linux 2.6.11 there were only four columns
+        let stat = parse_stat("cpu 1 2 3 4\ncpu0 1 2 3 4\n").unwrap();
+        assert_eq!(stat.total.idle, 4);
+        assert_eq!(stat.total.steal, 0);
+
+        assert_eq!(parse_stat("intr 1 2 3\n"), None);
+        assert_eq!(parse_stat("cpu 1 2 x 4\n"), None);
+        assert_eq!(parse_stat("cpu 1 2 3 4\ncpuX 1 2 3 4\n"), None);
+    }
+
+    #[test]
+    fn test_shares_split_out_guest_time() {
+        let earlier = CpuTimes::default();
+        let later = CpuTimes {
+            user: 40,
+            nice: 10,
+            system: 10,
+            idle: 20,
+            iowait: 5,
+            irq: 1,
+            softirq: 4,
+            steal: 10,
+            guest: 15,
+            guest_nice: 5,
+        };
+        let shares = CpuShares::between(&earlier, &later).unwrap();
+        assert_eq!(shares.user, 25.0);
+        assert_eq!(shares.nice, 5.0);
+        assert_eq!(shares.guest, 20.0);
+        assert_eq!(shares.steal, 10.0);
+        let sum: f32 = CpuMode::ALL.iter().map(|&mode| shares.get(mode)).sum();
+        assert!((sum - 100.0).abs() < 1e-3);
+        assert_eq!(shares.busy(), 75.0);
+        assert_eq!(CpuShares::between(&later, &later), None);
+    }
+
+    #[test]
+    fn test_tracker_computes_per_core_deltas() {
+        let fixture = ProcStat::read(&fixture_proc_root()).unwrap();
+        let later = parse_stat(
+            "cpu  2300 20 800 15900 200 10 70 400 50 0\n\
+             cpu0 1050 20 300 8000 50 5 25 150 0 0\n\
+             cpu1 1250 0 500 7900 150 5 45 250 50 0\n",
+        )
+        .unwrap();
+
+        let mut tracker = CpuTimeTracker::default();
+        assert_eq!(tracker.sample(fixture), None);
+        let usage = tracker.sample(later.clone()).unwrap();
+        assert_eq!(usage.total.steal, 25.0);
+        assert_eq!(usage.total.idle, 25.0);
+        assert_eq!(usage.cores.len(), 2);
+        assert_eq!(usage.cores[0].id, 0);
+        assert_eq!(usage.cores[0].shares.steal, 50.0);
+        assert!((usage.cores[1].shares.idle - 100.0 / 3.0).abs() < 1e-3);
+
+        // A repeated reading keeps the shares
+        assert_eq!(tracker.sample(later), Some(usage));
+    }
+}
@@ -0,0 +1,869 @@
+//! CSV recording with log rotation.
+//!
+//! A [`CsvRecorder`] appends one row per [`MetricsSnapshot`] to a CSV file.
+//! The first column is an ISO-8601 UTC timestamp; the remaining columns
+//! come from the selected [`MetricGroup`]s, always in [`MetricGroup::ALL`]
+//! order, so the layout of a file never depends on the order in which
+//! groups were requested. Unavailable values are left empty.
+//!
+//! The output is rotated when it grows past a size limit or when it has
+//! covered a time span, see [`Rotation`]. Rotated files are numbered:
+//! `metrics.csv.1` is the most recent, `metrics.csv.2` the one before, and
+//! so on. Every file starts with its own header row.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::{Duration, SystemTime};
+
+use crate::cputime::CpuMode;
+use crate::pressure::Resource;
+use crate::refresh::{Collector, RefreshPlan};
+use crate::snapshot::MetricsSnapshot;
+use crate::timefmt::iso8601;
+
+/// Group of related CSV columns.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MetricGroup {
+    /// Average CPU usage
+    Cpu,
+    /// Usage of every core, one column per core
+    Cores,
+    /// Physical memory
+    Memory,
+    /// Swap space
+    Swap,
+    /// Number of processes
+    Processes,
+    /// Filesystem capacity summed over non-pseudo filesystems
+    Disks,
+    /// Throughput summed over all interfaces
+    Networks,
+    /// Share of CPU time spent in each mode, including steal time
+    CpuTime,
+    /// Memory breakdown from `/proc/meminfo`
+    Meminfo,
+    /// Pressure stall averages of the CPU, memory and I/O
+    Pressure,
+    /// Usage and limits of the monitor's cgroup
+    Container,
+}
+
+impl MetricGroup {
+    /// All groups, in column order.
+    pub const ALL: [MetricGroup; 11] = [
+        MetricGroup::Cpu,
+        MetricGroup::Cores,
+        MetricGroup::Memory,
+        MetricGroup::Swap,
+        MetricGroup::Processes,
+        MetricGroup::Disks,
+        MetricGroup::Networks,
+        MetricGroup::CpuTime,
+        MetricGroup::Meminfo,
+        MetricGroup::Pressure,
+        MetricGroup::Container,
+    ];
+
+    /// Groups recorded when none are selected: the host-wide figures
+    /// available on every platform, without the per-core columns and the
+    /// process count, which needs a process scan.
+    pub const DEFAULT: [MetricGroup; 5] = [
+        MetricGroup::Cpu,
+        MetricGroup::Memory,
+        MetricGroup::Swap,
+        MetricGroup::Disks,
+        MetricGroup::Networks,
+    ];
+
+    /// Returns the lowercase identifier of the group.
+    pub fn name(self) -> &'static str {
+        match self {
+            MetricGroup::Cpu => "cpu",
+            MetricGroup::Cores => "cores",
+            MetricGroup::Memory => "memory",
+            MetricGroup::Swap => "swap",
+            MetricGroup::Processes => "processes",
+            MetricGroup::Disks => "disks",
+            MetricGroup::Networks => "networks",
+            MetricGroup::CpuTime => "cpu_time",
+            MetricGroup::Meminfo => "meminfo",
+            MetricGroup::Pressure => "pressure",
+            MetricGroup::Container => "container",
+        }
+    }
+
+    /// Returns the collector providing the group's data.
+    pub fn collector(self) -> Collector {
+        match self {
+            MetricGroup::Cpu
+            | MetricGroup::Cores
+            | MetricGroup::CpuTime
+            | MetricGroup::Pressure
+            | MetricGroup::Container => Collector::Cpu,
+            MetricGroup::Memory | MetricGroup::Swap | MetricGroup::Meminfo => Collector::Memory,
+            MetricGroup::Processes => Collector::Processes,
+            MetricGroup::Disks => Collector::Disks,
+            MetricGroup::Networks => Collector::Networks,
+        }
+    }
+
+    /// Parses a comma-separated list of group names, e.g. `cpu,memory`.
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::csv::MetricGroup;
+    ///
+    /// let groups = MetricGroup::parse_list("memory, cpu").unwrap();
+    /// assert_eq!(groups, vec![MetricGroup::Memory, MetricGroup::Cpu]);
+    /// assert!(MetricGroup::parse_list("cpu,gpu").is_err());
+    /// ```
+    pub fn parse_list(list: &str) -> Result<Vec<MetricGroup>, UnknownGroup> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(str::parse)
+            .collect()
+    }
+}
+
+impl FromStr for MetricGroup {
+    type Err = UnknownGroup;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|group| group.name() == name)
+            .ok_or_else(|| UnknownGroup(name.to_string()))
+    }
+}
+
+/// Error returned when parsing an unknown metric group name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownGroup(pub String);
+
+impl fmt::Display for UnknownGroup {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let names: Vec<&str> = MetricGroup::ALL.iter().map(|g| g.name()).collect();
+        write!(
+            f,
+            "unknown metric group '{}' (expected one of: {})",
+            self.0,
+            names.join(", ")
+        )
+    }
+}
+
+impl Error for UnknownGroup {}
+
+/// Builds the refresh plan collecting exactly what `groups` need.
+///
+/// # Arguments
+/// * `groups` - Recorded metric groups
+///
+/// # Returns
+/// * `RefreshPlan` - The default plan restricted to the needed collectors
+pub fn refresh_plan(groups: &[MetricGroup]) -> RefreshPlan {
+    let default = RefreshPlan::default();
+    Collector::ALL
+        .into_iter()
+        .filter(|&collector| groups.iter().any(|g| g.collector() == collector))
+        .fold(RefreshPlan::none(), |plan, collector| {
+            plan.with_interval(collector, default.interval(collector).unwrap_or_default())
+        })
+}
+
+/// When the output file is rotated.
+///
+/// # Fields
+/// * `max_bytes` - Rotate before a row would grow the file past this size
+/// * `max_age` - Rotate once the rows of a file span this much time,
+///   measured on the snapshot timestamps
+/// * `keep` - Number of rotated files kept; older ones are deleted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Rotation {
+    pub max_bytes: Option<u64>,
+    pub max_age: Option<Duration>,
+    pub keep: usize,
+}
+
+/// Default number of rotated files kept.
+pub const DEFAULT_KEEP: usize = 5;
+
+impl Default for Rotation {
+    /// Never rotates; keeps `DEFAULT_KEEP` files once limits are set.
+    fn default() -> Self {
+        Self {
+            max_bytes: None,
+            max_age: None,
+            keep: DEFAULT_KEEP,
+        }
+    }
+}
+
+/// Columns of the `Meminfo` group, in bytes.
+const MEMINFO_COLUMNS: [&str; 10] = [
+    "memory_available_bytes",
+    "memory_buffers_bytes",
+    "memory_cached_bytes",
+    "memory_shared_bytes",
+    "memory_slab_bytes",
+    "memory_slab_reclaimable_bytes",
+    "memory_dirty_bytes",
+    "memory_writeback_bytes",
+    "memory_hugepages_total_bytes",
+    "memory_hugepages_used_bytes",
+];
+
+/// Averaging windows of the `Pressure` group's columns.
+const PRESSURE_WINDOWS: [&str; 3] = ["avg10", "avg60", "avg300"];
+
+/// Columns of the `Container` group.
+const CONTAINER_COLUMNS: [&str; 5] = [
+    "container_memory_used_bytes",
+    "container_memory_limit_bytes",
+    "container_cpu_limit_cpus",
+    "container_cpu_usage",
+    "container_cpu_throttled_periods",
+];
+
+/// Column layout of a recording, fixed when the first row is written.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Layout {
+    groups: Vec<MetricGroup>,
+    /// Core ids of the `Cores` group, from the first snapshot
+    cores: Vec<usize>,
+}
+
+impl Layout {
+    fn new(groups: &[MetricGroup], first: &MetricsSnapshot) -> Self {
+        Self {
+            groups: MetricGroup::ALL
+                .into_iter()
+                .filter(|group| groups.contains(group))
+                .collect(),
+            cores: first.cores.iter().map(|core| core.id).collect(),
+        }
+    }
+
+    fn header(&self) -> String {
+        let mut columns = vec!["timestamp".to_string()];
+        for group in &self.groups {
+            match group {
+                MetricGroup::Cpu => columns.push("cpu_usage".to_string()),
+                MetricGroup::Cores => {
+                    columns.extend(self.cores.iter().map(|id| format!("core{}_usage", id)))
+                }
+                MetricGroup::Memory => columns.extend(
+                    ["memory_total_bytes", "memory_used_bytes", "memory_usage"].map(String::from),
+                ),
+                MetricGroup::Swap => columns.extend(
+                    ["swap_total_bytes", "swap_used_bytes", "swap_usage"].map(String::from),
+                ),
+                MetricGroup::Processes => columns.push("process_count".to_string()),
+                MetricGroup::Disks => {
+                    columns.extend(["disk_total_bytes", "disk_available_bytes"].map(String::from))
+                }
+                MetricGroup::Networks => columns.extend(
+                    ["network_rx_bytes_per_sec", "network_tx_bytes_per_sec"].map(String::from),
+                ),
+                MetricGroup::CpuTime => columns.extend(
+                    CpuMode::ALL
+                        .iter()
+                        .map(|mode| format!("cpu_{}_share", mode.name())),
+                ),
+                MetricGroup::Meminfo => columns.extend(MEMINFO_COLUMNS.map(String::from)),
+                MetricGroup::Pressure => {
+                    for resource in Resource::ALL {
+                        for kind in ["some", "full"] {
+                            columns.extend(PRESSURE_WINDOWS.map(|window| {
+                                format!("{}_pressure_{}_{}", resource.name(), kind, window)
+                            }));
+                        }
+                    }
+                }
+                MetricGroup::Container => columns.extend(CONTAINER_COLUMNS.map(String::from)),
+            }
+        }
+        columns.join(",")
+    }
+
+    fn row(&self, snapshot: &MetricsSnapshot) -> String {
+        let percent = |value: Option<f32>| {
+            value
+                .filter(|value| value.is_finite())
+                .map(|value| format!("{:.2}", value))
+                .unwrap_or_default()
+        };
+        let mut fields = vec![iso8601(snapshot.timestamp)];
+        for group in &self.groups {
+            match group {
+                MetricGroup::Cpu => fields.push(percent(snapshot.cpu_usage().ok())),
+                MetricGroup::Cores => fields.extend(self.cores.iter().map(|id| {
+                    percent(
+                        snapshot
+                            .cores
+                            .iter()
+                            .find(|core| core.id == *id)
+                            .map(|core| core.usage),
+                    )
+                })),
+                MetricGroup::Memory => fields.extend([
+                    snapshot.total_memory.to_string(),
+                    snapshot.used_memory.to_string(),
+                    percent(snapshot.memory_usage().ok()),
+                ]),
+                MetricGroup::Swap => fields.extend([
+                    snapshot.total_swap.to_string(),
+                    snapshot.used_swap.to_string(),
+                    percent(snapshot.swap_usage()),
+                ]),
+                MetricGroup::Processes => fields.push(snapshot.processes.len().to_string()),
+                MetricGroup::Disks => {
+                    let disks = snapshot.disks.iter().filter(|disk| !disk.is_pseudo());
+                    let (total, available) = disks.fold((0, 0), |(total, available), disk| {
+                        (total + disk.total, available + disk.available)
+                    });
+                    fields.extend([total.to_string(), available.to_string()]);
+                }
+                MetricGroup::Networks => {
+                    let (rx, tx) = snapshot.networks.iter().fold((0.0, 0.0), |(rx, tx), i| {
+                        (rx + i.rx_bytes_per_sec, tx + i.tx_bytes_per_sec)
+                    });
+                    fields.extend([format!("{:.1}", rx), format!("{:.1}", tx)]);
+                }
+                MetricGroup::CpuTime => {
+                    let total = snapshot.cpu_time.as_ref().map(|usage| &usage.total);
+                    fields.extend(
+                        CpuMode::ALL
+                            .iter()
+                            .map(|&mode| percent(total.map(|shares| shares.get(mode)))),
+                    );
+                }
+                MetricGroup::Meminfo => match &snapshot.meminfo {
+                    Some(meminfo) => fields.extend(
+                        [
+                            meminfo.available,
+                            meminfo.buffers,
+                            meminfo.cached,
+                            meminfo.shared,
+                            meminfo.slab,
+                            meminfo.slab_reclaimable,
+                            meminfo.dirty,
+                            meminfo.writeback,
+                            meminfo.hugepages.total_bytes(),
+                            meminfo.hugepages.used_bytes(),
+                        ]
+                        .map(|bytes| bytes.to_string()),
+                    ),
+                    None => fields.extend(MEMINFO_COLUMNS.map(|_| String::new())),
+                },
+                MetricGroup::Pressure => {
+                    for resource in Resource::ALL {
+                        let stalls = snapshot
+                            .pressure
+                            .as_ref()
+                            .and_then(|pressure| pressure.get(resource));
+                        for stats in [
+                            stalls.map(|stalls| stalls.some),
+                            stalls.and_then(|stalls| stalls.full),
+                        ] {
+                            fields.extend([
+                                percent(stats.map(|stats| stats.avg10)),
+                                percent(stats.map(|stats| stats.avg60)),
+                                percent(stats.map(|stats| stats.avg300)),
+                            ]);
+                        }
+                    }
+                }
+                MetricGroup::Container => match &snapshot.container {
+                    Some(container) => fields.extend([
+                        container.memory_used.to_string(),
+                        container
+                            .memory_limit
+                            .map(|limit| limit.to_string())
+                            .unwrap_or_default(),
+                        container
+                            .cpu_limit()
+                            .map(|cpus| format!("{:.2}", cpus))
+                            .unwrap_or_default(),
+                        percent(container.cpu_usage),
+                        container.cpu.nr_throttled.to_string(),
+                    ]),
+                    None => fields.extend(CONTAINER_COLUMNS.map(|_| String::new())),
+                },
+            }
+        }
+        fields.join(",")
+    }
+}
+
+/// Open output file and what has been written to it.
+#[derive(Debug)]
+struct Output {
+    file: File,
+    bytes: u64,
+    /// Timestamp of the first row, `None` while only the header is written
+    started: Option<SystemTime>,
+}
+
+/// Appends snapshots as CSV rows to a rotating file.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use system_monitor::csv::{CsvRecorder, MetricGroup, Rotation};
+/// use system_monitor::App;
+///
+/// let rotation = Rotation {
+///     max_bytes: Some(10 * 1024 * 1024),
+///     max_age: Some(Duration::from_secs(24 * 3600)),
+///     ..Rotation::default()
+/// };
+/// let mut recorder =
+///     CsvRecorder::new("metrics.csv", &[MetricGroup::Cpu, MetricGroup::Memory], rotation).unwrap();
+/// let mut app = App::new();
+/// loop {
+///     recorder.record(&app.update()).unwrap();
+///     std::thread::sleep(Duration::from_secs(1));
+/// }
+/// ```
+#[derive(Debug)]
+pub struct CsvRecorder {
+    path: PathBuf,
+    groups: Vec<MetricGroup>,
+    rotation: Rotation,
+    layout: Option<Layout>,
+    output: Option<Output>,
+}
+
+impl CsvRecorder {
+    /// Creates a recorder writing to `path`.
+    ///
+    /// An existing non-empty file at `path` is rotated away first, so
+    /// every file has a single header matching its rows.
+    ///
+    /// # Arguments
+    /// * `path` - Output file
+    /// * `groups` - Metric groups recorded as columns; empty selects
+    ///   [`MetricGroup::DEFAULT`]
+    /// * `rotation` - Rotation limits
+    ///
+    /// # Errors
+    /// * The existing file cannot be rotated
+    pub fn new(
+        path: impl Into<PathBuf>,
+        groups: &[MetricGroup],
+        rotation: Rotation,
+    ) -> io::Result<Self> {
+        let recorder = Self {
+            path: path.into(),
+            groups: if groups.is_empty() {
+                MetricGroup::DEFAULT.to_vec()
+            } else {
+                groups.to_vec()
+            },
+            rotation,
+            layout: None,
+            output: None,
+        };
+        if fs::metadata(&recorder.path).is_ok_and(|meta| meta.len() > 0) {
+            recorder.shift_files()?;
+        }
+        Ok(recorder)
+    }
+
+    /// Returns the path of the file currently written.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the recorded groups, in column order once recording started.
+    pub fn groups(&self) -> &[MetricGroup] {
+        self.layout
+            .as_ref()
+            .map_or(&self.groups, |layout| &layout.groups)
+    }
+
+    /// Appends one row, rotating the file first if a limit is reached.
+    ///
+    /// The row is written with a single `write` call and is not buffered,
+    /// so a killed recorder leaves at most a partial last line.
+    pub fn record(&mut self, snapshot: &MetricsSnapshot) -> io::Result<()> {
+        let layout = self
+            .layout
+            .get_or_insert_with(|| Layout::new(&self.groups, snapshot));
+        let row = format!("{}\n", layout.row(snapshot));
+
+        if self
+            .output
+            .as_ref()
+            .is_some_and(|output| self.needs_rotation(output, snapshot.timestamp, row.len()))
+        {
+            self.output = None;
+            self.shift_files()?;
+        }
+        let output = match &mut self.output {
+            Some(output) => output,
+            None => self.output.insert(self.open()?),
+        };
+        output.file.write_all(row.as_bytes())?;
+        output.bytes += row.len() as u64;
+        output.started.get_or_insert(snapshot.timestamp);
+        Ok(())
+    }
+
+    /// Returns `true` if writing a row of `len` bytes taken at `timestamp`
+    /// would exceed a rotation limit.
+    ///
+    /// A file holding no row yet is never rotated, so a row larger than
+    /// the size limit still gets written.
+    fn needs_rotation(&self, output: &Output, timestamp: SystemTime, len: usize) -> bool {
+        let Some(started) = output.started else {
+            return false;
+        };
+        let too_big = self
+            .rotation
+            .max_bytes
+            .is_some_and(|max| output.bytes + len as u64 > max);
+        let too_old = self.rotation.max_age.is_some_and(|max| {
+            timestamp
+                .duration_since(started)
+                .is_ok_and(|age| age >= max)
+        });
+        too_big || too_old
+    }
+
+    /// Creates the output file and writes the header.
+    fn open(&self) -> io::Result<Output> {
+        let mut file = OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(&self.path)?;
+        let header = format!(
+            "{}\n",
+            self.layout.as_ref().map(Layout::header).unwrap_or_default()
+        );
+        file.write_all(header.as_bytes())?;
+        Ok(Output {
+            file,
+            bytes: header.len() as u64,
+            started: None,
+        })
+    }
+
+    /// Renames `path` to `path.1`, `path.1` to `path.2`, and so on,
+    /// deleting the file that would exceed `keep`.
+    fn shift_files(&self) -> io::Result<()> {
+        if self.rotation.keep == 0 {
+            return remove_if_exists(&self.path);
+        }
+        remove_if_exists(&self.rotated_path(self.rotation.keep))?;
+        for index in (1..self.rotation.keep).rev() {
+            rename_if_exists(&self.rotated_path(index), &self.rotated_path(index + 1))?;
+        }
+        rename_if_exists(&self.path, &self.rotated_path(1))
+    }
+
+    /// Returns the path of the `index`-th most recent rotated file.
+    pub fn rotated_path(&self, index: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", index));
+        PathBuf::from(name)
+    }
+}
+
+fn remove_if_exists(path: &Path) -> io::Result<()> {
+    match fs::remove_file(path) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+fn rename_if_exists(from: &Path, to: &Path) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(()),
+        result => result,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cputime::{CpuShares, CpuTimeUsage};
+    use crate::disk::DiskUsage;
+    use crate::meminfo::MemInfo;
+    use crate::pressure::{Pressure, ResourcePressure, StallStats};
+    use crate::snapshot::CoreUsage;
+    use std::time::UNIX_EPOCH;
+
+    /// Directory removed when the test ends.
+    struct TempDir(PathBuf);
+
+    impl TempDir {
+        fn new(name: &str) -> Self {
+            let path = std::env::temp_dir().join(format!(
+                "system-monitor-csv-{}-{}",
+                name,
+                std::process::id()
+            ));
+            let _ = fs::remove_dir_all(&path);
+            fs::create_dir_all(&path).unwrap();
+            Self(path)
+        }
+    }
+
+    impl Drop for TempDir {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    fn snapshot(seconds: u64) -> MetricsSnapshot {
+        MetricsSnapshot {
+            timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+            cores: vec![
+                CoreUsage {
+                    id: 0,
+                    name: "cpu0".to_string(),
+                    usage: 10.0,
+                },
+                CoreUsage {
+                    id: 1,
+                    name: "cpu1".to_string(),
+                    usage: 30.0,
+                },
+            ],
+            total_memory: 1000,
+            used_memory: 250,
+            disks: vec![DiskUsage {
+                name: "/dev/sda1".to_string(),
+                mount_point: "/".to_string(),
+                file_system: "ext4".to_string(),
+                total: 500,
+                available: 100,
+            }],
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    fn lines(path: &Path) -> Vec<String> {
+        fs::read_to_string(path)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect()
+    }
+
+    #[test]
+    fn test_header_and_rows() {
+        let dir = TempDir::new("rows");
+        let path = dir.0.join("metrics.csv");
+        let groups = [MetricGroup::Memory, MetricGroup::Cpu, MetricGroup::Swap];
+        let mut recorder = CsvRecorder::new(&path, &groups, Rotation::default()).unwrap();
+        recorder.record(&snapshot(0)).unwrap();
+        recorder.record(&snapshot(1)).unwrap();
+
+        assert_eq!(
+            lines(&path),
+            vec![
+                "timestamp,cpu_usage,memory_total_bytes,memory_used_bytes,memory_usage,\
+                 swap_total_bytes,swap_used_bytes,swap_usage",
+                "1970-01-01T00:00:00.000Z,20.00,1000,250,25.00,0,0,",
+                "1970-01-01T00:00:01.000Z,20.00,1000,250,25.00,0,0,",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_core_columns_are_fixed_by_first_snapshot() {
+        let dir = TempDir::new("cores");
+        let path = dir.0.join("metrics.csv");
+        let mut recorder =
+            CsvRecorder::new(&path, &[MetricGroup::Cores], Rotation::default()).unwrap();
+        recorder.record(&snapshot(0)).unwrap();
+        let mut fewer = snapshot(1);
+        fewer.cores.truncate(1);
+        recorder.record(&fewer).unwrap();
+
+        let lines = lines(&path);
+        assert_eq!(lines[0], "timestamp,core0_usage,core1_usage");
+        assert_eq!(lines[2], "1970-01-01T00:00:01.000Z,10.00,");
+    }
+
+    #[test]
+    fn test_unavailable_values_are_empty() {
+        let dir = TempDir::new("empty");
+        let path = dir.0.join("metrics.csv");
+        let mut recorder =
+            CsvRecorder::new(&path, &[MetricGroup::Cpu], Rotation::default()).unwrap();
+        let mut snapshot = snapshot(0);
+        snapshot.cores[0].usage = f32::NAN;
+        recorder.record(&snapshot).unwrap();
+        assert_eq!(lines(&path)[1], "1970-01-01T00:00:00.000Z,");
+    }
+
+    #[test]
+    fn test_size_rotation() {
+        let dir = TempDir::new("size");
+        let path = dir.0.join("metrics.csv");
+        // Header (20 bytes) and one row (31 bytes) fit, a second row does not
+        let rotation = Rotation {
+            max_bytes: Some(60),
+            keep: 2,
+            ..Rotation::default()
+        };
+        let mut recorder = CsvRecorder::new(&path, &[MetricGroup::Cpu], rotation).unwrap();
+        for seconds in 0..4 {
+            recorder.record(&snapshot(seconds)).unwrap();
+        }
+
+        assert_eq!(lines(&path).len(), 2);
+        assert_eq!(lines(&path)[0], "timestamp,cpu_usage");
+        assert!(lines(&path)[1].starts_with("1970-01-01T00:00:03"));
+        assert!(lines(&recorder.rotated_path(1))[1].starts_with("1970-01-01T00:00:02"));
+        assert!(lines(&recorder.rotated_path(2))[1].starts_with("1970-01-01T00:00:01"));
+        assert!(
+            !recorder.rotated_path(3).exists(),
+            "Files beyond `keep` are deleted"
+        );
+    }
+
+    #[test]
+    fn test_time_rotation() {
+        let dir = TempDir::new("time");
+        let path = dir.0.join("metrics.csv");
+        let rotation = Rotation {
+            max_age: Some(Duration::from_secs(60)),
+            ..Rotation::default()
+        };
+        let mut recorder = CsvRecorder::new(&path, &[MetricGroup::Cpu], rotation).unwrap();
+        for seconds in [0, 30, 59, 60, 90] {
+            recorder.record(&snapshot(seconds)).unwrap();
+        }
+
+        assert_eq!(lines(&recorder.rotated_path(1)).len(), 4);
+        assert_eq!(lines(&path).len(), 3);
+        assert!(lines(&path)[1].starts_with("1970-01-01T00:01:00"));
+    }
+
+    #[test]
+    fn test_existing_file_is_rotated_away() {
+        let dir = TempDir::new("existing");
+        let path = dir.0.join("metrics.csv");
+        fs::write(&path, "old,layout\n1,2\n").unwrap();
+        let mut recorder =
+            CsvRecorder::new(&path, &[MetricGroup::Cpu], Rotation::default()).unwrap();
+        recorder.record(&snapshot(0)).unwrap();
+
+        assert_eq!(lines(&path)[0], "timestamp,cpu_usage");
+        assert_eq!(lines(&recorder.rotated_path(1))[0], "old,layout");
+    }
+
+    #[test]
+    fn test_default_groups() {
+        let recorder =
+            CsvRecorder::new("/nonexistent/metrics.csv", &[], Rotation::default()).unwrap();
+        assert_eq!(recorder.groups(), MetricGroup::DEFAULT);
+    }
+
+    #[test]
+    fn test_disk_totals_skip_pseudo_filesystems() {
+        let mut snapshot = snapshot(0);
+        snapshot.disks.push(DiskUsage {
+            name: "tmpfs".to_string(),
+            mount_point: "/run".to_string(),
+            file_system: "tmpfs".to_string(),
+            total: 1_000_000,
+            available: 1_000_000,
+        });
+        let layout = Layout::new(&[MetricGroup::Disks], &snapshot);
+        assert_eq!(layout.row(&snapshot), "1970-01-01T00:00:00.000Z,500,100");
+    }
+
+    #[test]
+    fn test_linux_breakdown_columns() {
+        let mut snapshot = snapshot(0);
+        snapshot.cpu_time = Some(CpuTimeUsage {
+            total: CpuShares {
+                user: 50.0,
+                steal: 12.5,
+                idle: 37.5,
+                ..CpuShares::default()
+            },
+            cores: Vec::new(),
+        });
+        snapshot.meminfo = Some(MemInfo {
+            available: 700,
+            cached: 200,
+            ..MemInfo::default()
+        });
+        let stats = |avg10| StallStats {
+            avg10,
+            ..StallStats::default()
+        };
+        snapshot.pressure = Some(Pressure {
+            cpu: Some(ResourcePressure {
+                some: stats(4.2),
+                full: None,
+            }),
+            ..Pressure::default()
+        });
+        let groups = [
+            MetricGroup::Container,
+            MetricGroup::Pressure,
+            MetricGroup::Meminfo,
+            MetricGroup::CpuTime,
+        ];
+        let layout = Layout::new(&groups, &snapshot);
+        let header = layout.header();
+        let row = layout.row(&snapshot);
+        let columns: Vec<&str> = header.split(',').collect();
+        let fields: Vec<&str> = row.split(',').collect();
+        assert_eq!(columns.len(), fields.len());
+        let field = |name: &str| fields[columns.iter().position(|&c| c == name).unwrap()];
+
+        assert_eq!(columns[1], "cpu_user_share", "Groups follow column order");
+        assert_eq!(field("cpu_steal_share"), "12.50");
+        assert_eq!(field("memory_available_bytes"), "700");
+        assert_eq!(field("memory_cached_bytes"), "200");
+        assert_eq!(field("cpu_pressure_some_avg10"), "4.20");
+        assert_eq!(field("cpu_pressure_full_avg10"), "", "CPU has no full line");
+        assert_eq!(field("io_pressure_some_avg300"), "");
+        assert_eq!(field("container_memory_used_bytes"), "", "No cgroup");
+    }
+
+    #[test]
+    fn test_breakdown_groups_are_opt_in() {
+        for group in [
+            MetricGroup::CpuTime,
+            MetricGroup::Meminfo,
+            MetricGroup::Pressure,
+            MetricGroup::Container,
+        ] {
+            assert!(!MetricGroup::DEFAULT.contains(&group));
+            assert_eq!(group.name().parse(), Ok(group));
+        }
+    }
+
+    #[test]
+    fn test_refresh_plan_only_enables_needed_collectors() {
+        let plan = refresh_plan(&[MetricGroup::Cores, MetricGroup::Swap]);
+        assert!(plan.is_enabled(Collector::Cpu));
+        assert!(plan.is_enabled(Collector::Memory));
+        assert!(!plan.is_enabled(Collector::Processes));
+        assert!(!plan.is_enabled(Collector::Disks));
+    }
+
+    #[test]
+    fn test_parse_groups() {
+        assert_eq!("disks".parse(), Ok(MetricGroup::Disks));
+        let err = MetricGroup::parse_list("cpu,gpu").unwrap_err();
+        assert_eq!(err, UnknownGroup("gpu".to_string()));
+        assert!(err.to_string().contains("expected one of: cpu, cores"));
+    }
+}
@@ -0,0 +1,136 @@
+//! Disk and filesystem usage per mount point.
+//!
+//! Provides the [`DiskUsage`] record collected for every mounted
+//! filesystem and the filter used to hide pseudo filesystems such as
+//! `tmpfs` or `overlay`, which rarely reflect real storage pressure.
+
+use serde::{Deserialize, Serialize};
+
+/// Filesystem types hidden by default.
+///
+/// These are memory-backed, virtual or layered filesystems whose usage
+/// does not correspond to a physical storage device.
+pub const PSEUDO_FILESYSTEMS: &[&str] = &[
+    "autofs",
+    "binfmt_misc",
+    "bpf",
+    "cgroup",
+    "cgroup2",
+    "configfs",
+    "debugfs",
+    "devpts",
+    "devtmpfs",
+    "efivarfs",
+    "fusectl",
+    "hugetlbfs",
+    "mqueue",
+    "nsfs",
+    "overlay",
+    "proc",
+    "pstore",
+    "ramfs",
+    "securityfs",
+    "squashfs",
+    "sysfs",
+    "tmpfs",
+    "tracefs",
+];
+
+/// Usage of a single mounted filesystem.
+///
+/// # Fields
+/// * `name` - Device name (e.g. `/dev/sda1`)
+/// * `mount_point` - Path where the filesystem is mounted
+/// * `file_system` - Filesystem type (e.g. `ext4`, `tmpfs`)
+/// * `total` - Total capacity in bytes
+/// * `available` - Space available to unprivileged users in bytes
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DiskUsage {
+    pub name: String,
+    pub mount_point: String,
+    pub file_system: String,
+    pub total: u64,
+    pub available: u64,
+}
+
+impl DiskUsage {
+    /// Returns the used space in bytes.
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Returns the used space as a percentage of the total capacity.
+    ///
+    /// # Returns
+    /// * `f32` - Usage between 0.0 and 100.0, or 0.0 for empty filesystems
+    pub fn used_percent(&self) -> f32 {
+        if self.total == 0 {
+            return 0.0;
+        }
+        (self.used() as f64 / self.total as f64 * 100.0) as f32
+    }
+
+    /// Returns `true` if this is a pseudo filesystem.
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::DiskUsage;
+    ///
+    /// let disk = DiskUsage {
+    ///     name: "tmpfs".to_string(),
+    ///     mount_point: "/run".to_string(),
+    ///     file_system: "tmpfs".to_string(),
+    ///     total: 1024,
+    ///     available: 1024,
+    /// };
+    /// assert!(disk.is_pseudo());
+    /// ```
+    pub fn is_pseudo(&self) -> bool {
+        PSEUDO_FILESYSTEMS.contains(&self.file_system.as_str())
+            || self.file_system.starts_with("fuse.")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn disk(file_system: &str, total: u64, available: u64) -> DiskUsage {
+        DiskUsage {
+            name: "/dev/sda1".to_string(),
+            mount_point: "/".to_string(),
+            file_system: file_system.to_string(),
+            total,
+            available,
+        }
+    }
+
+    #[test]
+    fn test_used_space() {
+        let root = disk("ext4", 1000, 250);
+        assert_eq!(root.used(), 750);
+        assert_eq!(root.used_percent(), 75.0);
+    }
+
+    #[test]
+    fn test_empty_filesystem() {
+        let empty = disk("ext4", 0, 0);
+        assert_eq!(empty.used(), 0);
+        assert_eq!(empty.used_percent(), 0.0);
+    }
+
+    #[test]
+    fn test_available_exceeding_total_does_not_underflow() {
+        assert_eq!(disk("ext4", 100, 200).used(), 0);
+    }
+
+    #[test]
+    fn test_pseudo_filesystems() {
+        for fs in ["tmpfs", "overlay", "devtmpfs", "squashfs", "fuse.portal"] {
+            assert!(disk(fs, 1, 1).is_pseudo(), "{} should be pseudo", fs);
+        }
+        for fs in ["ext4", "xfs", "btrfs", "apfs", "ntfs", "vfat", "fuseblk"] {
+            assert!(!disk(fs, 1, 1).is_pseudo(), "{} should be real", fs);
+        }
+    }
+}
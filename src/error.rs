@@ -0,0 +1,67 @@
+//! Errors reported when a metric cannot be computed.
+//!
+//! Containers and restricted hosts may report no CPU cores or zero total
+//! memory. Rather than dividing by zero and handing NaN or infinity to the
+//! caller, the affected accessors return a [`MonitorError`] describing why
+//! the metric is unavailable.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::snapshot::Metric;
+
+/// Reason why a metric is unavailable.
+///
+/// # Example
+/// ```
+/// use system_monitor::{App, MonitorError, Reading, ScriptedSource};
+///
+/// let mut app = App::with_source(ScriptedSource::new(vec![Reading::default()]), 10);
+/// app.update();
+/// assert_eq!(app.get_cpu_usage(), Err(MonitorError::NoCpuCores));
+/// assert_eq!(app.get_memory_usage(), Err(MonitorError::NoMemory));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MonitorError {
+    /// The source reported no CPU cores
+    NoCpuCores,
+    /// The source reported zero total memory
+    NoMemory,
+    /// The source reported a value that is not a finite number
+    NonFinite(Metric),
+}
+
+impl fmt::Display for MonitorError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MonitorError::NoCpuCores => write!(f, "no CPU cores reported"),
+            MonitorError::NoMemory => write!(f, "total memory reported as zero"),
+            MonitorError::NonFinite(metric) => {
+                write!(f, "{} usage is not a finite number", metric.name())
+            }
+        }
+    }
+}
+
+impl Error for MonitorError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_display() {
+        assert_eq!(
+            MonitorError::NoCpuCores.to_string(),
+            "no CPU cores reported"
+        );
+        assert_eq!(
+            MonitorError::NoMemory.to_string(),
+            "total memory reported as zero"
+        );
+        assert_eq!(
+            MonitorError::NonFinite(Metric::Cpu).to_string(),
+            "cpu usage is not a finite number"
+        );
+    }
+}
@@ -0,0 +1,192 @@
+//! HTTP endpoint exposing metrics to Prometheus.
+//!
+//! [`MetricsServer`] serves the latest snapshot of a
+//! [`BackgroundCollector`] on `/metrics` in the Prometheus text exposition
+//! format. Requests are handled one at a time on the calling thread; the
+//! metrics themselves are collected on the collector's thread, so a scrape
+//! never waits for a refresh.
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream, ToSocketAddrs};
+use std::time::Duration;
+
+use crate::background::BackgroundCollector;
+use crate::prometheus;
+
+/// Default address of the `/metrics` endpoint.
+pub const DEFAULT_LISTEN_ADDR: &str = "127.0.0.1:9184";
+
+/// Path on which the metrics are served.
+pub const METRICS_PATH: &str = "/metrics";
+
+/// Time allowed for a client to send its request.
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Largest request head accepted, in bytes.
+const MAX_REQUEST_SIZE: usize = 8 * 1024;
+
+/// Minimal HTTP response.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Response {
+    status: &'static str,
+    content_type: &'static str,
+    body: String,
+}
+
+impl Response {
+    fn text(status: &'static str, body: &str) -> Self {
+        Self {
+            status,
+            content_type: "text/plain; charset=utf-8",
+            body: format!("{}\n", body),
+        }
+    }
+}
+
+/// Prometheus scrape endpoint backed by a background collector.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use system_monitor::exporter::{MetricsServer, DEFAULT_LISTEN_ADDR};
+/// use system_monitor::{App, BackgroundCollector};
+///
+/// let collector = BackgroundCollector::spawn(App::new(), Duration::from_secs(1)).unwrap();
+/// let server = MetricsServer::bind(DEFAULT_LISTEN_ADDR, collector).unwrap();
+/// println!("Serving on http://{}/metrics", server.local_addr().unwrap());
+/// server.serve().unwrap();
+/// ```
+#[derive(Debug)]
+pub struct MetricsServer {
+    listener: TcpListener,
+    collector: BackgroundCollector,
+}
+
+impl MetricsServer {
+    /// Binds the endpoint to `addr`.
+    ///
+    /// # Arguments
+    /// * `addr` - Address to listen on; port 0 picks a free port
+    /// * `collector` - Collector whose latest snapshot is served
+    pub fn bind(addr: impl ToSocketAddrs, collector: BackgroundCollector) -> io::Result<Self> {
+        Ok(Self {
+            listener: TcpListener::bind(addr)?,
+            collector,
+        })
+    }
+
+    /// Returns the address the endpoint is listening on.
+    pub fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    /// Handles requests until accepting a connection fails.
+    ///
+    /// Errors on individual connections (e.g. a client hanging up) are
+    /// ignored so that one bad client cannot stop the endpoint.
+    pub fn serve(&self) -> io::Result<()> {
+        for stream in self.listener.incoming() {
+            let _ = self.handle(stream?);
+        }
+        Ok(())
+    }
+
+    /// Reads one request from `stream` and writes the response.
+    fn handle(&self, mut stream: TcpStream) -> io::Result<()> {
+        stream.set_read_timeout(Some(REQUEST_TIMEOUT))?;
+        let response = match read_request_line(&stream) {
+            Ok(line) => self.respond(&line),
+            Err(_) => Response::text("400 Bad Request", "Bad Request"),
+        };
+        write!(
+            stream,
+            "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            response.status,
+            response.content_type,
+            response.body.len(),
+            response.body
+        )?;
+        stream.flush()
+    }
+
+    /// Routes a request line such as `GET /metrics HTTP/1.1`.
+    fn respond(&self, request_line: &str) -> Response {
+        let mut parts = request_line.split_whitespace();
+        let (method, target) = match (parts.next(), parts.next()) {
+            (Some(method), Some(target)) => (method, target),
+            _ => return Response::text("400 Bad Request", "Bad Request"),
+        };
+        if method != "GET" {
+            return Response::text("405 Method Not Allowed", "Method Not Allowed");
+        }
+        // Query strings (e.g. from Prometheus' `params`) are ignored
+        match target.split('?').next() {
+            Some(METRICS_PATH) => Response {
+                status: "200 OK",
+                content_type: prometheus::CONTENT_TYPE,
+                body: prometheus::encode(&self.collector.latest()),
+            },
+            Some("/") => Response::text("200 OK", "System Monitor exporter: see /metrics"),
+            _ => Response::text("404 Not Found", "Not Found"),
+        }
+    }
+}
+
+/// Reads the request line and discards the headers that follow.
+fn read_request_line(stream: &TcpStream) -> io::Result<String> {
+    let mut reader = BufReader::new(stream.take(MAX_REQUEST_SIZE as u64));
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    loop {
+        let mut header = String::new();
+        if reader.read_line(&mut header)? == 0 || header.trim_end().is_empty() {
+            break;
+        }
+    }
+    Ok(request_line.trim_end().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::{Reading, ScriptedSource};
+    use crate::App;
+
+    fn server() -> MetricsServer {
+        let reading = Reading {
+            total_memory: 100,
+            used_memory: 40,
+            ..Reading::default()
+        };
+        let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+        let collector = BackgroundCollector::spawn(app, Duration::from_secs(3600)).unwrap();
+        MetricsServer::bind("127.0.0.1:0", collector).unwrap()
+    }
+
+    #[test]
+    fn test_metrics_route() {
+        let response = server().respond("GET /metrics HTTP/1.1");
+        assert_eq!(response.status, "200 OK");
+        assert_eq!(response.content_type, prometheus::CONTENT_TYPE);
+        assert!(response
+            .body
+            .contains("system_monitor_memory_used_bytes 40\n"));
+    }
+
+    #[test]
+    fn test_query_string_is_ignored() {
+        let response = server().respond("GET /metrics?target=x HTTP/1.1");
+        assert_eq!(response.status, "200 OK");
+    }
+
+    #[test]
+    fn test_unknown_path_and_method() {
+        let server = server();
+        assert_eq!(server.respond("GET /nope HTTP/1.1").status, "404 Not Found");
+        assert_eq!(
+            server.respond("POST /metrics HTTP/1.1").status,
+            "405 Method Not Allowed"
+        );
+        assert_eq!(server.respond("").status, "400 Bad Request");
+    }
+}
@@ -0,0 +1,168 @@
+//! Bounded metric history.
+//!
+//! [`History`] is a fixed-capacity ring buffer: once full, every new
+//! sample evicts the oldest one. It backs the per-metric time series kept
+//! by [`App`](crate::App) and the per-interface throughput sparklines.
+
+use std::collections::VecDeque;
+use std::time::{Duration, SystemTime};
+
+/// Default number of samples kept per metric.
+///
+/// At the default one-second sample interval this covers five minutes.
+pub const DEFAULT_HISTORY_LEN: usize = 300;
+
+/// A timestamped metric value.
+///
+/// # Fields
+/// * `timestamp` - Time at which the value was collected
+/// * `value` - The collected value
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Sample {
+    pub timestamp: SystemTime,
+    pub value: f32,
+}
+
+/// Fixed-capacity ring buffer of samples, oldest first.
+///
+/// # Example
+/// ```
+/// use system_monitor::History;
+///
+/// let mut history = History::new(2);
+/// history.push(1);
+/// history.push(2);
+/// history.push(3);
+/// assert_eq!(history.iter().copied().collect::<Vec<_>>(), vec![2, 3]);
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct History<T> {
+    capacity: usize,
+    samples: VecDeque<T>,
+}
+
+impl<T> History<T> {
+    /// Creates an empty history holding at most `capacity` samples.
+    ///
+    /// A capacity of zero is raised to one so the latest sample is always
+    /// available.
+    pub fn new(capacity: usize) -> Self {
+        let capacity = capacity.max(1);
+        Self {
+            capacity,
+            samples: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Appends a sample, evicting the oldest one if the history is full.
+    pub fn push(&mut self, sample: T) {
+        if self.samples.len() == self.capacity {
+            self.samples.pop_front();
+        }
+        self.samples.push_back(sample);
+    }
+
+    /// Returns the maximum number of samples kept.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Returns the number of samples currently kept.
+    pub fn len(&self) -> usize {
+        self.samples.len()
+    }
+
+    /// Returns `true` if no sample has been recorded.
+    pub fn is_empty(&self) -> bool {
+        self.samples.is_empty()
+    }
+
+    /// Returns the most recent sample.
+    pub fn latest(&self) -> Option<&T> {
+        self.samples.back()
+    }
+
+    /// Iterates over the samples, oldest first.
+    pub fn iter(&self) -> impl DoubleEndedIterator<Item = &T> + ExactSizeIterator {
+        self.samples.iter()
+    }
+
+    /// Removes all samples.
+    pub fn clear(&mut self) {
+        self.samples.clear();
+    }
+}
+
+impl History<Sample> {
+    /// Returns the time covered by the samples, from oldest to newest.
+    pub fn span(&self) -> Duration {
+        match (self.samples.front(), self.samples.back()) {
+            (Some(oldest), Some(newest)) => newest
+                .timestamp
+                .duration_since(oldest.timestamp)
+                .unwrap_or_default(),
+            _ => Duration::ZERO,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn values(history: &History<u32>) -> Vec<u32> {
+        history.iter().copied().collect()
+    }
+
+    #[test]
+    fn test_push_within_capacity() {
+        let mut history = History::new(3);
+        history.push(1);
+        history.push(2);
+        assert_eq!(values(&history), vec![1, 2]);
+        assert_eq!(history.len(), 2);
+        assert_eq!(history.latest(), Some(&2));
+    }
+
+    #[test]
+    fn test_oldest_evicted_when_full() {
+        let mut history = History::new(3);
+        for value in 1..=5 {
+            history.push(value);
+        }
+        assert_eq!(values(&history), vec![3, 4, 5]);
+        assert_eq!(history.len(), history.capacity());
+    }
+
+    #[test]
+    fn test_zero_capacity_keeps_latest() {
+        let mut history = History::new(0);
+        history.push(1);
+        history.push(2);
+        assert_eq!(history.capacity(), 1);
+        assert_eq!(values(&history), vec![2]);
+    }
+
+    #[test]
+    fn test_clear() {
+        let mut history = History::new(2);
+        history.push(1);
+        history.clear();
+        assert!(history.is_empty());
+        assert_eq!(history.latest(), None);
+    }
+
+    #[test]
+    fn test_span() {
+        let start = SystemTime::UNIX_EPOCH;
+        let mut history = History::new(10);
+        assert_eq!(history.span(), Duration::ZERO);
+        for seconds in [0, 1, 5] {
+            history.push(Sample {
+                timestamp: start + Duration::from_secs(seconds),
+                value: 0.0,
+            });
+        }
+        assert_eq!(history.span(), Duration::from_secs(5));
+    }
+}
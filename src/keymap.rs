@@ -0,0 +1,371 @@
+//! Key bindings of the terminal UI.
+//!
+//! A [`KeyMap`] maps key presses to [`Action`]s. Every action has default
+//! keys; the `[keys]` table of the configuration file replaces the keys of
+//! individual actions, e.g. `toggle_pause = ["space"]`.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use serde::Deserialize;
+
+use crate::{Action, Scroll};
+
+/// A key, optionally combined with Ctrl, Alt or Shift.
+///
+/// Parsed from strings such as `q`, `ctrl+c`, `shift+tab`, `pgdn` or `f5`.
+/// Letters are case-sensitive; key names and modifiers are not.
+///
+/// # Fields
+/// * `code` - The key itself
+/// * `modifiers` - Modifiers that must be held
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct KeySpec {
+    code: KeyCode,
+    modifiers: KeyModifiers,
+}
+
+impl KeySpec {
+    /// Creates a binding for `code` pressed without modifiers.
+    pub const fn new(code: KeyCode) -> Self {
+        Self {
+            code,
+            modifiers: KeyModifiers::NONE,
+        }
+    }
+
+    /// Creates a binding for `c` pressed together with Ctrl.
+    pub const fn ctrl(c: char) -> Self {
+        Self {
+            code: KeyCode::Char(c),
+            modifiers: KeyModifiers::CONTROL,
+        }
+    }
+
+    /// Returns `true` if `key` is a press of this binding.
+    ///
+    /// Shift is ignored on characters and Shift+Tab, since terminals
+    /// report it inconsistently for symbols such as `+` or `<`.
+    pub fn matches(&self, key: &KeyEvent) -> bool {
+        let mut modifiers = key.modifiers;
+        if matches!(key.code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        self.code == key.code && self.modifiers == modifiers
+    }
+}
+
+impl FromStr for KeySpec {
+    type Err = String;
+
+    fn from_str(spec: &str) -> Result<Self, Self::Err> {
+        let mut modifiers = KeyModifiers::NONE;
+        let mut key = spec.trim();
+        // A lone or trailing '+' is the plus key, not a separator
+        while let Some((modifier, rest)) = key
+            .split_once('+')
+            .filter(|(m, r)| !m.is_empty() && !r.is_empty())
+        {
+            modifiers |= match modifier.to_ascii_lowercase().as_str() {
+                "ctrl" | "control" => KeyModifiers::CONTROL,
+                "alt" => KeyModifiers::ALT,
+                "shift" => KeyModifiers::SHIFT,
+                _ => return Err(format!("unknown modifier '{}' in key '{}'", modifier, spec)),
+            };
+            key = rest;
+        }
+
+        let mut chars = key.chars();
+        let code = match (chars.next(), chars.next()) {
+            (Some(c), None) => KeyCode::Char(c),
+            _ => match key.to_ascii_lowercase().as_str() {
+                "tab" => KeyCode::Tab,
+                "backtab" => KeyCode::BackTab,
+                "enter" | "return" => KeyCode::Enter,
+                "esc" | "escape" => KeyCode::Esc,
+                "space" => KeyCode::Char(' '),
+                "backspace" => KeyCode::Backspace,
+                "delete" | "del" => KeyCode::Delete,
+                "insert" | "ins" => KeyCode::Insert,
+                "up" => KeyCode::Up,
+                "down" => KeyCode::Down,
+                "left" => KeyCode::Left,
+                "right" => KeyCode::Right,
+                "home" => KeyCode::Home,
+                "end" => KeyCode::End,
+                "pageup" | "pgup" => KeyCode::PageUp,
+                "pagedown" | "pgdn" => KeyCode::PageDown,
+                name => match name.strip_prefix('f').and_then(|n| n.parse().ok()) {
+                    Some(n @ 1..=12) => KeyCode::F(n),
+                    _ => return Err(format!("unknown key '{}'", spec)),
+                },
+            },
+        };
+
+        // Shift is part of the character or of Shift+Tab
+        let code = match code {
+            KeyCode::Tab if modifiers.contains(KeyModifiers::SHIFT) => KeyCode::BackTab,
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::SHIFT) => {
+                KeyCode::Char(c.to_ascii_uppercase())
+            }
+            KeyCode::Char(c) if modifiers.contains(KeyModifiers::CONTROL) => {
+                KeyCode::Char(c.to_ascii_lowercase())
+            }
+            code => code,
+        };
+        if matches!(code, KeyCode::Char(_) | KeyCode::BackTab) {
+            modifiers.remove(KeyModifiers::SHIFT);
+        }
+        Ok(Self { code, modifiers })
+    }
+}
+
+impl TryFrom<String> for KeySpec {
+    type Error = String;
+
+    fn try_from(spec: String) -> Result<Self, Self::Error> {
+        spec.parse()
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let ctrl = self.modifiers.contains(KeyModifiers::CONTROL);
+        if ctrl {
+            f.write_str("Ctrl+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::ALT) {
+            f.write_str("Alt+")?;
+        }
+        if self.modifiers.contains(KeyModifiers::SHIFT) {
+            f.write_str("Shift+")?;
+        }
+        match self.code {
+            KeyCode::Char(' ') => f.write_str("Space"),
+            KeyCode::Char(c) if ctrl => write!(f, "{}", c.to_ascii_uppercase()),
+            KeyCode::Char(c) => write!(f, "{}", c),
+            KeyCode::Tab => f.write_str("Tab"),
+            KeyCode::BackTab => f.write_str("S-Tab"),
+            KeyCode::Enter => f.write_str("Enter"),
+            KeyCode::Esc => f.write_str("Esc"),
+            KeyCode::Backspace => f.write_str("Backspace"),
+            KeyCode::Delete => f.write_str("Del"),
+            KeyCode::Insert => f.write_str("Ins"),
+            KeyCode::Up => f.write_str("Up"),
+            KeyCode::Down => f.write_str("Down"),
+            KeyCode::Left => f.write_str("Left"),
+            KeyCode::Right => f.write_str("Right"),
+            KeyCode::Home => f.write_str("Home"),
+            KeyCode::End => f.write_str("End"),
+            KeyCode::PageUp => f.write_str("PgUp"),
+            KeyCode::PageDown => f.write_str("PgDn"),
+            KeyCode::F(n) => write!(f, "F{}", n),
+            other => write!(f, "{:?}", other),
+        }
+    }
+}
+
+/// Mapping from key presses to actions.
+///
+/// Raw mode swallows `SIGINT`, so Ctrl+C always quits, whatever the
+/// bindings say.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct KeyMap {
+    bindings: Vec<(Action, Vec<KeySpec>)>,
+}
+
+impl Default for KeyMap {
+    fn default() -> Self {
+        let key = KeySpec::new;
+        let char = |c| KeySpec::new(KeyCode::Char(c));
+        let bindings = Action::ALL
+            .into_iter()
+            .map(|action| {
+                let keys = match action {
+                    Action::Quit => vec![char('q'), KeySpec::ctrl('c')],
+                    Action::ToggleHelp => vec![char('h')],
+                    Action::CloseHelp => vec![key(KeyCode::Esc)],
+                    Action::TogglePause => vec![char('p')],
+                    Action::ResetStats => vec![char('r')],
+                    Action::NextView => vec![key(KeyCode::Tab)],
+                    Action::PreviousView => vec![key(KeyCode::BackTab)],
+                    Action::Scroll(Scroll::Up) => vec![key(KeyCode::Up), char('k')],
+                    Action::Scroll(Scroll::Down) => vec![key(KeyCode::Down), char('j')],
+                    Action::Scroll(Scroll::PageUp) => vec![key(KeyCode::PageUp)],
+                    Action::Scroll(Scroll::PageDown) => vec![key(KeyCode::PageDown)],
+                    Action::Scroll(Scroll::Top) => vec![key(KeyCode::Home)],
+                    Action::Scroll(Scroll::Bottom) => vec![key(KeyCode::End)],
+                    Action::SortNextColumn => vec![char('>')],
+                    Action::SortPreviousColumn => vec![char('<')],
+                    Action::InvertSort => vec![char('i')],
+                    Action::TogglePseudoFilesystems => vec![char('f')],
+                    Action::StepForward => vec![key(KeyCode::Right), char('.')],
+                    Action::StepBackward => vec![key(KeyCode::Left), char(',')],
+                    Action::Faster => vec![char('+'), char('=')],
+                    Action::Slower => vec![char('-')],
+                };
+                (action, keys)
+            })
+            .collect();
+        Self { bindings }
+    }
+}
+
+impl KeyMap {
+    /// Returns the action bound to `key`.
+    ///
+    /// # Returns
+    /// * `Option<Action>` - The bound action, or `None` for unbound keys
+    pub fn action(&self, key: &KeyEvent) -> Option<Action> {
+        if KeySpec::ctrl('c').matches(key) {
+            return Some(Action::Quit);
+        }
+        self.bindings
+            .iter()
+            .find(|(_, keys)| keys.iter().any(|spec| spec.matches(key)))
+            .map(|&(action, _)| action)
+    }
+
+    /// Returns the keys bound to `action`.
+    pub fn keys(&self, action: Action) -> &[KeySpec] {
+        self.bindings
+            .iter()
+            .find(|(bound, _)| *bound == action)
+            .map_or(&[], |(_, keys)| keys)
+    }
+
+    /// Replaces the keys of `action`.
+    ///
+    /// The keys are taken away from any other action they were bound to,
+    /// so rebinding never leaves a key with two meanings.
+    pub fn bind(&mut self, action: Action, keys: Vec<KeySpec>) {
+        for (bound, bound_keys) in &mut self.bindings {
+            if *bound == action {
+                bound_keys.clone_from(&keys);
+            } else {
+                bound_keys.retain(|key| !keys.contains(key));
+            }
+        }
+    }
+
+    /// Describes the keys of `actions` for the help overlay.
+    ///
+    /// A single action lists all of its keys (`q / Ctrl+C`); a group of
+    /// actions lists the first key of each (`PgUp / PgDn`).
+    pub fn describe(&self, actions: &[Action]) -> String {
+        let keys: Vec<String> = match actions {
+            [action] => self.keys(*action).iter().map(KeySpec::to_string).collect(),
+            _ => actions
+                .iter()
+                .filter_map(|&action| self.keys(action).first())
+                .map(KeySpec::to_string)
+                .collect(),
+        };
+        if keys.is_empty() {
+            "unbound".to_string()
+        } else {
+            keys.join(" / ")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn press(code: KeyCode, modifiers: KeyModifiers) -> KeyEvent {
+        KeyEvent::new(code, modifiers)
+    }
+
+    #[test]
+    fn test_parse_key_specs() {
+        let parse = |spec: &str| spec.parse::<KeySpec>().unwrap();
+        assert_eq!(parse("q"), KeySpec::new(KeyCode::Char('q')));
+        assert_eq!(parse("Q"), KeySpec::new(KeyCode::Char('Q')));
+        assert_eq!(parse("+"), KeySpec::new(KeyCode::Char('+')));
+        assert_eq!(parse("Ctrl+C"), KeySpec::ctrl('c'));
+        assert_eq!(parse("shift+tab"), KeySpec::new(KeyCode::BackTab));
+        assert_eq!(parse("shift+x"), KeySpec::new(KeyCode::Char('X')));
+        assert_eq!(parse("PgDn"), KeySpec::new(KeyCode::PageDown));
+        assert_eq!(parse("f5"), KeySpec::new(KeyCode::F(5)));
+        assert_eq!(parse("space"), KeySpec::new(KeyCode::Char(' ')));
+        assert_eq!(
+            parse("alt++"),
+            KeySpec {
+                code: KeyCode::Char('+'),
+                modifiers: KeyModifiers::ALT,
+            }
+        );
+    }
+
+    #[test]
+    fn test_invalid_key_specs() {
+        assert_eq!(
+            "hyper+q".parse::<KeySpec>(),
+            Err("unknown modifier 'hyper' in key 'hyper+q'".to_string())
+        );
+        assert_eq!(
+            "pgdown".parse::<KeySpec>(),
+            Err("unknown key 'pgdown'".to_string())
+        );
+        assert!("f13".parse::<KeySpec>().is_err());
+        assert!("".parse::<KeySpec>().is_err());
+    }
+
+    #[test]
+    fn test_display_round_trips() {
+        for spec in ["q", "Ctrl+C", "S-Tab", "PgUp", "F12", "Space", "Alt+x"] {
+            let parsed: KeySpec = spec.replace("S-Tab", "backtab").parse().unwrap();
+            assert_eq!(parsed.to_string(), spec);
+        }
+    }
+
+    #[test]
+    fn test_shift_is_ignored_on_characters() {
+        let keys = KeyMap::default();
+        let plus = press(KeyCode::Char('+'), KeyModifiers::SHIFT);
+        assert_eq!(keys.action(&plus), Some(Action::Faster));
+        let back_tab = press(KeyCode::BackTab, KeyModifiers::SHIFT);
+        assert_eq!(keys.action(&back_tab), Some(Action::PreviousView));
+        let ctrl_q = press(KeyCode::Char('q'), KeyModifiers::CONTROL);
+        assert_eq!(keys.action(&ctrl_q), None);
+    }
+
+    #[test]
+    fn test_rebinding_moves_keys() {
+        let mut keys = KeyMap::default();
+        keys.bind(Action::TogglePause, vec![KeySpec::new(KeyCode::Char('q'))]);
+        let q = press(KeyCode::Char('q'), KeyModifiers::NONE);
+        let p = press(KeyCode::Char('p'), KeyModifiers::NONE);
+        assert_eq!(keys.action(&q), Some(Action::TogglePause));
+        assert_eq!(keys.action(&p), None);
+        assert_eq!(keys.keys(Action::Quit), &[KeySpec::ctrl('c')]);
+    }
+
+    #[test]
+    fn test_ctrl_c_always_quits() {
+        let mut keys = KeyMap::default();
+        keys.bind(Action::Quit, vec![KeySpec::new(KeyCode::Char('x'))]);
+        keys.bind(Action::ResetStats, vec![KeySpec::ctrl('c')]);
+        let ctrl_c = press(KeyCode::Char('c'), KeyModifiers::CONTROL);
+        assert_eq!(keys.action(&ctrl_c), Some(Action::Quit));
+    }
+
+    #[test]
+    fn test_describe() {
+        let keys = KeyMap::default();
+        assert_eq!(keys.describe(&[Action::Quit]), "q / Ctrl+C");
+        assert_eq!(
+            keys.describe(&[
+                Action::Scroll(Scroll::PageUp),
+                Action::Scroll(Scroll::PageDown)
+            ]),
+            "PgUp / PgDn"
+        );
+        let mut keys = keys;
+        keys.bind(Action::InvertSort, Vec::new());
+        assert_eq!(keys.describe(&[Action::InvertSort]), "unbound");
+    }
+}
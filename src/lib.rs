@@ -1,24 +1,43 @@
 //! System resource monitoring library for cross-platform system metrics.
 //!
 //! This library provides functionality for monitoring system resources including:
-//! * CPU usage tracking
-//! * Memory usage monitoring
+//! * CPU usage tracking (aggregate and per core)
+//! * Memory and swap usage monitoring
+//! * Process listing with sortable columns
+//! * Disk usage per mount point
+//! * Network interface throughput
 //! * Real-time metrics updates
+//! * Session statistics (min/max/average since the last reset)
+//! * Typed errors (`MonitorError`) for metrics the host cannot report
+//! * Bounded per-metric history for time-series charts
+//! * Background collection on a dedicated thread (`BackgroundCollector`)
+//! * Prometheus `/metrics` endpoint (`exporter` and `prometheus` modules)
+//! * Newline-delimited JSON output (`ndjson` module)
+//! * CSV recording with size- and time-based rotation (`csv` module)
+//! * Session recording and replay (`session` module)
+//! * Threshold alerts with pending/firing/resolved states (`alert` module)
+//! * Alert notifications through commands, log files and webhooks
+//!   (`notify` module)
+//! * Container limits and usage from cgroup v2 (`cgroup` module)
+//! * Pressure stall information for CPU, memory and I/O (`pressure` module)
+//! * CPU time breakdown per mode, including steal time (`cputime` module)
+//! * Memory breakdown with available, cached, slab and huge pages
+//!   (`meminfo` module)
 //!
 //! # Architecture
 //! The library is designed with a modular architecture that separates:
 //! * State management (`App` struct)
-//! * System metrics collection
-//! * Data type definitions
+//! * System metrics collection (`source` module, pluggable via `MetricSource`)
+//! * Data type definitions (`snapshot` module)
 //!
 //! # Usage
 //! ```no_run
 //! use system_monitor::App;
 //!
 //! let mut app = App::new();
-//! app.update(); // Update system metrics
-//! let cpu = app.get_cpu_usage();
-//! let memory = app.get_memory_usage();
+//! let snapshot = app.update(); // Update system metrics
+//! let cpu = snapshot.cpu_usage(); // Err(MonitorError) if no core is reported
+//! let memory = snapshot.memory_usage();
 //! ```
 //!
 //! # Platform Support
@@ -27,15 +46,147 @@
 //! * Linux
 //! * macOS
 
-use sysinfo::{CpuExt, System, SystemExt};
+pub mod alert;
+pub mod background;
+pub mod cgroup;
+pub mod cputime;
+pub mod csv;
+pub mod disk;
+pub mod error;
+pub mod exporter;
+pub mod history;
+pub mod meminfo;
+pub mod ndjson;
+pub mod network;
+pub mod notify;
+pub mod pressure;
+pub mod process;
+pub mod prometheus;
+pub mod refresh;
+pub mod session;
+pub mod snapshot;
+pub mod source;
+pub mod timefmt;
+
+pub use alert::{Alert, AlertEngine, AlertEvent, AlertMetric, AlertRule, AlertState};
+pub use background::BackgroundCollector;
+pub use cgroup::ContainerUsage;
+pub use disk::DiskUsage;
+pub use error::MonitorError;
+pub use exporter::MetricsServer;
+pub use history::{History, Sample, DEFAULT_HISTORY_LEN};
+pub use network::{InterfaceUsage, NetworkCounters};
+pub use notify::{AlertAction, BackgroundNotifier, DeliveryFailure, Notifier};
+pub use pressure::Pressure;
+
+use cgroup::ContainerTracker;
+use cputime::CpuTimeTracker;
+use network::NetworkTracker;
+pub use process::{ProcessColumn, ProcessInfo, ProcessSort};
+pub use refresh::{Collector, RefreshPlan};
+pub use snapshot::{CoreUsage, Metric, MetricsSnapshot};
+pub use source::{MetricSource, Reading, ScriptedSource, SysinfoSource};
+use std::mem;
+
+/// Number of alert state changes kept by [`App::alert_events`], and of
+/// failed notifications kept by [`App::delivery_failures`].
+pub const ALERT_LOG_LEN: usize = 100;
+
+/// Accumulated statistics for a single metric.
+///
+/// Tracks the minimum, maximum and running average of every sample
+/// recorded since creation or the last call to [`MetricStats::reset`].
+/// Non-finite samples (NaN, infinity) are ignored.
+///
+/// # Example
+/// ```
+/// use system_monitor::MetricStats;
+///
+/// let mut stats = MetricStats::default();
+/// stats.record(10.0);
+/// stats.record(30.0);
+/// assert_eq!(stats.min(), Some(10.0));
+/// assert_eq!(stats.max(), Some(30.0));
+/// assert_eq!(stats.average(), Some(20.0));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct MetricStats {
+    min: f32,
+    max: f32,
+    sum: f64,
+    count: u64,
+}
+
+impl MetricStats {
+    /// Records a new sample.
+    ///
+    /// # Arguments
+    /// * `value` - The sampled value; ignored if not finite
+    pub fn record(&mut self, value: f32) {
+        if !value.is_finite() {
+            return;
+        }
+        if self.count == 0 {
+            self.min = value;
+            self.max = value;
+        } else {
+            self.min = self.min.min(value);
+            self.max = self.max.max(value);
+        }
+        self.sum += f64::from(value);
+        self.count += 1;
+    }
+
+    /// Clears all recorded samples.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+
+    /// Returns the number of samples recorded.
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    /// Returns the smallest recorded sample, or `None` if empty.
+    pub fn min(&self) -> Option<f32> {
+        (self.count > 0).then_some(self.min)
+    }
+
+    /// Returns the largest recorded sample, or `None` if empty.
+    pub fn max(&self) -> Option<f32> {
+        (self.count > 0).then_some(self.max)
+    }
+
+    /// Returns the mean of all recorded samples, or `None` if empty.
+    pub fn average(&self) -> Option<f32> {
+        (self.count > 0).then(|| (self.sum / self.count as f64) as f32)
+    }
+}
 
 /// Main application state for system monitoring.
 ///
 /// Manages the state of system resource metrics and provides methods
-/// for updating and accessing these metrics in real-time.
+/// for updating and accessing these metrics in real-time. Every call to
+/// [`App::update`] produces a new [`MetricsSnapshot`]; the getters read
+/// from the latest snapshot and never query the system themselves.
+///
+/// Raw metrics come from a [`MetricSource`]: [`SysinfoSource`] by default,
+/// or any other implementation passed to [`App::with_source`].
 ///
 /// # Fields
-/// * `system` - System information provider from sysinfo
+/// * `source` - Provider of raw metrics (`None` while lent to a
+///   [`BackgroundCollector`])
+/// * `latest` - Snapshot produced by the most recent refresh
+/// * `network` - Tracker turning interface counters into rates
+/// * `cpu_history` - Recent CPU usage samples
+/// * `memory_history` - Recent memory usage samples
+/// * `swap_history` - Recent swap usage samples (empty without swap)
+/// * `cpu_stats` - CPU usage statistics since the last reset
+/// * `memory_stats` - Memory usage statistics since the last reset
+/// * `alerts` - Alert rules evaluated against every new snapshot
+/// * `alert_events` - Most recent alert state changes
+/// * `notifier` - Notifier the alert state changes are sent to, if any
+/// * `delivery_failures` - Most recent notifications that failed
 ///
 /// # Example
 /// ```no_run
@@ -43,19 +194,38 @@ use sysinfo::{CpuExt, System, SystemExt};
 ///
 /// let mut app = App::new();
 /// app.update();
-/// println!("CPU Usage: {}%", app.get_cpu_usage());
-/// println!("Memory Usage: {}%", app.get_memory_usage());
+/// match app.get_cpu_usage() {
+///     Ok(cpu) => println!("CPU Usage: {}%", cpu),
+///     Err(err) => println!("CPU Usage unavailable: {}", err),
+/// }
+/// if let Ok(memory) = app.get_memory_usage() {
+///     println!("Memory Usage: {}%", memory);
+/// }
 /// ```
 #[derive(Debug)]
 pub struct App {
-    system: System,
+    source: Option<Box<dyn MetricSource>>,
+    latest: MetricsSnapshot,
+    network: NetworkTracker,
+    cgroup: ContainerTracker,
+    cpu_time: CpuTimeTracker,
+    cpu_history: History<Sample>,
+    memory_history: History<Sample>,
+    swap_history: History<Sample>,
+    cpu_stats: MetricStats,
+    memory_stats: MetricStats,
+    alerts: AlertEngine,
+    alert_events: History<AlertEvent>,
+    notifier: Option<BackgroundNotifier>,
+    delivery_failures: History<DeliveryFailure>,
 }
 
 impl App {
     /// Creates a new App instance with initialized system monitoring.
     ///
-    /// Initializes the system information provider and sets initial
-    /// usage values to 0.
+    /// Initializes the system information provider and takes an
+    /// initial snapshot. CPU usage in the initial snapshot is 0 because
+    /// usage is computed between two refreshes.
     ///
     /// # Returns
     /// * `App` - A new App instance ready for monitoring
@@ -67,35 +237,333 @@ impl App {
     /// let app = App::new();
     /// ```
     pub fn new() -> Self {
-        let mut system = System::new_all();
-        system.refresh_all();
-        Self { system }
+        Self::with_history_len(DEFAULT_HISTORY_LEN)
+    }
+
+    /// Creates a new App instance keeping `history_len` samples per metric.
+    ///
+    /// The time span covered by the history is `history_len` times the
+    /// interval between calls to [`App::update`].
+    ///
+    /// # Arguments
+    /// * `history_len` - Number of samples kept per metric (at least 1)
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// // Ten minutes of history when updating every second
+    /// let app = App::with_history_len(600);
+    /// ```
+    pub fn with_history_len(history_len: usize) -> Self {
+        Self::with_source(SysinfoSource::new(), history_len)
+    }
+
+    /// Creates a new App instance reading metrics from `source`.
+    ///
+    /// The first reading is taken immediately to build the initial
+    /// snapshot; it is not recorded into the history or statistics.
+    ///
+    /// # Arguments
+    /// * `source` - Provider of raw metrics
+    /// * `history_len` - Number of samples kept per metric (at least 1)
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::{App, Reading, ScriptedSource};
+    ///
+    /// let source = ScriptedSource::new(vec![Reading::default()]);
+    /// let app = App::with_source(source, 60);
+    /// assert!(app.snapshot().cores.is_empty());
+    /// ```
+    pub fn with_source(source: impl MetricSource + 'static, history_len: usize) -> Self {
+        let mut source: Box<dyn MetricSource> = Box::new(source);
+        let mut network = NetworkTracker::default();
+        let mut cgroup = ContainerTracker::default();
+        let mut cpu_time = CpuTimeTracker::default();
+        let latest = Self::derive(
+            source.read(),
+            None,
+            &mut network,
+            &mut cgroup,
+            &mut cpu_time,
+        );
+        Self {
+            source: Some(source),
+            latest,
+            network,
+            cgroup,
+            cpu_time,
+            cpu_history: History::new(history_len),
+            memory_history: History::new(history_len),
+            swap_history: History::new(history_len),
+            cpu_stats: MetricStats::default(),
+            memory_stats: MetricStats::default(),
+            alerts: AlertEngine::default(),
+            alert_events: History::new(ALERT_LOG_LEN),
+            notifier: None,
+            delivery_failures: History::new(ALERT_LOG_LEN),
+        }
+    }
+
+    /// Creates an App instance without a metric source.
+    ///
+    /// The app starts from an empty snapshot and only changes through
+    /// [`App::ingest`] and [`App::ingest_snapshot`], e.g. when replaying a
+    /// recorded session; [`App::update`] leaves it unchanged.
+    ///
+    /// # Arguments
+    /// * `history_len` - Number of samples kept per metric (at least 1)
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::without_source(60);
+    /// app.update();
+    /// assert!(app.snapshot().cores.is_empty());
+    /// ```
+    pub fn without_source(history_len: usize) -> Self {
+        let mut app = Self::with_source(ScriptedSource::default(), history_len);
+        app.source = None;
+        app
     }
 
     /// Updates system metrics with current values.
     ///
-    /// Refreshes both CPU and memory usage metrics by querying
-    /// the system information provider.
+    /// Refreshes all metrics by taking a new reading from the metric
+    /// source, stores the result as the latest snapshot and records the new
+    /// values into the metric histories and the session statistics.
     ///
-    /// # Implementation Note
-    /// Currently returns actual system metrics.
+    /// While the app is driven by a [`BackgroundCollector`], the source
+    /// lives on the collector thread and this returns the latest snapshot
+    /// unchanged.
+    ///
+    /// # Returns
+    /// * `MetricsSnapshot` - A copy of the newly collected snapshot
     ///
     /// # Example
     /// ```no_run
     /// use system_monitor::App;
     ///
     /// let mut app = App::new();
-    /// app.update();
+    /// let snapshot = app.update();
     /// // Metrics are now updated with current system values
+    /// assert_eq!(&snapshot, app.snapshot());
+    /// ```
+    pub fn update(&mut self) -> MetricsSnapshot {
+        match self.source.as_mut() {
+            Some(source) => {
+                let reading = source.read();
+                self.ingest(reading)
+            }
+            None => self.latest.clone(),
+        }
+    }
+
+    /// Records a reading taken outside of [`App::update`].
+    ///
+    /// Derives a snapshot from `reading`, stores it as the latest snapshot
+    /// and records it into the histories and statistics, exactly like
+    /// `update` does with a reading from the app's own source. This is
+    /// cheap compared to reading a source, which lets a
+    /// [`BackgroundCollector`] read on its own thread and only hold the
+    /// app for this step.
+    ///
+    /// # Arguments
+    /// * `reading` - Raw metrics, newer than the latest snapshot
+    ///
+    /// # Returns
+    /// * `MetricsSnapshot` - A copy of the newly derived snapshot
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::{App, Reading, ScriptedSource};
+    ///
+    /// let mut app = App::with_source(ScriptedSource::default(), 10);
+    /// let reading = Reading { total_memory: 200, used_memory: 50, ..Reading::default() };
+    /// assert_eq!(app.ingest(reading).memory_usage(), Ok(25.0));
+    /// ```
+    pub fn ingest(&mut self, reading: Reading) -> MetricsSnapshot {
+        self.latest = Self::derive(
+            reading,
+            Some(&mut self.latest),
+            &mut self.network,
+            &mut self.cgroup,
+            &mut self.cpu_time,
+        );
+        self.record_latest();
+        self.latest.clone()
+    }
+
+    /// Records a snapshot that was derived elsewhere, e.g. a recorded one.
+    ///
+    /// Unlike [`App::ingest`], nothing is derived: the snapshot becomes the
+    /// latest snapshot as is, and its values (including the network rates)
+    /// are recorded into the histories and statistics.
+    ///
+    /// # Arguments
+    /// * `snapshot` - Snapshot newer than the latest one
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::{App, Metric};
+    ///
+    /// let mut app = App::without_source(10);
+    /// let mut snapshot = app.snapshot().clone();
+    /// snapshot.total_memory = 200;
+    /// snapshot.used_memory = 50;
+    /// app.ingest_snapshot(snapshot);
+    /// assert_eq!(app.get_memory_usage(), Ok(25.0));
+    /// assert_eq!(app.history(Metric::Memory).len(), 1);
+    /// ```
+    pub fn ingest_snapshot(&mut self, snapshot: MetricsSnapshot) {
+        self.network.record(&snapshot.networks);
+        self.latest = snapshot;
+        self.record_latest();
+    }
+
+    /// Records the latest snapshot into the histories and statistics, and
+    /// evaluates the alert rules against it.
+    ///
+    /// Alert state changes are queued on the notifier, and the failed
+    /// deliveries it reported since the previous snapshot are collected.
+    fn record_latest(&mut self) {
+        for event in self.alerts.evaluate(&self.latest) {
+            if let Some(notifier) = &self.notifier {
+                notifier.send(event.clone());
+            }
+            self.alert_events.push(event);
+        }
+        if let Some(notifier) = &self.notifier {
+            for failure in notifier.try_failures() {
+                self.delivery_failures.push(failure);
+            }
+        }
+        for metric in Metric::ALL {
+            if let Some(value) = metric.value(&self.latest).filter(|v| v.is_finite()) {
+                let sample = Sample {
+                    timestamp: self.latest.timestamp,
+                    value,
+                };
+                match metric {
+                    Metric::Cpu => self.cpu_history.push(sample),
+                    Metric::Memory => self.memory_history.push(sample),
+                    Metric::Swap => self.swap_history.push(sample),
+                }
+            }
+        }
+        if let Ok(usage) = self.latest.cpu_usage() {
+            self.cpu_stats.record(usage);
+        }
+        if let Ok(usage) = self.latest.memory_usage() {
+            self.memory_stats.record(usage);
+        }
+    }
+
+    /// Returns the snapshot produced by the most recent refresh.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// let snapshot = app.snapshot();
+    /// println!("{} cores at {:?}", snapshot.cores.len(), snapshot.timestamp);
+    /// ```
+    pub fn snapshot(&self) -> &MetricsSnapshot {
+        &self.latest
+    }
+
+    /// Returns the CPU usage percentage from the latest snapshot.
+    ///
+    /// # Returns
+    /// * `Ok(f32)` - CPU usage as a percentage between 0.0 and 100.0
+    /// * `Err(MonitorError)` - No usable core was reported
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// let cpu_usage = app.get_cpu_usage().expect("CPU usage unavailable");
+    /// assert!((0.0..=100.0).contains(&cpu_usage));
+    /// ```
+    pub fn get_cpu_usage(&self) -> Result<f32, MonitorError> {
+        self.latest.cpu_usage()
+    }
+
+    /// Returns the memory usage percentage from the latest snapshot.
+    ///
+    /// # Returns
+    /// * `Ok(f32)` - Memory usage as a percentage between 0.0 and 100.0
+    /// * `Err(MonitorError::NoMemory)` - Total memory was reported as zero
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// let memory_usage = app.get_memory_usage().expect("Memory usage unavailable");
+    /// assert!((0.0..=100.0).contains(&memory_usage));
     /// ```
-    pub fn update(&mut self) {
-        self.system.refresh_all();
+    pub fn get_memory_usage(&self) -> Result<f32, MonitorError> {
+        self.latest.memory_usage()
     }
 
-    /// Returns the current CPU usage percentage.
+    /// Returns the swap usage percentage from the latest snapshot.
+    ///
+    /// # Returns
+    /// * `Option<f32>` - Swap usage as a percentage between 0.0 and 100.0,
+    ///   or `None` if the host has no swap configured
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// match app.get_swap_usage() {
+    ///     Some(swap) => println!("Swap Usage: {}%", swap),
+    ///     None => println!("No swap configured"),
+    /// }
+    /// ```
+    pub fn get_swap_usage(&self) -> Option<f32> {
+        self.latest.swap_usage()
+    }
+
+    /// Returns every running process from the latest snapshot.
+    ///
+    /// # Arguments
+    /// * `sort` - Order in which to return the processes
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::{App, ProcessSort};
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// for process in app.get_processes(ProcessSort::default()).iter().take(10) {
+    ///     println!("{:>7} {:<20} {:.1}%", process.pid, process.name, process.cpu_usage);
+    /// }
+    /// ```
+    pub fn get_processes(&self, sort: ProcessSort) -> Vec<ProcessInfo> {
+        let mut processes = self.latest.processes.clone();
+        sort.apply(&mut processes);
+        processes
+    }
+
+    /// Returns the usage of every mounted filesystem from the latest snapshot.
+    ///
+    /// # Arguments
+    /// * `include_pseudo` - Whether to include pseudo filesystems such as
+    ///   `tmpfs` and `overlay`
     ///
     /// # Returns
-    /// * `f32` - CPU usage as a percentage between 0.0 and 100.0
+    /// * `Vec<DiskUsage>` - One entry per mount point, ordered by mount point
     ///
     /// # Example
     /// ```no_run
@@ -103,37 +571,307 @@ impl App {
     ///
     /// let mut app = App::new();
     /// app.update();
-    /// let cpu_usage = app.get_cpu_usage();
-    /// assert!(cpu_usage >= 0.0 && cpu_usage <= 100.0);
+    /// for disk in app.get_disks(false) {
+    ///     println!("{} ({}): {:.1}%", disk.mount_point, disk.file_system, disk.used_percent());
+    /// }
     /// ```
-    pub fn get_cpu_usage(&mut self) -> f32 {
-        self.system.refresh_cpu();
-        self.system
-            .cpus()
+    pub fn get_disks(&self, include_pseudo: bool) -> Vec<DiskUsage> {
+        let mut disks: Vec<DiskUsage> = self
+            .latest
+            .disks
             .iter()
-            .map(|cpu| cpu.cpu_usage())
-            .sum::<f32>()
-            / self.system.cpus().len() as f32
+            .filter(|disk| include_pseudo || !disk.is_pseudo())
+            .cloned()
+            .collect();
+        disks.sort_by(|a, b| a.mount_point.cmp(&b.mount_point));
+        disks
     }
 
-    /// Returns the current memory usage percentage.
+    /// Returns the throughput of every network interface from the latest
+    /// snapshot.
+    ///
+    /// Rates are computed over the wall-clock time between the two most
+    /// recent calls to [`App::update`]; totals count traffic since the
+    /// `App` was created.
     ///
     /// # Returns
-    /// * `f32` - Memory usage as a percentage between 0.0 and 100.0
+    /// * `Vec<InterfaceUsage>` - One entry per interface, ordered by name
     ///
     /// # Example
     /// ```no_run
     /// use system_monitor::App;
     ///
     /// let mut app = App::new();
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
     /// app.update();
-    /// let memory_usage = app.get_memory_usage();
-    /// assert!(memory_usage >= 0.0 && memory_usage <= 100.0);
+    /// for interface in app.get_network_usage() {
+    ///     println!(
+    ///         "{}: rx {:.0} B/s, tx {:.0} B/s",
+    ///         interface.name, interface.rx_bytes_per_sec, interface.tx_bytes_per_sec
+    ///     );
+    /// }
+    /// ```
+    pub fn get_network_usage(&self) -> Vec<InterfaceUsage> {
+        self.latest.networks.clone()
+    }
+
+    /// Returns the recent combined (rx + tx) throughput of an interface.
+    ///
+    /// # Returns
+    /// * `Option<Vec<u64>>` - Bytes per second per update, oldest first,
+    ///   or `None` if the interface is unknown
+    pub fn get_network_history(&self, interface: &str) -> Option<Vec<u64>> {
+        self.network
+            .history(interface)
+            .map(|history| history.iter().copied().collect())
+    }
+
+    /// Returns the usage of every logical CPU core from the latest snapshot.
+    ///
+    /// Unlike [`App::get_cpu_usage`], which averages all cores into one
+    /// value, this exposes single-core saturation.
+    ///
+    /// # Returns
+    /// * `Vec<CoreUsage>` - One entry per core, ordered by core id
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// for core in app.get_per_core_usage() {
+    ///     println!("{} ({}): {:.1}%", core.name, core.id, core.usage);
+    /// }
+    /// ```
+    pub fn get_per_core_usage(&self) -> Vec<CoreUsage> {
+        self.latest.cores.clone()
+    }
+
+    /// Returns the recent samples of a metric, oldest first.
+    ///
+    /// One sample is recorded per call to [`App::update`]; the oldest
+    /// samples are dropped once the configured history length is reached.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::{App, Metric};
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// let history = app.history(Metric::Cpu);
+    /// println!("{} CPU samples over {:?}", history.len(), history.span());
+    /// ```
+    pub fn history(&self, metric: Metric) -> &History<Sample> {
+        match metric {
+            Metric::Cpu => &self.cpu_history,
+            Metric::Memory => &self.memory_history,
+            Metric::Swap => &self.swap_history,
+        }
+    }
+
+    /// Returns CPU usage statistics accumulated since the last reset.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::App;
+    ///
+    /// let mut app = App::new();
+    /// app.update();
+    /// if let Some(peak) = app.cpu_stats().max() {
+    ///     println!("Peak CPU: {}%", peak);
+    /// }
+    /// ```
+    pub fn cpu_stats(&self) -> &MetricStats {
+        &self.cpu_stats
+    }
+
+    /// Returns memory usage statistics accumulated since the last reset.
+    pub fn memory_stats(&self) -> &MetricStats {
+        &self.memory_stats
+    }
+
+    /// Replaces the alert rules evaluated against every new snapshot.
+    ///
+    /// Every rule starts inactive and the alert event log is cleared.
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::{App, Reading, ScriptedSource};
+    ///
+    /// let mut app = App::with_source(ScriptedSource::default(), 10);
+    /// app.set_alert_rules(vec!["memory > 90%".parse().unwrap()]);
+    /// app.ingest(Reading { total_memory: 100, used_memory: 95, ..Reading::default() });
+    /// assert_eq!(app.alerts().firing().count(), 1);
+    /// assert_eq!(app.alert_events().len(), 1);
     /// ```
-    pub fn get_memory_usage(&mut self) -> f32 {
-        let total_memory = self.system.total_memory() as f32;
-        let used_memory = self.system.used_memory() as f32;
-        (used_memory / total_memory) * 100.0
+    pub fn set_alert_rules(&mut self, rules: Vec<AlertRule>) {
+        self.alerts = AlertEngine::new(rules);
+        self.alert_events.clear();
+    }
+
+    /// Returns the alert rules and their current states.
+    pub fn alerts(&self) -> &AlertEngine {
+        &self.alerts
+    }
+
+    /// Returns the most recent alert state changes, oldest first.
+    ///
+    /// At most [`ALERT_LOG_LEN`] events are kept.
+    pub fn alert_events(&self) -> &History<AlertEvent> {
+        &self.alert_events
+    }
+
+    /// Sends every later alert state change to `notifier`.
+    ///
+    /// Replaces the previous notifier, whose queued notifications are still
+    /// delivered.
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::notify::{ActionKind, AlertAction, BackgroundNotifier, Notifier};
+    /// use system_monitor::{App, Reading, ScriptedSource};
+    ///
+    /// let path = std::env::temp_dir().join(format!("app-doc-{}.log", std::process::id()));
+    /// let action = AlertAction::new(ActionKind::Log(path.clone()));
+    /// let notifier = BackgroundNotifier::spawn(Notifier::new(vec![action]), |_| {}).unwrap();
+    ///
+    /// let mut app = App::with_source(ScriptedSource::default(), 10);
+    /// app.set_alert_rules(vec!["memory > 90%".parse().unwrap()]);
+    /// app.set_notifier(notifier);
+    /// app.ingest(Reading { total_memory: 100, used_memory: 95, ..Reading::default() });
+    /// app.take_notifier().unwrap().finish();
+    ///
+    /// let log = std::fs::read_to_string(&path).unwrap();
+    /// std::fs::remove_file(&path).unwrap();
+    /// assert!(log.contains("firing memory > 90%: 95.0%"));
+    /// ```
+    pub fn set_notifier(&mut self, notifier: BackgroundNotifier) {
+        self.notifier = Some(notifier);
+    }
+
+    /// Takes the notifier out of the app, e.g. to wait for its queued
+    /// notifications with [`BackgroundNotifier::finish`].
+    pub fn take_notifier(&mut self) -> Option<BackgroundNotifier> {
+        self.notifier.take()
+    }
+
+    /// Returns the most recent notifications that could not be delivered,
+    /// oldest first.
+    ///
+    /// Failures are collected whenever a new snapshot is recorded. At most
+    /// [`ALERT_LOG_LEN`] failures are kept.
+    pub fn delivery_failures(&self) -> &History<DeliveryFailure> {
+        &self.delivery_failures
+    }
+
+    /// Clears all accumulated session statistics.
+    ///
+    /// Current metric values are unaffected; only min/max/average
+    /// tracking restarts from the next call to [`App::update`].
+    pub fn reset_stats(&mut self) {
+        self.cpu_stats.reset();
+        self.memory_stats.reset();
+    }
+
+    /// Takes the source out of the app, e.g. to read it on another thread.
+    pub(crate) fn take_source(&mut self) -> Option<Box<dyn MetricSource>> {
+        self.source.take()
+    }
+
+    /// Puts back a source taken with [`App::take_source`].
+    pub(crate) fn restore_source(&mut self, source: Option<Box<dyn MetricSource>>) {
+        self.source = source;
+    }
+
+    /// Copies the app's state, without its source and notifier, e.g. to
+    /// resume replaying a session from a checkpoint.
+    pub(crate) fn clone_state(&self) -> App {
+        App {
+            source: None,
+            latest: self.latest.clone(),
+            network: self.network.clone(),
+            cgroup: self.cgroup.clone(),
+            cpu_time: self.cpu_time.clone(),
+            cpu_history: self.cpu_history.clone(),
+            memory_history: self.memory_history.clone(),
+            swap_history: self.swap_history.clone(),
+            cpu_stats: self.cpu_stats,
+            memory_stats: self.memory_stats,
+            alerts: self.alerts.clone(),
+            alert_events: self.alert_events.clone(),
+            notifier: None,
+            delivery_failures: self.delivery_failures.clone(),
+        }
+    }
+
+    /// Builds a snapshot from a raw reading.
+    ///
+    /// Turns the cumulative network counters into rates using `network`,
+    /// the cgroup's CPU time into a usage using `cgroup`, and the time per
+    /// CPU mode into shares using `cpu_time`. Lists, container usage,
+    /// pressure, CPU time shares and the memory breakdown the source did
+    /// not refresh are moved over from `previous`. With a memory breakdown
+    /// the total and used memory come from it, so the headline usage is
+    /// based on `MemAvailable`.
+    fn derive(
+        reading: Reading,
+        previous: Option<&mut MetricsSnapshot>,
+        network: &mut NetworkTracker,
+        cgroup: &mut ContainerTracker,
+        cpu_time: &mut CpuTimeTracker,
+    ) -> MetricsSnapshot {
+        let (processes, disks, networks, container, pressure, shares, meminfo) = match previous {
+            Some(previous) => (
+                mem::take(&mut previous.processes),
+                mem::take(&mut previous.disks),
+                mem::take(&mut previous.networks),
+                previous.container.take(),
+                previous.pressure.take(),
+                previous.cpu_time.take(),
+                previous.meminfo.take(),
+            ),
+            None => Default::default(),
+        };
+        let networks = match reading.network_counters {
+            Some(counters) => network.sample(counters, reading.timestamp),
+            None => networks,
+        };
+        let container = match reading.container {
+            Some(usage) => Some(cgroup.sample(usage, reading.timestamp, reading.cores.len())),
+            None => container,
+        };
+        let shares = match reading.cpu_times {
+            Some(stat) => cpu_time.sample(stat),
+            None => shares,
+        };
+        let meminfo = reading.meminfo.or(meminfo);
+        let (total_memory, used_memory) = match &meminfo {
+            Some(meminfo) => (meminfo.total, meminfo.used()),
+            None => (reading.total_memory, reading.used_memory),
+        };
+
+        MetricsSnapshot {
+            timestamp: reading.timestamp,
+            cores: reading.cores,
+            total_memory,
+            used_memory,
+            total_swap: reading.total_swap,
+            used_swap: reading.used_swap,
+            processes: reading.processes.unwrap_or(processes),
+            disks: reading.disks.unwrap_or(disks),
+            networks,
+            container,
+            pressure: reading.pressure.or(pressure),
+            cpu_time: shares,
+            meminfo,
+        }
+    }
+}
+
+impl Default for App {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -158,7 +896,7 @@ mod tests {
         fn test_new_app_creation() {
             let app = create_app();
             assert!(
-                app.system.cpus().len() > 0,
+                !app.snapshot().cores.is_empty(),
                 "Should detect at least one CPU"
             );
         }
@@ -169,10 +907,10 @@ mod tests {
 
         #[test]
         fn test_cpu_usage_range() {
-            let mut app = create_app();
-            let usage = app.get_cpu_usage();
+            let app = create_app();
+            let usage = app.get_cpu_usage().unwrap();
             assert!(
-                usage >= 0.0 && usage <= 100.0,
+                (0.0..=100.0).contains(&usage),
                 "CPU usage should be between 0% and 100%, got {}%",
                 usage
             );
@@ -181,19 +919,19 @@ mod tests {
         #[test]
         fn test_cpu_updates() {
             let mut app = create_app();
-            let initial = app.get_cpu_usage();
+            let initial = app.get_cpu_usage().unwrap();
             thread::sleep(Duration::from_millis(100));
             app.update();
-            let updated = app.get_cpu_usage();
+            let updated = app.get_cpu_usage().unwrap();
 
             // Verify both readings are valid
             assert!(
-                initial >= 0.0 && initial <= 100.0,
+                (0.0..=100.0).contains(&initial),
                 "Initial CPU usage should be valid, got {}%",
                 initial
             );
             assert!(
-                updated >= 0.0 && updated <= 100.0,
+                (0.0..=100.0).contains(&updated),
                 "Updated CPU usage should be valid, got {}%",
                 updated
             );
@@ -201,6 +939,49 @@ mod tests {
             // Values might be different due to actual CPU usage changes
             println!("CPU usage changed from {}% to {}%", initial, updated);
         }
+
+        #[test]
+        fn test_getters_read_latest_snapshot() {
+            let mut app = create_app();
+            let snapshot = app.update();
+            assert_eq!(app.get_cpu_usage(), snapshot.cpu_usage());
+            assert_eq!(app.get_memory_usage(), snapshot.memory_usage());
+            assert_eq!(app.get_per_core_usage(), snapshot.cores);
+        }
+
+        #[test]
+        fn test_snapshot_timestamps_advance() {
+            let mut app = create_app();
+            let first = app.update();
+            thread::sleep(Duration::from_millis(10));
+            let second = app.update();
+            assert!(
+                second.timestamp > first.timestamp,
+                "Each update should produce a newer snapshot"
+            );
+            assert_eq!(&second, app.snapshot());
+        }
+
+        #[test]
+        fn test_per_core_usage() {
+            let app = create_app();
+            let cores = app.get_per_core_usage();
+            assert_eq!(
+                cores.len(),
+                app.snapshot().cores.len(),
+                "Should report one entry per core"
+            );
+            for (index, core) in cores.iter().enumerate() {
+                assert_eq!(core.id, index, "Core ids should follow OS order");
+                assert!(!core.name.is_empty(), "Core {} should have a name", index);
+                assert!(
+                    (0.0..=100.0).contains(&core.usage),
+                    "Core {} usage should be between 0% and 100%, got {}%",
+                    core.name,
+                    core.usage
+                );
+            }
+        }
     }
 
     mod memory_monitoring {
@@ -208,10 +989,10 @@ mod tests {
 
         #[test]
         fn test_memory_usage_range() {
-            let mut app = create_app();
-            let usage = app.get_memory_usage();
+            let app = create_app();
+            let usage = app.get_memory_usage().unwrap();
             assert!(
-                usage >= 0.0 && usage <= 100.0,
+                (0.0..=100.0).contains(&usage),
                 "Memory usage should be between 0% and 100%, got {}%",
                 usage
             );
@@ -220,19 +1001,19 @@ mod tests {
         #[test]
         fn test_memory_updates() {
             let mut app = create_app();
-            let initial = app.get_memory_usage();
+            let initial = app.get_memory_usage().unwrap();
             thread::sleep(Duration::from_millis(100));
             app.update();
-            let updated = app.get_memory_usage();
+            let updated = app.get_memory_usage().unwrap();
 
             // Verify both readings are valid
             assert!(
-                initial >= 0.0 && initial <= 100.0,
+                (0.0..=100.0).contains(&initial),
                 "Initial memory usage should be valid, got {}%",
                 initial
             );
             assert!(
-                updated >= 0.0 && updated <= 100.0,
+                (0.0..=100.0).contains(&updated),
                 "Updated memory usage should be valid, got {}%",
                 updated
             );
@@ -247,5 +1028,476 @@ mod tests {
 
             println!("Memory usage changed from {}% to {}%", initial, updated);
         }
+
+        #[test]
+        fn test_swap_usage() {
+            let mut app = create_app();
+            let snapshot = app.update();
+            assert!(snapshot.used_swap <= snapshot.total_swap);
+            match app.get_swap_usage() {
+                Some(usage) => assert!(
+                    (0.0..=100.0).contains(&usage),
+                    "Swap usage should be between 0% and 100%, got {}%",
+                    usage
+                ),
+                None => assert_eq!(snapshot.total_swap, 0, "Swap is only absent without swap"),
+            }
+        }
+    }
+
+    mod process_listing {
+        use super::*;
+
+        #[test]
+        fn test_lists_current_process() {
+            let app = create_app();
+            let own_pid = std::process::id();
+            let processes = app.get_processes(ProcessSort::default());
+            let own = processes
+                .iter()
+                .find(|p| p.pid == own_pid)
+                .expect("Test process should be listed");
+            assert!(!own.name.is_empty(), "Process name should be known");
+            assert!(own.memory > 0, "Test process should have a resident set");
+        }
+
+        #[test]
+        fn test_processes_are_sorted() {
+            let app = create_app();
+            let sort = ProcessSort {
+                column: ProcessColumn::Pid,
+                descending: false,
+            };
+            let processes = app.get_processes(sort);
+            assert!(
+                processes.windows(2).all(|w| w[0].pid < w[1].pid),
+                "Processes should be ordered by ascending pid"
+            );
+        }
+    }
+
+    mod disk_usage {
+        use super::*;
+
+        #[test]
+        fn test_disks_are_consistent() {
+            let app = create_app();
+            for disk in app.get_disks(true) {
+                assert!(!disk.mount_point.is_empty(), "Mount point should be known");
+                assert!(
+                    (0.0..=100.0).contains(&disk.used_percent()),
+                    "{} usage should be between 0% and 100%, got {}%",
+                    disk.mount_point,
+                    disk.used_percent()
+                );
+            }
+        }
+
+        #[test]
+        fn test_pseudo_filesystems_filtered() {
+            let app = create_app();
+            let all = app.get_disks(true);
+            let real = app.get_disks(false);
+            assert!(real.iter().all(|disk| !disk.is_pseudo()));
+            assert_eq!(
+                real.len(),
+                all.iter().filter(|disk| !disk.is_pseudo()).count()
+            );
+            assert!(
+                real.windows(2)
+                    .all(|w| w[0].mount_point <= w[1].mount_point),
+                "Disks should be ordered by mount point"
+            );
+        }
+    }
+
+    mod network_usage {
+        use super::*;
+
+        #[test]
+        fn test_interfaces_reported_with_history() {
+            let mut app = create_app();
+            app.update();
+            let interfaces = app.get_network_usage();
+            assert!(
+                interfaces.windows(2).all(|w| w[0].name < w[1].name),
+                "Interfaces should be ordered by name"
+            );
+            for interface in &interfaces {
+                assert!(interface.rx_bytes_per_sec >= 0.0);
+                assert!(interface.tx_bytes_per_sec >= 0.0);
+                let history = app
+                    .get_network_history(&interface.name)
+                    .expect("Every reported interface should have a history");
+                assert_eq!(history.len(), 2, "One history entry per collection");
+            }
+        }
+    }
+
+    mod metric_history {
+        use super::*;
+
+        #[test]
+        fn test_update_appends_history() {
+            let mut app = App::with_history_len(10);
+            assert!(app.history(Metric::Cpu).is_empty());
+            let snapshot = app.update();
+            let latest = app.history(Metric::Cpu).latest().copied().unwrap();
+            assert_eq!(latest.timestamp, snapshot.timestamp);
+            assert_eq!(latest.value, snapshot.cpu_usage().unwrap());
+            assert_eq!(app.history(Metric::Memory).len(), 1);
+            assert_eq!(
+                app.history(Metric::Swap).len(),
+                usize::from(snapshot.has_swap())
+            );
+        }
+
+        #[test]
+        fn test_history_length_is_bounded() {
+            let mut app = App::with_history_len(3);
+            for _ in 0..5 {
+                app.update();
+            }
+            assert_eq!(app.history(Metric::Cpu).len(), 3);
+            assert_eq!(app.history(Metric::Memory).capacity(), 3);
+        }
+    }
+
+    mod scripted_source {
+        use super::*;
+        use std::time::SystemTime;
+
+        fn core(id: usize, usage: f32) -> CoreUsage {
+            CoreUsage {
+                id,
+                name: format!("cpu{}", id),
+                usage,
+            }
+        }
+
+        fn reading(seconds: u64, cores: &[f32], used_memory: u64) -> Reading {
+            Reading {
+                timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+                cores: cores
+                    .iter()
+                    .enumerate()
+                    .map(|(id, &usage)| core(id, usage))
+                    .collect(),
+                total_memory: 1000,
+                used_memory,
+                ..Reading::default()
+            }
+        }
+
+        fn scripted_app(readings: Vec<Reading>) -> App {
+            App::with_source(ScriptedSource::new(readings), 10)
+        }
+
+        #[test]
+        fn test_exact_cpu_average() {
+            let mut app = scripted_app(vec![
+                reading(0, &[0.0, 0.0], 0),
+                reading(1, &[10.0, 30.0, 50.0, 70.0], 0),
+            ]);
+            let snapshot = app.update();
+            assert_eq!(snapshot.cpu_usage(), Ok(40.0));
+            assert_eq!(app.get_per_core_usage()[3], core(3, 70.0));
+        }
+
+        #[test]
+        fn test_exact_memory_usage() {
+            let mut app = scripted_app(vec![reading(0, &[0.0], 0), reading(1, &[0.0], 125)]);
+            app.update();
+            assert_eq!(app.get_memory_usage(), Ok(12.5));
+        }
+
+        #[test]
+        fn test_exact_statistics_and_history() {
+            let mut app = scripted_app(vec![
+                reading(0, &[0.0], 0),
+                reading(1, &[20.0], 100),
+                reading(2, &[60.0], 300),
+                reading(3, &[40.0], 200),
+            ]);
+            for _ in 0..3 {
+                app.update();
+            }
+            assert_eq!(app.cpu_stats().min(), Some(20.0));
+            assert_eq!(app.cpu_stats().max(), Some(60.0));
+            assert_eq!(app.cpu_stats().average(), Some(40.0));
+            assert_eq!(app.memory_stats().average(), Some(20.0));
+
+            let values: Vec<f32> = app.history(Metric::Cpu).iter().map(|s| s.value).collect();
+            assert_eq!(values, vec![20.0, 60.0, 40.0]);
+            assert_eq!(app.history(Metric::Cpu).span(), Duration::from_secs(2));
+        }
+
+        #[test]
+        fn test_exact_network_rates() {
+            let counters = |rx_bytes, tx_bytes| {
+                Some(vec![(
+                    "eth0".to_string(),
+                    NetworkCounters {
+                        rx_bytes,
+                        tx_bytes,
+                        ..NetworkCounters::default()
+                    },
+                )])
+            };
+            let mut first = reading(10, &[0.0], 0);
+            first.network_counters = counters(1_000, 0);
+            let mut second = reading(14, &[0.0], 0);
+            second.network_counters = counters(9_000, 2_000);
+
+            let mut app = scripted_app(vec![first, second]);
+            let interface = &app.update().networks[0];
+            assert_eq!(interface.rx_bytes_per_sec, 2_000.0);
+            assert_eq!(interface.tx_bytes_per_sec, 500.0);
+            assert_eq!(interface.total.rx_bytes, 8_000);
+            assert_eq!(app.get_network_history("eth0"), Some(vec![0, 2_500]));
+        }
+
+        #[test]
+        fn test_lists_kept_between_refreshes() {
+            let mut first = reading(0, &[0.0], 0);
+            first.processes = Some(vec![ProcessInfo {
+                pid: 1,
+                name: "init".to_string(),
+                user: None,
+                cpu_usage: 0.0,
+                memory: 0,
+                state: "Sleeping".to_string(),
+                command: "init".to_string(),
+            }]);
+            first.network_counters = Some(vec![("eth0".to_string(), NetworkCounters::default())]);
+            let mut third = reading(4, &[0.0], 0);
+            third.network_counters = Some(vec![(
+                "eth0".to_string(),
+                NetworkCounters {
+                    rx_bytes: 4_000,
+                    ..NetworkCounters::default()
+                },
+            )]);
+
+            let mut app = scripted_app(vec![first, reading(2, &[0.0], 0), third]);
+            let skipped = app.update();
+            assert_eq!(skipped.processes.len(), 1, "Processes were not refreshed");
+            assert_eq!(skipped.networks.len(), 1, "Networks were not refreshed");
+
+            // The rate spans both ticks since the last network refresh
+            let refreshed = app.update();
+            assert_eq!(refreshed.networks[0].rx_bytes_per_sec, 1_000.0);
+            assert_eq!(refreshed.processes.len(), 1);
+        }
+
+        #[test]
+        fn test_container_cpu_usage() {
+            let container = |usage_usec| {
+                Some(ContainerUsage {
+                    cpu_quota: Some(cgroup::CpuQuota {
+                        quota_usec: 100_000,
+                        period_usec: 100_000,
+                    }),
+                    cpu: cgroup::CpuStat {
+                        usage_usec,
+                        ..cgroup::CpuStat::default()
+                    },
+                    ..ContainerUsage::default()
+                })
+            };
+            let mut first = reading(0, &[0.0, 0.0], 0);
+            first.container = container(0);
+            let mut third = reading(4, &[0.0, 0.0], 0);
+            third.container = container(1_000_000);
+
+            let mut app = scripted_app(vec![first, reading(2, &[0.0, 0.0], 0), third]);
+            assert_eq!(app.snapshot().container.as_ref().unwrap().cpu_usage, None);
+            let skipped = app.update();
+            assert!(skipped.container.is_some(), "Container usage is kept");
+
+            // One second of CPU time over the four seconds since the last
+            // cgroup reading, against a one-CPU quota
+            let refreshed = app.update();
+            assert_eq!(refreshed.container.unwrap().cpu_usage, Some(25.0));
+        }
+
+        #[test]
+        fn test_cpu_time_shares() {
+            let stat = |user, idle| cputime::ProcStat {
+                total: cputime::CpuTimes {
+                    user,
+                    idle,
+                    ..cputime::CpuTimes::default()
+                },
+                cores: Vec::new(),
+            };
+            let mut first = reading(0, &[0.0], 0);
+            first.cpu_times = Some(stat(100, 100));
+            let mut third = reading(2, &[0.0], 0);
+            third.cpu_times = Some(stat(175, 125));
+
+            let mut app = scripted_app(vec![first, reading(1, &[0.0], 0), third]);
+            assert_eq!(app.snapshot().cpu_time, None, "One reading has no deltas");
+            assert_eq!(app.update().cpu_time, None);
+
+            let refreshed = app.update();
+            assert_eq!(refreshed.cpu_time.unwrap().total.user, 75.0);
+        }
+
+        #[test]
+        fn test_meminfo_sets_used_memory() {
+            let meminfo = meminfo::MemInfo {
+                total: 1_000,
+                free: 100,
+                available: 750,
+                ..meminfo::MemInfo::default()
+            };
+            let mut first = reading(0, &[0.0], 900);
+            first.meminfo = Some(meminfo);
+
+            let mut app = scripted_app(vec![first, reading(1, &[0.0], 900)]);
+            assert_eq!(app.snapshot().used_memory, 250, "Based on MemAvailable");
+            assert_eq!(app.get_memory_usage(), Ok(25.0));
+
+            // The breakdown is kept while the memory collector is not due
+            let skipped = app.update();
+            assert_eq!(skipped.meminfo, Some(meminfo));
+            assert_eq!(skipped.memory_usage(), Ok(25.0));
+        }
+
+        #[test]
+        fn test_snapshot_timestamp_comes_from_source() {
+            let mut app = scripted_app(vec![reading(0, &[0.0], 0), reading(42, &[0.0], 0)]);
+            assert_eq!(
+                app.update().timestamp,
+                SystemTime::UNIX_EPOCH + Duration::from_secs(42)
+            );
+        }
+    }
+
+    mod error_handling {
+        use super::*;
+
+        fn scripted_app(readings: Vec<Reading>) -> App {
+            let mut app = App::with_source(ScriptedSource::new(readings), 10);
+            app.update();
+            app
+        }
+
+        fn valid_reading() -> Reading {
+            Reading {
+                cores: vec![CoreUsage {
+                    id: 0,
+                    name: "cpu0".to_string(),
+                    usage: 50.0,
+                }],
+                total_memory: 100,
+                used_memory: 50,
+                ..Reading::default()
+            }
+        }
+
+        #[test]
+        fn test_no_cores_reported() {
+            let reading = Reading {
+                cores: Vec::new(),
+                ..valid_reading()
+            };
+            let app = scripted_app(vec![reading.clone(), reading]);
+            assert_eq!(app.get_cpu_usage(), Err(MonitorError::NoCpuCores));
+            assert_eq!(app.get_memory_usage(), Ok(50.0));
+            assert!(app.history(Metric::Cpu).is_empty());
+            assert_eq!(app.cpu_stats().count(), 0);
+        }
+
+        #[test]
+        fn test_zero_total_memory() {
+            let reading = Reading {
+                total_memory: 0,
+                used_memory: 0,
+                ..valid_reading()
+            };
+            let app = scripted_app(vec![reading.clone(), reading]);
+            assert_eq!(app.get_memory_usage(), Err(MonitorError::NoMemory));
+            assert_eq!(app.get_cpu_usage(), Ok(50.0));
+            assert!(app.history(Metric::Memory).is_empty());
+            assert_eq!(app.memory_stats().count(), 0);
+        }
+
+        #[test]
+        fn test_non_finite_core_usage() {
+            let mut reading = valid_reading();
+            reading.cores[0].usage = f32::NAN;
+            let app = scripted_app(vec![reading.clone(), reading]);
+            assert_eq!(
+                app.get_cpu_usage(),
+                Err(MonitorError::NonFinite(Metric::Cpu))
+            );
+            assert_eq!(app.cpu_stats().count(), 0);
+        }
+
+        #[test]
+        fn test_recovers_once_source_reports_again() {
+            let mut app = scripted_app(vec![
+                Reading::default(),
+                Reading::default(),
+                valid_reading(),
+            ]);
+            assert!(app.get_cpu_usage().is_err());
+            assert!(app.get_memory_usage().is_err());
+
+            app.update();
+            assert_eq!(app.get_cpu_usage(), Ok(50.0));
+            assert_eq!(app.get_memory_usage(), Ok(50.0));
+            assert_eq!(app.cpu_stats().count(), 1);
+        }
+    }
+
+    mod statistics {
+        use super::*;
+
+        #[test]
+        fn test_empty_stats() {
+            let stats = MetricStats::default();
+            assert_eq!(stats.count(), 0);
+            assert_eq!(stats.min(), None);
+            assert_eq!(stats.max(), None);
+            assert_eq!(stats.average(), None);
+        }
+
+        #[test]
+        fn test_min_max_average() {
+            let mut stats = MetricStats::default();
+            for value in [40.0, 10.0, 70.0] {
+                stats.record(value);
+            }
+            assert_eq!(stats.count(), 3);
+            assert_eq!(stats.min(), Some(10.0));
+            assert_eq!(stats.max(), Some(70.0));
+            assert_eq!(stats.average(), Some(40.0));
+        }
+
+        #[test]
+        fn test_non_finite_samples_ignored() {
+            let mut stats = MetricStats::default();
+            stats.record(f32::NAN);
+            stats.record(f32::INFINITY);
+            stats.record(5.0);
+            assert_eq!(stats.count(), 1);
+            assert_eq!(stats.min(), Some(5.0));
+        }
+
+        #[test]
+        fn test_update_records_and_reset_clears() {
+            let mut app = create_app();
+            app.update();
+            app.update();
+            assert_eq!(app.cpu_stats().count(), 2);
+            assert_eq!(app.memory_stats().count(), 2);
+
+            app.reset_stats();
+            assert_eq!(app.cpu_stats().count(), 0);
+            assert_eq!(app.memory_stats().max(), None);
+        }
     }
 }
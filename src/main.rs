@@ -7,23 +7,101 @@
 //! # Features
 //! * Real-time system metrics visualization
 //! * CPU and memory usage gauges
+//! * Per-core CPU usage bars
+//! * Swap usage gauge (hidden when no swap is configured)
+//! * Scrollable process table sortable by any column
+//! * Disk usage per mount point, pseudo filesystems hidden by default
+//! * Network throughput per interface with sparklines
+//! * CPU, memory and swap history charts next to the gauges
+//! * Threshold alerts: firing metrics turn red, a banner lists them and the
+//!   alerts view shows every rule with its recent state changes
+//! * Alert notifications through commands, log files and webhooks, in the
+//!   terminal UI and every headless mode
+//! * Container CPU and memory gauges when running under cgroup v2 limits
+//! * Pressure stall information for CPU, memory and I/O
+//! * Metrics sampled on a background thread, keeping input responsive
 //! * Cross-platform terminal UI
 //!
 //! # Controls
-//! * Press 'q' to quit the application
+//! Default bindings, which the `[keys]` table of the configuration file
+//! can change:
+//! * Press 'q' (or Ctrl+C) to quit the application
+//! * Press 'h' to toggle the help overlay
+//! * Press 'p' to pause/resume monitoring
+//! * Press 'r' to reset session statistics
+//! * Press Tab / Shift+Tab to switch between the overview, processes, disks,
+//!   network, alerts and pressure views
+//! * Use the arrow keys, PgUp/PgDn and Home/End to scroll the process table
+//! * Press '<' / '>' to change the sort column and 'i' to invert the order
+//! * Press 'f' to show/hide pseudo filesystems in the disks view
+//! * While replaying, press Left / Right to step and '-' / '+' to change
+//!   the playback speed
+//!
+//! # Command line
+//! `system-monitor [--interval SECS] [--views LIST] [--session PATH]`
+//! starts the terminal UI, sampling every `SECS` seconds and showing only
+//! the listed views. Metrics needed by hidden views only are not
+//! collected. Run `system-monitor --help` for every command and option;
+//! invalid arguments are reported before the terminal is touched.
+//!
+//! # Configuration
+//! Refresh interval, shown views, layout, colors, gauge thresholds, alert
+//! rules and actions, and key bindings are read from a TOML file: `--config PATH`, else
+//! `$SYSTEM_MONITOR_CONFIG`, else `$XDG_CONFIG_HOME/system-monitor/config.toml`
+//! if it exists. `SYSTEM_MONITOR_INTERVAL` and `SYSTEM_MONITOR_VIEWS`
+//! override the file, and command-line options override both. Mistakes are
+//! reported with their line and column. See the `config` module.
+//!
+//! # Record & replay
+//! `system-monitor --session PATH` runs the terminal UI and appends every
+//! sample to `PATH` as one JSON object per line. `system-monitor replay
+//! PATH` plays such a session back in the terminal UI at its recorded
+//! pace.
+//!
+//! # Headless mode
+//! `system-monitor serve [ADDR]` skips the terminal UI and serves the
+//! metrics on `http://ADDR/metrics` in the Prometheus text format
+//! (default `127.0.0.1:9184`).
+//!
+//! `system-monitor json [--interval SECS] [--count N] [--processes]` prints
+//! one JSON object per sample to stdout until `N` samples were written or
+//! the process is interrupted. The process list is only collected with
+//! `--processes`.
+//!
+//! `system-monitor record PATH [--groups LIST] [--max-size BYTES]
+//! [--max-age SECS] [--keep N] [--interval SECS]` appends one CSV row per
+//! sample to `PATH`, rotating it to `PATH.1`, `PATH.2`, ... when a limit is
+//! reached. `csv` is an alias of `record`.
+//!
+//! `system-monitor summary [--interval SECS] [--top N]` prints the current
+//! usage and the busiest processes once and exits.
 //!
 //! # Layout
 //! The interface is divided into three sections:
-//! 1. Title bar (2 units high)
-//! 2. CPU usage gauge (50% of remaining space)
-//! 3. Memory usage gauge (50% of remaining space)
+//! 1. Title bar and view tabs (2 units high)
+//! 2. CPU usage gauge, CPU time breakdown and per-core bars (50% of
+//!    remaining space by default)
+//! 3. Memory and swap usage gauges (the rest)
+
+mod cli;
+mod config;
+mod keymap;
 
 use std::error::Error;
+use std::fmt::Write as _;
 use std::io;
-use std::time::Duration;
+use std::panic;
+use std::path::Path;
+use std::process::ExitCode;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use clap::ValueEnum;
 
 use crossterm::{
-    event::{self, Event, KeyCode},
+    cursor,
+    event::{self, Event, KeyEventKind},
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
@@ -31,25 +109,530 @@ use tui::{
     backend::{Backend, CrosstermBackend},
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
+    symbols,
     text::{Span, Spans},
-    widgets::{Block, Borders, Gauge, Paragraph},
+    widgets::{
+        Axis, Block, Borders, Cell, Chart, Clear, Dataset, Gauge, GraphType, Paragraph, Row,
+        Sparkline, Table, TableState, Tabs, Wrap,
+    },
     Frame, Terminal,
 };
 
 // Import App from our library
-use system_monitor::App;
+use cli::{Cli, Command, TuiArgs};
+use config::{Config, LayoutConfig, Theme, Threshold};
+use keymap::{KeyMap, KeySpec};
+use serde::Deserialize;
+use system_monitor::{
+    alert::Transition,
+    cputime::{CpuMode, CpuShares, CpuTimeUsage},
+    csv::{self, CsvRecorder, MetricGroup, Rotation},
+    meminfo::MemInfo,
+    ndjson,
+    pressure::{Resource, StallStats},
+    session::{PlaybackStatus, Player, Session, SessionWriter},
+    timefmt::iso8601,
+    AlertEngine, AlertMetric, AlertRule, AlertState, App, BackgroundCollector, BackgroundNotifier,
+    Collector, ContainerUsage, CoreUsage, DiskUsage, History, Metric, MetricStats, MetricsServer,
+    MonitorError, Notifier, ProcessColumn, ProcessInfo, ProcessSort, RefreshPlan, Sample,
+    SysinfoSource,
+};
+
+/// Counters describing what the event loop did before it exited.
+///
+/// # Fields
+/// * `frames` - Number of frames drawn
+/// * `samples` - Number of snapshots received from the background collector
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+struct LoopStats {
+    frames: u64,
+    samples: u64,
+}
+
+/// Source of terminal input events.
+///
+/// Abstracts over `crossterm::event` so the event loop can be driven by
+/// synthetic events in tests.
+trait EventSource {
+    /// Waits up to `timeout` for an event to become available.
+    ///
+    /// # Returns
+    /// * `io::Result<bool>` - `true` if `read` will not block
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool>;
+
+    /// Reads the next available event.
+    fn read(&mut self) -> io::Result<Event>;
+}
+
+/// Event source reading from the real terminal through crossterm.
+struct CrosstermEvents;
+
+impl EventSource for CrosstermEvents {
+    fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+        event::poll(timeout)
+    }
+
+    fn read(&mut self) -> io::Result<Event> {
+        event::read()
+    }
+}
+
+/// Action requested by a key press.
+///
+/// Named in the `[keys]` table of the configuration file by its
+/// [`Action::name`], e.g. `toggle_pause`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Deserialize)]
+#[serde(try_from = "String")]
+enum Action {
+    /// Exit the application
+    Quit,
+    /// Show or hide the help overlay
+    ToggleHelp,
+    /// Close the help overlay if it is open
+    CloseHelp,
+    /// Suspend or resume metric sampling
+    TogglePause,
+    /// Clear the session statistics
+    ResetStats,
+    /// Switch to the next view
+    NextView,
+    /// Switch to the previous view
+    PreviousView,
+    /// Move the process table selection
+    Scroll(Scroll),
+    /// Sort the process table by the next column
+    SortNextColumn,
+    /// Sort the process table by the previous column
+    SortPreviousColumn,
+    /// Reverse the process table sort order
+    InvertSort,
+    /// Show or hide pseudo filesystems in the disks view
+    TogglePseudoFilesystems,
+    /// Show the next recorded snapshot (replay only)
+    StepForward,
+    /// Show the previous recorded snapshot (replay only)
+    StepBackward,
+    /// Increase the playback speed (replay only)
+    Faster,
+    /// Decrease the playback speed (replay only)
+    Slower,
+}
+
+/// Movement of the process table selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Scroll {
+    Up,
+    Down,
+    PageUp,
+    PageDown,
+    Top,
+    Bottom,
+}
+
+impl Action {
+    /// All actions, in help order.
+    const ALL: [Action; 21] = [
+        Action::Quit,
+        Action::ToggleHelp,
+        Action::CloseHelp,
+        Action::TogglePause,
+        Action::ResetStats,
+        Action::NextView,
+        Action::PreviousView,
+        Action::Scroll(Scroll::Up),
+        Action::Scroll(Scroll::Down),
+        Action::Scroll(Scroll::PageUp),
+        Action::Scroll(Scroll::PageDown),
+        Action::Scroll(Scroll::Top),
+        Action::Scroll(Scroll::Bottom),
+        Action::SortNextColumn,
+        Action::SortPreviousColumn,
+        Action::InvertSort,
+        Action::TogglePseudoFilesystems,
+        Action::StepForward,
+        Action::StepBackward,
+        Action::Faster,
+        Action::Slower,
+    ];
+
+    /// Returns the name of the action in the configuration file.
+    fn name(self) -> &'static str {
+        match self {
+            Action::Quit => "quit",
+            Action::ToggleHelp => "toggle_help",
+            Action::CloseHelp => "close_help",
+            Action::TogglePause => "toggle_pause",
+            Action::ResetStats => "reset_stats",
+            Action::NextView => "next_view",
+            Action::PreviousView => "previous_view",
+            Action::Scroll(Scroll::Up) => "scroll_up",
+            Action::Scroll(Scroll::Down) => "scroll_down",
+            Action::Scroll(Scroll::PageUp) => "page_up",
+            Action::Scroll(Scroll::PageDown) => "page_down",
+            Action::Scroll(Scroll::Top) => "scroll_top",
+            Action::Scroll(Scroll::Bottom) => "scroll_bottom",
+            Action::SortNextColumn => "sort_next_column",
+            Action::SortPreviousColumn => "sort_previous_column",
+            Action::InvertSort => "invert_sort",
+            Action::TogglePseudoFilesystems => "toggle_pseudo_filesystems",
+            Action::StepForward => "step_forward",
+            Action::StepBackward => "step_backward",
+            Action::Faster => "faster",
+            Action::Slower => "slower",
+        }
+    }
+}
+
+impl FromStr for Action {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Action::ALL
+            .into_iter()
+            .find(|action| action.name() == name)
+            .ok_or_else(|| format!("unknown action '{}'", name))
+    }
+}
+
+impl TryFrom<String> for Action {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        name.parse()
+    }
+}
+
+/// Number of rows moved by `Scroll::PageUp` and `Scroll::PageDown`.
+const PAGE_SIZE: usize = 10;
+
+/// Top-level view selected with Tab / Shift+Tab.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, ValueEnum, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum View {
+    /// CPU, per-core, memory and swap gauges
+    #[default]
+    Overview,
+    /// Sortable process table
+    Processes,
+    /// Usage bar per mount point
+    Disks,
+    /// Throughput and sparkline per network interface
+    Network,
+    /// Alert rules, their states and recent state changes
+    Alerts,
+    /// Pressure stall information for CPU, memory and I/O
+    Pressure,
+}
+
+impl View {
+    /// All views in tab order.
+    const ALL: [View; 6] = [
+        View::Overview,
+        View::Processes,
+        View::Disks,
+        View::Network,
+        View::Alerts,
+        View::Pressure,
+    ];
+
+    /// Returns the tab title.
+    fn title(self) -> &'static str {
+        match self {
+            View::Overview => "Overview",
+            View::Processes => "Processes",
+            View::Disks => "Disks",
+            View::Network => "Network",
+            View::Alerts => "Alerts",
+            View::Pressure => "Pressure",
+        }
+    }
+
+    /// Returns the view after this one in `shown`, wrapping around.
+    ///
+    /// A view missing from `shown` moves to the first shown view.
+    fn next_in(self, shown: &[View]) -> Self {
+        match shown.iter().position(|&v| v == self) {
+            Some(i) => shown[(i + 1) % shown.len()],
+            None => shown.first().copied().unwrap_or(self),
+        }
+    }
+
+    /// Returns the view before this one in `shown`, wrapping around.
+    ///
+    /// A view missing from `shown` moves to the first shown view.
+    fn previous_in(self, shown: &[View]) -> Self {
+        match shown.iter().position(|&v| v == self) {
+            Some(i) => shown[(i + shown.len() - 1) % shown.len()],
+            None => shown.first().copied().unwrap_or(self),
+        }
+    }
+
+    /// Returns the collectors whose data only this view displays.
+    fn collectors(self) -> &'static [Collector] {
+        match self {
+            View::Overview => &[],
+            View::Processes => &[Collector::Processes],
+            View::Disks => &[Collector::Disks],
+            View::Network => &[Collector::Networks],
+            View::Alerts | View::Pressure => &[],
+        }
+    }
+}
+
+/// Builds the refresh plan collecting what the `shown` views display.
+///
+/// CPU and memory are always collected: they are cheap and feed the
+/// session statistics. Disks are also collected while a rule of `alerts`
+/// watches them.
+fn views_refresh_plan(shown: &[View], alerts: &[AlertRule]) -> RefreshPlan {
+    let plan = View::ALL
+        .into_iter()
+        .filter(|view| !shown.contains(view))
+        .flat_map(View::collectors)
+        .fold(RefreshPlan::default(), |plan, &collector| {
+            plan.without(collector)
+        });
+    with_alert_collectors(plan, alerts)
+}
+
+/// Enables in `plan` the collectors that the `alerts` rules evaluate, at
+/// their default interval.
+fn with_alert_collectors(plan: RefreshPlan, alerts: &[AlertRule]) -> RefreshPlan {
+    let default = RefreshPlan::default();
+    alerts
+        .iter()
+        .map(|rule| rule.metric.collector())
+        .filter(|&collector| !plan.is_enabled(collector))
+        .fold(plan, |plan, collector| {
+            plan.with_interval(collector, default.interval(collector).unwrap_or_default())
+        })
+}
+
+/// Applies the configured alert rules to `app` and starts the configured
+/// alert actions.
+///
+/// # Arguments
+/// * `app` - App sampling live metrics
+/// * `config` - Alert rules and actions
+/// * `report` - Whether failed notifications are printed to stderr; the
+///   terminal UI lists them in the alerts view instead
+fn start_alerting(app: &mut App, config: &Config, report: bool) -> io::Result<()> {
+    app.set_alert_rules(config.alerts.clone());
+    if !config.actions.is_empty() {
+        let notifier = Notifier::new(config.actions.clone());
+        app.set_notifier(BackgroundNotifier::spawn(notifier, move |failure| {
+            if report {
+                eprintln!("{}", failure);
+            }
+        })?);
+    }
+    Ok(())
+}
+
+/// Entries of the help overlay, in display order: the actions whose keys
+/// are listed and their description.
+const HELP: &[(&[Action], &str)] = &[
+    (&[Action::Quit], "Quit the application"),
+    (&[Action::ToggleHelp], "Toggle this help"),
+    (&[Action::TogglePause], "Pause/Resume monitoring"),
+    (&[Action::ResetStats], "Reset statistics"),
+    (&[Action::NextView, Action::PreviousView], "Switch view"),
+    (
+        &[Action::Scroll(Scroll::Up), Action::Scroll(Scroll::Down)],
+        "Select process",
+    ),
+    (
+        &[
+            Action::Scroll(Scroll::PageUp),
+            Action::Scroll(Scroll::PageDown),
+        ],
+        "Scroll processes by a page",
+    ),
+    (
+        &[Action::Scroll(Scroll::Top), Action::Scroll(Scroll::Bottom)],
+        "Jump to first/last process",
+    ),
+    (
+        &[Action::SortPreviousColumn, Action::SortNextColumn],
+        "Sort processes by previous/next column",
+    ),
+    (&[Action::InvertSort], "Invert process sort order"),
+    (
+        &[Action::TogglePseudoFilesystems],
+        "Show/hide pseudo filesystems",
+    ),
+    (
+        &[Action::StepBackward, Action::StepForward],
+        "Replay: step backward/forward",
+    ),
+    (
+        &[Action::Slower, Action::Faster],
+        "Replay: slower/faster playback",
+    ),
+    (&[Action::CloseHelp], "Close this help"),
+];
+
+/// Presentation state of the TUI that is not part of the metrics.
+///
+/// # Fields
+/// * `show_help` - Whether the help overlay is displayed
+/// * `paused` - Whether metric sampling is suspended
+/// * `view` - Currently displayed view
+/// * `process_sort` - Sort order of the process table
+/// * `selected_process` - Selected row of the process table
+/// * `show_pseudo_filesystems` - Whether the disks view lists pseudo
+///   filesystems such as tmpfs and overlay
+/// * `playback` - Replay position and speed, `None` when monitoring live
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+struct UiState {
+    show_help: bool,
+    paused: bool,
+    view: View,
+    process_sort: ProcessSort,
+    selected_process: usize,
+    show_pseudo_filesystems: bool,
+    playback: Option<PlaybackStatus>,
+}
+
+impl UiState {
+    /// Moves the process selection, keeping it within `rows` rows.
+    fn scroll(&mut self, scroll: Scroll, rows: usize) {
+        let last = rows.saturating_sub(1);
+        self.selected_process = match scroll {
+            Scroll::Up => self.selected_process.saturating_sub(1),
+            Scroll::Down => self.selected_process + 1,
+            Scroll::PageUp => self.selected_process.saturating_sub(PAGE_SIZE),
+            Scroll::PageDown => self.selected_process + PAGE_SIZE,
+            Scroll::Top => 0,
+            Scroll::Bottom => last,
+        }
+        .min(last);
+    }
+}
+
+/// Provider of the application state displayed by the event loop.
+///
+/// Abstracts over live monitoring and session replay so both are rendered
+/// by the same loop and the same panels.
+trait Feed {
+    /// Picks up the snapshots that became available since the last call.
+    ///
+    /// # Returns
+    /// * `io::Result<u64>` - Number of new snapshots
+    fn poll(&mut self) -> io::Result<u64>;
+
+    /// Runs `f` with exclusive access to the displayed app.
+    fn with_app<R>(&mut self, f: impl FnOnce(&mut App) -> R) -> R;
+
+    /// Suspends or resumes sampling or playback.
+    fn set_paused(&mut self, paused: bool);
+
+    /// Applies a playback action; ignored when monitoring live.
+    fn playback(&mut self, _action: Action) {}
+
+    /// Returns the replay position, or `None` when monitoring live.
+    fn status(&self) -> Option<PlaybackStatus> {
+        None
+    }
+}
+
+/// Live metrics sampled by a background collector.
+///
+/// # Fields
+/// * `collector` - Collector sampling the application state
+/// * `session` - Session file every new snapshot is appended to, if any
+struct LiveFeed<'a> {
+    collector: &'a BackgroundCollector,
+    session: Option<SessionWriter>,
+}
+
+impl Feed for LiveFeed<'_> {
+    fn poll(&mut self) -> io::Result<u64> {
+        let mut count = 0;
+        for snapshot in self.collector.try_iter() {
+            if let Some(session) = &mut self.session {
+                session.record(&snapshot)?;
+            }
+            count += 1;
+        }
+        Ok(count)
+    }
+
+    fn with_app<R>(&mut self, f: impl FnOnce(&mut App) -> R) -> R {
+        self.collector.with_app(f)
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.collector.set_paused(paused);
+    }
+}
+
+/// Recorded session played back at its recorded pace.
+///
+/// # Fields
+/// * `player` - Playback position and the app rebuilt from it
+/// * `last_poll` - Wall-clock time playback was last advanced
+struct ReplayFeed {
+    player: Player,
+    last_poll: Instant,
+}
+
+impl Feed for ReplayFeed {
+    fn poll(&mut self) -> io::Result<u64> {
+        let now = Instant::now();
+        let steps = self.player.advance(now - self.last_poll);
+        self.last_poll = now;
+        Ok(steps as u64)
+    }
+
+    fn with_app<R>(&mut self, f: impl FnOnce(&mut App) -> R) -> R {
+        f(self.player.app_mut())
+    }
+
+    fn set_paused(&mut self, paused: bool) {
+        self.player.set_paused(paused);
+    }
+
+    fn playback(&mut self, action: Action) {
+        match action {
+            Action::StepForward => {
+                self.player.step_forward();
+            }
+            Action::StepBackward => {
+                self.player.step_backward();
+            }
+            Action::Faster => self.player.faster(),
+            Action::Slower => self.player.slower(),
+            _ => {}
+        }
+    }
+
+    fn status(&self) -> Option<PlaybackStatus> {
+        Some(self.player.status())
+    }
+}
 
 /// Runs the application's main event loop.
 ///
-/// Handles terminal events and updates the UI at regular intervals.
-/// The loop continues until the user presses 'q' to quit.
+/// Metrics are sampled by a [`BackgroundCollector`] on its own thread, so
+/// a slow refresh never stalls input handling or drawing. The loop never
+/// blocks on input: each iteration waits at most until the next render is
+/// due (bounded by `config.tick_rate`), picks up the snapshots published
+/// in the meantime and redraws the UI as required. Resize events, key
+/// presses and new snapshots trigger an immediate redraw. While paused,
+/// the collector stops sampling but the UI keeps responding to input. The
+/// loop exits when the user presses 'q' or Ctrl+C.
 ///
 /// # Arguments
 /// * `terminal` - Mutable reference to the terminal backend
-/// * `app` - Mutable reference to the application state
+/// * `app` - Application state, sampled in the background while running
+/// * `events` - Source of input events
+/// * `config` - Tick, render and sample cadences, and the shown views
+/// * `session` - Session file every sample is appended to, if any
 ///
 /// # Returns
-/// * `io::Result<()>` - Success if the application exits normally
+/// * `io::Result<(LoopStats, App)>` - Loop counters and the application
+///   state if the application exits normally
 ///
 /// # Example
 /// ```no_run
@@ -59,144 +642,2202 @@ use system_monitor::App;
 ///
 /// let backend = CrosstermBackend::new(std::io::stdout());
 /// let mut terminal = Terminal::new(backend).unwrap();
-/// let mut app = App::new();
-/// run_app(&mut terminal, &mut app).unwrap();
+/// run_app(&mut terminal, App::new(), &mut CrosstermEvents, Config::default(), None).unwrap();
 /// ```
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
+fn run_app<B: Backend, E: EventSource>(
+    terminal: &mut Terminal<B>,
+    app: App,
+    events: &mut E,
+    config: Config,
+    session: Option<SessionWriter>,
+) -> io::Result<(LoopStats, App)> {
+    let collector = BackgroundCollector::spawn(app, config.sample_interval)?;
+    let mut feed = LiveFeed {
+        collector: &collector,
+        session,
+    };
+    let result = event_loop(terminal, &mut feed, events, config);
+    let app = collector.stop();
+    result.map(|stats| (stats, app))
+}
+
+/// Runs the main event loop on a recorded session.
+///
+/// All panels render from the recorded snapshots. Playback starts at the
+/// first snapshot and follows the recorded pace; 'p' pauses, Left/Right
+/// step and '-'/'+' change the speed.
+///
+/// # Arguments
+/// * `terminal` - Mutable reference to the terminal backend
+/// * `player` - Player positioned where playback starts
+/// * `events` - Source of input events
+/// * `config` - Tick and render cadences
+///
+/// # Returns
+/// * `io::Result<(LoopStats, Player)>` - Loop counters and the player at
+///   the position where the user quit
+fn run_replay<B: Backend, E: EventSource>(
+    terminal: &mut Terminal<B>,
+    player: Player,
+    events: &mut E,
+    config: Config,
+) -> io::Result<(LoopStats, Player)> {
+    let mut feed = ReplayFeed {
+        player,
+        last_poll: Instant::now(),
+    };
+    let stats = event_loop(terminal, &mut feed, events, config)?;
+    Ok((stats, feed.player))
+}
+
+/// Handles input and drawing until the user quits.
+///
+/// # Arguments
+/// * `terminal` - Mutable reference to the terminal backend
+/// * `feed` - Live or recorded metrics to display
+/// * `events` - Source of input events
+/// * `config` - Tick and render cadences
+fn event_loop<B: Backend, E: EventSource, F: Feed>(
+    terminal: &mut Terminal<B>,
+    feed: &mut F,
+    events: &mut E,
+    config: Config,
+) -> io::Result<LoopStats> {
+    let mut stats = LoopStats::default();
+    let mut last_render = Instant::now();
+    let mut needs_redraw = true;
+    let mut state = UiState {
+        view: config.views.first().copied().unwrap_or_default(),
+        ..UiState::default()
+    };
+
     loop {
-        terminal.draw(|f| ui(f, app))?;
+        let new_samples = feed.poll()?;
+        if new_samples > 0 {
+            stats.samples += new_samples;
+            needs_redraw = true;
+        }
+        state.playback = feed.status();
+
+        if needs_redraw || last_render.elapsed() >= config.render_interval {
+            feed.with_app(|app| terminal.draw(|f| ui(f, app, &state, &config)))?;
+            stats.frames += 1;
+            last_render = Instant::now();
+            needs_redraw = false;
+        }
+
+        let until_render = config.render_interval.saturating_sub(last_render.elapsed());
+        let timeout = config.tick_rate.min(until_render);
 
-        if let Event::Key(key) = event::read()? {
-            if let KeyCode::Char('q') = key.code {
-                return Ok(());
+        if events.poll(timeout)? {
+            match events.read()? {
+                Event::Key(key) if key.kind == KeyEventKind::Press => {
+                    match config.keys.action(&key) {
+                        Some(Action::Quit) => return Ok(stats),
+                        Some(Action::ToggleHelp) => state.show_help = !state.show_help,
+                        Some(Action::CloseHelp) => state.show_help = false,
+                        Some(Action::TogglePause) => {
+                            state.paused = !state.paused;
+                            feed.set_paused(state.paused);
+                        }
+                        Some(Action::ResetStats) => feed.with_app(|app| app.reset_stats()),
+                        Some(Action::NextView) => state.view = state.view.next_in(&config.views),
+                        Some(Action::PreviousView) => {
+                            state.view = state.view.previous_in(&config.views)
+                        }
+                        Some(Action::Scroll(scroll)) => {
+                            let count = feed.with_app(|app| app.snapshot().processes.len());
+                            state.scroll(scroll, count)
+                        }
+                        Some(Action::SortNextColumn) => {
+                            state.process_sort.column = state.process_sort.column.next()
+                        }
+                        Some(Action::SortPreviousColumn) => {
+                            state.process_sort.column = state.process_sort.column.previous()
+                        }
+                        Some(Action::InvertSort) => {
+                            state.process_sort.descending = !state.process_sort.descending
+                        }
+                        Some(Action::TogglePseudoFilesystems) => {
+                            state.show_pseudo_filesystems = !state.show_pseudo_filesystems
+                        }
+                        Some(
+                            action @ (Action::StepForward
+                            | Action::StepBackward
+                            | Action::Faster
+                            | Action::Slower),
+                        ) => {
+                            feed.playback(action);
+                            state.playback = feed.status();
+                        }
+                        None => continue,
+                    }
+                    needs_redraw = true;
+                }
+                Event::Resize(_, _) => needs_redraw = true,
+                _ => {}
             }
         }
+    }
+}
 
-        app.update();
-        std::thread::sleep(Duration::from_millis(250));
+/// Formats a panel title with the session statistics of its metric.
+///
+/// # Arguments
+/// * `name` - Panel name
+/// * `stats` - Statistics accumulated since the last reset
+fn stats_title(name: &str, stats: &MetricStats) -> String {
+    match (stats.min(), stats.average(), stats.max()) {
+        (Some(min), Some(avg), Some(max)) => format!(
+            "{} (min {:.1}% / avg {:.1}% / max {:.1}%)",
+            name, min, avg, max
+        ),
+        _ => name.to_string(),
     }
 }
 
-/// Renders the user interface.
+/// Computes a rectangle of the given size centered within `area`.
 ///
-/// Creates a vertical layout with three sections:
-/// * Title section (2 units high)
-/// * CPU usage gauge (50% of remaining space)
-/// * Memory usage gauge (50% of remaining space)
+/// The size is clamped to `area` so the result never extends past it.
 ///
 /// # Arguments
-/// * `f` - Frame used for rendering
-/// * `app` - Mutable reference to application state
-///
-/// # Type Parameters
-/// * `B` - Backend implementing the `Backend` trait
-fn ui<B: Backend>(f: &mut Frame<B>, app: &mut App) {
-    let chunks = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(2),   // Fixed height for title
-            Constraint::Ratio(1, 2), // Half of remaining space
-            Constraint::Ratio(1, 2), // Half of remaining space
-        ])
-        .split(f.size());
+/// * `width` - Desired width
+/// * `height` - Desired height
+/// * `area` - Enclosing rectangle
+fn centered_rect(width: u16, height: u16, area: Rect) -> Rect {
+    let width = width.min(area.width);
+    let height = height.min(area.height);
+    Rect::new(
+        area.x + (area.width - width) / 2,
+        area.y + (area.height - height) / 2,
+        width,
+        height,
+    )
+}
 
-    // Title
-    let title = Paragraph::new(Spans::from(vec![
-        Span::styled(
-            "System Monitor",
-            Style::default()
-                .fg(Color::Green)
-                .add_modifier(Modifier::BOLD),
-        ),
-        Span::raw(" (Press 'q' to quit)"),
-    ]));
-    f.render_widget(title, chunks[0]);
+/// Renders the help overlay listing the configured key bindings.
+fn render_help<B: Backend>(f: &mut Frame<B>, keys: &KeyMap) {
+    let entries: Vec<(String, &str)> = HELP
+        .iter()
+        .map(|&(actions, description)| (keys.describe(actions), description))
+        .collect();
+    let key_width = entries
+        .iter()
+        .map(|(key, _)| key.chars().count())
+        .max()
+        .unwrap_or(0);
+    // Two extra rows and columns for the border
+    let width = entries
+        .iter()
+        .map(|(_, description)| (key_width + 1 + description.len()) as u16 + 2)
+        .max()
+        .unwrap_or(0)
+        .max(56);
+    let area = centered_rect(width, entries.len() as u16 + 2, f.size());
+    let lines: Vec<Spans> = entries
+        .into_iter()
+        .map(|(key, description)| {
+            Spans::from(vec![
+                Span::styled(
+                    format!("{:<width$} ", key, width = key_width.max(11)),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(description),
+            ])
+        })
+        .collect();
+    let help = Paragraph::new(lines).block(Block::default().title("Help").borders(Borders::ALL));
+    f.render_widget(Clear, area);
+    f.render_widget(help, area);
+}
 
-    // CPU Usage Gauge
-    let cpu_usage = app.get_cpu_usage();
-    let cpu_gauge = Gauge::default()
-        .block(Block::default().title("CPU Usage").borders(Borders::ALL))
-        .gauge_style(Style::default().fg(Color::Cyan))
-        .percent(cpu_usage as u16);
-    f.render_widget(cpu_gauge, chunks[1]);
-
-    // Memory Usage Gauge
-    let memory_usage = app.get_memory_usage();
-    let memory_gauge = Gauge::default()
-        .block(Block::default().title("Memory Usage").borders(Borders::ALL))
-        .gauge_style(Style::default().fg(Color::Magenta))
-        .percent(memory_usage as u16);
-    f.render_widget(memory_gauge, chunks[2]);
+/// Formats a byte count using binary units (KiB, MiB, GiB, ...).
+fn format_bytes(bytes: u64) -> String {
+    const UNITS: [&str; 6] = ["B", "KiB", "MiB", "GiB", "TiB", "PiB"];
+    let mut value = bytes as f64;
+    let mut unit = 0;
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{} {}", bytes, UNITS[0])
+    } else {
+        format!("{:.1} {}", value, UNITS[unit])
+    }
 }
 
-// UI-specific tests
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// Formats a gauge label such as `1.5 GiB / 8.0 GiB (18%)`.
+fn usage_label(used: u64, total: u64, percent: f32) -> String {
+    format!(
+        "{} / {} ({:.0}%)",
+        format_bytes(used),
+        format_bytes(total),
+        percent
+    )
+}
 
-    /// Tests the basic UI layout configuration.
-    ///
-    /// Verifies:
-    /// * Correct number of sections (3)
-    /// * Title height (2 units)
-    /// * Full width usage
-    /// * Proper vertical positioning
-    /// * Height distribution
-    #[test]
-    fn test_ui_layout() {
-        // Create a mock terminal size
-        let size = Rect::new(0, 0, 100, 100); // Mock terminal of 100x100
+/// Converts a percentage into a gauge ratio.
+///
+/// Values outside 0-100 are clamped and non-finite values map to an empty
+/// gauge, since `Gauge` panics on ratios outside 0.0-1.0.
+fn gauge_ratio(percent: f32) -> f64 {
+    if percent.is_finite() {
+        f64::from(percent.clamp(0.0, 100.0)) / 100.0
+    } else {
+        0.0
+    }
+}
 
-        let chunks = Layout::default()
-            .direction(Direction::Vertical)
-            .constraints([
-                Constraint::Length(2),   // Fixed height for title
-                Constraint::Ratio(1, 2), // Half of remaining space
-                Constraint::Ratio(1, 2), // Half of remaining space
-            ])
-            .split(size);
+/// Returns the style of a gauge showing `percent`.
+///
+/// # Arguments
+/// * `normal` - Color of the gauge below its warning threshold
+/// * `threshold` - Warning and critical levels of the metric
+/// * `theme` - Theme providing the warning and critical colors
+fn level_style(normal: Color, threshold: &Threshold, percent: f32, theme: &Theme) -> Style {
+    Style::default().fg(theme.level_color(normal, threshold.level(percent)))
+}
 
-        // Test layout structure
-        assert_eq!(chunks.len(), 3, "Layout should have three sections");
+/// Returns the style of a gauge showing `percent` of `metric`.
+///
+/// While an alert on `metric` is firing the gauge is drawn in the critical
+/// color whatever its thresholds, else as [`level_style`] picks.
+fn gauge_style(
+    alerts: &AlertEngine,
+    metric: AlertMetric,
+    normal: Color,
+    threshold: &Threshold,
+    percent: f32,
+    theme: &Theme,
+) -> Style {
+    if alerts.is_firing(metric) {
+        Style::default().fg(theme.critical)
+    } else {
+        level_style(normal, threshold, percent, theme)
+    }
+}
 
-        // Test section heights
-        assert_eq!(chunks[0].height, 2, "Title should be exactly 2 units high");
+/// Turns `gauge` into an empty, greyed-out gauge explaining why its metric
+/// is unavailable.
+fn unavailable_gauge<'a>(gauge: Gauge<'a>, err: &MonitorError, theme: &Theme) -> Gauge<'a> {
+    gauge
+        .gauge_style(Style::default().fg(theme.unavailable))
+        .ratio(0.0)
+        .label(format!("unavailable ({})", err))
+}
 
-        // Test that sections fill the width
-        assert_eq!(chunks[0].width, 100, "Sections should use full width");
-        assert_eq!(chunks[1].width, 100, "Sections should use full width");
-        assert_eq!(chunks[2].width, 100, "Sections should use full width");
+/// Splits the memory section into memory and swap gauges.
+///
+/// When the host has no swap configured, the memory gauge takes the
+/// whole section and the swap gauge is hidden.
+///
+/// # Returns
+/// * `Vec<Rect>` - Memory area, followed by the swap area if `has_swap`
+fn memory_layout(area: Rect, has_swap: bool) -> Vec<Rect> {
+    if !has_swap {
+        return vec![area];
+    }
+    Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Ratio(1, 2), Constraint::Ratio(1, 2)])
+        .split(area)
+}
 
-        // Test vertical positioning
-        assert_eq!(chunks[0].y, 0, "Title should start at top");
-        assert_eq!(chunks[1].y, 2, "CPU gauge should start after title");
-        assert_eq!(
-            chunks[2].y,
-            chunks[1].y + chunks[1].height,
-            "Memory gauge should start after CPU gauge"
-        );
+/// Minimum width of one column of per-core bars.
+const MIN_CORE_COLUMN_WIDTH: u16 = 24;
 
-        // Test that the layout fills the entire height
-        assert_eq!(
-            chunks[0].height + chunks[1].height + chunks[2].height,
-            size.height,
-            "Layout should fill entire height"
-        );
+/// Lays out one single-line cell per core inside `area`.
+///
+/// Cores fill the grid column by column. The number of columns grows with
+/// the available width (at least `MIN_CORE_COLUMN_WIDTH` per column) so wide
+/// terminals show more cores side by side. Cores that do not fit are
+/// dropped from the end.
+///
+/// # Arguments
+/// * `area` - Area available for the bars
+/// * `cores` - Number of cores to lay out
+///
+/// # Returns
+/// * `Vec<Rect>` - One cell per visible core, in core order
+fn core_grid(area: Rect, cores: usize) -> Vec<Rect> {
+    if cores == 0 || area.width == 0 || area.height == 0 {
+        return Vec::new();
+    }
 
-        // Test that CPU and Memory sections are approximately equal
-        assert!(
-            (chunks[1].height as i32 - chunks[2].height as i32).abs() <= 2,
-            "CPU and Memory sections should be approximately equal"
-        );
+    let max_columns = usize::from((area.width / MIN_CORE_COLUMN_WIDTH).max(1));
+    let columns = cores.min(max_columns);
+    let rows = cores.div_ceil(columns).min(usize::from(area.height));
+    let column_width = area.width / columns as u16;
+
+    (0..cores.min(rows * columns))
+        .map(|index| {
+            let column = (index / rows) as u16;
+            let row = (index % rows) as u16;
+            Rect::new(
+                area.x + column * column_width,
+                area.y + row,
+                column_width.saturating_sub(1).max(1),
+                1,
+            )
+        })
+        .collect()
+}
+
+/// Returns the busy modes of `shares` with their colors in `theme`, in
+/// stacking order.
+fn cpu_segments(shares: &CpuShares, theme: &Theme) -> Vec<(f32, Color)> {
+    CpuMode::ALL
+        .iter()
+        .filter_map(|&mode| Some((shares.get(mode), theme.cpu_mode_color(mode)?)))
+        .collect()
+}
+
+/// Builds a bar `width` cells wide stacking `segments` left to right.
+///
+/// Each segment is a percentage of the whole width with its color. Segment
+/// ends are rounded from the running total, so rounding errors do not add
+/// up and the filled part matches the sum of the percentages. Whatever is
+/// left after the last segment stays empty.
+///
+/// # Arguments
+/// * `segments` - Percentages between 0.0 and 100.0 and their colors
+/// * `width` - Width of the bar in cells
+///
+/// # Returns
+/// * `Vec<Span>` - One span of colored cells per non-empty segment
+fn stacked_bar(segments: &[(f32, Color)], width: u16) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut total = 0.0;
+    let mut start = 0;
+    for &(percent, color) in segments {
+        if !percent.is_finite() {
+            continue;
+        }
+        total += percent.max(0.0);
+        let end = (gauge_ratio(total) * f64::from(width)).round() as usize;
+        if end > start {
+            spans.push(Span::styled(
+                " ".repeat(end - start),
+                Style::default().bg(color),
+            ));
+            start = end;
+        }
     }
+    spans
+}
 
-    /// Tests layout constraints across various terminal sizes.
-    ///
-    /// Verifies layout behavior for:
-    /// * Standard terminal (80x24)
-    /// * Large terminal (120x40)
-    /// * Small terminal (60x20)
-    /// * Tiny terminal (10x10)
+/// Renders the overall CPU time breakdown: a stacked bar of the busy modes
+/// and a legend with their shares in the colors of the bar.
+///
+/// Modes without any time are left out of the legend, except steal time,
+/// which is always listed since its absence is worth knowing on a VM.
+/// Iowait is listed uncolored, as part of the empty rest of the bar; idle
+/// time is the rest of the busy share in the title.
+fn render_cpu_time<B: Backend>(f: &mut Frame<B>, usage: &CpuTimeUsage, area: Rect, theme: &Theme) {
+    let total = &usage.total;
+    let block = Block::default()
+        .title(format!("CPU Time (busy {:.1}%)", total.busy()))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let legend: Vec<Span> = CpuMode::ALL
+        .iter()
+        .filter(|&&mode| mode != CpuMode::Idle)
+        .filter_map(|&mode| {
+            let share = total.get(mode);
+            let style = match theme.cpu_mode_color(mode) {
+                Some(color) => Style::default().fg(color),
+                None => Style::default(),
+            };
+            (share >= 0.05 || mode == CpuMode::Steal)
+                .then(|| Span::styled(format!("{} {:.1}% ", mode.name(), share), style))
+        })
+        .collect();
+    let lines = vec![
+        Spans::from(stacked_bar(&cpu_segments(total, theme), inner.width)),
+        Spans::from(legend),
+    ];
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Renders one usage bar per CPU core.
+///
+/// Cores with a CPU time breakdown in `cpu_time` (matched by name, e.g.
+/// `cpu3`) get a stacked bar of their busy modes after their name and
+/// usage; the others a plain gauge.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `cores` - Per-core usage to display
+/// * `cpu_time` - Per-core CPU time breakdown, if available
+/// * `area` - Area of the panel including its border
+/// * `config` - Colors and CPU thresholds
+fn render_per_core<B: Backend>(
+    f: &mut Frame<B>,
+    cores: &[CoreUsage],
+    cpu_time: Option<&CpuTimeUsage>,
+    area: Rect,
+    config: &Config,
+) {
+    let block = Block::default()
+        .title("Per-Core Usage")
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    if cores.is_empty() {
+        let message = Paragraph::new(format!("unavailable ({})", MonitorError::NoCpuCores))
+            .style(Style::default().fg(config.theme.unavailable));
+        f.render_widget(message, inner);
+        return;
+    }
+
+    for (core, cell) in cores.iter().zip(core_grid(inner, cores.len())) {
+        let label = if core.usage.is_finite() {
+            format!("{} {:.1}%", core.name, core.usage)
+        } else {
+            format!("{} unavailable", core.name)
+        };
+        // `/proc/stat` skips offline CPUs while sysinfo indexes the online
+        // ones consecutively, so match on the kernel's name instead
+        let shares = cpu_time
+            .and_then(|usage| {
+                usage
+                    .cores
+                    .iter()
+                    .find(|shares| core.name == format!("cpu{}", shares.id))
+            })
+            .map(|shares| &shares.shares);
+        if let Some(shares) = shares {
+            let label = format!("{:<12}", label);
+            let width = cell.width.saturating_sub(label.chars().count() as u16);
+            let mut spans = vec![Span::raw(label)];
+            spans.extend(stacked_bar(&cpu_segments(shares, &config.theme), width));
+            f.render_widget(Paragraph::new(Spans::from(spans)), cell);
+            continue;
+        }
+        let bar = Gauge::default()
+            .gauge_style(level_style(
+                config.theme.cpu,
+                &config.thresholds.cpu,
+                core.usage,
+                &config.theme,
+            ))
+            .ratio(gauge_ratio(core.usage))
+            .label(label);
+        f.render_widget(bar, cell);
+    }
+}
+
+/// Builds the gauge of the container's CPU usage against its quota.
+///
+/// The label shows how often the cgroup was throttled, since a container
+/// pinned at its quota is slowed down even when the host is idle.
+fn container_cpu_gauge<'a>(container: &ContainerUsage, cpus: f64, config: &Config) -> Gauge<'a> {
+    let gauge = Gauge::default().block(
+        Block::default()
+            .title(format!("Container CPU (limit {:.1} CPUs)", cpus))
+            .borders(Borders::ALL),
+    );
+    let Some(usage) = container.cpu_usage else {
+        return gauge
+            .gauge_style(Style::default().fg(config.theme.unavailable))
+            .ratio(0.0)
+            .label("measuring");
+    };
+    gauge
+        .gauge_style(level_style(
+            config.theme.cpu,
+            &config.thresholds.cpu,
+            usage,
+            &config.theme,
+        ))
+        .ratio(gauge_ratio(usage))
+        .label(format!(
+            "{:.1}% (throttled {} of {} periods)",
+            usage, container.cpu.nr_throttled, container.cpu.nr_periods
+        ))
+}
+
+/// Builds the gauge of the container's memory against `memory.max`.
+fn container_memory_gauge<'a>(
+    container: &ContainerUsage,
+    limit: u64,
+    usage: f32,
+    config: &Config,
+) -> Gauge<'a> {
+    Gauge::default()
+        .block(
+            Block::default()
+                .title(format!("Container Memory (limit {})", format_bytes(limit)))
+                .borders(Borders::ALL),
+        )
+        .gauge_style(level_style(
+            config.theme.memory,
+            &config.thresholds.memory,
+            usage,
+            &config.theme,
+        ))
+        .ratio(gauge_ratio(usage))
+        .label(usage_label(container.memory_used, limit, usage))
+}
+
+/// Renders the memory usage as a bar segmented into application memory,
+/// buffers, shared memory and reclaimable caches, with their sizes below.
+///
+/// The headline percentage in front of the bar is based on `MemAvailable`,
+/// so caches the kernel can drop do not count as used even though they
+/// fill the bar. A last line lists slab, dirty and writeback memory and the
+/// huge page pool if one is configured.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `meminfo` - Memory breakdown to display
+/// * `title` - Title of the panel
+/// * `used_color` - Color of the application memory, which follows the
+///   memory thresholds and alerts
+/// * `area` - Area of the panel including its border
+/// * `theme` - Colors of the other segments
+fn render_memory_breakdown<B: Backend>(
+    f: &mut Frame<B>,
+    meminfo: &MemInfo,
+    title: String,
+    used_color: Color,
+    area: Rect,
+    theme: &Theme,
+) {
+    let block = Block::default().title(title).borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    let share = |bytes: u64| bytes as f32 / meminfo.total.max(1) as f32 * 100.0;
+    let segments = [
+        ("used", meminfo.application(), used_color),
+        ("buffers", meminfo.buffers, theme.memory_buffers),
+        ("shared", meminfo.shared, theme.memory_shared),
+        ("cache", meminfo.cache(), theme.memory_cache),
+    ];
+
+    let headline = format!("{:5.1}% ", meminfo.usage().unwrap_or(0.0));
+    let width = inner.width.saturating_sub(headline.len() as u16);
+    let mut bar = vec![Span::raw(headline)];
+    bar.extend(stacked_bar(
+        &segments.map(|(_, bytes, color)| (share(bytes), color)),
+        width,
+    ));
+
+    let legend: Vec<Span> = segments
+        .iter()
+        .map(|&(name, bytes, color)| {
+            Span::styled(
+                format!("{} {}  ", name, format_bytes(bytes)),
+                Style::default().fg(color),
+            )
+        })
+        .collect();
+
+    let mut details = format!(
+        "available {}  slab {}  dirty {}  writeback {}",
+        format_bytes(meminfo.available),
+        format_bytes(meminfo.slab),
+        format_bytes(meminfo.dirty),
+        format_bytes(meminfo.writeback)
+    );
+    let hugepages = &meminfo.hugepages;
+    if hugepages.total > 0 {
+        let _ = write!(
+            details,
+            "  hugepages {} / {}",
+            format_bytes(hugepages.used_bytes()),
+            format_bytes(hugepages.total_bytes())
+        );
+    }
+
+    let lines = vec![Spans::from(bar), Spans::from(legend), Spans::from(details)];
+    f.render_widget(Paragraph::new(lines), inner);
+}
+
+/// Renders the overview: CPU gauge, per-core bars, memory and swap gauges.
+///
+/// When the time per CPU mode is known (Linux), a stacked bar of user,
+/// system, iowait, steal and the other busy modes is added below the CPU
+/// gauge, and the per-core bars are stacked the same way. With the memory
+/// breakdown (Linux), the memory gauge becomes a bar segmented into used,
+/// buffers, shared and cached memory.
+///
+/// On terminals at least `layout.min_chart_width` wide, each section is
+/// split with a history chart of its metrics on the right. Gauges change
+/// color above their warning and critical thresholds, and turn to the
+/// critical color while an alert on their metric is firing. When the
+/// monitor runs in a cgroup with a CPU quota or memory limit, gauges of the
+/// container's usage against those limits are added to each section.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `app` - Reference to application state
+/// * `cpu_area` - Area of the CPU section
+/// * `memory_area` - Area of the memory section
+/// * `config` - Layout, colors and thresholds
+fn render_overview<B: Backend>(
+    f: &mut Frame<B>,
+    app: &App,
+    cpu_area: Rect,
+    memory_area: Rect,
+    config: &Config,
+) {
+    let snapshot = app.snapshot();
+    let theme = &config.theme;
+    let thresholds = &config.thresholds;
+    let alerts = app.alerts();
+    let (cpu_area, cpu_chart_area) = split_for_chart(cpu_area, &config.layout);
+    let (memory_area, memory_chart_area) = split_for_chart(memory_area, &config.layout);
+
+    let container = snapshot.container.as_ref();
+    let container_cpus = container.and_then(ContainerUsage::cpu_limit);
+    let container_memory = container.and_then(|container| {
+        Some((
+            container,
+            container.memory_limit?,
+            container.memory_usage()?,
+        ))
+    });
+
+    // CPU section: aggregate gauge, then the container's gauge if it has a
+    // quota and the CPU time breakdown if known, above the per-core bars
+    let container_height = if container_cpus.is_some() { 3 } else { 0 };
+    let cpu_time_height = if snapshot.cpu_time.is_some() { 4 } else { 0 };
+    let cpu_chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Length(container_height),
+            Constraint::Length(cpu_time_height),
+            Constraint::Min(0),
+        ])
+        .split(cpu_area);
+
+    // CPU Usage Gauge
+    let cpu_gauge = Gauge::default().block(
+        Block::default()
+            .title(stats_title("CPU Usage", app.cpu_stats()))
+            .borders(Borders::ALL),
+    );
+    let cpu_gauge = match snapshot.cpu_usage() {
+        Ok(usage) => cpu_gauge
+            .gauge_style(gauge_style(
+                alerts,
+                AlertMetric::Cpu,
+                theme.cpu,
+                &thresholds.cpu,
+                usage,
+                theme,
+            ))
+            .ratio(gauge_ratio(usage)),
+        Err(err) => unavailable_gauge(cpu_gauge, &err, theme),
+    };
+    f.render_widget(cpu_gauge, cpu_chunks[0]);
+
+    // Container CPU Gauge
+    if let (Some(container), Some(cpus)) = (container, container_cpus) {
+        f.render_widget(container_cpu_gauge(container, cpus, config), cpu_chunks[1]);
+    }
+
+    // CPU Time Breakdown
+    if let Some(cpu_time) = &snapshot.cpu_time {
+        render_cpu_time(f, cpu_time, cpu_chunks[2], theme);
+    }
+
+    // Per-Core Usage Bars
+    render_per_core(
+        f,
+        &snapshot.cores,
+        snapshot.cpu_time.as_ref(),
+        cpu_chunks[3],
+        config,
+    );
+
+    // Memory section: swap gauge below memory, only if swap is configured,
+    // and the container's gauge at the bottom if it has a memory limit
+    let (memory_area, container_memory_area) = match container_memory {
+        Some(_) => {
+            let parts = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([Constraint::Min(0), Constraint::Length(3)])
+                .split(memory_area);
+            (parts[0], Some(parts[1]))
+        }
+        None => (memory_area, None),
+    };
+    let memory_chunks = memory_layout(memory_area, snapshot.has_swap());
+
+    // Memory Usage Gauge, segmented when the breakdown is known
+    let memory_title = stats_title("Memory Usage", app.memory_stats());
+    if let (Some(meminfo), Ok(usage)) = (&snapshot.meminfo, snapshot.memory_usage()) {
+        let style = gauge_style(
+            alerts,
+            AlertMetric::Memory,
+            theme.memory,
+            &thresholds.memory,
+            usage,
+            theme,
+        );
+        let used_color = style.fg.unwrap_or(theme.memory);
+        render_memory_breakdown(
+            f,
+            meminfo,
+            memory_title,
+            used_color,
+            memory_chunks[0],
+            theme,
+        );
+    } else {
+        let memory_gauge =
+            Gauge::default().block(Block::default().title(memory_title).borders(Borders::ALL));
+        let memory_gauge = match snapshot.memory_usage() {
+            Ok(usage) => memory_gauge
+                .gauge_style(gauge_style(
+                    alerts,
+                    AlertMetric::Memory,
+                    theme.memory,
+                    &thresholds.memory,
+                    usage,
+                    theme,
+                ))
+                .ratio(gauge_ratio(usage))
+                .label(usage_label(
+                    snapshot.used_memory,
+                    snapshot.total_memory,
+                    usage,
+                )),
+            Err(err) => unavailable_gauge(memory_gauge, &err, theme),
+        };
+        f.render_widget(memory_gauge, memory_chunks[0]);
+    }
+
+    // Swap Usage Gauge
+    if let Some(swap_usage) = snapshot.swap_usage() {
+        let swap_gauge = Gauge::default()
+            .block(Block::default().title("Swap Usage").borders(Borders::ALL))
+            .gauge_style(gauge_style(
+                alerts,
+                AlertMetric::Swap,
+                theme.swap,
+                &thresholds.swap,
+                swap_usage,
+                theme,
+            ))
+            .ratio(gauge_ratio(swap_usage))
+            .label(usage_label(
+                snapshot.used_swap,
+                snapshot.total_swap,
+                swap_usage,
+            ));
+        f.render_widget(swap_gauge, memory_chunks[1]);
+    }
+
+    // Container Memory Gauge
+    if let (Some((container, limit, usage)), Some(area)) = (container_memory, container_memory_area)
+    {
+        f.render_widget(
+            container_memory_gauge(container, limit, usage, config),
+            area,
+        );
+    }
+
+    // History Charts
+    if let Some(area) = cpu_chart_area {
+        render_history_chart(
+            f,
+            "CPU History",
+            &[(app.history(Metric::Cpu), "CPU", theme.cpu)],
+            area,
+        );
+    }
+    if let Some(area) = memory_chart_area {
+        let mut series = vec![(app.history(Metric::Memory), "Memory", theme.memory)];
+        if snapshot.has_swap() {
+            series.push((app.history(Metric::Swap), "Swap", theme.swap));
+        }
+        render_history_chart(f, "Memory History", &series, area);
+    }
+}
+
+/// Splits a section into a gauge area and an optional chart area.
+///
+/// # Returns
+/// * `(Rect, Option<Rect>)` - Gauge area and chart area, with
+///   `layout.chart_percent` of the width given to the chart, or the whole
+///   area and `None` if it is narrower than `layout.min_chart_width`
+fn split_for_chart(area: Rect, layout: &LayoutConfig) -> (Rect, Option<Rect>) {
+    if area.width < layout.min_chart_width {
+        return (area, None);
+    }
+    let parts = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(100 - layout.chart_percent),
+            Constraint::Percentage(layout.chart_percent),
+        ])
+        .split(area);
+    (parts[0], Some(parts[1]))
+}
+
+/// Formats a duration compactly, e.g. `45s`, `5m` or `2m30s`.
+fn format_span(span: Duration) -> String {
+    let seconds = span.as_secs();
+    match (seconds / 60, seconds % 60) {
+        (0, s) => format!("{}s", s),
+        (m, 0) => format!("{}m", m),
+        (m, s) => format!("{}m{}s", m, s),
+    }
+}
+
+/// Converts a history into chart points.
+///
+/// The x coordinate is the age of the sample in seconds relative to the
+/// newest sample (so the newest point is at 0 and older points are
+/// negative); the y coordinate is the value.
+fn chart_points(history: &History<Sample>) -> Vec<(f64, f64)> {
+    let Some(newest) = history.latest() else {
+        return Vec::new();
+    };
+    history
+        .iter()
+        .map(|sample| {
+            let age = newest
+                .timestamp
+                .duration_since(sample.timestamp)
+                .unwrap_or_default();
+            (-age.as_secs_f64(), f64::from(sample.value))
+        })
+        .collect()
+}
+
+/// Renders a line chart of one or more percentage histories.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `title` - Chart title; the covered time span is appended
+/// * `series` - History, legend name and color of each line
+/// * `area` - Area of the chart including its border
+fn render_history_chart<B: Backend>(
+    f: &mut Frame<B>,
+    title: &str,
+    series: &[(&History<Sample>, &str, Color)],
+    area: Rect,
+) {
+    let span = series
+        .iter()
+        .map(|(history, _, _)| history.span())
+        .max()
+        .unwrap_or_default();
+    let window = span.as_secs_f64().max(1.0);
+    let points: Vec<Vec<(f64, f64)>> = series
+        .iter()
+        .map(|(history, _, _)| chart_points(history))
+        .collect();
+    let datasets = series
+        .iter()
+        .zip(&points)
+        .map(|((_, name, color), data)| {
+            Dataset::default()
+                .name(*name)
+                .marker(symbols::Marker::Braille)
+                .graph_type(GraphType::Line)
+                .style(Style::default().fg(*color))
+                .data(data)
+        })
+        .collect();
+
+    let chart = Chart::new(datasets)
+        .block(
+            Block::default()
+                .title(format!("{} (last {})", title, format_span(span)))
+                .borders(Borders::ALL),
+        )
+        .x_axis(Axis::default().bounds([-window, 0.0]).labels(vec![
+            Span::raw(format!("-{}", format_span(span))),
+            Span::raw("now"),
+        ]))
+        .y_axis(
+            Axis::default()
+                .bounds([0.0, 100.0])
+                .labels(vec![Span::raw("0%"), Span::raw("100%")]),
+        );
+    f.render_widget(chart, area);
+}
+
+/// Width of the process table columns, in `ProcessColumn::ALL` order.
+const PROCESS_COLUMN_WIDTHS: [Constraint; 7] = [
+    Constraint::Length(7),
+    Constraint::Length(16),
+    Constraint::Length(10),
+    Constraint::Length(6),
+    Constraint::Length(10),
+    Constraint::Length(10),
+    Constraint::Min(10),
+];
+
+/// Renders the scrollable, sortable process table.
+///
+/// The header of the sort column is highlighted and marked with an arrow
+/// pointing in the sort direction.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `processes` - Processes from the latest snapshot, unsorted
+/// * `state` - Presentation state holding sort order and selection
+/// * `area` - Area of the table including its border
+/// * `theme` - Colors, used for the sort column
+fn render_processes<B: Backend>(
+    f: &mut Frame<B>,
+    processes: &[ProcessInfo],
+    state: &UiState,
+    area: Rect,
+    theme: &Theme,
+) {
+    let mut processes = processes.to_vec();
+    state.process_sort.apply(&mut processes);
+
+    let header = Row::new(ProcessColumn::ALL.iter().map(|&column| {
+        if column == state.process_sort.column {
+            let arrow = if state.process_sort.descending {
+                "▼"
+            } else {
+                "▲"
+            };
+            Cell::from(format!("{}{}", column.title(), arrow)).style(
+                Style::default()
+                    .fg(theme.highlight)
+                    .add_modifier(Modifier::BOLD),
+            )
+        } else {
+            Cell::from(column.title()).style(Style::default().add_modifier(Modifier::BOLD))
+        }
+    }));
+
+    let rows = processes.iter().map(|process| {
+        Row::new(vec![
+            Cell::from(process.pid.to_string()),
+            Cell::from(process.name.clone()),
+            Cell::from(process.user.clone().unwrap_or_else(|| "-".to_string())),
+            Cell::from(format!("{:.1}", process.cpu_usage)),
+            Cell::from(format_bytes(process.memory)),
+            Cell::from(process.state.clone()),
+            Cell::from(process.command.clone()),
+        ])
+    });
+
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .title(format!("Processes ({})", processes.len()))
+                .borders(Borders::ALL),
+        )
+        .widths(&PROCESS_COLUMN_WIDTHS)
+        .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut table_state = TableState::default();
+    if !processes.is_empty() {
+        table_state.select(Some(state.selected_process.min(processes.len() - 1)));
+    }
+    f.render_stateful_widget(table, area, &mut table_state);
+}
+
+/// Renders one usage bar per mounted filesystem.
+///
+/// While a disk alert is firing, the bars of the filesystems meeting its
+/// condition are drawn in the critical color.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `disks` - Filesystems to display, already filtered
+/// * `alerts` - Alert states, used to highlight alerting filesystems
+/// * `state` - Presentation state, used for the filter hint in the title
+/// * `area` - Area of the panel including its border
+/// * `config` - Colors and disk thresholds
+fn render_disks<B: Backend>(
+    f: &mut Frame<B>,
+    disks: &[DiskUsage],
+    alerts: &AlertEngine,
+    state: &UiState,
+    area: Rect,
+    config: &Config,
+) {
+    let filter = if state.show_pseudo_filesystems {
+        "all filesystems"
+    } else {
+        "pseudo filesystems hidden"
+    };
+    let block = Block::default()
+        .title(format!("Disks ({}, {})", disks.len(), filter))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    for (row, disk) in disks.iter().enumerate().take(usize::from(inner.height)) {
+        let alerting = alerts.firing().any(|alert| {
+            alert.rule.metric == AlertMetric::Disk && alert.rule.holds(disk.used_percent())
+        });
+        let style = if alerting {
+            Style::default().fg(config.theme.critical)
+        } else {
+            level_style(
+                config.theme.disk,
+                &config.thresholds.disk,
+                disk.used_percent(),
+                &config.theme,
+            )
+        };
+        let bar = Gauge::default()
+            .gauge_style(style)
+            .ratio(gauge_ratio(disk.used_percent()))
+            .label(format!(
+                "{} ({}) {}",
+                disk.mount_point,
+                disk.file_system,
+                usage_label(disk.used(), disk.total, disk.used_percent())
+            ));
+        let cell = Rect::new(inner.x, inner.y + row as u16, inner.width, 1);
+        f.render_widget(bar, cell);
+    }
+}
+
+/// Width of the text part of a network interface row.
+const NETWORK_LABEL_WIDTH: u16 = 64;
+
+/// Formats a byte rate such as `1.5 MiB/s`.
+fn format_rate(bytes_per_sec: f64) -> String {
+    format!("{}/s", format_bytes(bytes_per_sec as u64))
+}
+
+/// Renders one row per network interface.
+///
+/// Each row shows the receive and transmit rates, the traffic since start
+/// and a sparkline of the recent combined throughput.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `app` - Reference to application state
+/// * `area` - Area of the panel including its border
+/// * `theme` - Colors of the rates and sparklines
+fn render_network<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, theme: &Theme) {
+    let interfaces = app.get_network_usage();
+    let block = Block::default()
+        .title(format!("Network ({} interfaces)", interfaces.len()))
+        .borders(Borders::ALL);
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+
+    for (row, interface) in interfaces
+        .iter()
+        .enumerate()
+        .take(usize::from(inner.height))
+    {
+        let line = Rect::new(inner.x, inner.y + row as u16, inner.width, 1);
+        let columns = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Length(NETWORK_LABEL_WIDTH), Constraint::Min(0)])
+            .split(line);
+
+        let label = Paragraph::new(Spans::from(vec![
+            Span::styled(
+                format!("{:<12}", interface.name),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" rx {:>12}", format_rate(interface.rx_bytes_per_sec)),
+                Style::default().fg(theme.receive),
+            ),
+            Span::styled(
+                format!(" tx {:>12}", format_rate(interface.tx_bytes_per_sec)),
+                Style::default().fg(theme.transmit),
+            ),
+            Span::raw(format!(
+                "  total {}/{}",
+                format_bytes(interface.total.rx_bytes),
+                format_bytes(interface.total.tx_bytes)
+            )),
+        ]));
+        f.render_widget(label, columns[0]);
+
+        let history = app.get_network_history(&interface.name).unwrap_or_default();
+        // Show the most recent samples that fit in the available width
+        let visible = history.len().saturating_sub(usize::from(columns[1].width));
+        let sparkline = Sparkline::default()
+            .data(&history[visible..])
+            .style(Style::default().fg(theme.sparkline));
+        f.render_widget(sparkline, columns[1]);
+    }
+}
+
+/// Column widths of the alert rule table.
+const ALERT_COLUMN_WIDTHS: [Constraint; 5] = [
+    Constraint::Length(20),
+    Constraint::Length(24),
+    Constraint::Length(9),
+    Constraint::Length(8),
+    Constraint::Min(6),
+];
+
+/// Column widths of the alert event table.
+const ALERT_EVENT_COLUMN_WIDTHS: [Constraint; 4] = [
+    Constraint::Length(20),
+    Constraint::Length(9),
+    Constraint::Length(8),
+    Constraint::Min(10),
+];
+
+/// Returns the style of a row in an alert state.
+fn alert_state_style(state: AlertState, theme: &Theme) -> Style {
+    match state {
+        AlertState::Firing { .. } => Style::default()
+            .fg(theme.critical)
+            .add_modifier(Modifier::BOLD),
+        AlertState::Pending { .. } => Style::default().fg(theme.warning),
+        AlertState::Inactive | AlertState::Resolved { .. } => Style::default(),
+    }
+}
+
+/// Formats an optional alert value as a percentage, or `-`.
+fn format_alert_value(value: Option<f32>) -> String {
+    value.map_or_else(|| "-".to_string(), |value| format!("{:.1}%", value))
+}
+
+/// Renders the alert rules with their states, and the recent state
+/// changes below them, newest first. When a notification could not be
+/// delivered, the latest failure is shown at the bottom.
+///
+/// The time in the current state is measured up to the latest snapshot,
+/// so it follows the position when replaying.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `app` - Reference to application state
+/// * `area` - Area of the view
+/// * `theme` - Colors of firing and pending alerts
+fn render_alerts<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, theme: &Theme) {
+    let alerts = app.alerts().alerts();
+    let block = Block::default()
+        .title(format!(
+            "Alerts ({} rules, {} firing)",
+            alerts.len(),
+            app.alerts().firing().count()
+        ))
+        .borders(Borders::ALL);
+    if alerts.is_empty() {
+        let message = Paragraph::new(
+            "No alert rules configured. Add [[alerts]] entries such as \
+             rule = \"cpu > 90% for 30s\" to the configuration file.",
+        )
+        .block(block)
+        .style(Style::default().fg(theme.unavailable));
+        f.render_widget(message, area);
+        return;
+    }
+
+    let failures = app.delivery_failures();
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            // Border and header around one row per rule
+            Constraint::Length(u16::try_from(alerts.len() + 3).unwrap_or(u16::MAX)),
+            Constraint::Min(0),
+            Constraint::Length(if failures.is_empty() { 0 } else { 4 }),
+        ])
+        .split(area);
+
+    let now = app.snapshot().timestamp;
+    let elapsed = |since| format_span(now.duration_since(since).unwrap_or_default());
+    let header = Row::new(["Alert", "Condition", "State", "Value", "For"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = alerts.iter().map(|alert| {
+        let duration = match alert.state {
+            AlertState::Inactive => "-".to_string(),
+            AlertState::Pending { since } | AlertState::Firing { since } => elapsed(since),
+            AlertState::Resolved { at } => elapsed(at),
+        };
+        Row::new(vec![
+            Cell::from(alert.rule.name.clone()),
+            Cell::from(alert.rule.condition()),
+            Cell::from(alert.state.name()),
+            Cell::from(format_alert_value(alert.value)),
+            Cell::from(duration),
+        ])
+        .style(alert_state_style(alert.state, theme))
+    });
+    let table = Table::new(rows)
+        .header(header)
+        .block(block)
+        .widths(&ALERT_COLUMN_WIDTHS);
+    f.render_widget(table, chunks[0]);
+
+    let events = app.alert_events();
+    let header = Row::new(["Time", "State", "Value", "Alert"])
+        .style(Style::default().add_modifier(Modifier::BOLD));
+    let rows = events.iter().rev().map(|event| {
+        let style = match event.transition {
+            Transition::Firing => Style::default().fg(theme.critical),
+            Transition::Pending => Style::default().fg(theme.warning),
+            Transition::Resolved => Style::default(),
+        };
+        Row::new(vec![
+            Cell::from(iso8601(event.timestamp)),
+            Cell::from(event.transition.name()),
+            Cell::from(format_alert_value(Some(event.value))),
+            Cell::from(event.rule.name.clone()),
+        ])
+        .style(style)
+    });
+    let table = Table::new(rows)
+        .header(header)
+        .block(
+            Block::default()
+                .title(format!("Recent Events ({})", events.len()))
+                .borders(Borders::ALL),
+        )
+        .widths(&ALERT_EVENT_COLUMN_WIDTHS);
+    f.render_widget(table, chunks[1]);
+
+    if let Some(failure) = failures.latest() {
+        let message = Paragraph::new(failure.to_string())
+            .style(Style::default().fg(theme.critical))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(format!("Notification Failures ({})", failures.len()))
+                    .borders(Borders::ALL),
+            );
+        f.render_widget(message, chunks[2]);
+    }
+}
+
+/// Formats one line of a pressure file as a gauge label, e.g.
+/// `some: 12.5% / 8.2% / 3.1% over 10s / 60s / 300s, 3m1s stalled`.
+fn stall_label(kind: &str, stats: &StallStats) -> String {
+    format!(
+        "{}: {:.1}% / {:.1}% / {:.1}% over 10s / 60s / 300s, {} stalled",
+        kind,
+        stats.avg10,
+        stats.avg60,
+        stats.avg300,
+        format_span(Duration::from_micros(stats.total_usec))
+    )
+}
+
+/// Renders the pressure stall information: for each of CPU, memory and
+/// I/O, a `some` and a `full` bar filled to the share of the last 10
+/// seconds in which tasks were stalled.
+///
+/// Bars change color above the pressure thresholds, and the `some` bar
+/// turns to the critical color while an alert on its resource is firing.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `app` - Reference to application state
+/// * `area` - Area of the view
+/// * `config` - Colors and pressure thresholds
+fn render_pressure<B: Backend>(f: &mut Frame<B>, app: &App, area: Rect, config: &Config) {
+    let theme = &config.theme;
+    let Some(pressure) = app.snapshot().pressure else {
+        let message = Paragraph::new(
+            "Pressure stall information is unavailable. It requires Linux 4.20 \
+             or later with PSI enabled.",
+        )
+        .block(Block::default().title("Pressure").borders(Borders::ALL))
+        .style(Style::default().fg(theme.unavailable))
+        .wrap(Wrap { trim: true });
+        f.render_widget(message, area);
+        return;
+    };
+
+    let sections = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(4),
+            Constraint::Length(4),
+            Constraint::Length(4),
+            Constraint::Min(0),
+        ])
+        .split(area);
+    let resources = [
+        (Resource::Cpu, "CPU", AlertMetric::CpuPressure, theme.cpu),
+        (
+            Resource::Memory,
+            "Memory",
+            AlertMetric::MemoryPressure,
+            theme.memory,
+        ),
+        (Resource::Io, "I/O", AlertMetric::IoPressure, theme.disk),
+    ];
+    for ((resource, title, metric, color), section) in resources.into_iter().zip(sections.iter()) {
+        let block = Block::default()
+            .title(format!("{} Pressure", title))
+            .borders(Borders::ALL);
+        let inner = block.inner(*section);
+        f.render_widget(block, *section);
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Length(1), Constraint::Length(1)])
+            .split(inner);
+
+        let Some(stalls) = pressure.get(resource) else {
+            let message = Paragraph::new("unavailable (not reported by the kernel)")
+                .style(Style::default().fg(theme.unavailable));
+            f.render_widget(message, rows[0]);
+            continue;
+        };
+        let some = Gauge::default()
+            .gauge_style(gauge_style(
+                app.alerts(),
+                metric,
+                color,
+                &config.thresholds.pressure,
+                stalls.some.avg10,
+                theme,
+            ))
+            .ratio(gauge_ratio(stalls.some.avg10))
+            .label(stall_label("some", &stalls.some));
+        f.render_widget(some, rows[0]);
+        match &stalls.full {
+            Some(full) => {
+                let bar = Gauge::default()
+                    .gauge_style(level_style(
+                        color,
+                        &config.thresholds.pressure,
+                        full.avg10,
+                        theme,
+                    ))
+                    .ratio(gauge_ratio(full.avg10))
+                    .label(stall_label("full", full));
+                f.render_widget(bar, rows[1]);
+            }
+            None => {
+                let message = Paragraph::new("full: not reported by the kernel")
+                    .style(Style::default().fg(theme.unavailable));
+                f.render_widget(message, rows[1]);
+            }
+        }
+    }
+}
+
+/// Builds the banner listing the firing alerts, or `None` if no alert is
+/// firing.
+fn alert_banner(alerts: &AlertEngine, theme: &Theme) -> Option<Paragraph<'static>> {
+    let firing: Vec<String> = alerts
+        .firing()
+        .map(|alert| format!("{} ({})", alert.rule.name, format_alert_value(alert.value)))
+        .collect();
+    if firing.is_empty() {
+        return None;
+    }
+    Some(
+        Paragraph::new(format!(" ALERT: {}", firing.join(", "))).style(
+            Style::default()
+                .fg(Color::White)
+                .bg(theme.critical)
+                .add_modifier(Modifier::BOLD),
+        ),
+    )
+}
+
+/// Renders the user interface.
+///
+/// Creates a vertical layout with three sections:
+/// * Title section (2 units high, 3 while an alert is firing): title line,
+///   view tabs and the banner listing the firing alerts
+/// * CPU section (`layout.cpu_percent` of remaining space, 50% by
+///   default): aggregate gauge and per-core bars
+/// * Memory section (the rest): memory gauge and, if the host has swap
+///   configured, a swap gauge
+///
+/// In the processes, disks, network, alerts and pressure views, the CPU and
+/// memory sections are merged and show the process table, the per-mount
+/// bars, the interface list, the alert rules or the stall bars instead.
+///
+/// All values are read from the latest `MetricsSnapshot`, so every panel in
+/// a frame reflects the same refresh. Gauge titles show the session
+/// min/avg/max, the title bar shows when monitoring is paused, and the help
+/// overlay is drawn on top when enabled.
+///
+/// # Arguments
+/// * `f` - Frame used for rendering
+/// * `app` - Reference to application state
+/// * `state` - Presentation state (help overlay, pause)
+/// * `config` - Shown views, layout, colors, thresholds and key bindings
+///
+/// # Type Parameters
+/// * `B` - Backend implementing the `Backend` trait
+fn ui<B: Backend>(f: &mut Frame<B>, app: &App, state: &UiState, config: &Config) {
+    let snapshot = app.snapshot();
+    let theme = &config.theme;
+    let views = &config.views;
+    let banner = alert_banner(app.alerts(), theme);
+    let title_height = if banner.is_some() { 3 } else { 2 };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(title_height), // Fixed height for title
+            Constraint::Percentage(config.layout.cpu_percent),
+            Constraint::Percentage(100 - config.layout.cpu_percent),
+        ])
+        .split(f.size());
+
+    // Title
+    let mut title_spans = vec![Span::styled(
+        "System Monitor",
+        Style::default()
+            .fg(theme.title)
+            .add_modifier(Modifier::BOLD),
+    )];
+    // The playback position takes the place of the key hint when replaying
+    match state.playback {
+        None => {
+            let key = |action| {
+                config
+                    .keys
+                    .keys(action)
+                    .first()
+                    .map_or_else(|| "?".to_string(), KeySpec::to_string)
+            };
+            title_spans.push(Span::raw(format!(
+                " (Press '{}' to quit, '{}' for help)",
+                key(Action::Quit),
+                key(Action::ToggleHelp)
+            )))
+        }
+        Some(playback) => title_spans.push(Span::styled(
+            format!(
+                " [REPLAY {}/{} at {}x, {}]",
+                playback.position + 1,
+                playback.len,
+                playback.speed,
+                iso8601(snapshot.timestamp)
+            ),
+            Style::default()
+                .fg(theme.replay)
+                .add_modifier(Modifier::BOLD),
+        )),
+    }
+    if state.paused {
+        title_spans.push(Span::styled(
+            " [PAUSED]",
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+    let title_rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),
+            Constraint::Length(1),
+            Constraint::Length(1),
+        ])
+        .split(chunks[0]);
+    f.render_widget(Paragraph::new(Spans::from(title_spans)), title_rows[0]);
+
+    // View tabs
+    let tabs = Tabs::new(views.iter().map(|v| Spans::from(v.title())).collect())
+        .select(views.iter().position(|&v| v == state.view).unwrap_or(0))
+        .highlight_style(
+            Style::default()
+                .fg(theme.highlight)
+                .add_modifier(Modifier::BOLD),
+        );
+    f.render_widget(tabs, title_rows[1]);
+    if let Some(banner) = banner {
+        f.render_widget(banner, title_rows[2]);
+    }
+
+    let body = chunks[1].union(chunks[2]);
+    match state.view {
+        View::Overview => render_overview(f, app, chunks[1], chunks[2], config),
+        View::Processes => render_processes(f, &snapshot.processes, state, body, theme),
+        View::Disks => render_disks(
+            f,
+            &app.get_disks(state.show_pseudo_filesystems),
+            app.alerts(),
+            state,
+            body,
+            config,
+        ),
+        View::Network => render_network(f, app, body, theme),
+        View::Alerts => render_alerts(f, app, body, theme),
+        View::Pressure => render_pressure(f, app, body, config),
+    }
+
+    if state.show_help {
+        render_help(f, &config.keys);
+    }
+}
+
+/// Restores the terminal to its normal state.
+///
+/// Leaves the alternate screen, disables raw mode and shows the cursor.
+/// Safe to call more than once, which lets it be used from both the normal
+/// exit path and the panic hook.
+fn restore_terminal() -> io::Result<()> {
+    disable_raw_mode()?;
+    execute!(io::stdout(), LeaveAlternateScreen, cursor::Show)
+}
+
+/// Runs the headless Prometheus exporter until the process is killed.
+///
+/// # Arguments
+/// * `addr` - Address the `/metrics` endpoint listens on
+/// * `interval` - Interval between two samples
+/// * `config` - Alert rules and actions
+///
+/// # Errors
+/// * The address cannot be bound
+/// * The collector thread cannot be spawned
+fn serve(addr: &str, interval: Duration, config: &Config) -> Result<(), Box<dyn Error>> {
+    let mut app = App::new();
+    start_alerting(&mut app, config, true)?;
+    let collector = BackgroundCollector::spawn(app, interval)?;
+    let server = MetricsServer::bind(addr, collector)
+        .map_err(|e| format!("cannot listen on {}: {}", addr, e))?;
+    eprintln!("Serving metrics on http://{}/metrics", server.local_addr()?);
+    server.serve()?;
+    Ok(())
+}
+
+/// Prints one JSON line per sample to stdout.
+///
+/// After the last line, waits for the alert notifications still queued.
+///
+/// # Arguments
+/// * `interval` - Interval between two samples
+/// * `config` - History length, alert rules and actions
+/// * `count` - Number of lines to print, or `None` to run until interrupted
+/// * `processes` - Whether the process list is collected and printed
+///
+/// # Errors
+/// * The collector thread cannot be spawned
+/// * Writing to stdout fails for another reason than a closed pipe
+fn stream_json(
+    interval: Duration,
+    config: &Config,
+    count: Option<u64>,
+    processes: bool,
+) -> Result<(), Box<dyn Error>> {
+    let mut plan = RefreshPlan::default();
+    if !processes {
+        plan = plan.without(Collector::Processes);
+    }
+    let mut app = App::with_source(SysinfoSource::with_plan(plan), config.history_len);
+    start_alerting(&mut app, config, true)?;
+    let collector = BackgroundCollector::spawn_lossless(app, interval)?;
+    ndjson::stream(&collector, &mut io::stdout().lock(), count)?;
+    if let Some(notifier) = collector.stop().take_notifier() {
+        // Failures were already reported as they happened
+        notifier.finish();
+    }
+    Ok(())
+}
+
+/// Appends one CSV row per sample to a rotating file until interrupted.
+///
+/// The collector never drops a sample: while writing stalls, sampling
+/// waits instead of leaving gaps in the time series.
+///
+/// # Arguments
+/// * `path` - Output file
+/// * `interval` - Interval between two samples
+/// * `config` - History length, alert rules and actions
+/// * `groups` - Metric groups to record; empty for the default groups
+/// * `rotation` - When the output file is rotated
+///
+/// # Errors
+/// * The output file cannot be written or rotated
+fn record_csv(
+    path: &Path,
+    interval: Duration,
+    config: &Config,
+    groups: &[MetricGroup],
+    rotation: Rotation,
+) -> Result<(), Box<dyn Error>> {
+    let mut recorder = CsvRecorder::new(path, groups, rotation)
+        .map_err(|e| format!("{}: {}", path.display(), e))?;
+    let plan = with_alert_collectors(csv::refresh_plan(recorder.groups()), &config.alerts);
+    let mut app = App::with_source(SysinfoSource::with_plan(plan), config.history_len);
+    start_alerting(&mut app, config, true)?;
+    let collector = BackgroundCollector::spawn_lossless(app, interval)?;
+    while let Some(snapshot) = collector.recv() {
+        recorder.record(&snapshot)?;
+    }
+    Ok(())
+}
+
+/// Samples the system twice, `interval` apart, and prints a summary.
+///
+/// Two samples are needed because CPU usage is measured between refreshes.
+/// Every collector is refreshed on both, so the process CPU usage of the
+/// second sample covers the whole interval.
+///
+/// # Arguments
+/// * `interval` - Time between the two samples
+/// * `top` - Number of processes listed
+fn print_summary(interval: Duration, top: usize) {
+    let mut app = App::with_source(SysinfoSource::with_plan(RefreshPlan::every_tick()), 1);
+    thread::sleep(interval);
+    app.update();
+    print!("{}", format_summary(&app, top));
+}
+
+/// Formats the latest snapshot of `app` as a plain-text report.
+///
+/// # Arguments
+/// * `app` - Application state to report on
+/// * `top` - Number of processes listed, busiest first
+///
+/// # Returns
+/// * `String` - One line per metric, disk, interface and process
+fn format_summary(app: &App, top: usize) -> String {
+    let snapshot = app.snapshot();
+    let mut out = String::new();
+    let _ = writeln!(out, "System Monitor at {}", iso8601(snapshot.timestamp));
+    let _ = match snapshot.cpu_usage() {
+        Ok(usage) => writeln!(
+            out,
+            "CPU        {:.1}% ({} cores)",
+            usage,
+            snapshot.cores.len()
+        ),
+        Err(e) => writeln!(out, "CPU        unavailable ({})", e),
+    };
+    let _ = match snapshot.memory_usage() {
+        Ok(usage) => writeln!(
+            out,
+            "Memory     {}",
+            usage_label(snapshot.used_memory, snapshot.total_memory, usage)
+        ),
+        Err(e) => writeln!(out, "Memory     unavailable ({})", e),
+    };
+    let _ = match snapshot.swap_usage() {
+        Some(usage) => writeln!(
+            out,
+            "Swap       {}",
+            usage_label(snapshot.used_swap, snapshot.total_swap, usage)
+        ),
+        None => writeln!(out, "Swap       not configured"),
+    };
+
+    for disk in app.get_disks(false) {
+        let _ = writeln!(
+            out,
+            "Disk       {} {}",
+            disk.mount_point,
+            usage_label(disk.used(), disk.total, disk.used_percent())
+        );
+    }
+    for interface in &snapshot.networks {
+        let _ = writeln!(
+            out,
+            "Network    {} rx {} tx {}",
+            interface.name,
+            format_rate(interface.rx_bytes_per_sec),
+            format_rate(interface.tx_bytes_per_sec)
+        );
+    }
+
+    let mut processes = snapshot.processes.clone();
+    ProcessSort::default().apply(&mut processes);
+    let _ = writeln!(out, "Processes  {}", processes.len());
+    for process in processes.iter().take(top) {
+        let _ = writeln!(
+            out,
+            "  {:>7} {:>5.1}% {:>10}  {}",
+            process.pid,
+            process.cpu_usage,
+            format_bytes(process.memory),
+            process.name
+        );
+    }
+    out
+}
+
+/// Application entry point.
+///
+/// Parses the command line, resolves the configuration and runs the
+/// selected output mode, reporting failures on stderr with a non-zero exit
+/// status. Invalid arguments are reported by the parser with a usage hint,
+/// and an invalid configuration with its location, before anything else
+/// runs.
+///
+/// # Returns
+/// * `ExitCode` - Success if the selected mode exits normally
+///
+/// # Example
+/// ```no_run
+/// fn main() -> std::process::ExitCode {
+///     // Application setup and execution
+///     std::process::ExitCode::SUCCESS
+/// }
+/// ```
+fn main() -> ExitCode {
+    let (config_path, command) = Cli::parse_args().into_parts();
+    let result = Config::load(config_path.as_deref())
+        .map_err(Box::<dyn Error>::from)
+        .and_then(|config| run(command, config));
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(e) => {
+            eprintln!("error: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Runs the output mode selected on the command line.
+///
+/// `serve`, `json`, `record` and `summary` run headless. The terminal UI
+/// (the default) and `replay` set up the terminal environment, run the
+/// main event loop, and ensure proper cleanup on exit.
+///
+/// # Arguments
+/// * `command` - Output mode and its options
+/// * `config` - Resolved configuration; options given on the command line
+///   take precedence over it
+///
+/// # Errors
+/// * Files that cannot be read or written
+/// * Terminal initialization failures
+/// * Event handling errors
+/// * Terminal cleanup failures
+fn run(command: Command, config: Config) -> Result<(), Box<dyn Error>> {
+    let rotation = command.rotation();
+    let history_len = config.history_len;
+    match command {
+        Command::Tui(args) => run_tui(args, config),
+        Command::Serve { addr, sampling } => {
+            serve(&addr, sampling.interval_or(config.sample_interval), &config)
+        }
+        Command::Json {
+            sampling,
+            count,
+            processes,
+        } => stream_json(
+            sampling.interval_or(config.sample_interval),
+            &config,
+            count,
+            processes,
+        ),
+        Command::Record {
+            path,
+            sampling,
+            groups,
+            ..
+        } => record_csv(
+            &path,
+            sampling.interval_or(config.sample_interval),
+            &config,
+            &groups,
+            rotation.unwrap_or_default(),
+        ),
+        Command::Replay { path } => {
+            let session = Session::load(&path).map_err(|e| format!("{}: {}", path.display(), e))?;
+            let mut player = Player::new(session, history_len);
+            player.set_alert_rules(config.alerts.clone());
+            with_terminal(|terminal| run_replay(terminal, player, &mut CrosstermEvents, config))
+        }
+        Command::Summary { sampling, top } => {
+            print_summary(sampling.interval_or(config.sample_interval), top);
+            Ok(())
+        }
+    }
+}
+
+/// Runs the terminal UI on live metrics.
+///
+/// Metrics that no shown view displays are not collected.
+///
+/// # Arguments
+/// * `args` - Sample interval, shown views and optional session file
+/// * `config` - Resolved configuration, overridden by `args`
+fn run_tui(args: TuiArgs, mut config: Config) -> Result<(), Box<dyn Error>> {
+    let session = match &args.session {
+        Some(path) => {
+            Some(SessionWriter::create(path).map_err(|e| format!("{}: {}", path.display(), e))?)
+        }
+        None => None,
+    };
+    config.sample_interval = args.sampling.interval_or(config.sample_interval);
+    if let Some(views) = args.views {
+        config.set_views(views);
+    }
+    let plan = views_refresh_plan(&config.views, &config.alerts);
+    let mut app = App::with_source(SysinfoSource::with_plan(plan), config.history_len);
+    start_alerting(&mut app, &config, false)?;
+    with_terminal(|terminal| run_app(terminal, app, &mut CrosstermEvents, config, session))
+}
+
+/// Runs `f` on a terminal switched to raw mode and the alternate screen.
+///
+/// A panic hook restores the terminal before the panic message is printed
+/// so a crash never leaves the shell in raw mode. The terminal is restored
+/// whether or not `f` succeeds.
+///
+/// # Arguments
+/// * `f` - Function driving the terminal UI
+fn with_terminal<T>(
+    f: impl FnOnce(&mut Terminal<CrosstermBackend<io::Stdout>>) -> io::Result<T>,
+) -> Result<(), Box<dyn Error>> {
+    // Restore the terminal before reporting panics
+    let default_hook = panic::take_hook();
+    panic::set_hook(Box::new(move |info| {
+        let _ = restore_terminal();
+        default_hook(info);
+    }));
+
+    // Setup terminal
+    enable_raw_mode()?;
+    let mut stdout = io::stdout();
+    execute!(stdout, EnterAlternateScreen)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let result = f(&mut terminal);
+
+    // Restore terminal
+    restore_terminal()?;
+
+    // Map io::Error to Box<dyn Error>
+    result.map(|_| ()).map_err(|e| e.into())
+}
+
+// UI-specific tests
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+    use std::collections::VecDeque;
+    use system_monitor::notify::ActionKind;
+    use system_monitor::{AlertAction, Reading, ScriptedSource, DEFAULT_HISTORY_LEN};
+    use tui::backend::TestBackend;
+
+    /// Step of a scripted input sequence.
+    enum Step {
+        /// No input arrives; the poll times out.
+        Idle,
+        /// The given event is delivered.
+        Input(Event),
+    }
+
+    /// Event source replaying a fixed script of synthetic events.
+    ///
+    /// Idle steps sleep for the requested poll timeout so the loop's
+    /// timers advance as they would against a real terminal. Running past
+    /// the end of the script is reported as an error so a test can never
+    /// hang waiting for input.
+    struct ScriptedEvents {
+        steps: VecDeque<Step>,
+    }
+
+    impl ScriptedEvents {
+        fn new(steps: Vec<Step>) -> Self {
+            Self {
+                steps: steps.into(),
+            }
+        }
+    }
+
+    impl EventSource for ScriptedEvents {
+        fn poll(&mut self, timeout: Duration) -> io::Result<bool> {
+            match self.steps.front() {
+                Some(Step::Input(_)) => Ok(true),
+                Some(Step::Idle) => {
+                    self.steps.pop_front();
+                    std::thread::sleep(timeout);
+                    Ok(false)
+                }
+                None => Err(io::Error::new(
+                    io::ErrorKind::UnexpectedEof,
+                    "event script exhausted",
+                )),
+            }
+        }
+
+        fn read(&mut self) -> io::Result<Event> {
+            match self.steps.pop_front() {
+                Some(Step::Input(event)) => Ok(event),
+                _ => Err(io::Error::new(
+                    io::ErrorKind::WouldBlock,
+                    "no event available",
+                )),
+            }
+        }
+    }
+
+    fn key(code: KeyCode) -> Step {
+        Step::Input(Event::Key(KeyEvent::new(code, KeyModifiers::NONE)))
+    }
+
+    fn test_terminal() -> Terminal<TestBackend> {
+        Terminal::new(TestBackend::new(80, 24)).unwrap()
+    }
+
+    /// Proc root holding the fixture files under `tests/fixtures`.
+    fn fixture_proc_root() -> std::path::PathBuf {
+        Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc")
+    }
+
+    /// Concatenates every cell of the terminal's last frame.
+    fn screen_text(terminal: &Terminal<TestBackend>) -> String {
+        terminal
+            .backend()
+            .buffer()
+            .content()
+            .iter()
+            .map(|c| c.symbol.as_str())
+            .collect()
+    }
+
+    /// Prefixes `steps` with the Tab presses that switch to `view`.
+    fn in_view(view: View, steps: Vec<Step>) -> Vec<Step> {
+        let tabs = View::ALL.iter().position(|&v| v == view).unwrap();
+        let mut script: Vec<Step> = (0..tabs).map(|_| key(KeyCode::Tab)).collect();
+        script.extend(steps);
+        script
+    }
+
+    fn fast_config() -> Config {
+        Config {
+            tick_rate: Duration::from_millis(5),
+            render_interval: Duration::from_millis(5),
+            sample_interval: Duration::from_millis(5),
+            ..Config::default()
+        }
+    }
+
+    mod event_loop {
+        use super::*;
+
+        #[test]
+        fn test_quit_on_q() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            let (stats, _) = run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert_eq!(
+                stats.frames, 1,
+                "Initial frame should be drawn before input"
+            );
+            assert_eq!(
+                stats.samples, 0,
+                "No sample is due before the first interval"
+            );
+        }
+
+        #[test]
+        fn test_quit_on_ctrl_c() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut events = ScriptedEvents::new(vec![Step::Input(Event::Key(KeyEvent::new(
+                KeyCode::Char('c'),
+                KeyModifiers::CONTROL,
+            )))]);
+
+            assert!(run_app(&mut terminal, app, &mut events, fast_config(), None).is_ok());
+        }
+
+        #[test]
+        fn test_plain_c_does_not_quit() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('c'))]);
+
+            let result = run_app(&mut terminal, app, &mut events, fast_config(), None);
+            assert_eq!(
+                result.unwrap_err().kind(),
+                io::ErrorKind::UnexpectedEof,
+                "Loop should keep running until the script is exhausted"
+            );
+        }
+
+        #[test]
+        fn test_key_release_is_ignored() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut release = KeyEvent::new(KeyCode::Char('q'), KeyModifiers::NONE);
+            release.kind = KeyEventKind::Release;
+            let mut events = ScriptedEvents::new(vec![Step::Input(Event::Key(release))]);
+
+            assert!(run_app(&mut terminal, app, &mut events, fast_config(), None).is_err());
+        }
+
+        #[test]
+        fn test_redraws_without_input() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut steps: Vec<Step> = (0..5).map(|_| Step::Idle).collect();
+            steps.push(key(KeyCode::Char('q')));
+            let mut events = ScriptedEvents::new(steps);
+
+            let (stats, _) = run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(
+                stats.frames > 1,
+                "UI should redraw on its own, drew {} frames",
+                stats.frames
+            );
+            assert!(
+                stats.samples > 0,
+                "Metrics should be sampled without input, took {} samples",
+                stats.samples
+            );
+        }
+
+        #[test]
+        fn test_sample_cadence_independent_of_render() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let config = Config {
+                sample_interval: Duration::from_secs(60),
+                ..fast_config()
+            };
+            let mut steps: Vec<Step> = (0..5).map(|_| Step::Idle).collect();
+            steps.push(key(KeyCode::Char('q')));
+            let mut events = ScriptedEvents::new(steps);
+
+            let (stats, _) = run_app(&mut terminal, app, &mut events, config, None).unwrap();
+            assert!(stats.frames > 1, "UI should keep redrawing");
+            assert_eq!(stats.samples, 0, "No sample should be due within a minute");
+        }
+
+        #[test]
+        fn test_slow_source_does_not_stall_input() {
+            /// Source taking far longer to read than the sample interval.
+            #[derive(Debug)]
+            struct SlowSource;
+
+            impl system_monitor::MetricSource for SlowSource {
+                fn read(&mut self) -> Reading {
+                    std::thread::sleep(Duration::from_millis(200));
+                    Reading::default()
+                }
+            }
+
+            let mut terminal = test_terminal();
+            let app = App::with_source(SlowSource, 10);
+            let mut steps: Vec<Step> = (0..10).map(|_| Step::Idle).collect();
+            steps.push(key(KeyCode::Char('q')));
+            let mut events = ScriptedEvents::new(steps);
+
+            let start = Instant::now();
+            let (stats, _) = run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(stats.frames > 1, "UI should keep redrawing");
+            // Inline sampling would block for 200ms on every idle step
+            assert!(
+                start.elapsed() < Duration::from_secs(1),
+                "Input handling took {:?}",
+                start.elapsed()
+            );
+        }
+
+        #[test]
+        fn test_resize_triggers_redraw() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let config = Config {
+                render_interval: Duration::from_secs(60),
+                sample_interval: Duration::from_secs(60),
+                ..fast_config()
+            };
+            let mut events = ScriptedEvents::new(vec![
+                Step::Input(Event::Resize(100, 30)),
+                key(KeyCode::Char('q')),
+            ]);
+
+            let (stats, _) = run_app(&mut terminal, app, &mut events, config, None).unwrap();
+            assert_eq!(stats.frames, 2, "Resize should force exactly one redraw");
+        }
+
+        #[test]
+        fn test_renders_gauges() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("System Monitor"), "Title should be rendered");
+            assert!(text.contains("CPU Usage"), "CPU gauge should be rendered");
+            assert!(
+                text.contains("Memory Usage"),
+                "Memory gauge should be rendered"
+            );
+        }
+    }
+
+    /// Tests the basic UI layout configuration.
+    ///
+    /// Verifies:
+    /// * Correct number of sections (3)
+    /// * Title height (2 units)
+    /// * Full width usage
+    /// * Proper vertical positioning
+    /// * Height distribution
+    #[test]
+    fn test_ui_layout() {
+        // Create a mock terminal size
+        let size = Rect::new(0, 0, 100, 100); // Mock terminal of 100x100
+
+        let chunks = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([
+                Constraint::Length(2),   // Fixed height for title
+                Constraint::Ratio(1, 2), // Half of remaining space
+                Constraint::Ratio(1, 2), // Half of remaining space
+            ])
+            .split(size);
+
+        // Test layout structure
+        assert_eq!(chunks.len(), 3, "Layout should have three sections");
+
+        // Test section heights
+        assert_eq!(chunks[0].height, 2, "Title should be exactly 2 units high");
+
+        // Test that sections fill the width
+        assert_eq!(chunks[0].width, 100, "Sections should use full width");
+        assert_eq!(chunks[1].width, 100, "Sections should use full width");
+        assert_eq!(chunks[2].width, 100, "Sections should use full width");
+
+        // Test vertical positioning
+        assert_eq!(chunks[0].y, 0, "Title should start at top");
+        assert_eq!(chunks[1].y, 2, "CPU gauge should start after title");
+        assert_eq!(
+            chunks[2].y,
+            chunks[1].y + chunks[1].height,
+            "Memory gauge should start after CPU gauge"
+        );
+
+        // Test that the layout fills the entire height
+        assert_eq!(
+            chunks[0].height + chunks[1].height + chunks[2].height,
+            size.height,
+            "Layout should fill entire height"
+        );
+
+        // Test that CPU and Memory sections are approximately equal
+        assert!(
+            (chunks[1].height as i32 - chunks[2].height as i32).abs() <= 2,
+            "CPU and Memory sections should be approximately equal"
+        );
+    }
+
+    /// Tests layout constraints across various terminal sizes.
+    ///
+    /// Verifies layout behavior for:
+    /// * Standard terminal (80x24)
+    /// * Large terminal (120x40)
+    /// * Small terminal (60x20)
+    /// * Tiny terminal (10x10)
     /// * Maximum reasonable size (255x255)
     ///
     /// Checks:
@@ -236,154 +2877,1597 @@ mod tests {
                 chunks[0].height + chunks[1].height + chunks[2].height
             );
 
-            // Verify minimum heights
+            // Verify minimum heights
+            assert!(
+                chunks[0].height > 0,
+                "Title section should have non-zero height"
+            );
+            assert!(
+                chunks[1].height > 0,
+                "CPU gauge should have non-zero height"
+            );
+            assert!(
+                chunks[2].height > 0,
+                "Memory gauge should have non-zero height"
+            );
+
+            // Verify the title is exactly 2 units high
+            assert_eq!(chunks[0].height, 2, "Title should be exactly 2 units high");
+
+            // Verify total height
+            let total_height = chunks[0].height + chunks[1].height + chunks[2].height;
+            assert_eq!(
+                total_height, height,
+                "Total height {} should match terminal height {}",
+                total_height, height
+            );
+
+            // Verify the difference between sections is at most 2
+            assert!(
+                (chunks[1].height as i32 - chunks[2].height as i32).abs() <= 2,
+                "CPU height {} and Memory height {} should differ by at most 2 units",
+                chunks[1].height,
+                chunks[2].height
+            );
+
+            // Verify that sections are reasonable proportions
+            let remaining_height = height - chunks[0].height;
+            assert!(
+                chunks[1].height <= remaining_height,
+                "CPU section height {} should not exceed remaining height {}",
+                chunks[1].height,
+                remaining_height
+            );
+            assert!(
+                chunks[2].height <= remaining_height,
+                "Memory section height {} should not exceed remaining height {}",
+                chunks[2].height,
+                remaining_height
+            );
+
+            // Verify that sections together use all remaining space
+            assert_eq!(
+                chunks[1].height + chunks[2].height,
+                remaining_height,
+                "CPU and Memory sections should use all remaining space"
+            );
+        }
+    }
+
+    /// Tests layout behavior with extremely large terminal sizes.
+    ///
+    /// Verifies layout stability with:
+    /// * Very large terminals (1000x1000)
+    /// * Maximum possible dimensions (u16::MAX)
+    ///
+    /// Ensures:
+    /// * Layout remains functional
+    /// * No integer overflow occurs
+    /// * Proper section ordering
+    #[test]
+    fn test_large_terminal_size() {
+        // Test handling of unreasonably large terminal sizes
+        let large_sizes = vec![(1000, 1000), (500, 500), (u16::MAX, u16::MAX)];
+
+        for (width, height) in large_sizes {
+            let size = Rect::new(0, 0, width, height);
+            let chunks = Layout::default()
+                .direction(Direction::Vertical)
+                .constraints([
+                    Constraint::Length(2),
+                    Constraint::Ratio(1, 2),
+                    Constraint::Ratio(1, 2),
+                ])
+                .split(size);
+
+            println!("\nLarge terminal size: {}x{}", width, height);
+            println!("Title height: {}", chunks[0].height);
+            println!("CPU height: {}", chunks[1].height);
+            println!("Memory height: {}", chunks[2].height);
+            let total = chunks[0].height + chunks[1].height + chunks[2].height;
+            println!("Total height: {} (Original height: {})", total, height);
+
+            // For large terminals, we only verify:
+            // 1. Title is still 2 units
+            assert_eq!(chunks[0].height, 2, "Title should be exactly 2 units high");
+
+            // 2. Sections are non-zero
+            assert!(chunks[1].height > 0, "CPU section should be non-zero");
+            assert!(chunks[2].height > 0, "Memory section should be non-zero");
+
+            // 3. Sections are approximately equal
+            assert!(
+                (chunks[1].height as i32 - chunks[2].height as i32).abs() <= 2,
+                "CPU and Memory sections should be approximately equal"
+            );
+
+            // 4. Layout is valid (no overflows)
+            assert!(chunks[0].y < chunks[1].y);
+            assert!(chunks[1].y < chunks[2].y);
+        }
+    }
+
+    mod key_controls {
+        use super::*;
+
+        #[test]
+        fn test_key_mapping() {
+            let keys = KeyMap::default();
+            let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+            assert_eq!(keys.action(&press(KeyCode::Char('q'))), Some(Action::Quit));
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('h'))),
+                Some(Action::ToggleHelp)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('p'))),
+                Some(Action::TogglePause)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('r'))),
+                Some(Action::ResetStats)
+            );
+            assert_eq!(keys.action(&press(KeyCode::Esc)), Some(Action::CloseHelp));
+            assert_eq!(
+                keys.action(&press(KeyCode::Right)),
+                Some(Action::StepForward)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Left)),
+                Some(Action::StepBackward)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('+'))),
+                Some(Action::Faster)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('-'))),
+                Some(Action::Slower)
+            );
+            assert_eq!(keys.action(&press(KeyCode::Char('x'))), None);
+        }
+
+        #[test]
+        fn test_configured_keys_drive_the_loop() {
+            let mut config = fast_config();
+            config
+                .keys
+                .bind(Action::Quit, vec![KeySpec::new(KeyCode::Char('x'))]);
+            config
+                .keys
+                .bind(Action::ToggleHelp, vec![KeySpec::new(KeyCode::F(1))]);
+            let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+            let mut events = ScriptedEvents::new(vec![
+                key(KeyCode::Char('q')),
+                key(KeyCode::F(1)),
+                Step::Idle,
+                key(KeyCode::Char('x')),
+            ]);
+
+            run_app(&mut terminal, App::new(), &mut events, config, None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("(Press 'x' to quit, 'F1' for help)"));
+            assert!(text.contains("Toggle this help"), "F1 should open the help");
+            assert!(events.steps.is_empty(), "'x' should have quit");
+        }
+
+        #[test]
+        fn test_help_overlay_toggles() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut events = ScriptedEvents::new(vec![
+                key(KeyCode::Char('h')),
+                Step::Idle,
+                key(KeyCode::Char('q')),
+            ]);
+
+            let (_, app) = run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            for (_, description) in HELP {
+                assert!(
+                    text.contains(description),
+                    "Help should list '{}'",
+                    description
+                );
+            }
+
+            let mut events = ScriptedEvents::new(vec![
+                key(KeyCode::Char('h')),
+                key(KeyCode::Esc),
+                Step::Idle,
+                key(KeyCode::Char('q')),
+            ]);
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(
+                !screen_text(&terminal).contains("Toggle this help"),
+                "Esc should close the help overlay"
+            );
+        }
+
+        #[test]
+        fn test_pause_freezes_sampling() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            // Sampling runs on its own thread: leave enough time for the
+            // pause to take effect before the first sample would be due
+            let config = Config {
+                sample_interval: Duration::from_millis(30),
+                ..fast_config()
+            };
+            let mut steps = vec![key(KeyCode::Char('p'))];
+            steps.extend((0..10).map(|_| Step::Idle));
+            steps.push(key(KeyCode::Char('q')));
+            let mut events = ScriptedEvents::new(steps);
+
+            let (stats, _) = run_app(&mut terminal, app, &mut events, config, None).unwrap();
+            assert_eq!(stats.samples, 0, "No samples should be taken while paused");
+            assert!(stats.frames > 1, "UI should stay responsive while paused");
+            assert!(screen_text(&terminal).contains("[PAUSED]"));
+        }
+
+        #[test]
+        fn test_resume_after_pause() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let mut steps = vec![key(KeyCode::Char('p')), key(KeyCode::Char('p'))];
+            steps.extend((0..5).map(|_| Step::Idle));
+            steps.push(key(KeyCode::Char('q')));
+            let mut events = ScriptedEvents::new(steps);
+
+            let (stats, _) = run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(stats.samples > 0, "Sampling should resume after unpausing");
+            assert!(!screen_text(&terminal).contains("[PAUSED]"));
+        }
+
+        #[test]
+        fn test_reset_clears_statistics() {
+            let mut terminal = test_terminal();
+            let mut app = App::new();
+            app.update();
+            app.update();
+            let config = Config {
+                sample_interval: Duration::from_secs(60),
+                ..fast_config()
+            };
+            let mut events =
+                ScriptedEvents::new(vec![key(KeyCode::Char('r')), key(KeyCode::Char('q'))]);
+
+            let (_, app) = run_app(&mut terminal, app, &mut events, config, None).unwrap();
+            assert_eq!(app.cpu_stats().count(), 0);
+            assert_eq!(app.memory_stats().count(), 0);
+        }
+
+        #[test]
+        fn test_stats_title() {
+            let mut stats = MetricStats::default();
+            assert_eq!(stats_title("CPU Usage", &stats), "CPU Usage");
+
+            stats.record(10.0);
+            stats.record(20.0);
+            assert_eq!(
+                stats_title("CPU Usage", &stats),
+                "CPU Usage (min 10.0% / avg 15.0% / max 20.0%)"
+            );
+        }
+    }
+
+    mod per_core {
+        use super::*;
+
+        #[test]
+        fn test_single_column_on_narrow_area() {
+            let cells = core_grid(Rect::new(0, 0, 30, 10), 4);
+            assert_eq!(cells.len(), 4);
+            for (row, cell) in cells.iter().enumerate() {
+                assert_eq!(cell.x, 0, "All cores should share one column");
+                assert_eq!(cell.y, row as u16, "Cores should stack vertically");
+            }
+        }
+
+        #[test]
+        fn test_wraps_into_columns_on_wide_area() {
+            let cells = core_grid(Rect::new(0, 0, 100, 10), 8);
+            assert_eq!(cells.len(), 8);
+            // 100 / 24 = 4 columns, 2 rows, filled column by column
+            assert_eq!((cells[0].x, cells[0].y), (0, 0));
+            assert_eq!((cells[1].x, cells[1].y), (0, 1));
+            assert_eq!((cells[2].x, cells[2].y), (25, 0));
+            assert_eq!((cells[7].x, cells[7].y), (75, 1));
+        }
+
+        #[test]
+        fn test_cells_stay_inside_area() {
+            let area = Rect::new(3, 5, 80, 4);
+            for cores in [1, 3, 16, 64, 256] {
+                for cell in core_grid(area, cores) {
+                    assert!(cell.x >= area.x && cell.right() <= area.right());
+                    assert!(cell.y >= area.y && cell.bottom() <= area.bottom());
+                }
+            }
+        }
+
+        #[test]
+        fn test_overflowing_cores_are_dropped() {
+            // 48 / 24 = 2 columns of 2 rows
+            let cells = core_grid(Rect::new(0, 0, 48, 2), 10);
+            assert_eq!(cells.len(), 4);
+        }
+
+        #[test]
+        fn test_empty_grid() {
+            assert!(core_grid(Rect::new(0, 0, 80, 10), 0).is_empty());
+            assert!(core_grid(Rect::new(0, 0, 80, 0), 4).is_empty());
+        }
+
+        #[test]
+        fn test_renders_core_names() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let app = App::new();
+            let names: Vec<String> = app
+                .get_per_core_usage()
+                .into_iter()
+                .map(|c| c.name)
+                .collect();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("Per-Core Usage"));
+            assert!(text.contains(&names[0]), "First core should be rendered");
+        }
+    }
+
+    mod cpu_time {
+        use super::*;
+        use std::time::UNIX_EPOCH;
+        use system_monitor::cputime::{parse_stat, ProcStat};
+        use system_monitor::{Reading, ScriptedSource};
+
+        /// Counters of the `/proc/stat` fixture.
+        fn fixture_stat() -> ProcStat {
+            ProcStat::read(&fixture_proc_root()).unwrap()
+        }
+
+        /// Reading of two cores at `secs` with the given counters.
+        fn reading(secs: u64, stat: ProcStat) -> Reading {
+            let core = |id: usize| CoreUsage {
+                id,
+                name: format!("cpu{}", id),
+                usage: 50.0,
+            };
+            Reading {
+                timestamp: UNIX_EPOCH + Duration::from_secs(secs),
+                cores: vec![core(0), core(1)],
+                cpu_times: Some(stat),
+                ..Reading::default()
+            }
+        }
+
+        /// App whose snapshot has a quarter of the time stolen overall and
+        /// half of it on core 0.
+        fn app_with_steal() -> App {
+            let later = parse_stat(
+                "cpu  2300 20 800 15900 200 10 70 400 50 0\n\
+                 cpu0 1050 20 300 8000 50 5 25 150 0 0\n\
+                 cpu1 1250 0 500 7900 150 5 45 250 50 0\n",
+            )
+            .unwrap();
+            let mut app =
+                App::with_source(ScriptedSource::new(vec![reading(0, fixture_stat())]), 10);
+            app.ingest(reading(1, later));
+            app
+        }
+
+        #[test]
+        fn test_stacked_bar_rounds_running_total() {
+            let segments = [
+                (25.0, Color::Green),
+                (25.4, Color::Red),
+                (0.0, Color::Blue),
+                (24.8, Color::Cyan),
+            ];
+            let spans = stacked_bar(&segments, 40);
+            let widths: Vec<usize> = spans.iter().map(|span| span.content.len()).collect();
+            assert_eq!(widths, vec![10, 10, 10]);
+            assert_eq!(spans[2].style.bg, Some(Color::Cyan));
+
+            // Shares adding up to more than 100% never overflow the bar
+            let spans = stacked_bar(&[(80.0, Color::Green), (80.0, Color::Red)], 10);
+            let filled: usize = spans.iter().map(|span| span.content.len()).sum();
+            assert_eq!(filled, 10);
+            assert!(stacked_bar(&[(f32::NAN, Color::Green)], 10).is_empty());
+        }
+
+        #[test]
+        fn test_renders_breakdown_with_steal() {
+            let app = app_with_steal();
+            let mut terminal = test_terminal();
+            terminal
+                .draw(|f| ui(f, &app, &UiState::default(), &Config::default()))
+                .unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("CPU Time (busy 75.0%)"));
+            assert!(text.contains("user 25.0% system 25.0% steal 25.0%"));
+            assert!(!text.contains("iowait"), "Modes without time are omitted");
+
+            let buffer = terminal.backend().buffer();
+            let steal_cells = buffer
+                .content()
+                .iter()
+                .filter(|cell| cell.bg == Theme::default().cpu_steal)
+                .count();
+            assert!(steal_cells > 0, "Steal time should be drawn in its color");
+        }
+
+        #[test]
+        fn test_bar_matches_busy_share_with_iowait() {
+            let usage = CpuTimeUsage {
+                total: CpuShares {
+                    user: 40.0,
+                    iowait: 20.0,
+                    idle: 40.0,
+                    ..CpuShares::default()
+                },
+                cores: Vec::new(),
+            };
+            let mut terminal = Terminal::new(TestBackend::new(42, 4)).unwrap();
+            terminal
+                .draw(|f| render_cpu_time(f, &usage, f.size(), &Theme::default()))
+                .unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("CPU Time (busy 40.0%)"));
+            assert!(text.contains("user 40.0% steal 0.0% iowait 20.0%"));
+
+            // 40% of the 40 inner cells, iowait left empty like idle time
+            let buffer = terminal.backend().buffer();
+            let filled = (0..42)
+                .filter(|&x| buffer.get(x, 1).bg != Color::Reset)
+                .count();
+            assert_eq!(filled, 16);
+        }
+
+        #[test]
+        fn test_per_core_bars_are_stacked() {
+            let app = app_with_steal();
+            let mut terminal = test_terminal();
+            terminal
+                .draw(|f| ui(f, &app, &UiState::default(), &Config::default()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            let area = buffer.area;
+            let row = (0..area.height)
+                .find(|&y| {
+                    let line: String = (0..area.width)
+                        .map(|x| buffer.get(x, y).symbol.as_str())
+                        .collect();
+                    line.contains("cpu0 50.0%")
+                })
+                .expect("Core 0 should be rendered");
+            let colors: Vec<Color> = (0..area.width)
+                .map(|x| buffer.get(x, row).bg)
+                .filter(|&bg| bg != Color::Reset)
+                .collect();
+            // Half of core 0's time was user time, the other half stolen
+            let theme = Theme::default();
+            assert!(colors.contains(&theme.cpu_user));
+            assert!(colors.contains(&theme.cpu_steal));
+            assert!(!colors.contains(&theme.cpu_system));
+        }
+
+        #[test]
+        fn test_per_core_breakdown_matches_names_with_offline_cpus() {
+            // cpu1 is offline: sysinfo reports cpu0 and cpu2 at indexes 0 and 1
+            let reading = |secs, stat| {
+                let mut reading = reading(secs, stat);
+                reading.cores[1].name = "cpu2".to_string();
+                reading
+            };
+            let first = parse_stat("cpu  0 0 0 0\ncpu0 0 0 0 0\ncpu2 0 0 0 0\n").unwrap();
+            let later = parse_stat(
+                "cpu  0 0 100 100 0 0 0 100\n\
+                 cpu0 0 0 0 100 0 0 0 0\n\
+                 cpu2 0 0 100 0 0 0 0 100\n",
+            )
+            .unwrap();
+            let mut app = App::with_source(ScriptedSource::new(vec![reading(0, first)]), 10);
+            app.ingest(reading(1, later));
+
+            let mut terminal = test_terminal();
+            terminal
+                .draw(|f| ui(f, &app, &UiState::default(), &Config::default()))
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            let area = buffer.area;
+            let row_colors = |name: &str| {
+                let row = (0..area.height)
+                    .find(|&y| {
+                        let line: String = (0..area.width)
+                            .map(|x| buffer.get(x, y).symbol.as_str())
+                            .collect();
+                        line.contains(name)
+                    })
+                    .expect("Core should be rendered");
+                (0..area.width)
+                    .map(|x| buffer.get(x, row).bg)
+                    .filter(|&bg| bg != Color::Reset)
+                    .collect::<Vec<_>>()
+            };
+            assert!(row_colors("cpu0 50.0%").is_empty(), "cpu0 was idle");
+            let cpu2 = row_colors("cpu2 50.0%");
+            let theme = Theme::default();
+            assert!(cpu2.contains(&theme.cpu_system) && cpu2.contains(&theme.cpu_steal));
+        }
+
+        #[test]
+        fn test_mode_colors_follow_theme() {
+            let app = app_with_steal();
+            let mut config = Config::default();
+            config.theme.cpu_steal = Color::LightRed;
+            let mut terminal = test_terminal();
+            terminal
+                .draw(|f| ui(f, &app, &UiState::default(), &config))
+                .unwrap();
+            let colors: Vec<Color> = terminal
+                .backend()
+                .buffer()
+                .content()
+                .iter()
+                .map(|cell| cell.bg)
+                .collect();
+            assert!(colors.contains(&Color::LightRed));
+        }
+
+        #[test]
+        fn test_hidden_until_two_readings() {
+            let app = App::with_source(ScriptedSource::new(vec![reading(0, fixture_stat())]), 10);
+            let mut terminal = test_terminal();
+            terminal
+                .draw(|f| ui(f, &app, &UiState::default(), &Config::default()))
+                .unwrap();
+            assert!(!screen_text(&terminal).contains("CPU Time"));
+        }
+    }
+
+    mod memory_panel {
+        use super::*;
+        use std::time::UNIX_EPOCH;
+        use system_monitor::cgroup::{CpuQuota, CpuStat};
+
+        /// App whose snapshot carries the `/proc/meminfo` fixture.
+        fn app_with_meminfo(alert_rules: Vec<AlertRule>) -> App {
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.set_alert_rules(alert_rules);
+            app.ingest(Reading {
+                meminfo: MemInfo::read(&fixture_proc_root()),
+                ..Reading::default()
+            });
+            app
+        }
+
+        /// Draws the overview on a wide terminal without history charts.
+        fn draw_wide(app: &App) -> Terminal<TestBackend> {
+            let config = Config {
+                layout: LayoutConfig {
+                    min_chart_width: u16::MAX,
+                    ..LayoutConfig::default()
+                },
+                ..Config::default()
+            };
+            let mut terminal = Terminal::new(TestBackend::new(120, 24)).unwrap();
+            terminal
+                .draw(|f| ui(f, app, &UiState::default(), &config))
+                .unwrap();
+            terminal
+        }
+
+        /// Background colors of the row showing the memory headline.
+        fn memory_bar_colors(terminal: &Terminal<TestBackend>) -> Vec<Color> {
+            let buffer = terminal.backend().buffer();
+            let area = buffer.area;
+            let row = (0..area.height)
+                .find(|&y| {
+                    let line: String = (0..area.width)
+                        .map(|x| buffer.get(x, y).symbol.as_str())
+                        .collect();
+                    line.contains("│ 40.0% ")
+                })
+                .expect("Memory headline should be rendered");
+            (0..area.width)
+                .map(|x| buffer.get(x, row).bg)
+                .filter(|&bg| bg != Color::Reset)
+                .collect()
+        }
+
+        #[test]
+        fn test_renders_segmented_memory_breakdown() {
+            let app = app_with_meminfo(Vec::new());
+            assert_eq!(app.snapshot().memory_usage(), Ok(40.0));
+
+            let terminal = draw_wide(&app);
+            let text = screen_text(&terminal);
+            assert!(
+                text.contains("used 6.0 GiB  buffers 402.6 MiB  shared 511.1 MiB  cache 7.0 GiB")
+            );
+            assert!(text.contains("available 9.3 GiB  slab 869.1 MiB  dirty 2.0 MiB"));
+            assert!(text.contains("writeback 512.0 KiB  hugepages 8.0 MiB / 32.0 MiB"));
+
+            let colors = memory_bar_colors(&terminal);
+            let theme = Theme::default();
+            for color in [
+                theme.memory,
+                theme.memory_buffers,
+                theme.memory_shared,
+                theme.memory_cache,
+            ] {
+                assert!(colors.contains(&color), "{:?} segment is missing", color);
+            }
+            // Used, buffers, shared and cache fill everything but free memory
+            let bar_width = 120 - 2 - " 40.0% ".len();
+            let free = 1_824_588.0 / 16_318_480.0;
+            let expected = (bar_width as f64 * (1.0 - free)).round() as usize;
+            assert_eq!(colors.len(), expected);
+        }
+
+        #[test]
+        fn test_firing_memory_alert_colors_used_segment() {
+            let app = app_with_meminfo(vec!["memory > 30%".parse().unwrap()]);
+            assert!(app.alerts().is_firing(AlertMetric::Memory));
+
+            let colors = memory_bar_colors(&draw_wide(&app));
+            assert!(colors.contains(&Theme::default().critical));
+            assert!(
+                colors.contains(&Theme::default().memory_cache),
+                "Caches keep their color"
+            );
+        }
+
+        #[test]
+        fn test_swap_hidden_without_swap() {
+            let area = Rect::new(0, 0, 80, 20);
+            assert_eq!(memory_layout(area, false), vec![area]);
+        }
+
+        #[test]
+        fn test_swap_shares_memory_section() {
+            let area = Rect::new(0, 10, 80, 20);
+            let chunks = memory_layout(area, true);
+            assert_eq!(chunks.len(), 2);
+            assert_eq!(chunks[0].y, 10);
+            assert_eq!(chunks[1].y, chunks[0].bottom());
+            assert_eq!(chunks[0].height + chunks[1].height, area.height);
+        }
+
+        #[test]
+        fn test_format_bytes() {
+            assert_eq!(format_bytes(0), "0 B");
+            assert_eq!(format_bytes(1023), "1023 B");
+            assert_eq!(format_bytes(1536), "1.5 KiB");
+            assert_eq!(format_bytes(8 * 1024 * 1024 * 1024), "8.0 GiB");
+        }
+
+        #[test]
+        fn test_usage_label() {
+            assert_eq!(
+                usage_label(512 * 1024 * 1024, 2 * 1024 * 1024 * 1024, 25.0),
+                "512.0 MiB / 2.0 GiB (25%)"
+            );
+        }
+
+        #[test]
+        fn test_renders_exact_scripted_values() {
+            const GIB: u64 = 1024 * 1024 * 1024;
+            let reading = Reading {
+                cores: vec![
+                    CoreUsage {
+                        id: 0,
+                        name: "cpu0".to_string(),
+                        usage: 30.0,
+                    },
+                    CoreUsage {
+                        id: 1,
+                        name: "cpu1".to_string(),
+                        usage: 50.0,
+                    },
+                ],
+                total_memory: 4 * GIB,
+                used_memory: GIB,
+                total_swap: 2 * GIB,
+                used_swap: GIB,
+                ..Reading::default()
+            };
+            let mut terminal = test_terminal();
+            let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
             assert!(
-                chunks[0].height > 0,
-                "Title section should have non-zero height"
+                text.contains("40%"),
+                "CPU gauge should show the core average"
             );
+            assert!(text.contains("1.0 GiB / 4.0 GiB (25%)"));
+            assert!(text.contains("1.0 GiB / 2.0 GiB (50%)"));
+        }
+
+        #[test]
+        fn test_renders_unavailable_metrics() {
+            let mut terminal = Terminal::new(TestBackend::new(160, 30)).unwrap();
+            let app = App::with_source(ScriptedSource::new(vec![Reading::default()]), 10);
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("unavailable (no CPU cores reported)"));
+            assert!(text.contains("unavailable (total memory reported as zero)"));
             assert!(
-                chunks[1].height > 0,
-                "CPU gauge should have non-zero height"
+                !text.contains("NaN"),
+                "No garbage values should be rendered"
             );
+        }
+
+        #[test]
+        fn test_out_of_range_values_do_not_panic() {
+            let reading = Reading {
+                cores: vec![
+                    CoreUsage {
+                        id: 0,
+                        name: "cpu0".to_string(),
+                        usage: f32::NAN,
+                    },
+                    CoreUsage {
+                        id: 1,
+                        name: "cpu1".to_string(),
+                        usage: 250.0,
+                    },
+                ],
+                total_memory: 100,
+                used_memory: 400,
+                total_swap: 100,
+                used_swap: 400,
+                ..Reading::default()
+            };
+            let mut terminal = test_terminal();
+            let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(screen_text(&terminal).contains("cpu0 unavailable"));
+        }
+
+        #[test]
+        fn test_gauge_ratio_is_clamped() {
+            assert_eq!(gauge_ratio(50.0), 0.5);
+            assert_eq!(gauge_ratio(-5.0), 0.0);
+            assert_eq!(gauge_ratio(400.0), 1.0);
+            assert_eq!(gauge_ratio(f32::NAN), 0.0);
+            assert_eq!(gauge_ratio(f32::INFINITY), 0.0);
+        }
+
+        #[test]
+        fn test_renders_container_limits() {
+            const MIB: u64 = 1024 * 1024;
+            let reading = |seconds, usage_usec| Reading {
+                timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+                cores: vec![CoreUsage {
+                    id: 0,
+                    name: "cpu0".to_string(),
+                    usage: 10.0,
+                }],
+                total_memory: 8192 * MIB,
+                used_memory: 1024 * MIB,
+                container: Some(ContainerUsage {
+                    cgroup: "/docker/abc".to_string(),
+                    memory_used: 128 * MIB,
+                    memory_limit: Some(512 * MIB),
+                    cpu_quota: Some(CpuQuota {
+                        quota_usec: 50_000,
+                        period_usec: 100_000,
+                    }),
+                    cpu: CpuStat {
+                        usage_usec,
+                        nr_periods: 20,
+                        nr_throttled: 4,
+                        ..CpuStat::default()
+                    },
+                    cpu_usage: None,
+                }),
+                ..Reading::default()
+            };
+            let source = ScriptedSource::new(vec![reading(0, 0), reading(2, 800_000)]);
+            let mut terminal = Terminal::new(TestBackend::new(120, 30)).unwrap();
+            let mut app = App::with_source(source, 10);
+            app.update();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("Container CPU (limit 0.5 CPUs)"));
+            assert!(text.contains("80.0% (throttled 4 of 20 periods)"));
+            assert!(text.contains("Container Memory (limit 512.0 MiB)"));
+            assert!(text.contains("128.0 MiB / 512.0 MiB (25%)"));
             assert!(
-                chunks[2].height > 0,
-                "Memory gauge should have non-zero height"
+                text.contains("1.0 GiB / 8.0 GiB (12%)"),
+                "Host memory is still shown"
             );
+        }
 
-            // Verify the title is exactly 2 units high
-            assert_eq!(chunks[0].height, 2, "Title should be exactly 2 units high");
+        #[test]
+        fn test_container_without_limits_is_hidden() {
+            let reading = Reading {
+                total_memory: 1000,
+                used_memory: 100,
+                container: Some(ContainerUsage::default()),
+                ..Reading::default()
+            };
+            let mut terminal = test_terminal();
+            let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
 
-            // Verify total height
-            let total_height = chunks[0].height + chunks[1].height + chunks[2].height;
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(!screen_text(&terminal).contains("Container"));
+        }
+
+        #[test]
+        fn test_swap_gauge_follows_host() {
+            let mut terminal = test_terminal();
+            let app = App::new();
+            let has_swap = app.snapshot().has_swap();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert_eq!(screen_text(&terminal).contains("Swap Usage"), has_swap);
+        }
+    }
+
+    mod process_table {
+        use super::*;
+
+        #[test]
+        fn test_view_cycling_wraps() {
+            let all = &View::ALL;
+            assert_eq!(View::Overview.next_in(all), View::Processes);
+            assert_eq!(View::ALL[View::ALL.len() - 1].next_in(all), View::Overview);
             assert_eq!(
-                total_height, height,
-                "Total height {} should match terminal height {}",
-                total_height, height
+                View::Overview.previous_in(all),
+                View::ALL[View::ALL.len() - 1]
             );
+            for view in View::ALL {
+                assert_eq!(view.next_in(all).previous_in(all), view);
+            }
+        }
 
-            // Verify the difference between sections is at most 2
+        #[test]
+        fn test_view_cycling_skips_hidden_views() {
+            let shown = [View::Overview, View::Network];
+            assert_eq!(View::Overview.next_in(&shown), View::Network);
+            assert_eq!(View::Network.next_in(&shown), View::Overview);
+            assert_eq!(View::Overview.previous_in(&shown), View::Network);
+            assert_eq!(View::Disks.next_in(&shown), View::Overview);
+        }
+
+        #[test]
+        fn test_hidden_views_are_not_collected() {
+            let plan = views_refresh_plan(&[View::Overview, View::Disks], &[]);
+            assert!(plan.is_enabled(Collector::Cpu));
+            assert!(plan.is_enabled(Collector::Memory));
+            assert!(plan.is_enabled(Collector::Disks));
+            assert!(!plan.is_enabled(Collector::Processes));
+            assert!(!plan.is_enabled(Collector::Networks));
+            assert_eq!(views_refresh_plan(&View::ALL, &[]), RefreshPlan::default());
+        }
+
+        #[test]
+        fn test_disk_alerts_keep_disks_collected() {
+            let rules = ["disk > 90%".parse().unwrap()];
+            assert!(views_refresh_plan(&[View::Overview], &rules).is_enabled(Collector::Disks));
+            let rules = ["cpu > 90%".parse().unwrap()];
+            assert!(!views_refresh_plan(&[View::Overview], &rules).is_enabled(Collector::Disks));
+            let plan = with_alert_collectors(RefreshPlan::none(), &rules);
+            assert!(plan.is_enabled(Collector::Cpu));
+            assert!(!plan.is_enabled(Collector::Memory));
+        }
+
+        #[test]
+        fn test_only_shown_views_are_tabs() {
+            let mut terminal = test_terminal();
+            let config = Config {
+                views: vec![View::Disks, View::Network],
+                ..fast_config()
+            };
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+            run_app(&mut terminal, App::new(), &mut events, config, None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("Disks"));
+            assert!(text.contains("Network"));
+            assert!(!text.contains("Processes"));
             assert!(
-                (chunks[1].height as i32 - chunks[2].height as i32).abs() <= 2,
-                "CPU height {} and Memory height {} should differ by at most 2 units",
-                chunks[1].height,
-                chunks[2].height
+                text.contains("Disks ("),
+                "First shown view should be selected"
             );
+        }
 
-            // Verify that sections are reasonable proportions
-            let remaining_height = height - chunks[0].height;
+        #[test]
+        fn test_scroll_stays_in_bounds() {
+            let mut state = UiState::default();
+            state.scroll(Scroll::Up, 5);
+            assert_eq!(state.selected_process, 0);
+            state.scroll(Scroll::PageDown, 5);
+            assert_eq!(
+                state.selected_process, 4,
+                "Page down should stop at the last row"
+            );
+            state.scroll(Scroll::Down, 5);
+            assert_eq!(state.selected_process, 4);
+            state.scroll(Scroll::Top, 5);
+            assert_eq!(state.selected_process, 0);
+            state.scroll(Scroll::Bottom, 5);
+            assert_eq!(state.selected_process, 4);
+            state.scroll(Scroll::PageUp, 5);
+            assert_eq!(state.selected_process, 0);
+            state.scroll(Scroll::Bottom, 0);
+            assert_eq!(state.selected_process, 0, "Empty table keeps the first row");
+        }
+
+        #[test]
+        fn test_sort_key_mapping() {
+            let keys = KeyMap::default();
+            let press = |code| KeyEvent::new(code, KeyModifiers::NONE);
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('>'))),
+                Some(Action::SortNextColumn)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('<'))),
+                Some(Action::SortPreviousColumn)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::Char('i'))),
+                Some(Action::InvertSort)
+            );
+            assert_eq!(keys.action(&press(KeyCode::Tab)), Some(Action::NextView));
+            assert_eq!(
+                keys.action(&press(KeyCode::BackTab)),
+                Some(Action::PreviousView)
+            );
+            assert_eq!(
+                keys.action(&press(KeyCode::End)),
+                Some(Action::Scroll(Scroll::Bottom))
+            );
+        }
+
+        #[test]
+        fn test_renders_process_table() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let app = App::new();
+            let mut events =
+                ScriptedEvents::new(in_view(View::Processes, vec![key(KeyCode::Char('q'))]));
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
             assert!(
-                chunks[1].height <= remaining_height,
-                "CPU section height {} should not exceed remaining height {}",
-                chunks[1].height,
-                remaining_height
+                text.contains("Processes ("),
+                "Process table should be rendered"
             );
             assert!(
-                chunks[2].height <= remaining_height,
-                "Memory section height {} should not exceed remaining height {}",
-                chunks[2].height,
-                remaining_height
+                text.contains("CPU%▼"),
+                "Default sort column should be marked"
+            );
+            assert!(
+                !text.contains("Per-Core Usage"),
+                "Overview should be hidden"
             );
+        }
 
-            // Verify that sections together use all remaining space
+        #[test]
+        fn test_sort_keys_change_header() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let app = App::new();
+            let mut events = ScriptedEvents::new(in_view(
+                View::Processes,
+                vec![
+                    key(KeyCode::Char('>')),
+                    key(KeyCode::Char('i')),
+                    key(KeyCode::Char('q')),
+                ],
+            ));
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(
+                text.contains("RSS▲"),
+                "Memory column should be sorted ascending"
+            );
+            assert!(!text.contains("CPU%▼"));
+        }
+    }
+
+    mod disk_panel {
+        use super::*;
+
+        #[test]
+        fn test_pseudo_filter_key() {
+            let press = KeyEvent::new(KeyCode::Char('f'), KeyModifiers::NONE);
             assert_eq!(
-                chunks[1].height + chunks[2].height,
-                remaining_height,
-                "CPU and Memory sections should use all remaining space"
+                KeyMap::default().action(&press),
+                Some(Action::TogglePseudoFilesystems)
+            );
+        }
+
+        #[test]
+        fn test_renders_disks_view() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let app = App::new();
+            let real = app.get_disks(false);
+            let mut events =
+                ScriptedEvents::new(in_view(View::Disks, vec![key(KeyCode::Char('q'))]));
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains(&format!(
+                "Disks ({}, pseudo filesystems hidden)",
+                real.len()
+            )));
+            if let Some(first) = real.first() {
+                assert!(
+                    text.contains(&first.mount_point),
+                    "Mount points should be listed"
+                );
+            }
+        }
+
+        #[test]
+        fn test_toggle_shows_pseudo_filesystems() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let app = App::new();
+            let all = app.get_disks(true);
+            let mut events = ScriptedEvents::new(in_view(
+                View::Disks,
+                vec![key(KeyCode::Char('f')), key(KeyCode::Char('q'))],
+            ));
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            assert!(
+                screen_text(&terminal).contains(&format!("Disks ({}, all filesystems)", all.len()))
             );
         }
+
+        #[test]
+        fn test_bar_color_follows_thresholds() {
+            let disk = |available| DiskUsage {
+                name: "sda1".to_string(),
+                mount_point: "/".to_string(),
+                file_system: "ext4".to_string(),
+                total: 100,
+                available,
+            };
+            let config = Config::default();
+            let theme = config.theme;
+            for (available, color) in [(50, theme.disk), (10, theme.warning), (2, theme.critical)] {
+                let mut terminal = Terminal::new(TestBackend::new(80, 5)).unwrap();
+                terminal
+                    .draw(|f| {
+                        render_disks(
+                            f,
+                            &[disk(available)],
+                            &AlertEngine::default(),
+                            &UiState::default(),
+                            f.size(),
+                            &config,
+                        )
+                    })
+                    .unwrap();
+                // Filled part of the bar, inside the border
+                let cell = terminal.backend().buffer().get(1, 1);
+                assert_eq!(cell.bg, color, "{}% available", available);
+            }
+        }
     }
 
-    /// Tests layout behavior with extremely large terminal sizes.
-    ///
-    /// Verifies layout stability with:
-    /// * Very large terminals (1000x1000)
-    /// * Maximum possible dimensions (u16::MAX)
-    ///
-    /// Ensures:
-    /// * Layout remains functional
-    /// * No integer overflow occurs
-    /// * Proper section ordering
-    #[test]
-    fn test_large_terminal_size() {
-        // Test handling of unreasonably large terminal sizes
-        let large_sizes = vec![(1000, 1000), (500, 500), (u16::MAX, u16::MAX)];
+    mod pressure_view {
+        use super::*;
+        use system_monitor::Pressure;
 
-        for (width, height) in large_sizes {
-            let size = Rect::new(0, 0, width, height);
-            let chunks = Layout::default()
-                .direction(Direction::Vertical)
-                .constraints([
-                    Constraint::Length(2),
-                    Constraint::Ratio(1, 2),
-                    Constraint::Ratio(1, 2),
-                ])
-                .split(size);
+        /// Returns the pressure read from the fixture files.
+        fn fixture_pressure() -> Pressure {
+            Pressure::read(&fixture_proc_root()).unwrap()
+        }
 
-            println!("\nLarge terminal size: {}x{}", width, height);
-            println!("Title height: {}", chunks[0].height);
-            println!("CPU height: {}", chunks[1].height);
-            println!("Memory height: {}", chunks[2].height);
-            let total = chunks[0].height + chunks[1].height + chunks[2].height;
-            println!("Total height: {} (Original height: {})", total, height);
+        fn draw(app: &App) -> Terminal<TestBackend> {
+            let state = UiState {
+                view: View::Pressure,
+                ..UiState::default()
+            };
+            let mut terminal = Terminal::new(TestBackend::new(100, 24)).unwrap();
+            terminal
+                .draw(|f| ui(f, app, &state, &Config::default()))
+                .unwrap();
+            terminal
+        }
 
-            // For large terminals, we only verify:
-            // 1. Title is still 2 units
-            assert_eq!(chunks[0].height, 2, "Title should be exactly 2 units high");
+        #[test]
+        fn test_renders_stall_bars() {
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.ingest(Reading {
+                pressure: Some(fixture_pressure()),
+                ..Reading::default()
+            });
+            let text = screen_text(&draw(&app));
+            assert!(text.contains("CPU Pressure"));
+            assert!(text.contains("some: 12.5% / 8.2% / 3.1% over 10s / 60s / 300s, 3m1s stalled"));
+            assert!(text.contains("I/O Pressure"));
+            assert!(text.contains("full: 25.8% / 18.0% / 7.5%"));
+        }
 
-            // 2. Sections are non-zero
-            assert!(chunks[1].height > 0, "CPU section should be non-zero");
-            assert!(chunks[2].height > 0, "Memory section should be non-zero");
+        #[test]
+        fn test_missing_full_line_and_resources() {
+            let mut pressure = fixture_pressure();
+            pressure.cpu.as_mut().unwrap().full = None;
+            pressure.io = None;
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.ingest(Reading {
+                pressure: Some(pressure),
+                ..Reading::default()
+            });
+            let text = screen_text(&draw(&app));
+            assert!(text.contains("full: not reported by the kernel"));
+            assert!(text.contains("unavailable (not reported by the kernel)"));
+        }
 
-            // 3. Sections are approximately equal
+        #[test]
+        fn test_unavailable_without_psi() {
+            let app = App::with_source(ScriptedSource::default(), 10);
+            let text = screen_text(&draw(&app));
+            assert!(text.contains("Pressure stall information is unavailable"));
+        }
+
+        #[test]
+        fn test_firing_pressure_alert_turns_bar_red() {
+            let theme = Theme::default();
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.set_alert_rules(vec!["io_pressure > 20%".parse().unwrap()]);
+            app.ingest(Reading {
+                pressure: Some(fixture_pressure()),
+                ..Reading::default()
+            });
+            assert!(app.alerts().is_firing(AlertMetric::IoPressure));
+
+            let terminal = draw(&app);
+            assert!(screen_text(&terminal).contains("ALERT: io_pressure > 20% (30.0%)"));
+            let buffer = terminal.backend().buffer();
+            let io_bar: Vec<Color> = (0..buffer.area.width)
+                .map(|x| buffer.get(x, 12).bg)
+                .filter(|&bg| bg != Color::Reset)
+                .collect();
+            assert!(!io_bar.is_empty(), "The I/O some bar is on row 12");
+            assert!(io_bar.iter().all(|&bg| bg == theme.critical));
+        }
+    }
+
+    mod alerts {
+        use super::*;
+
+        const GIB: u64 = 1024 * 1024 * 1024;
+
+        /// Returns an app whose memory is 25% used, below its warning
+        /// threshold, evaluating `rules`.
+        fn app_with_rules(rules: &[&str]) -> App {
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.set_alert_rules(rules.iter().map(|rule| rule.parse().unwrap()).collect());
+            app.ingest(Reading {
+                total_memory: 4 * GIB,
+                used_memory: GIB,
+                ..Reading::default()
+            });
+            app
+        }
+
+        fn draw(app: &App, state: &UiState) -> Terminal<TestBackend> {
+            let mut terminal = test_terminal();
+            terminal
+                .draw(|f| ui(f, app, state, &Config::default()))
+                .unwrap();
+            terminal
+        }
+
+        /// Returns the background colors of the filled gauge cells below
+        /// the title section.
+        fn body_colors(terminal: &Terminal<TestBackend>, from_row: u16) -> Vec<Color> {
+            let buffer = terminal.backend().buffer();
+            let area = buffer.area;
+            (from_row..area.height)
+                .flat_map(|y| (0..area.width).map(move |x| (x, y)))
+                .map(|(x, y)| buffer.get(x, y).bg)
+                .filter(|&bg| bg != Color::Reset)
+                .collect()
+        }
+
+        #[test]
+        fn test_firing_alert_shows_banner_and_turns_gauge_red() {
+            let theme = Theme::default();
+            let app = app_with_rules(&["memory > 20%"]);
+            let terminal = draw(&app, &UiState::default());
+            let text = screen_text(&terminal);
+            assert!(text.contains("ALERT: memory > 20% (25.0%)"));
+            let colors = body_colors(&terminal, 3);
+            assert!(colors.contains(&theme.critical));
+            assert!(!colors.contains(&theme.memory));
+        }
+
+        #[test]
+        fn test_no_banner_without_firing_alerts() {
+            let theme = Theme::default();
+            let app = app_with_rules(&["memory > 50%", "memory > 20% for 1m"]);
+            let terminal = draw(&app, &UiState::default());
+            assert!(!screen_text(&terminal).contains("ALERT:"));
+            let colors = body_colors(&terminal, 2);
+            assert!(colors.contains(&theme.memory));
+            assert!(!colors.contains(&theme.critical));
+        }
+
+        #[test]
+        fn test_alerts_view_lists_rules_and_events() {
+            let app = app_with_rules(&["memory > 20%", "memory > 20% for 1m", "cpu > 90%"]);
+            let state = UiState {
+                view: View::Alerts,
+                ..UiState::default()
+            };
+            let text = screen_text(&draw(&app, &state));
+            assert!(text.contains("Alerts (3 rules, 1 firing)"));
+            assert!(text.contains("firing"));
+            assert!(text.contains("pending"));
+            assert!(text.contains("inactive"));
+            assert!(text.contains("Recent Events (2)"));
+        }
+
+        #[test]
+        fn test_alerts_view_shows_delivery_failures() {
+            let action =
+                AlertAction::new(ActionKind::Log("missing/alerts.log".into())).with_retries(0);
+            let notifier = BackgroundNotifier::spawn(Notifier::new(vec![action]), |_| {}).unwrap();
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.set_alert_rules(vec!["memory > 20%".parse().unwrap()]);
+            app.set_notifier(notifier);
+            // Failures are collected with the snapshots that follow them
+            let started = Instant::now();
+            while app.delivery_failures().is_empty() && started.elapsed() < Duration::from_secs(5) {
+                app.ingest(Reading {
+                    total_memory: 4 * GIB,
+                    used_memory: GIB,
+                    ..Reading::default()
+                });
+                thread::sleep(Duration::from_millis(5));
+            }
+
+            let state = UiState {
+                view: View::Alerts,
+                ..UiState::default()
+            };
+            let text = screen_text(&draw(&app, &state));
+            assert!(text.contains("Notification Failures (1)"));
+            assert!(text
+                .contains("log missing/alerts.log: cannot notify that 'memory > 20%' is firing"));
+        }
+
+        #[test]
+        fn test_alerts_view_explains_configuration() {
+            let state = UiState {
+                view: View::Alerts,
+                ..UiState::default()
+            };
+            let text = screen_text(&draw(&app_with_rules(&[]), &state));
+            assert!(text.contains("No alert rules configured"));
+        }
+
+        #[test]
+        fn test_disk_bars_follow_firing_disk_alert() {
+            let disk = |mount_point: &str, available| DiskUsage {
+                name: "sda1".to_string(),
+                mount_point: mount_point.to_string(),
+                file_system: "ext4".to_string(),
+                total: 100,
+                available,
+            };
+            let disks = [disk("/", 50), disk("/data", 5)];
+            let mut app = App::with_source(ScriptedSource::default(), 10);
+            app.set_alert_rules(vec!["disk > 90%".parse().unwrap()]);
+            app.ingest(Reading {
+                disks: Some(disks.to_vec()),
+                ..Reading::default()
+            });
+
+            let config = Config::default();
+            let mut terminal = Terminal::new(TestBackend::new(80, 4)).unwrap();
+            terminal
+                .draw(|f| {
+                    render_disks(
+                        f,
+                        &disks,
+                        app.alerts(),
+                        &UiState::default(),
+                        f.size(),
+                        &config,
+                    )
+                })
+                .unwrap();
+            let buffer = terminal.backend().buffer();
+            assert_eq!(buffer.get(1, 1).bg, config.theme.disk);
+            assert_eq!(buffer.get(1, 2).bg, config.theme.critical);
+        }
+    }
+
+    mod network_panel {
+        use super::*;
+
+        #[test]
+        fn test_format_rate() {
+            assert_eq!(format_rate(0.0), "0 B/s");
+            assert_eq!(format_rate(1536.0), "1.5 KiB/s");
+        }
+
+        #[test]
+        fn test_renders_network_view() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let mut app = App::new();
+            app.update();
+            let interfaces = app.get_network_usage();
+            let mut events =
+                ScriptedEvents::new(in_view(View::Network, vec![key(KeyCode::Char('q'))]));
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
             assert!(
-                (chunks[1].height as i32 - chunks[2].height as i32).abs() <= 2,
-                "CPU and Memory sections should be approximately equal"
+                text.contains("Network ("),
+                "Network panel should be rendered"
             );
+            if let Some(first) = interfaces.first() {
+                assert!(text.contains(&first.name), "Interfaces should be listed");
+            }
+        }
+    }
 
-            // 4. Layout is valid (no overflows)
-            assert!(chunks[0].y < chunks[1].y);
-            assert!(chunks[1].y < chunks[2].y);
+    mod history_charts {
+        use super::*;
+        use std::time::SystemTime;
+
+        #[test]
+        fn test_format_span() {
+            assert_eq!(format_span(Duration::from_secs(0)), "0s");
+            assert_eq!(format_span(Duration::from_secs(45)), "45s");
+            assert_eq!(format_span(Duration::from_secs(300)), "5m");
+            assert_eq!(format_span(Duration::from_secs(150)), "2m30s");
+        }
+
+        #[test]
+        fn test_chart_points_relative_to_newest() {
+            let start = SystemTime::UNIX_EPOCH;
+            let mut history = History::new(10);
+            for (seconds, value) in [(0, 10.0), (2, 20.0), (5, 30.0)] {
+                history.push(Sample {
+                    timestamp: start + Duration::from_secs(seconds),
+                    value,
+                });
+            }
+            assert_eq!(
+                chart_points(&history),
+                vec![(-5.0, 10.0), (-3.0, 20.0), (0.0, 30.0)]
+            );
+            assert!(chart_points(&History::new(10)).is_empty());
+        }
+
+        #[test]
+        fn test_charts_only_on_wide_terminals() {
+            let layout = LayoutConfig::default();
+            let (area, chart) = split_for_chart(Rect::new(0, 0, 60, 10), &layout);
+            assert_eq!(area.width, 60);
+            assert!(chart.is_none());
+
+            let (area, chart) = split_for_chart(Rect::new(0, 0, 120, 10), &layout);
+            let chart = chart.expect("Wide sections should have a chart");
+            assert_eq!(area.width + chart.width, 120);
+            assert_eq!(chart.x, area.right());
+        }
+
+        #[test]
+        fn test_chart_split_follows_layout() {
+            let layout = LayoutConfig {
+                chart_percent: 25,
+                min_chart_width: 100,
+                ..LayoutConfig::default()
+            };
+            let (_, chart) = split_for_chart(Rect::new(0, 0, 90, 10), &layout);
+            assert!(chart.is_none());
+
+            let (area, chart) = split_for_chart(Rect::new(0, 0, 120, 10), &layout);
+            assert_eq!(area.width, 90);
+            assert_eq!(chart.map(|chart| chart.width), Some(30));
+        }
+
+        #[test]
+        fn test_renders_history_charts() {
+            let mut terminal = Terminal::new(TestBackend::new(120, 40)).unwrap();
+            let mut app = App::new();
+            app.update();
+            app.update();
+            let mut events = ScriptedEvents::new(vec![key(KeyCode::Char('q'))]);
+
+            run_app(&mut terminal, app, &mut events, fast_config(), None).unwrap();
+            let text = screen_text(&terminal);
+            assert!(text.contains("CPU History"), "CPU chart should be rendered");
+            assert!(
+                text.contains("Memory History"),
+                "Memory chart should be rendered"
+            );
         }
     }
-}
 
-/// Application entry point.
-///
-/// Sets up the terminal environment, creates the application state,
-/// runs the main event loop, and ensures proper cleanup on exit.
-///
-/// # Returns
-/// * `Result<(), Box<dyn Error>>` - Ok if application exits normally
-///
-/// # Errors
-/// * Terminal initialization failures
-/// * Event handling errors
-/// * Terminal cleanup failures
-///
-/// # Example
-/// ```no_run
-/// fn main() -> Result<(), Box<dyn std::error::Error>> {
-///     // Application setup and execution
-///     Ok(())
-/// }
-/// ```
-fn main() -> Result<(), Box<dyn Error>> {
-    // Setup terminal
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(stdout, EnterAlternateScreen)?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    mod replay {
+        use super::*;
+        use std::time::UNIX_EPOCH;
+        use system_monitor::MetricsSnapshot;
 
-    // Create and run the app
-    let mut app = App::new();
-    let result = run_app(&mut terminal, &mut app);
+        /// Builds a session whose samples are an hour apart, so playback
+        /// never advances on its own during a test.
+        fn player(usages: &[f32]) -> Player {
+            let snapshots = usages
+                .iter()
+                .enumerate()
+                .map(|(i, &usage)| MetricsSnapshot {
+                    timestamp: UNIX_EPOCH + Duration::from_secs(3600 * i as u64),
+                    cores: vec![CoreUsage {
+                        id: 0,
+                        name: "cpu0".to_string(),
+                        usage,
+                    }],
+                    total_memory: 1000,
+                    used_memory: 250,
+                    ..MetricsSnapshot::default()
+                })
+                .collect();
+            Player::new(Session::new(snapshots).unwrap(), DEFAULT_HISTORY_LEN)
+        }
 
-    // Restore terminal
-    disable_raw_mode()?;
-    execute!(terminal.backend_mut(), LeaveAlternateScreen)?;
-    terminal.show_cursor()?;
+        #[test]
+        fn test_renders_recorded_values() {
+            let mut terminal = test_terminal();
+            let mut events = ScriptedEvents::new(vec![Step::Idle, key(KeyCode::Char('q'))]);
 
-    // Map io::Error to Box<dyn Error>
-    result.map_err(|e| e.into())
+            let (_, player) = run_replay(
+                &mut terminal,
+                player(&[42.0, 10.0]),
+                &mut events,
+                fast_config(),
+            )
+            .unwrap();
+            let text = screen_text(&terminal);
+            assert_eq!(player.position(), 0);
+            assert!(text.contains("42.0%"), "Recorded CPU usage should be shown");
+            assert!(
+                text.contains("25.0%"),
+                "Recorded memory usage should be shown"
+            );
+            assert!(
+                text.contains("REPLAY 1/2 at 1x, 1970-01-01T00:00:00.000Z"),
+                "Title should show the playback position"
+            );
+        }
+
+        #[test]
+        fn test_arrow_keys_step_through_session() {
+            let mut terminal = test_terminal();
+            let mut events = ScriptedEvents::new(vec![
+                key(KeyCode::Right),
+                key(KeyCode::Right),
+                key(KeyCode::Left),
+                Step::Idle,
+                key(KeyCode::Char('q')),
+            ]);
+
+            let (_, player) = run_replay(
+                &mut terminal,
+                player(&[10.0, 20.0, 30.0]),
+                &mut events,
+                fast_config(),
+            )
+            .unwrap();
+            assert_eq!(player.position(), 1);
+            assert_eq!(player.app().get_cpu_usage(), Ok(20.0));
+            assert!(screen_text(&terminal).contains("REPLAY 2/3"));
+        }
+
+        #[test]
+        fn test_speed_and_pause_keys() {
+            let mut terminal = test_terminal();
+            let mut events = ScriptedEvents::new(vec![
+                key(KeyCode::Char('+')),
+                key(KeyCode::Char('+')),
+                key(KeyCode::Char('p')),
+                Step::Idle,
+                key(KeyCode::Char('q')),
+            ]);
+
+            let (_, player) = run_replay(
+                &mut terminal,
+                player(&[10.0, 20.0]),
+                &mut events,
+                fast_config(),
+            )
+            .unwrap();
+            assert_eq!(player.speed(), 4.0);
+            assert!(player.is_paused());
+            let text = screen_text(&terminal);
+            assert!(text.contains("at 4x"));
+            assert!(text.contains("[PAUSED]"));
+        }
+
+        #[test]
+        fn test_live_session_is_recorded() {
+            let path = std::env::temp_dir().join(format!(
+                "system-monitor-tui-session-{}.ndjson",
+                std::process::id()
+            ));
+            let mut terminal = test_terminal();
+            let mut steps: Vec<Step> = (0..5).map(|_| Step::Idle).collect();
+            steps.push(key(KeyCode::Char('q')));
+            let mut events = ScriptedEvents::new(steps);
+
+            let session = SessionWriter::create(&path).unwrap();
+            let (stats, _) = run_app(
+                &mut terminal,
+                App::new(),
+                &mut events,
+                fast_config(),
+                Some(session),
+            )
+            .unwrap();
+            let recorded = Session::load(&path).unwrap();
+            std::fs::remove_file(&path).unwrap();
+            assert!(stats.samples > 0);
+            assert_eq!(recorded.len() as u64, stats.samples);
+            assert!(
+                !screen_text(&terminal).contains("REPLAY"),
+                "Live monitoring should not show a playback position"
+            );
+        }
+    }
+
+    mod summary {
+        use super::*;
+
+        #[test]
+        fn test_summary_lists_busiest_processes() {
+            let process = |pid, cpu_usage| ProcessInfo {
+                pid,
+                name: format!("worker{}", pid),
+                user: None,
+                cpu_usage,
+                memory: 2048,
+                state: "Runnable".to_string(),
+                command: String::new(),
+            };
+            let reading = Reading {
+                timestamp: std::time::UNIX_EPOCH,
+                cores: vec![CoreUsage {
+                    id: 0,
+                    name: "cpu0".to_string(),
+                    usage: 12.5,
+                }],
+                total_memory: 4096,
+                used_memory: 1024,
+                processes: Some(vec![process(1, 1.0), process(2, 80.0), process(3, 5.0)]),
+                ..Reading::default()
+            };
+            let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+
+            let summary = format_summary(&app, 2);
+            let lines: Vec<&str> = summary.lines().collect();
+            assert_eq!(lines[0], "System Monitor at 1970-01-01T00:00:00.000Z");
+            assert_eq!(lines[1], "CPU        12.5% (1 cores)");
+            assert_eq!(lines[2], "Memory     1.0 KiB / 4.0 KiB (25%)");
+            assert_eq!(lines[3], "Swap       not configured");
+            assert_eq!(lines[4], "Processes  3");
+            assert!(lines[5].ends_with("worker2"));
+            assert!(lines[6].ends_with("worker3"));
+            assert_eq!(lines.len(), 7);
+        }
+    }
 }
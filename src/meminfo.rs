@@ -0,0 +1,267 @@
+//! Detailed memory breakdown from `/proc/meminfo`.
+//!
+//! "Used" memory is ambiguous: the page cache fills otherwise free memory
+//! and is given back under pressure, while shared memory and unreclaimable
+//! kernel slabs are not. Linux 3.14 and later estimate how much memory can
+//! be allocated without swapping in `MemAvailable`, which is what the
+//! headline usage is based on. The other fields explain where the rest
+//! goes:
+//!
+//! ```text
+//! MemTotal:       16318480 kB
+//! MemFree:         1824588 kB
+//! MemAvailable:    9791088 kB
+//! Buffers:          412300 kB
+//! Cached:          7210450 kB
+//! ```
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Huge pages reserved through `hugetlbfs`.
+///
+/// # Fields
+/// * `total` - Number of huge pages in the pool
+/// * `free` - Number of huge pages not yet allocated
+/// * `reserved` - Number of free huge pages promised to a mapping
+/// * `surplus` - Number of huge pages above the configured pool size
+/// * `page_size` - Size of one huge page in bytes
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HugePages {
+    pub total: u64,
+    pub free: u64,
+    pub reserved: u64,
+    pub surplus: u64,
+    pub page_size: u64,
+}
+
+impl HugePages {
+    /// Returns the size of the pool in bytes.
+    pub fn total_bytes(&self) -> u64 {
+        self.total * self.page_size
+    }
+
+    /// Returns the size of the allocated huge pages in bytes.
+    pub fn used_bytes(&self) -> u64 {
+        self.total.saturating_sub(self.free) * self.page_size
+    }
+}
+
+/// Memory breakdown as reported by `/proc/meminfo`, in bytes.
+///
+/// # Fields
+/// * `total` - Usable physical memory (`MemTotal`)
+/// * `free` - Memory not used for anything (`MemFree`)
+/// * `available` - Memory that can be allocated without swapping
+///   (`MemAvailable`, estimated on kernels before 3.14)
+/// * `buffers` - Block device buffers (`Buffers`)
+/// * `cached` - Page cache, including shared memory (`Cached`)
+/// * `shared` - Shared memory and tmpfs (`Shmem`)
+/// * `slab` - Kernel slab caches (`Slab`)
+/// * `slab_reclaimable` - Part of the slab caches that can be reclaimed
+///   (`SReclaimable`)
+/// * `dirty` - Memory waiting to be written back to disk (`Dirty`)
+/// * `writeback` - Memory being written back to disk (`Writeback`)
+/// * `hugepages` - Huge page pool
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct MemInfo {
+    pub total: u64,
+    pub free: u64,
+    pub available: u64,
+    pub buffers: u64,
+    pub cached: u64,
+    pub shared: u64,
+    pub slab: u64,
+    pub slab_reclaimable: u64,
+    pub dirty: u64,
+    pub writeback: u64,
+    pub hugepages: HugePages,
+}
+
+impl MemInfo {
+    /// Reads `meminfo` under `proc_root`.
+    ///
+    /// # Returns
+    /// * `Option<MemInfo>` - The breakdown, or `None` if the file is missing
+    ///   (other platforms) or malformed
+    pub fn read(proc_root: &Path) -> Option<Self> {
+        let text = fs::read_to_string(proc_root.join("meminfo")).ok()?;
+        parse_meminfo(&text)
+    }
+
+    /// Returns the memory that cannot be allocated without swapping.
+    pub fn used(&self) -> u64 {
+        self.total.saturating_sub(self.available)
+    }
+
+    /// Returns the used memory as a percentage of the total.
+    ///
+    /// # Returns
+    /// * `Option<f32>` - Usage based on `MemAvailable`, or `None` if the
+    ///   total is zero
+    pub fn usage(&self) -> Option<f32> {
+        (self.total > 0).then(|| self.used() as f32 / self.total as f32 * 100.0)
+    }
+
+    /// Returns the memory used by applications and unreclaimable kernel
+    /// allocations: everything that is neither free, buffers, page cache
+    /// nor reclaimable slab.
+    pub fn application(&self) -> u64 {
+        self.total
+            .saturating_sub(self.free)
+            .saturating_sub(self.buffers)
+            .saturating_sub(self.cached)
+            .saturating_sub(self.slab_reclaimable)
+    }
+
+    /// Returns the reclaimable caches: the page cache without shared
+    /// memory, plus the reclaimable slab caches.
+    pub fn cache(&self) -> u64 {
+        (self.cached + self.slab_reclaimable).saturating_sub(self.shared)
+    }
+}
+
+/// Parses the contents of `/proc/meminfo`.
+///
+/// Values in `kB` are converted to bytes; huge page counts have no unit.
+/// Unknown fields are ignored. Kernels before 3.14 do not report
+/// `MemAvailable`; it is then estimated as free memory plus buffers and
+/// page cache.
+///
+/// # Returns
+/// * `Option<MemInfo>` - The breakdown, or `None` if `MemTotal` or
+///   `MemFree` is missing or a field is not a number
+///
+/// # Example
+/// ```
+/// use system_monitor::meminfo::parse_meminfo;
+///
+/// let meminfo = parse_meminfo("MemTotal: 1000 kB\nMemFree: 100 kB\nMemAvailable: 750 kB\n").unwrap();
+/// assert_eq!(meminfo.available, 768_000);
+/// assert_eq!(meminfo.usage(), Some(25.0));
+/// ```
+pub fn parse_meminfo(text: &str) -> Option<MemInfo> {
+    let mut fields = HashMap::new();
+    for line in text.lines() {
+        let Some((key, value)) = line.split_once(':') else {
+            continue;
+        };
+        let mut parts = value.split_whitespace();
+        let number: u64 = parts.next()?.parse().ok()?;
+        let bytes = match parts.next() {
+            Some("kB") => number * 1024,
+            _ => number,
+        };
+        fields.insert(key.trim(), bytes);
+    }
+    let field = |key| fields.get(key).copied().unwrap_or(0);
+
+    let free = *fields.get("MemFree")?;
+    let buffers = field("Buffers");
+    let cached = field("Cached");
+    Some(MemInfo {
+        total: *fields.get("MemTotal")?,
+        free,
+        available: fields
+            .get("MemAvailable")
+            .copied()
+            .unwrap_or(free + buffers + cached),
+        buffers,
+        cached,
+        shared: field("Shmem"),
+        slab: field("Slab"),
+        slab_reclaimable: field("SReclaimable"),
+        dirty: field("Dirty"),
+        writeback: field("Writeback"),
+        hugepages: HugePages {
+            total: field("HugePages_Total"),
+            free: field("HugePages_Free"),
+            reserved: field("HugePages_Rsvd"),
+            surplus: field("HugePages_Surp"),
+            page_size: field("Hugepagesize"),
+        },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::fixture_proc_root;
+
+    const KIB: u64 = 1024;
+
+    #[test]
+    fn test_read_fixture_file() {
+        let meminfo = MemInfo::read(&fixture_proc_root()).unwrap();
+        assert_eq!(meminfo.total, 16_318_480 * KIB);
+        assert_eq!(meminfo.free, 1_824_588 * KIB);
+        assert_eq!(meminfo.available, 9_791_088 * KIB);
+        assert_eq!(meminfo.buffers, 412_300 * KIB);
+        assert_eq!(meminfo.cached, 7_210_450 * KIB);
+        assert_eq!(meminfo.shared, 523_400 * KIB);
+        assert_eq!(meminfo.slab, 890_000 * KIB);
+        assert_eq!(meminfo.slab_reclaimable, 610_000 * KIB);
+        assert_eq!(meminfo.dirty, 2_048 * KIB);
+        assert_eq!(meminfo.writeback, 512 * KIB);
+        assert_eq!(
+            meminfo.hugepages,
+            HugePages {
+                total: 16,
+                free: 12,
+                reserved: 2,
+                surplus: 0,
+                page_size: 2 * 1024 * KIB,
+            }
+        );
+    }
+
+    #[test]
+    fn test_headline_usage_is_based_on_available() {
+        let meminfo = MemInfo::read(&fixture_proc_root()).unwrap();
+        assert_eq!(meminfo.used(), 6_527_392 * KIB);
+        assert_eq!(meminfo.usage(), Some(40.0));
+    }
+
+    #[test]
+    fn test_segments_add_up_to_non_free_memory() {
+        let meminfo = MemInfo::read(&fixture_proc_root()).unwrap();
+        assert_eq!(meminfo.application(), 6_261_142 * KIB);
+        assert_eq!(meminfo.cache(), 7_297_050 * KIB);
+        assert_eq!(
+            meminfo.application() + meminfo.buffers + meminfo.shared + meminfo.cache(),
+            meminfo.total - meminfo.free
+        );
+    }
+
+    #[test]
+    fn test_hugepage_sizes() {
+        let hugepages = MemInfo::read(&fixture_proc_root()).unwrap().hugepages;
+        assert_eq!(hugepages.total_bytes(), 32 * 1024 * KIB);
+        assert_eq!(hugepages.used_bytes(), 8 * 1024 * KIB);
+    }
+
+    #[test]
+    fn test_missing_meminfo_file() {
+        assert_eq!(MemInfo::read(Path::new("/nonexistent/proc")), None);
+    }
+
+    #[test]
+    fn test_available_is_estimated_on_old_kernels() {
+        let meminfo =
+            parse_meminfo("MemTotal: 1000 kB\nMemFree: 100 kB\nBuffers: 50 kB\nCached: 250 kB\n")
+                .unwrap();
+        assert_eq!(meminfo.available, 400 * KIB);
+        assert_eq!(meminfo.hugepages, HugePages::default());
+    }
+
+    #[test]
+    fn test_malformed_meminfo() {
+        assert_eq!(parse_meminfo(""), None);
+        assert_eq!(parse_meminfo("MemFree: 100 kB\n"), None);
+        assert_eq!(parse_meminfo("MemTotal: 1000 kB\n"), None);
+        assert_eq!(parse_meminfo("MemTotal: 1000 kB\nMemFree: lots kB\n"), None);
+    }
+}
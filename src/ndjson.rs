@@ -0,0 +1,185 @@
+//! Newline-delimited JSON output.
+//!
+//! Every sample is written as one JSON object on its own line, so the
+//! output can be piped into `jq`, log shippers or scripts. Each line is the
+//! serialized [`MetricsSnapshot`] the TUI renders from, extended with the
+//! derived `cpu_usage`, `memory_usage` and `swap_usage` percentages.
+//! Values that are unavailable on the host are written as `null`.
+
+use std::io::{self, Write};
+
+use serde::Serialize;
+
+use crate::background::BackgroundCollector;
+use crate::snapshot::MetricsSnapshot;
+
+/// One output line: a snapshot and its derived percentages.
+///
+/// # Fields
+/// * `snapshot` - Collected metrics, flattened into the object
+/// * `cpu_usage` - Average core usage, `null` without cores
+/// * `memory_usage` - Memory usage, `null` when total memory is zero
+/// * `swap_usage` - Swap usage, `null` without swap
+#[derive(Debug, Serialize)]
+pub struct Record<'a> {
+    #[serde(flatten)]
+    pub snapshot: &'a MetricsSnapshot,
+    pub cpu_usage: Option<f32>,
+    pub memory_usage: Option<f32>,
+    pub swap_usage: Option<f32>,
+}
+
+impl<'a> Record<'a> {
+    /// Builds the record of `snapshot`.
+    pub fn new(snapshot: &'a MetricsSnapshot) -> Self {
+        Self {
+            snapshot,
+            cpu_usage: snapshot.cpu_usage().ok(),
+            memory_usage: snapshot.memory_usage().ok(),
+            swap_usage: snapshot.swap_usage(),
+        }
+    }
+}
+
+/// Encodes a snapshot as a single line of JSON, without the line feed.
+///
+/// # Example
+/// ```
+/// use system_monitor::{ndjson, App, Reading, ScriptedSource};
+///
+/// let reading = Reading { total_memory: 1024, used_memory: 256, ..Reading::default() };
+/// let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+/// let line = ndjson::encode(app.snapshot());
+/// assert!(line.contains("\"memory_usage\":25.0"));
+/// assert!(line.contains("\"cpu_usage\":null"));
+/// ```
+pub fn encode(snapshot: &MetricsSnapshot) -> String {
+    // Serializing plain data with string keys cannot fail
+    serde_json::to_string(&Record::new(snapshot)).unwrap_or_default()
+}
+
+/// Writes one line per snapshot published by `collector`.
+///
+/// Each line is flushed as soon as it is written so that consumers see
+/// samples in real time. A closed pipe (e.g. `| head -n 5`) ends the
+/// stream without an error.
+///
+/// # Arguments
+/// * `collector` - Collector whose snapshots are written; one started with
+///   [`BackgroundCollector::spawn_lossless`] writes every sample even when
+///   the reader stalls
+/// * `out` - Destination, usually stdout
+/// * `count` - Number of lines to write, or `None` to run until the
+///   collector stops or the output is closed
+///
+/// # Returns
+/// * `io::Result<u64>` - Number of lines written
+pub fn stream(
+    collector: &BackgroundCollector,
+    out: &mut impl Write,
+    count: Option<u64>,
+) -> io::Result<u64> {
+    let mut written = 0;
+    while count.is_none_or(|count| written < count) {
+        let Some(snapshot) = collector.recv() else {
+            break;
+        };
+        let result = writeln!(out, "{}", encode(&snapshot)).and_then(|()| out.flush());
+        match result {
+            Ok(()) => written += 1,
+            Err(e) if e.kind() == io::ErrorKind::BrokenPipe => break,
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::snapshot::CoreUsage;
+    use crate::source::{Reading, ScriptedSource};
+    use crate::App;
+    use serde_json::Value;
+    use std::time::{Duration, SystemTime};
+
+    fn reading(seconds: u64, usage: f32) -> Reading {
+        Reading {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            cores: vec![CoreUsage {
+                id: 0,
+                name: "cpu0".to_string(),
+                usage,
+            }],
+            total_memory: 100,
+            used_memory: 50,
+            ..Reading::default()
+        }
+    }
+
+    /// Writer standing in for a pipe whose reader has gone away.
+    struct ClosedPipe;
+
+    impl Write for ClosedPipe {
+        fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+            Err(io::ErrorKind::BrokenPipe.into())
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_encode_is_one_line_of_json() {
+        let app = App::with_source(ScriptedSource::new(vec![reading(10, 40.0)]), 10);
+        let line = encode(app.snapshot());
+        assert!(!line.contains('\n'));
+
+        let value: Value = serde_json::from_str(&line).unwrap();
+        assert_eq!(value["timestamp"], 10.0);
+        assert_eq!(value["cpu_usage"], 40.0);
+        assert_eq!(value["cores"][0]["name"], "cpu0");
+        assert_eq!(value["memory_usage"], 50.0);
+        assert_eq!(value["used_memory"], 50);
+        assert!(value["swap_usage"].is_null());
+    }
+
+    #[test]
+    fn test_non_finite_usage_is_null() {
+        let app = App::with_source(ScriptedSource::new(vec![reading(0, f32::NAN)]), 10);
+        let value: Value = serde_json::from_str(&encode(app.snapshot())).unwrap();
+        assert!(value["cpu_usage"].is_null());
+        assert!(value["cores"][0]["usage"].is_null());
+    }
+
+    #[test]
+    fn test_stream_writes_count_lines() {
+        let source = ScriptedSource::new(vec![
+            reading(0, 0.0),
+            reading(1, 10.0),
+            reading(2, 20.0),
+            reading(3, 30.0),
+        ]);
+        let app = App::with_source(source, 10);
+        let collector = BackgroundCollector::spawn_lossless(app, Duration::from_millis(1)).unwrap();
+
+        let mut out = Vec::new();
+        assert_eq!(stream(&collector, &mut out, Some(2)).unwrap(), 2);
+        let lines: Vec<Value> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["cpu_usage"], 10.0);
+        assert_eq!(lines[1]["cpu_usage"], 20.0);
+    }
+
+    #[test]
+    fn test_closed_pipe_ends_stream() {
+        let app = App::with_source(ScriptedSource::new(vec![reading(0, 0.0)]), 10);
+        let collector = BackgroundCollector::spawn_lossless(app, Duration::from_millis(1)).unwrap();
+        assert_eq!(stream(&collector, &mut ClosedPipe, None).unwrap(), 0);
+    }
+}
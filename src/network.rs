@@ -0,0 +1,337 @@
+//! Network interface throughput.
+//!
+//! The OS only exposes cumulative byte and packet counters per interface.
+//! [`NetworkTracker`] turns successive readings of those counters into
+//! per-second rates, using the wall-clock time elapsed between readings,
+//! and into totals accumulated since monitoring started.
+
+use std::collections::HashMap;
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::history::History;
+
+/// Number of throughput samples kept per interface for sparklines.
+pub const NETWORK_HISTORY_LEN: usize = 60;
+
+/// Cumulative traffic counters of one interface as reported by the OS.
+///
+/// # Fields
+/// * `rx_bytes` - Bytes received
+/// * `tx_bytes` - Bytes transmitted
+/// * `rx_packets` - Packets received
+/// * `tx_packets` - Packets transmitted
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct NetworkCounters {
+    pub rx_bytes: u64,
+    pub tx_bytes: u64,
+    pub rx_packets: u64,
+    pub tx_packets: u64,
+}
+
+impl NetworkCounters {
+    /// Returns the increase from `earlier` to `self`.
+    ///
+    /// A counter that went backwards (interface reset or wrap-around) is
+    /// treated as having restarted from zero.
+    fn since(&self, earlier: &NetworkCounters) -> NetworkCounters {
+        let delta = |now: u64, then: u64| if now >= then { now - then } else { now };
+        NetworkCounters {
+            rx_bytes: delta(self.rx_bytes, earlier.rx_bytes),
+            tx_bytes: delta(self.tx_bytes, earlier.tx_bytes),
+            rx_packets: delta(self.rx_packets, earlier.rx_packets),
+            tx_packets: delta(self.tx_packets, earlier.tx_packets),
+        }
+    }
+
+    /// Adds `other` to `self`, saturating on overflow.
+    fn accumulate(&mut self, other: &NetworkCounters) {
+        self.rx_bytes = self.rx_bytes.saturating_add(other.rx_bytes);
+        self.tx_bytes = self.tx_bytes.saturating_add(other.tx_bytes);
+        self.rx_packets = self.rx_packets.saturating_add(other.rx_packets);
+        self.tx_packets = self.tx_packets.saturating_add(other.tx_packets);
+    }
+}
+
+/// Throughput of a single network interface.
+///
+/// # Fields
+/// * `name` - Interface name (e.g. `eth0`)
+/// * `rx_bytes_per_sec` - Receive rate in bytes per second
+/// * `tx_bytes_per_sec` - Transmit rate in bytes per second
+/// * `rx_packets_per_sec` - Receive rate in packets per second
+/// * `tx_packets_per_sec` - Transmit rate in packets per second
+/// * `total` - Traffic since monitoring started
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct InterfaceUsage {
+    pub name: String,
+    pub rx_bytes_per_sec: f64,
+    pub tx_bytes_per_sec: f64,
+    pub rx_packets_per_sec: f64,
+    pub tx_packets_per_sec: f64,
+    pub total: NetworkCounters,
+}
+
+/// Converts cumulative interface counters into rates and totals.
+///
+/// Rates are computed between two consecutive calls to
+/// [`NetworkTracker::sample`]; the first sample of an interface reports
+/// zero rates. A short history of combined throughput is kept per
+/// interface for sparklines.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use system_monitor::network::{NetworkCounters, NetworkTracker};
+///
+/// let mut tracker = NetworkTracker::default();
+/// let start = SystemTime::now();
+/// let counters = |rx_bytes| NetworkCounters { rx_bytes, ..Default::default() };
+///
+/// tracker.sample(vec![("eth0".to_string(), counters(1_000))], start);
+/// let usage = tracker.sample(
+///     vec![("eth0".to_string(), counters(3_000))],
+///     start + Duration::from_secs(2),
+/// );
+/// assert_eq!(usage[0].rx_bytes_per_sec, 1_000.0);
+/// assert_eq!(usage[0].total.rx_bytes, 2_000);
+/// ```
+#[derive(Debug, Default, Clone)]
+pub struct NetworkTracker {
+    previous: HashMap<String, NetworkCounters>,
+    previous_at: Option<SystemTime>,
+    totals: HashMap<String, NetworkCounters>,
+    history: HashMap<String, History<u64>>,
+}
+
+impl NetworkTracker {
+    /// Records a new reading of the cumulative counters.
+    ///
+    /// Interfaces that disappeared since the previous reading are
+    /// forgotten.
+    ///
+    /// # Arguments
+    /// * `readings` - Cumulative counters per interface name
+    /// * `now` - Wall-clock time at which the counters were read
+    ///
+    /// # Returns
+    /// * `Vec<InterfaceUsage>` - Rates and totals, ordered by interface name
+    pub fn sample(
+        &mut self,
+        readings: Vec<(String, NetworkCounters)>,
+        now: SystemTime,
+    ) -> Vec<InterfaceUsage> {
+        // A clock going backwards yields zero rates rather than negative ones
+        let elapsed = self
+            .previous_at
+            .and_then(|then| now.duration_since(then).ok())
+            .map(|elapsed| elapsed.as_secs_f64())
+            .unwrap_or(0.0);
+        let rate = |delta: u64| {
+            if elapsed > 0.0 {
+                delta as f64 / elapsed
+            } else {
+                0.0
+            }
+        };
+
+        let mut usage = Vec::with_capacity(readings.len());
+        let mut previous = HashMap::with_capacity(readings.len());
+        for (name, counters) in readings {
+            let delta = match self.previous.get(&name) {
+                Some(earlier) => counters.since(earlier),
+                None => NetworkCounters::default(),
+            };
+            let total = self.totals.entry(name.clone()).or_default();
+            total.accumulate(&delta);
+
+            self.history
+                .entry(name.clone())
+                .or_insert_with(|| History::new(NETWORK_HISTORY_LEN))
+                .push(rate(delta.rx_bytes + delta.tx_bytes) as u64);
+
+            usage.push(InterfaceUsage {
+                name: name.clone(),
+                rx_bytes_per_sec: rate(delta.rx_bytes),
+                tx_bytes_per_sec: rate(delta.tx_bytes),
+                rx_packets_per_sec: rate(delta.rx_packets),
+                tx_packets_per_sec: rate(delta.tx_packets),
+                total: *total,
+            });
+            previous.insert(name, counters);
+        }
+
+        self.totals.retain(|name, _| previous.contains_key(name));
+        self.history.retain(|name, _| previous.contains_key(name));
+        self.previous = previous;
+        self.previous_at = Some(now);
+        usage.sort_by(|a, b| a.name.cmp(&b.name));
+        usage
+    }
+
+    /// Records usage that was computed elsewhere, e.g. replayed from a
+    /// recording, into the throughput history.
+    ///
+    /// Interfaces missing from `usage` are forgotten, like in
+    /// [`NetworkTracker::sample`]. Counters are left untouched.
+    pub fn record(&mut self, usage: &[InterfaceUsage]) {
+        for interface in usage {
+            self.history
+                .entry(interface.name.clone())
+                .or_insert_with(|| History::new(NETWORK_HISTORY_LEN))
+                .push((interface.rx_bytes_per_sec + interface.tx_bytes_per_sec) as u64);
+        }
+        self.history
+            .retain(|name, _| usage.iter().any(|interface| &interface.name == name));
+    }
+
+    /// Returns the recent combined (rx + tx) throughput of an interface.
+    ///
+    /// # Returns
+    /// * `Option<&History<u64>>` - Bytes per second, oldest first, or
+    ///   `None` for unknown interfaces
+    pub fn history(&self, name: &str) -> Option<&History<u64>> {
+        self.history.get(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn counters(rx_bytes: u64, tx_bytes: u64, rx_packets: u64, tx_packets: u64) -> NetworkCounters {
+        NetworkCounters {
+            rx_bytes,
+            tx_bytes,
+            rx_packets,
+            tx_packets,
+        }
+    }
+
+    fn reading(name: &str, counters: NetworkCounters) -> Vec<(String, NetworkCounters)> {
+        vec![(name.to_string(), counters)]
+    }
+
+    #[test]
+    fn test_first_sample_has_zero_rates() {
+        let mut tracker = NetworkTracker::default();
+        let usage = tracker.sample(reading("eth0", counters(500, 500, 5, 5)), SystemTime::now());
+        assert_eq!(usage[0].rx_bytes_per_sec, 0.0);
+        assert_eq!(usage[0].tx_bytes_per_sec, 0.0);
+        assert_eq!(usage[0].total, NetworkCounters::default());
+    }
+
+    #[test]
+    fn test_rates_use_elapsed_time() {
+        let mut tracker = NetworkTracker::default();
+        let start = SystemTime::now();
+        tracker.sample(reading("eth0", counters(0, 0, 0, 0)), start);
+        let usage = tracker.sample(
+            reading("eth0", counters(4_000, 2_000, 40, 20)),
+            start + Duration::from_millis(500),
+        );
+        assert_eq!(usage[0].rx_bytes_per_sec, 8_000.0);
+        assert_eq!(usage[0].tx_bytes_per_sec, 4_000.0);
+        assert_eq!(usage[0].rx_packets_per_sec, 80.0);
+        assert_eq!(usage[0].tx_packets_per_sec, 40.0);
+    }
+
+    #[test]
+    fn test_totals_accumulate_since_start() {
+        let mut tracker = NetworkTracker::default();
+        let start = SystemTime::now();
+        tracker.sample(reading("eth0", counters(100, 0, 1, 0)), start);
+        tracker.sample(
+            reading("eth0", counters(300, 50, 3, 1)),
+            start + Duration::from_secs(1),
+        );
+        let usage = tracker.sample(
+            reading("eth0", counters(600, 80, 6, 2)),
+            start + Duration::from_secs(2),
+        );
+        assert_eq!(usage[0].total, counters(500, 80, 5, 2));
+    }
+
+    #[test]
+    fn test_counter_reset_is_not_negative() {
+        let mut tracker = NetworkTracker::default();
+        let start = SystemTime::now();
+        tracker.sample(reading("eth0", counters(10_000, 0, 0, 0)), start);
+        let usage = tracker.sample(
+            reading("eth0", counters(1_000, 0, 0, 0)),
+            start + Duration::from_secs(1),
+        );
+        assert_eq!(usage[0].rx_bytes_per_sec, 1_000.0);
+        assert_eq!(usage[0].total.rx_bytes, 1_000);
+    }
+
+    #[test]
+    fn test_interfaces_sorted_and_removed() {
+        let mut tracker = NetworkTracker::default();
+        let start = SystemTime::now();
+        let usage = tracker.sample(
+            vec![
+                ("wlan0".to_string(), NetworkCounters::default()),
+                ("eth0".to_string(), NetworkCounters::default()),
+            ],
+            start,
+        );
+        let names: Vec<&str> = usage.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["eth0", "wlan0"]);
+
+        tracker.sample(reading("eth0", NetworkCounters::default()), start);
+        assert!(
+            tracker.history("wlan0").is_none(),
+            "Vanished interfaces should be forgotten"
+        );
+    }
+
+    #[test]
+    fn test_clock_going_backwards_gives_zero_rates() {
+        let mut tracker = NetworkTracker::default();
+        let start = SystemTime::now();
+        tracker.sample(reading("eth0", counters(0, 0, 0, 0)), start);
+        let usage = tracker.sample(
+            reading("eth0", counters(1_000, 0, 0, 0)),
+            start - Duration::from_secs(1),
+        );
+        assert_eq!(usage[0].rx_bytes_per_sec, 0.0);
+        assert_eq!(usage[0].total.rx_bytes, 1_000);
+    }
+
+    #[test]
+    fn test_history_is_bounded() {
+        let mut tracker = NetworkTracker::default();
+        let start = SystemTime::now();
+        for second in 0..(NETWORK_HISTORY_LEN as u64 + 10) {
+            tracker.sample(
+                reading("eth0", counters(second * 100, 0, 0, 0)),
+                start + Duration::from_secs(second),
+            );
+        }
+        let history = tracker.history("eth0").unwrap();
+        assert_eq!(history.len(), NETWORK_HISTORY_LEN);
+        assert_eq!(history.latest(), Some(&100));
+    }
+
+    #[test]
+    fn test_record_pushes_combined_rates() {
+        let mut tracker = NetworkTracker::default();
+        let usage = |name: &str, rx: f64, tx: f64| InterfaceUsage {
+            name: name.to_string(),
+            rx_bytes_per_sec: rx,
+            tx_bytes_per_sec: tx,
+            rx_packets_per_sec: 0.0,
+            tx_packets_per_sec: 0.0,
+            total: NetworkCounters::default(),
+        };
+        tracker.record(&[usage("eth0", 100.0, 50.0), usage("wlan0", 1.0, 1.0)]);
+        tracker.record(&[usage("eth0", 300.0, 0.0)]);
+
+        let history: Vec<u64> = tracker.history("eth0").unwrap().iter().copied().collect();
+        assert_eq!(history, vec![150, 300]);
+        assert!(tracker.history("wlan0").is_none());
+    }
+}
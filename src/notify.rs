@@ -0,0 +1,1027 @@
+//! Alert notification actions.
+//!
+//! An [`AlertAction`] tells someone outside the terminal that an alert
+//! started firing or resolved:
+//!
+//! * **exec** - runs a command with the alert details in
+//!   `SYSTEM_MONITOR_ALERT*` environment variables (see [`environment`])
+//! * **log** - appends one line per notification to a file
+//! * **webhook** - `POST`s a JSON [`Payload`] to an `http://` URL
+//!
+//! Pending alerts are not notified. Each attempt is bounded by the action's
+//! timeout, failed attempts are retried with a doubling delay, and an
+//! alert that fires again less than the cooldown after an action was last
+//! notified of it firing is not notified to that action, so a flapping
+//! alert cannot flood its receivers. A resolution is notified exactly when
+//! the firing it ends was, so receivers never miss one. The cooldown is
+//! measured on the event timestamps.
+//!
+//! A [`Notifier`] delivers synchronously; a [`BackgroundNotifier`] runs one
+//! on its own thread so that a slow webhook never delays sampling.
+//!
+//! # Example
+//! ```no_run
+//! use system_monitor::notify::{ActionKind, AlertAction, BackgroundNotifier, Notifier};
+//! use system_monitor::App;
+//!
+//! let webhook = AlertAction::new(ActionKind::Webhook(
+//!     "http://127.0.0.1:9000/alerts".parse().unwrap(),
+//! ));
+//! let notifier = BackgroundNotifier::spawn(Notifier::new(vec![webhook]), |failure| {
+//!     eprintln!("{}", failure)
+//! })
+//! .unwrap();
+//!
+//! let mut app = App::new();
+//! app.set_alert_rules(vec!["cpu > 90% for 30s".parse().unwrap()]);
+//! app.set_notifier(notifier);
+//! ```
+
+use std::collections::HashMap;
+use std::error::Error;
+use std::fmt;
+use std::fs::OpenOptions;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::path::PathBuf;
+use std::process::{Command, ExitStatus, Stdio};
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, Instant, SystemTime};
+
+use serde::Serialize;
+
+use crate::alert::{AlertEvent, Transition};
+use crate::timefmt::iso8601;
+
+/// Default time allowed for one attempt.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Default number of attempts after the first one fails.
+pub const DEFAULT_RETRIES: u32 = 2;
+
+/// Default delay before the first retry; doubled before each further one.
+pub const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(1);
+
+/// Default time during which an identical notification is suppressed.
+pub const DEFAULT_COOLDOWN: Duration = Duration::from_secs(300);
+
+/// Environment variable holding the alert name.
+pub const ENV_ALERT: &str = "SYSTEM_MONITOR_ALERT";
+
+/// Environment variable holding the transition: `firing` or `resolved`.
+pub const ENV_STATE: &str = "SYSTEM_MONITOR_ALERT_STATE";
+
+/// Environment variable holding the watched metric, e.g. `cpu`.
+pub const ENV_METRIC: &str = "SYSTEM_MONITOR_ALERT_METRIC";
+
+/// Environment variable holding the rule, e.g. `cpu > 90% for 30s`.
+pub const ENV_CONDITION: &str = "SYSTEM_MONITOR_ALERT_CONDITION";
+
+/// Environment variable holding the threshold, in percent.
+pub const ENV_THRESHOLD: &str = "SYSTEM_MONITOR_ALERT_THRESHOLD";
+
+/// Environment variable holding the value that caused the change, in
+/// percent.
+pub const ENV_VALUE: &str = "SYSTEM_MONITOR_ALERT_VALUE";
+
+/// Environment variable holding the ISO-8601 time of the change.
+pub const ENV_TIMESTAMP: &str = "SYSTEM_MONITOR_ALERT_TIMESTAMP";
+
+/// Largest webhook response head read, in bytes.
+const MAX_RESPONSE_SIZE: u64 = 8 * 1024;
+
+/// Interval at which a running command is checked for completion.
+const EXEC_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// `http://` URL a webhook is posted to.
+///
+/// HTTPS is not supported; use a local relay for remote endpoints.
+///
+/// # Example
+/// ```
+/// use system_monitor::notify::WebhookUrl;
+///
+/// let url: WebhookUrl = "http://localhost:9000/hooks/alert".parse().unwrap();
+/// assert_eq!((url.host(), url.port(), url.path()), ("localhost", 9000, "/hooks/alert"));
+/// assert!("https://example.com/".parse::<WebhookUrl>().is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUrl {
+    host: String,
+    port: u16,
+    path: String,
+}
+
+impl WebhookUrl {
+    /// Returns the host name or address, without brackets for IPv6.
+    pub fn host(&self) -> &str {
+        &self.host
+    }
+
+    /// Returns the port, 80 unless given.
+    pub fn port(&self) -> u16 {
+        self.port
+    }
+
+    /// Returns the path and query, `/` unless given.
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    /// Returns the `Host` header value.
+    fn authority(&self) -> String {
+        let host = if self.host.contains(':') {
+            format!("[{}]", self.host)
+        } else {
+            self.host.clone()
+        };
+        if self.port == 80 {
+            host
+        } else {
+            format!("{}:{}", host, self.port)
+        }
+    }
+}
+
+impl FromStr for WebhookUrl {
+    type Err = WebhookUrlError;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || WebhookUrlError(text.to_string());
+        let scheme_end = text.find("://").ok_or_else(invalid)?;
+        if !text[..scheme_end].eq_ignore_ascii_case("http") {
+            return Err(invalid());
+        }
+        let rest = &text[scheme_end + 3..];
+        let (authority, path) = match rest.find('/') {
+            Some(i) => (&rest[..i], &rest[i..]),
+            None => (rest, "/"),
+        };
+        let (host, port) = match authority.strip_prefix('[') {
+            // IPv6 literal such as [::1]:8080
+            Some(bracketed) => {
+                let (host, port) = bracketed.split_once(']').ok_or_else(invalid)?;
+                (host, port.strip_prefix(':'))
+            }
+            None => match authority.split_once(':') {
+                Some((host, port)) => (host, Some(port)),
+                None => (authority, None),
+            },
+        };
+        let port = match port {
+            Some(port) => port.parse().map_err(|_| invalid())?,
+            None => 80,
+        };
+        if host.is_empty() || path.chars().any(char::is_whitespace) {
+            return Err(invalid());
+        }
+        Ok(Self {
+            host: host.to_string(),
+            port,
+            path: path.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for WebhookUrl {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "http://{}{}", self.authority(), self.path)
+    }
+}
+
+/// Error parsing a [`WebhookUrl`]; holds the rejected text.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WebhookUrlError(pub String);
+
+impl fmt::Display for WebhookUrlError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "invalid webhook URL '{}' (expected http://HOST[:PORT][/PATH])",
+            self.0
+        )
+    }
+}
+
+impl Error for WebhookUrlError {}
+
+/// What an action does with a notification.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ActionKind {
+    /// Runs the program and arguments, not through a shell
+    Exec(Vec<String>),
+    /// Appends a line to the file, creating it if needed
+    Log(PathBuf),
+    /// Posts a JSON payload to the URL
+    Webhook(WebhookUrl),
+}
+
+impl fmt::Display for ActionKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionKind::Exec(command) => write!(f, "exec '{}'", command.join(" ")),
+            ActionKind::Log(path) => write!(f, "log {}", path.display()),
+            ActionKind::Webhook(url) => write!(f, "webhook {}", url),
+        }
+    }
+}
+
+/// Notification action with its delivery policy.
+///
+/// # Fields
+/// * `kind` - What the action does
+/// * `timeout` - Time allowed for one attempt; a command still running is
+///   killed. Appending to a log file is not bounded.
+/// * `retries` - Attempts made after the first one fails
+/// * `retry_delay` - Delay before the first retry, doubled before each
+///   further one
+/// * `cooldown` - Time after a firing notification during which the same
+///   alert firing again is not notified (nor its resolution)
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use system_monitor::notify::{ActionKind, AlertAction};
+///
+/// let action = AlertAction::new(ActionKind::Log("alerts.log".into()))
+///     .with_retries(0)
+///     .with_cooldown(Duration::from_secs(60));
+/// assert_eq!(action.to_string(), "log alerts.log");
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AlertAction {
+    pub kind: ActionKind,
+    pub timeout: Duration,
+    pub retries: u32,
+    pub retry_delay: Duration,
+    pub cooldown: Duration,
+}
+
+impl AlertAction {
+    /// Creates an action with the default delivery policy.
+    pub fn new(kind: ActionKind) -> Self {
+        Self {
+            kind,
+            timeout: DEFAULT_TIMEOUT,
+            retries: DEFAULT_RETRIES,
+            retry_delay: DEFAULT_RETRY_DELAY,
+            cooldown: DEFAULT_COOLDOWN,
+        }
+    }
+
+    /// Sets the time allowed for one attempt.
+    pub fn with_timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = timeout;
+        self
+    }
+
+    /// Sets the number of attempts made after the first one fails.
+    pub fn with_retries(mut self, retries: u32) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Sets the delay before the first retry.
+    pub fn with_retry_delay(mut self, delay: Duration) -> Self {
+        self.retry_delay = delay;
+        self
+    }
+
+    /// Sets the time during which an identical notification is suppressed.
+    pub fn with_cooldown(mut self, cooldown: Duration) -> Self {
+        self.cooldown = cooldown;
+        self
+    }
+
+    /// Makes a single attempt at notifying `event`.
+    ///
+    /// # Errors
+    /// * `NotifyError` - The command failed or timed out, the file could
+    ///   not be written, or the webhook was unreachable or rejected the
+    ///   payload
+    pub fn run(&self, event: &AlertEvent) -> Result<(), NotifyError> {
+        match &self.kind {
+            ActionKind::Exec(command) => exec(command, event, self.timeout),
+            ActionKind::Log(path) => {
+                let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                file.write_all(log_line(event).as_bytes())?;
+                Ok(())
+            }
+            ActionKind::Webhook(url) => post(url, &Payload::new(event), self.timeout),
+        }
+    }
+
+    /// Notifies `event`, retrying failed attempts.
+    ///
+    /// # Errors
+    /// * `DeliveryFailure` - Every attempt failed; holds the last error
+    pub fn deliver(&self, event: &AlertEvent) -> Result<(), DeliveryFailure> {
+        let mut delay = self.retry_delay;
+        let mut attempts = 0;
+        loop {
+            attempts += 1;
+            match self.run(event) {
+                Ok(()) => return Ok(()),
+                Err(_) if attempts <= self.retries => {
+                    thread::sleep(delay);
+                    delay = delay.saturating_mul(2);
+                }
+                Err(err) => {
+                    return Err(DeliveryFailure {
+                        action: self.kind.to_string(),
+                        alert: event.rule.name.clone(),
+                        transition: event.transition,
+                        timestamp: event.timestamp,
+                        attempts,
+                        error: err.to_string(),
+                    })
+                }
+            }
+        }
+    }
+}
+
+impl fmt::Display for AlertAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.kind.fmt(f)
+    }
+}
+
+/// Reason a single attempt failed.
+#[derive(Debug)]
+pub enum NotifyError {
+    /// The command, file or connection could not be used
+    Io(io::Error),
+    /// The attempt did not finish within the timeout
+    TimedOut,
+    /// The exec command list is empty
+    EmptyCommand,
+    /// The command exited unsuccessfully
+    Exit(ExitStatus),
+    /// The webhook answered with a non-2xx status line
+    Status(String),
+    /// The webhook answered with something other than HTTP
+    InvalidResponse,
+}
+
+impl From<io::Error> for NotifyError {
+    fn from(err: io::Error) -> Self {
+        match err.kind() {
+            io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => NotifyError::TimedOut,
+            _ => NotifyError::Io(err),
+        }
+    }
+}
+
+impl fmt::Display for NotifyError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            NotifyError::Io(err) => err.fmt(f),
+            NotifyError::TimedOut => f.write_str("timed out"),
+            NotifyError::EmptyCommand => f.write_str("no command given"),
+            NotifyError::Exit(status) => write!(f, "command failed ({})", status),
+            NotifyError::Status(status) => write!(f, "webhook answered '{}'", status),
+            NotifyError::InvalidResponse => f.write_str("webhook answered with invalid HTTP"),
+        }
+    }
+}
+
+impl Error for NotifyError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            NotifyError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+/// Notification that could not be delivered.
+///
+/// # Fields
+/// * `action` - Description of the action, e.g. `webhook http://...`
+/// * `alert` - Name of the alert
+/// * `transition` - Transition that was notified
+/// * `timestamp` - Time of the transition
+/// * `attempts` - Number of attempts made
+/// * `error` - Error of the last attempt
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DeliveryFailure {
+    pub action: String,
+    pub alert: String,
+    pub transition: Transition,
+    pub timestamp: SystemTime,
+    pub attempts: u32,
+    pub error: String,
+}
+
+impl fmt::Display for DeliveryFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}: cannot notify that '{}' is {}: {} (after {} attempts)",
+            self.action,
+            self.alert,
+            self.transition.name(),
+            self.error,
+            self.attempts
+        )
+    }
+}
+
+/// JSON body posted to webhooks.
+///
+/// # Fields
+/// * `alert` - Name of the alert
+/// * `state` - `firing` or `resolved`
+/// * `metric` - Watched metric, e.g. `cpu`
+/// * `condition` - Rule, e.g. `cpu > 90% for 30s`
+/// * `threshold` - Threshold, in percent
+/// * `value` - Value that caused the change, in percent
+/// * `timestamp` - ISO-8601 time of the change
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct Payload {
+    pub alert: String,
+    pub state: &'static str,
+    pub metric: &'static str,
+    pub condition: String,
+    pub threshold: f32,
+    pub value: f32,
+    pub timestamp: String,
+}
+
+impl Payload {
+    /// Builds the payload of `event`.
+    pub fn new(event: &AlertEvent) -> Self {
+        Self {
+            alert: event.rule.name.clone(),
+            state: event.transition.name(),
+            metric: event.rule.metric.name(),
+            condition: event.rule.condition(),
+            threshold: event.rule.threshold,
+            value: event.value,
+            timestamp: iso8601(event.timestamp),
+        }
+    }
+}
+
+/// Returns the environment variables describing `event` to an exec
+/// action.
+pub fn environment(event: &AlertEvent) -> Vec<(&'static str, String)> {
+    vec![
+        (ENV_ALERT, event.rule.name.clone()),
+        (ENV_STATE, event.transition.name().to_string()),
+        (ENV_METRIC, event.rule.metric.name().to_string()),
+        (ENV_CONDITION, event.rule.condition()),
+        (ENV_THRESHOLD, event.rule.threshold.to_string()),
+        (ENV_VALUE, format!("{:.1}", event.value)),
+        (ENV_TIMESTAMP, iso8601(event.timestamp)),
+    ]
+}
+
+/// Formats the line a log action appends for `event`, e.g.
+/// `2024-05-01T12:00:00.000Z firing cpu saturated: 95.0% (cpu > 90% for 30s)`.
+pub fn log_line(event: &AlertEvent) -> String {
+    format!(
+        "{} {} {}: {:.1}% ({})\n",
+        iso8601(event.timestamp),
+        event.transition.name(),
+        event.rule.name,
+        event.value,
+        event.rule.condition()
+    )
+}
+
+/// Runs `command` with the details of `event` in its environment.
+fn exec(command: &[String], event: &AlertEvent, timeout: Duration) -> Result<(), NotifyError> {
+    let (program, args) = command.split_first().ok_or(NotifyError::EmptyCommand)?;
+    let mut child = Command::new(program)
+        .args(args)
+        .envs(environment(event))
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .spawn()?;
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return if status.success() {
+                Ok(())
+            } else {
+                Err(NotifyError::Exit(status))
+            };
+        }
+        if Instant::now() >= deadline {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(NotifyError::TimedOut);
+        }
+        thread::sleep(EXEC_POLL_INTERVAL);
+    }
+}
+
+/// Posts `payload` to `url` and checks for a 2xx status.
+fn post(url: &WebhookUrl, payload: &Payload, timeout: Duration) -> Result<(), NotifyError> {
+    // Serializing plain data with string keys cannot fail
+    let body = serde_json::to_string(payload).unwrap_or_default();
+    let mut last_error = io::Error::new(io::ErrorKind::NotFound, "host not found");
+    let mut stream = None;
+    for addr in (url.host(), url.port()).to_socket_addrs()? {
+        match TcpStream::connect_timeout(&addr, timeout) {
+            Ok(connected) => {
+                stream = Some(connected);
+                break;
+            }
+            Err(err) => last_error = err,
+        }
+    }
+    let mut stream = stream.ok_or(last_error)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    write!(
+        stream,
+        "POST {} HTTP/1.1\r\nHost: {}\r\nUser-Agent: system-monitor/{}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+        url.path(),
+        url.authority(),
+        env!("CARGO_PKG_VERSION"),
+        body.len(),
+        body
+    )?;
+    stream.flush()?;
+
+    let mut status_line = String::new();
+    BufReader::new((&stream).take(MAX_RESPONSE_SIZE)).read_line(&mut status_line)?;
+    let mut parts = status_line.trim_end().splitn(2, ' ');
+    match (parts.next(), parts.next()) {
+        (Some(version), Some(status)) if version.starts_with("HTTP/") => {
+            if status.starts_with('2') {
+                Ok(())
+            } else {
+                Err(NotifyError::Status(status.to_string()))
+            }
+        }
+        _ => Err(NotifyError::InvalidResponse),
+    }
+}
+
+/// Delivers alert events to a set of actions, applying their cooldowns.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use system_monitor::alert::{AlertEvent, Transition};
+/// use system_monitor::notify::{ActionKind, AlertAction, Notifier};
+///
+/// let path = std::env::temp_dir().join(format!("notify-doc-{}.log", std::process::id()));
+/// let mut notifier = Notifier::new(vec![AlertAction::new(ActionKind::Log(path.clone()))]);
+/// let event = |seconds| AlertEvent {
+///     rule: "cpu > 90%".parse().unwrap(),
+///     transition: Transition::Firing,
+///     value: 95.0,
+///     timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+/// };
+/// assert!(notifier.notify(&event(0)).is_empty());
+/// // Within the five minute cooldown: not written again
+/// notifier.notify(&event(60));
+/// let log = std::fs::read_to_string(&path).unwrap();
+/// std::fs::remove_file(&path).unwrap();
+/// assert_eq!(log.lines().count(), 1);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Notifier {
+    actions: Vec<AlertAction>,
+    cooldowns: HashMap<(usize, String), Cooldown>,
+}
+
+/// Firing notifications of one alert to one action.
+///
+/// # Fields
+/// * `sent` - Time of the last firing that was notified
+/// * `suppressed` - Whether the latest firing fell within the cooldown and
+///   was not notified, in which case its resolution is not either
+#[derive(Debug, Clone, Copy)]
+struct Cooldown {
+    sent: SystemTime,
+    suppressed: bool,
+}
+
+impl Notifier {
+    /// Creates a notifier for `actions`, in order.
+    pub fn new(actions: Vec<AlertAction>) -> Self {
+        Self {
+            actions,
+            cooldowns: HashMap::new(),
+        }
+    }
+
+    /// Returns the actions, in order.
+    pub fn actions(&self) -> &[AlertAction] {
+        &self.actions
+    }
+
+    /// Runs every action whose cooldown allows it for `event`.
+    ///
+    /// Pending transitions are ignored. A firing alert is notified unless
+    /// the action was notified of it firing less than its cooldown ago; a
+    /// resolved alert is notified unless its firing was suppressed that
+    /// way. Blocks until every action succeeded or ran out of retries.
+    ///
+    /// # Returns
+    /// * `Vec<DeliveryFailure>` - The actions that failed, in order
+    pub fn notify(&mut self, event: &AlertEvent) -> Vec<DeliveryFailure> {
+        if event.transition == Transition::Pending {
+            return Vec::new();
+        }
+        let mut failures = Vec::new();
+        for (index, action) in self.actions.iter().enumerate() {
+            let key = (index, event.rule.name.clone());
+            let cooldown = self.cooldowns.get(&key).copied();
+            let send = match event.transition {
+                Transition::Firing => {
+                    let cooling = cooldown.is_some_and(|cooldown| {
+                        event
+                            .timestamp
+                            .duration_since(cooldown.sent)
+                            .unwrap_or_default()
+                            < action.cooldown
+                    });
+                    let cooldown = match cooldown {
+                        Some(cooldown) if cooling => Cooldown {
+                            suppressed: true,
+                            ..cooldown
+                        },
+                        _ => Cooldown {
+                            sent: event.timestamp,
+                            suppressed: false,
+                        },
+                    };
+                    self.cooldowns.insert(key, cooldown);
+                    !cooling
+                }
+                _ => !cooldown.is_some_and(|cooldown| cooldown.suppressed),
+            };
+            if !send {
+                continue;
+            }
+            if let Err(failure) = action.deliver(event) {
+                failures.push(failure);
+            }
+        }
+        failures
+    }
+}
+
+/// Runs a [`Notifier`] on its own thread.
+///
+/// Events are queued and delivered in order. Dropping the notifier stops
+/// the thread once the queue is empty, without waiting for it; call
+/// [`BackgroundNotifier::finish`] to wait.
+#[derive(Debug)]
+pub struct BackgroundNotifier {
+    events: Option<Sender<AlertEvent>>,
+    failures: Receiver<DeliveryFailure>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl BackgroundNotifier {
+    /// Starts delivering the events sent with
+    /// [`BackgroundNotifier::send`] to `notifier`.
+    ///
+    /// # Arguments
+    /// * `notifier` - Actions to run
+    /// * `on_failure` - Called on the notifier thread for every failed
+    ///   delivery, e.g. to log it; failures are also kept for
+    ///   [`BackgroundNotifier::try_failures`]
+    ///
+    /// # Returns
+    /// * `io::Result<Self>` - The running notifier, or the error raised
+    ///   when the thread could not be spawned
+    pub fn spawn(
+        mut notifier: Notifier,
+        mut on_failure: impl FnMut(&DeliveryFailure) + Send + 'static,
+    ) -> io::Result<Self> {
+        let (events, events_rx) = mpsc::channel::<AlertEvent>();
+        let (failures_tx, failures) = mpsc::channel();
+        let handle = thread::Builder::new()
+            .name("alert-notifier".to_string())
+            .spawn(move || {
+                for event in events_rx {
+                    for failure in notifier.notify(&event) {
+                        on_failure(&failure);
+                        let _ = failures_tx.send(failure);
+                    }
+                }
+            })?;
+        Ok(Self {
+            events: Some(events),
+            failures,
+            handle: Some(handle),
+        })
+    }
+
+    /// Queues `event` for delivery.
+    pub fn send(&self, event: AlertEvent) {
+        if let Some(events) = &self.events {
+            // The thread only exits once the sender is dropped
+            let _ = events.send(event);
+        }
+    }
+
+    /// Iterates over the failed deliveries reported so far, without
+    /// blocking.
+    pub fn try_failures(&self) -> impl Iterator<Item = DeliveryFailure> + '_ {
+        self.failures.try_iter()
+    }
+
+    /// Waits until every queued event has been delivered.
+    ///
+    /// # Returns
+    /// * `Vec<DeliveryFailure>` - Failed deliveries not yet taken with
+    ///   [`BackgroundNotifier::try_failures`]
+    pub fn finish(mut self) -> Vec<DeliveryFailure> {
+        self.events.take();
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+        self.failures.try_iter().collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::alert::AlertRule;
+    use std::net::TcpListener;
+    use std::time::UNIX_EPOCH;
+
+    fn event(transition: Transition, seconds: u64) -> AlertEvent {
+        let rule: AlertRule = "cpu > 90% for 30s".parse().unwrap();
+        AlertEvent {
+            rule: rule.named("cpu saturated"),
+            transition,
+            value: 95.0,
+            timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+        }
+    }
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "system-monitor-notify-{}-{}",
+            std::process::id(),
+            name
+        ))
+    }
+
+    /// Serves one canned status line per connection and returns the
+    /// received requests.
+    fn stand_in(statuses: &[&'static str]) -> (WebhookUrl, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/hook", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let statuses = statuses.to_vec();
+        let handle = thread::spawn(move || {
+            statuses
+                .into_iter()
+                .map(|status| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut reader = BufReader::new(stream.try_clone().unwrap());
+                    let mut request = String::new();
+                    let mut length = 0;
+                    loop {
+                        let mut line = String::new();
+                        reader.read_line(&mut line).unwrap();
+                        if let Some(value) = line.strip_prefix("Content-Length: ") {
+                            length = value.trim().parse().unwrap();
+                        }
+                        request.push_str(&line);
+                        if line == "\r\n" {
+                            break;
+                        }
+                    }
+                    let mut body = vec![0; length];
+                    reader.read_exact(&mut body).unwrap();
+                    request.push_str(&String::from_utf8(body).unwrap());
+                    write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+                    request
+                })
+                .collect()
+        });
+        (url, handle)
+    }
+
+    #[test]
+    fn test_webhook_url_parsing() {
+        let url: WebhookUrl = "http://example.com".parse().unwrap();
+        assert_eq!(
+            (url.host(), url.port(), url.path()),
+            ("example.com", 80, "/")
+        );
+        assert_eq!(url.to_string(), "http://example.com/");
+
+        let url: WebhookUrl = "HTTP://[::1]:8080/a?b=c".parse().unwrap();
+        assert_eq!(
+            (url.host(), url.port(), url.path()),
+            ("::1", 8080, "/a?b=c")
+        );
+        assert_eq!(url.to_string(), "http://[::1]:8080/a?b=c");
+
+        for text in [
+            "example.com",
+            "https://example.com/",
+            "http://",
+            "http://host:port/",
+            "http://host/a b",
+        ] {
+            assert_eq!(
+                text.parse::<WebhookUrl>(),
+                Err(WebhookUrlError(text.to_string()))
+            );
+        }
+    }
+
+    #[test]
+    fn test_payload_and_environment() {
+        let event = event(Transition::Firing, 1_700_000_000);
+        let json = serde_json::to_string(&Payload::new(&event)).unwrap();
+        assert_eq!(
+            json,
+            "{\"alert\":\"cpu saturated\",\"state\":\"firing\",\"metric\":\"cpu\",\
+             \"condition\":\"cpu > 90% for 30s\",\"threshold\":90.0,\"value\":95.0,\
+             \"timestamp\":\"2023-11-14T22:13:20.000Z\"}"
+        );
+        let env = environment(&event);
+        assert!(env.contains(&(ENV_ALERT, "cpu saturated".to_string())));
+        assert!(env.contains(&(ENV_STATE, "firing".to_string())));
+        assert!(env.contains(&(ENV_VALUE, "95.0".to_string())));
+        assert_eq!(
+            log_line(&event),
+            "2023-11-14T22:13:20.000Z firing cpu saturated: 95.0% (cpu > 90% for 30s)\n"
+        );
+    }
+
+    #[test]
+    fn test_webhook_posts_payload() {
+        let (url, server) = stand_in(&["204 No Content"]);
+        let action = AlertAction::new(ActionKind::Webhook(url));
+        action.deliver(&event(Transition::Resolved, 0)).unwrap();
+
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("POST /hook HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Content-Type: application/json\r\n"));
+        assert!(requests[0].ends_with("\"timestamp\":\"1970-01-01T00:00:00.000Z\"}"));
+        assert!(requests[0].contains("\"state\":\"resolved\""));
+    }
+
+    #[test]
+    fn test_webhook_retries_until_accepted() {
+        let (url, server) = stand_in(&["500 Internal Server Error", "503 Unavailable", "200 OK"]);
+        let action = AlertAction::new(ActionKind::Webhook(url))
+            .with_retries(2)
+            .with_retry_delay(Duration::from_millis(1));
+        assert_eq!(action.deliver(&event(Transition::Firing, 0)), Ok(()));
+        assert_eq!(server.join().unwrap().len(), 3);
+    }
+
+    #[test]
+    fn test_webhook_failure_after_retries() {
+        let (url, server) = stand_in(&["500 Internal Server Error", "502 Bad Gateway"]);
+        let action = AlertAction::new(ActionKind::Webhook(url.clone()))
+            .with_retries(1)
+            .with_retry_delay(Duration::from_millis(1));
+        let failure = action.deliver(&event(Transition::Firing, 0)).unwrap_err();
+        server.join().unwrap();
+        assert_eq!(failure.attempts, 2);
+        assert_eq!(failure.error, "webhook answered '502 Bad Gateway'");
+        assert_eq!(
+            failure.to_string(),
+            format!(
+                "webhook {}: cannot notify that 'cpu saturated' is firing: \
+                 webhook answered '502 Bad Gateway' (after 2 attempts)",
+                url
+            )
+        );
+    }
+
+    #[test]
+    fn test_webhook_times_out() {
+        // Accepts the connection but never answers
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap())
+            .parse()
+            .unwrap();
+        let action = AlertAction::new(ActionKind::Webhook(url))
+            .with_timeout(Duration::from_millis(50))
+            .with_retries(0);
+        let failure = action.deliver(&event(Transition::Firing, 0)).unwrap_err();
+        assert_eq!(failure.error, "timed out");
+        drop(listener);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_passes_environment() {
+        let path = temp_path("exec");
+        let script = format!(
+            "echo \"$SYSTEM_MONITOR_ALERT|$SYSTEM_MONITOR_ALERT_STATE|$SYSTEM_MONITOR_ALERT_VALUE\" > {}",
+            path.display()
+        );
+        let action = AlertAction::new(ActionKind::Exec(vec![
+            "sh".to_string(),
+            "-c".to_string(),
+            script,
+        ]));
+        action.run(&event(Transition::Firing, 0)).unwrap();
+        let output = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(output, "cpu saturated|firing|95.0\n");
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_exec_failures() {
+        let run = |command: &[&str], timeout| {
+            AlertAction::new(ActionKind::Exec(
+                command.iter().map(|arg| arg.to_string()).collect(),
+            ))
+            .with_timeout(timeout)
+            .run(&event(Transition::Firing, 0))
+        };
+        let long = Duration::from_secs(10);
+        assert!(matches!(run(&["false"], long), Err(NotifyError::Exit(_))));
+        assert!(matches!(run(&[], long), Err(NotifyError::EmptyCommand)));
+        assert!(matches!(
+            run(&["system-monitor-no-such-command"], long),
+            Err(NotifyError::Io(_))
+        ));
+        assert!(matches!(
+            run(&["sleep", "5"], Duration::from_millis(50)),
+            Err(NotifyError::TimedOut)
+        ));
+    }
+
+    #[test]
+    fn test_cooldown_suppresses_repeats() {
+        let path = temp_path("cooldown");
+        let action =
+            AlertAction::new(ActionKind::Log(path.clone())).with_cooldown(Duration::from_secs(60));
+        let mut notifier = Notifier::new(vec![action]);
+        for (transition, seconds) in [
+            (Transition::Pending, 0),
+            (Transition::Firing, 30),
+            (Transition::Resolved, 40),
+            (Transition::Firing, 50),
+            (Transition::Resolved, 60),
+            (Transition::Firing, 90),
+        ] {
+            assert!(notifier.notify(&event(transition, seconds)).is_empty());
+        }
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let states: Vec<_> = log
+            .lines()
+            .map(|line| line.split(' ').nth(1).unwrap())
+            .collect();
+        assert_eq!(states, vec!["firing", "resolved", "firing"]);
+    }
+
+    #[test]
+    fn test_resolution_follows_its_firing() {
+        let path = temp_path("cooldown-resolved");
+        let action =
+            AlertAction::new(ActionKind::Log(path.clone())).with_cooldown(Duration::from_secs(60));
+        let mut notifier = Notifier::new(vec![action]);
+        for (transition, seconds) in [
+            (Transition::Firing, 0),
+            (Transition::Resolved, 55),
+            (Transition::Firing, 61),
+            (Transition::Resolved, 100),
+        ] {
+            assert!(notifier.notify(&event(transition, seconds)).is_empty());
+        }
+        let log = std::fs::read_to_string(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let states: Vec<_> = log
+            .lines()
+            .map(|line| line.split(' ').nth(1).unwrap())
+            .collect();
+        assert_eq!(states, vec!["firing", "resolved", "firing", "resolved"]);
+    }
+
+    #[test]
+    fn test_background_notifier_reports_failures() {
+        let path = temp_path("missing-dir").join("alerts.log");
+        let action = AlertAction::new(ActionKind::Log(path)).with_retries(0);
+        let (reported_tx, reported) = mpsc::channel();
+        let notifier = BackgroundNotifier::spawn(Notifier::new(vec![action]), move |failure| {
+            let _ = reported_tx.send(failure.clone());
+        })
+        .unwrap();
+        notifier.send(event(Transition::Firing, 0));
+        let failures = notifier.finish();
+        assert_eq!(failures.len(), 1);
+        assert_eq!(failures[0].attempts, 1);
+        assert_eq!(reported.try_recv(), Ok(failures[0].clone()));
+    }
+}
@@ -0,0 +1,256 @@
+//! Pressure Stall Information (PSI).
+//!
+//! CPU and memory percentages tell how busy a resource is, not whether
+//! tasks are waiting for it. Linux 4.20 and later report the share of time
+//! in which tasks were stalled on the CPU, memory or I/O in
+//! `/proc/pressure/{cpu,memory,io}`:
+//!
+//! ```text
+//! some avg10=4.20 avg60=2.10 avg300=0.18 total=10367810
+//! full avg10=1.05 avg60=0.55 avg300=0.08 total=7591462
+//! ```
+//!
+//! `some` is the share of time in which at least one task was stalled,
+//! `full` the share in which all non-idle tasks were stalled at once. The
+//! averages cover the last 10, 60 and 300 seconds; `total` is the
+//! cumulative stall time in microseconds.
+
+use std::fs;
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// Resource whose stalls are reported.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Resource {
+    /// Tasks waiting for a CPU
+    Cpu,
+    /// Tasks waiting for memory (reclaim, swap-in, refaults)
+    Memory,
+    /// Tasks waiting for block I/O
+    Io,
+}
+
+impl Resource {
+    /// All resources in display order.
+    pub const ALL: [Resource; 3] = [Resource::Cpu, Resource::Memory, Resource::Io];
+
+    /// Returns the lowercase identifier, which is also the file name under
+    /// `/proc/pressure`.
+    pub fn name(self) -> &'static str {
+        match self {
+            Resource::Cpu => "cpu",
+            Resource::Memory => "memory",
+            Resource::Io => "io",
+        }
+    }
+}
+
+/// Stall averages and total of one line of a pressure file.
+///
+/// # Fields
+/// * `avg10` - Percentage of time stalled over the last 10 seconds
+/// * `avg60` - Percentage of time stalled over the last 60 seconds
+/// * `avg300` - Percentage of time stalled over the last 300 seconds
+/// * `total_usec` - Cumulative stall time in microseconds
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct StallStats {
+    pub avg10: f32,
+    pub avg60: f32,
+    pub avg300: f32,
+    pub total_usec: u64,
+}
+
+/// Pressure of one resource.
+///
+/// # Fields
+/// * `some` - Time in which at least one task was stalled
+/// * `full` - Time in which all non-idle tasks were stalled, `None` on
+///   kernels that do not report it (CPU before Linux 5.13)
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ResourcePressure {
+    pub some: StallStats,
+    pub full: Option<StallStats>,
+}
+
+/// Pressure of every resource the kernel reports.
+///
+/// # Fields
+/// * `cpu` - CPU pressure, if reported
+/// * `memory` - Memory pressure, if reported
+/// * `io` - I/O pressure, if reported
+///
+/// # Example
+/// ```no_run
+/// use std::path::Path;
+/// use system_monitor::pressure::{Pressure, Resource};
+/// use system_monitor::source::DEFAULT_PROC_ROOT;
+///
+/// if let Some(pressure) = Pressure::read(Path::new(DEFAULT_PROC_ROOT)) {
+///     if let Some(io) = pressure.get(Resource::Io) {
+///         println!("I/O stalled {:.1}% of the last 10s", io.some.avg10);
+///     }
+/// }
+/// ```
+#[derive(Debug, Default, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Pressure {
+    pub cpu: Option<ResourcePressure>,
+    pub memory: Option<ResourcePressure>,
+    pub io: Option<ResourcePressure>,
+}
+
+impl Pressure {
+    /// Reads `pressure/{cpu,memory,io}` under `proc_root`.
+    ///
+    /// A file that is missing, unreadable (PSI disabled with `psi=0`) or
+    /// malformed leaves its resource out.
+    ///
+    /// # Returns
+    /// * `Option<Pressure>` - The pressure, or `None` if no resource could
+    ///   be read, e.g. on kernels without PSI or other platforms
+    pub fn read(proc_root: &Path) -> Option<Self> {
+        let dir = proc_root.join("pressure");
+        let read = |resource: Resource| {
+            fs::read_to_string(dir.join(resource.name()))
+                .ok()
+                .and_then(|text| parse_pressure(&text))
+        };
+        let pressure = Self {
+            cpu: read(Resource::Cpu),
+            memory: read(Resource::Memory),
+            io: read(Resource::Io),
+        };
+        (pressure != Self::default()).then_some(pressure)
+    }
+
+    /// Returns the pressure of one resource, if reported.
+    pub fn get(&self, resource: Resource) -> Option<&ResourcePressure> {
+        match resource {
+            Resource::Cpu => self.cpu.as_ref(),
+            Resource::Memory => self.memory.as_ref(),
+            Resource::Io => self.io.as_ref(),
+        }
+    }
+}
+
+/// Parses the contents of a pressure file.
+///
+/// Unknown lines and fields are ignored so newer kernels keep working.
+///
+/// # Returns
+/// * `Option<ResourcePressure>` - The pressure, or `None` if the text has
+///   no well-formed `some` line
+///
+/// # Example
+/// ```
+/// use system_monitor::pressure::parse_pressure;
+///
+/// let pressure = parse_pressure("some avg10=1.50 avg60=0.75 avg300=0.25 total=4200\n").unwrap();
+/// assert_eq!(pressure.some.avg10, 1.5);
+/// assert_eq!(pressure.some.total_usec, 4200);
+/// assert_eq!(pressure.full, None);
+/// ```
+pub fn parse_pressure(text: &str) -> Option<ResourcePressure> {
+    let mut some = None;
+    let mut full = None;
+    for line in text.lines() {
+        let mut fields = line.split_whitespace();
+        let target = match fields.next() {
+            Some("some") => &mut some,
+            Some("full") => &mut full,
+            _ => continue,
+        };
+        *target = Some(parse_stall_stats(fields)?);
+    }
+    Some(ResourcePressure { some: some?, full })
+}
+
+/// Parses the `key=value` fields of one pressure line.
+///
+/// Returns `None` if a known field is not a number or one is missing.
+fn parse_stall_stats<'a>(fields: impl Iterator<Item = &'a str>) -> Option<StallStats> {
+    let (mut avg10, mut avg60, mut avg300, mut total_usec) = (None, None, None, None);
+    for field in fields {
+        let Some((key, value)) = field.split_once('=') else {
+            continue;
+        };
+        match key {
+            "avg10" => avg10 = Some(value.parse().ok()?),
+            "avg60" => avg60 = Some(value.parse().ok()?),
+            "avg300" => avg300 = Some(value.parse().ok()?),
+            "total" => total_usec = Some(value.parse().ok()?),
+            _ => {}
+        }
+    }
+    Some(StallStats {
+        avg10: avg10?,
+        avg60: avg60?,
+        avg300: avg300?,
+        total_usec: total_usec?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::source::fixture_proc_root;
+
+    #[test]
+    fn test_read_fixture_files() {
+        let pressure = Pressure::read(&fixture_proc_root()).unwrap();
+
+        let cpu = pressure.get(Resource::Cpu).unwrap();
+        assert_eq!(cpu.some.avg10, 12.5);
+        assert_eq!(cpu.some.avg300, 3.1);
+        assert_eq!(cpu.some.total_usec, 181_637_552);
+
+        let io = pressure.get(Resource::Io).unwrap();
+        assert_eq!(
+            io.full,
+            Some(StallStats {
+                avg10: 25.75,
+                avg60: 18.0,
+                avg300: 7.5,
+                total_usec: 810_000_000,
+            })
+        );
+        assert_eq!(pressure.memory.unwrap().some.avg60, 2.1);
+    }
+
+    #[test]
+    fn test_missing_pressure_directory() {
+        assert_eq!(Pressure::read(Path::new("/nonexistent/proc")), None);
+    }
+
+    #[test]
+    fn test_cpu_without_full_line() {
+        // Kernels before 5.13 only report `some` for the CPU
+        let pressure = parse_pressure("some avg10=0.00 avg60=0.10 avg300=0.20 total=42\n").unwrap();
+        assert_eq!(pressure.some.avg60, 0.1);
+        assert_eq!(pressure.full, None);
+    }
+
+    #[test]
+    fn test_unknown_fields_and_lines_are_ignored() {
+        let text = "some avg10=1.00 avg60=2.00 avg300=3.00 total=4 extra=5\n\
+                    partial avg10=9.00\n\
+                    full avg10=0.50 avg60=0.60 avg300=0.70 total=8\n";
+        let pressure = parse_pressure(text).unwrap();
+        assert_eq!(pressure.some.total_usec, 4);
+        assert_eq!(pressure.full.unwrap().avg300, 0.7);
+    }
+
+    #[test]
+    fn test_malformed_pressure() {
+        assert_eq!(parse_pressure(""), None);
+        assert_eq!(
+            parse_pressure("full avg10=0.00 avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+        assert_eq!(
+            parse_pressure("some avg10=high avg60=0.00 avg300=0.00 total=0\n"),
+            None
+        );
+        assert_eq!(parse_pressure("some avg10=0.00 avg60=0.00 total=0\n"), None);
+    }
+}
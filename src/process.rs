@@ -0,0 +1,227 @@
+//! Process listing and sorting.
+//!
+//! Provides the [`ProcessInfo`] record collected for every running process
+//! and the column-based ordering used by the process table.
+
+use std::cmp::Ordering;
+
+use serde::{Deserialize, Serialize};
+
+/// Information about a single running process.
+///
+/// # Fields
+/// * `pid` - Process identifier
+/// * `name` - Executable name
+/// * `user` - Name of the owning user, if it could be resolved
+/// * `cpu_usage` - CPU usage as a percentage of one core (may exceed 100.0
+///   for multi-threaded processes)
+/// * `memory` - Resident set size in bytes
+/// * `state` - Scheduler state (e.g. `Runnable`, `Sleeping`)
+/// * `command` - Full command line, arguments separated by spaces
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub name: String,
+    pub user: Option<String>,
+    #[serde(deserialize_with = "crate::snapshot::nan_as_null::deserialize")]
+    pub cpu_usage: f32,
+    pub memory: u64,
+    pub state: String,
+    pub command: String,
+}
+
+/// Column of the process table.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ProcessColumn {
+    Pid,
+    Name,
+    User,
+    Cpu,
+    Memory,
+    State,
+    Command,
+}
+
+impl ProcessColumn {
+    /// All columns in display order.
+    pub const ALL: [ProcessColumn; 7] = [
+        ProcessColumn::Pid,
+        ProcessColumn::Name,
+        ProcessColumn::User,
+        ProcessColumn::Cpu,
+        ProcessColumn::Memory,
+        ProcessColumn::State,
+        ProcessColumn::Command,
+    ];
+
+    /// Returns the column header.
+    pub fn title(self) -> &'static str {
+        match self {
+            ProcessColumn::Pid => "PID",
+            ProcessColumn::Name => "Name",
+            ProcessColumn::User => "User",
+            ProcessColumn::Cpu => "CPU%",
+            ProcessColumn::Memory => "RSS",
+            ProcessColumn::State => "State",
+            ProcessColumn::Command => "Command",
+        }
+    }
+
+    /// Returns the next column in display order, wrapping around.
+    pub fn next(self) -> Self {
+        let index = Self::ALL.iter().position(|&c| c == self).unwrap_or(0);
+        Self::ALL[(index + 1) % Self::ALL.len()]
+    }
+
+    /// Returns the previous column in display order, wrapping around.
+    pub fn previous(self) -> Self {
+        let index = Self::ALL.iter().position(|&c| c == self).unwrap_or(0);
+        Self::ALL[(index + Self::ALL.len() - 1) % Self::ALL.len()]
+    }
+
+    /// Compares two processes by this column in ascending order.
+    ///
+    /// Ties are broken by pid so the resulting order is stable across
+    /// refreshes.
+    pub fn compare(self, a: &ProcessInfo, b: &ProcessInfo) -> Ordering {
+        let ordering = match self {
+            ProcessColumn::Pid => Ordering::Equal,
+            ProcessColumn::Name => a.name.to_lowercase().cmp(&b.name.to_lowercase()),
+            ProcessColumn::User => a.user.cmp(&b.user),
+            ProcessColumn::Cpu => a.cpu_usage.total_cmp(&b.cpu_usage),
+            ProcessColumn::Memory => a.memory.cmp(&b.memory),
+            ProcessColumn::State => a.state.cmp(&b.state),
+            ProcessColumn::Command => a.command.cmp(&b.command),
+        };
+        ordering.then(a.pid.cmp(&b.pid))
+    }
+}
+
+/// Sort order of the process table.
+///
+/// # Fields
+/// * `column` - Column to sort by
+/// * `descending` - Whether the largest values come first
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProcessSort {
+    pub column: ProcessColumn,
+    pub descending: bool,
+}
+
+impl Default for ProcessSort {
+    /// Sorts by CPU usage, busiest first, like `top`.
+    fn default() -> Self {
+        Self {
+            column: ProcessColumn::Cpu,
+            descending: true,
+        }
+    }
+}
+
+impl ProcessSort {
+    /// Sorts `processes` in place according to this order.
+    ///
+    /// # Example
+    /// ```no_run
+    /// use system_monitor::{App, ProcessColumn, ProcessSort};
+    ///
+    /// let mut app = App::new();
+    /// let mut processes = app.update().processes;
+    /// ProcessSort { column: ProcessColumn::Memory, descending: true }.apply(&mut processes);
+    /// for process in processes.iter().take(5) {
+    ///     println!("{} {} {}", process.pid, process.name, process.memory);
+    /// }
+    /// ```
+    pub fn apply(&self, processes: &mut [ProcessInfo]) {
+        processes.sort_by(|a, b| {
+            let ordering = self.column.compare(a, b);
+            if self.descending {
+                ordering.reverse()
+            } else {
+                ordering
+            }
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn process(pid: u32, name: &str, cpu_usage: f32, memory: u64) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            name: name.to_string(),
+            user: None,
+            cpu_usage,
+            memory,
+            state: "Sleeping".to_string(),
+            command: name.to_string(),
+        }
+    }
+
+    fn pids(processes: &[ProcessInfo]) -> Vec<u32> {
+        processes.iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn test_default_sort_is_cpu_descending() {
+        let mut processes = vec![
+            process(1, "init", 0.5, 10),
+            process(2, "busy", 90.0, 20),
+            process(3, "idle", 0.0, 30),
+        ];
+        ProcessSort::default().apply(&mut processes);
+        assert_eq!(pids(&processes), vec![2, 1, 3]);
+    }
+
+    #[test]
+    fn test_sort_by_memory_ascending() {
+        let mut processes = vec![
+            process(1, "a", 0.0, 300),
+            process(2, "b", 0.0, 100),
+            process(3, "c", 0.0, 200),
+        ];
+        let sort = ProcessSort {
+            column: ProcessColumn::Memory,
+            descending: false,
+        };
+        sort.apply(&mut processes);
+        assert_eq!(pids(&processes), vec![2, 3, 1]);
+    }
+
+    #[test]
+    fn test_name_sort_ignores_case() {
+        let mut processes = vec![process(1, "zsh", 0.0, 0), process(2, "Xorg", 0.0, 0)];
+        let sort = ProcessSort {
+            column: ProcessColumn::Name,
+            descending: false,
+        };
+        sort.apply(&mut processes);
+        assert_eq!(pids(&processes), vec![2, 1]);
+    }
+
+    #[test]
+    fn test_ties_broken_by_pid() {
+        let mut processes = vec![
+            process(30, "a", 1.0, 0),
+            process(10, "b", 1.0, 0),
+            process(20, "c", 1.0, 0),
+        ];
+        let sort = ProcessSort {
+            column: ProcessColumn::Cpu,
+            descending: false,
+        };
+        sort.apply(&mut processes);
+        assert_eq!(pids(&processes), vec![10, 20, 30]);
+    }
+
+    #[test]
+    fn test_column_cycling_wraps() {
+        assert_eq!(ProcessColumn::Pid.previous(), ProcessColumn::Command);
+        assert_eq!(ProcessColumn::Command.next(), ProcessColumn::Pid);
+        for column in ProcessColumn::ALL {
+            assert_eq!(column.next().previous(), column);
+        }
+    }
+}
@@ -0,0 +1,676 @@
+//! Prometheus text exposition format.
+//!
+//! [`encode`] turns a [`MetricsSnapshot`] into the plain-text format
+//! scraped by Prometheus (version 0.0.4). Metric names follow the
+//! Prometheus naming conventions: base units (`_bytes`, `_seconds`),
+//! usages as `_ratio` between 0 and 1, and `_total` for counters.
+//! Metrics that are unavailable on the host are left out rather than
+//! exported as NaN.
+
+use std::fmt::Write;
+use std::time::UNIX_EPOCH;
+
+use crate::cputime::CpuMode;
+use crate::pressure::Resource;
+use crate::snapshot::MetricsSnapshot;
+
+/// `Content-Type` of the text exposition format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Prefix shared by all exported metric names.
+pub const NAMESPACE: &str = "system_monitor";
+
+/// Prometheus metric type.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MetricType {
+    /// Value that can go up and down
+    Gauge,
+    /// Monotonically increasing value
+    Counter,
+}
+
+impl MetricType {
+    /// Returns the type name used in `# TYPE` lines.
+    pub fn name(self) -> &'static str {
+        match self {
+            MetricType::Gauge => "gauge",
+            MetricType::Counter => "counter",
+        }
+    }
+}
+
+/// One metric family: a name, its metadata and its samples.
+struct Family {
+    name: &'static str,
+    kind: MetricType,
+    help: &'static str,
+    samples: Vec<(Vec<(&'static str, String)>, f64)>,
+}
+
+impl Family {
+    fn new(name: &'static str, kind: MetricType, help: &'static str) -> Self {
+        Self {
+            name,
+            kind,
+            help,
+            samples: Vec::new(),
+        }
+    }
+
+    /// Adds an unlabelled sample.
+    fn value(mut self, value: f64) -> Self {
+        self.samples.push((Vec::new(), value));
+        self
+    }
+
+    /// Adds a labelled sample.
+    fn sample(&mut self, labels: Vec<(&'static str, String)>, value: f64) {
+        self.samples.push((labels, value));
+    }
+
+    /// Appends the family to `out`, skipping non-finite samples.
+    ///
+    /// Families without samples are left out entirely.
+    fn write(&self, out: &mut String) {
+        let samples: Vec<_> = self
+            .samples
+            .iter()
+            .filter(|(_, value)| value.is_finite())
+            .collect();
+        if samples.is_empty() {
+            return;
+        }
+
+        let name = format!("{}_{}", NAMESPACE, self.name);
+        let _ = writeln!(out, "# HELP {} {}", name, self.help);
+        let _ = writeln!(out, "# TYPE {} {}", name, self.kind.name());
+        for (labels, value) in samples {
+            out.push_str(&name);
+            if !labels.is_empty() {
+                let labels: Vec<String> = labels
+                    .iter()
+                    .map(|(key, value)| format!("{}=\"{}\"", key, escape_label(value)))
+                    .collect();
+                let _ = write!(out, "{{{}}}", labels.join(","));
+            }
+            let _ = writeln!(out, " {}", value);
+        }
+    }
+}
+
+/// Escapes a label value: backslash, double quote and line feed.
+fn escape_label(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Encodes a snapshot in the Prometheus text exposition format.
+///
+/// Pseudo filesystems are skipped, like in the disks view. Network
+/// counters count traffic since monitoring started.
+///
+/// # Arguments
+/// * `snapshot` - Metrics to export
+///
+/// # Returns
+/// * `String` - One `# HELP` / `# TYPE` block per metric family
+///
+/// # Example
+/// ```
+/// use system_monitor::{App, Reading, ScriptedSource};
+/// use system_monitor::prometheus;
+///
+/// let reading = Reading { total_memory: 1024, used_memory: 256, ..Reading::default() };
+/// let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+/// let text = prometheus::encode(app.snapshot());
+/// assert!(text.contains("# TYPE system_monitor_memory_used_bytes gauge"));
+/// assert!(text.contains("system_monitor_memory_used_bytes 256\n"));
+/// ```
+pub fn encode(snapshot: &MetricsSnapshot) -> String {
+    let mut families = vec![
+        Family::new(
+            "cpu_usage_ratio",
+            MetricType::Gauge,
+            "Average CPU usage across all cores (0-1).",
+        ),
+        Family::new(
+            "cpu_core_usage_ratio",
+            MetricType::Gauge,
+            "CPU usage of a single logical core (0-1).",
+        ),
+        Family::new(
+            "memory_total_bytes",
+            MetricType::Gauge,
+            "Total physical memory in bytes.",
+        )
+        .value(snapshot.total_memory as f64),
+        Family::new(
+            "memory_used_bytes",
+            MetricType::Gauge,
+            "Used physical memory in bytes.",
+        )
+        .value(snapshot.used_memory as f64),
+        Family::new(
+            "swap_total_bytes",
+            MetricType::Gauge,
+            "Total swap space in bytes.",
+        )
+        .value(snapshot.total_swap as f64),
+        Family::new(
+            "swap_used_bytes",
+            MetricType::Gauge,
+            "Used swap space in bytes.",
+        )
+        .value(snapshot.used_swap as f64),
+        Family::new("processes", MetricType::Gauge, "Number of processes.")
+            .value(snapshot.processes.len() as f64),
+    ];
+
+    if let Ok(usage) = snapshot.cpu_usage() {
+        families[0].sample(Vec::new(), f64::from(usage) / 100.0);
+    }
+    for core in &snapshot.cores {
+        // Label with the kernel's CPU number (`cpu3` is core 3), which skips
+        // offline CPUs, rather than sysinfo's consecutive index
+        let id = core.name.strip_prefix("cpu").unwrap_or(&core.name);
+        families[1].sample(
+            vec![("core", id.to_string())],
+            f64::from(core.usage) / 100.0,
+        );
+    }
+
+    let mut disk_total = Family::new(
+        "disk_total_bytes",
+        MetricType::Gauge,
+        "Total capacity of a mounted filesystem in bytes.",
+    );
+    let mut disk_available = Family::new(
+        "disk_available_bytes",
+        MetricType::Gauge,
+        "Space available to unprivileged users on a mounted filesystem in bytes.",
+    );
+    for disk in snapshot.disks.iter().filter(|disk| !disk.is_pseudo()) {
+        let labels = vec![
+            ("mount", disk.mount_point.clone()),
+            ("device", disk.name.clone()),
+            ("fstype", disk.file_system.clone()),
+        ];
+        disk_total.sample(labels.clone(), disk.total as f64);
+        disk_available.sample(labels, disk.available as f64);
+    }
+    families.push(disk_total);
+    families.push(disk_available);
+
+    let mut rx_bytes = Family::new(
+        "network_receive_bytes_total",
+        MetricType::Counter,
+        "Bytes received on an interface since monitoring started.",
+    );
+    let mut tx_bytes = Family::new(
+        "network_transmit_bytes_total",
+        MetricType::Counter,
+        "Bytes transmitted on an interface since monitoring started.",
+    );
+    let mut rx_packets = Family::new(
+        "network_receive_packets_total",
+        MetricType::Counter,
+        "Packets received on an interface since monitoring started.",
+    );
+    let mut tx_packets = Family::new(
+        "network_transmit_packets_total",
+        MetricType::Counter,
+        "Packets transmitted on an interface since monitoring started.",
+    );
+    for interface in &snapshot.networks {
+        let labels = vec![("interface", interface.name.clone())];
+        rx_bytes.sample(labels.clone(), interface.total.rx_bytes as f64);
+        tx_bytes.sample(labels.clone(), interface.total.tx_bytes as f64);
+        rx_packets.sample(labels.clone(), interface.total.rx_packets as f64);
+        tx_packets.sample(labels, interface.total.tx_packets as f64);
+    }
+    families.extend([rx_bytes, tx_bytes, rx_packets, tx_packets]);
+
+    if let Some(container) = &snapshot.container {
+        let labels = vec![("cgroup", container.cgroup.clone())];
+        let mut memory_used = Family::new(
+            "container_memory_used_bytes",
+            MetricType::Gauge,
+            "Memory charged to the monitor's cgroup in bytes.",
+        );
+        memory_used.sample(labels.clone(), container.memory_used as f64);
+        let mut memory_limit = Family::new(
+            "container_memory_limit_bytes",
+            MetricType::Gauge,
+            "Memory limit of the monitor's cgroup in bytes.",
+        );
+        let mut cpu_limit = Family::new(
+            "container_cpu_limit_cpus",
+            MetricType::Gauge,
+            "CPU quota of the monitor's cgroup as a number of CPUs.",
+        );
+        let mut cpu_usage = Family::new(
+            "container_cpu_usage_ratio",
+            MetricType::Gauge,
+            "CPU usage of the monitor's cgroup relative to its quota (0-1).",
+        );
+        let mut cpu_seconds = Family::new(
+            "container_cpu_usage_seconds_total",
+            MetricType::Counter,
+            "CPU time consumed by the monitor's cgroup in seconds.",
+        );
+        cpu_seconds.sample(labels.clone(), container.cpu.usage_usec as f64 / 1e6);
+        let mut throttled = Family::new(
+            "container_cpu_throttled_seconds_total",
+            MetricType::Counter,
+            "Time the monitor's cgroup was throttled by its CPU quota in seconds.",
+        );
+        throttled.sample(labels.clone(), container.cpu.throttled_usec as f64 / 1e6);
+        if let Some(limit) = container.memory_limit {
+            memory_limit.sample(labels.clone(), limit as f64);
+        }
+        if let Some(cpus) = container.cpu_limit() {
+            cpu_limit.sample(labels.clone(), cpus);
+        }
+        if let Some(usage) = container.cpu_usage {
+            cpu_usage.sample(labels, f64::from(usage) / 100.0);
+        }
+        families.extend([
+            memory_used,
+            memory_limit,
+            cpu_limit,
+            cpu_usage,
+            cpu_seconds,
+            throttled,
+        ]);
+    }
+
+    if let Some(meminfo) = &snapshot.meminfo {
+        let hugepages = &meminfo.hugepages;
+        for (name, help, bytes) in [
+            (
+                "memory_available_bytes",
+                "Memory that can be allocated without swapping in bytes.",
+                meminfo.available,
+            ),
+            (
+                "memory_buffers_bytes",
+                "Block device buffers in bytes.",
+                meminfo.buffers,
+            ),
+            (
+                "memory_cached_bytes",
+                "Page cache, including shared memory, in bytes.",
+                meminfo.cached,
+            ),
+            (
+                "memory_shared_bytes",
+                "Shared memory and tmpfs in bytes.",
+                meminfo.shared,
+            ),
+            (
+                "memory_slab_bytes",
+                "Kernel slab caches in bytes.",
+                meminfo.slab,
+            ),
+            (
+                "memory_slab_reclaimable_bytes",
+                "Reclaimable kernel slab caches in bytes.",
+                meminfo.slab_reclaimable,
+            ),
+            (
+                "memory_dirty_bytes",
+                "Memory waiting to be written back to disk in bytes.",
+                meminfo.dirty,
+            ),
+            (
+                "memory_writeback_bytes",
+                "Memory being written back to disk in bytes.",
+                meminfo.writeback,
+            ),
+            (
+                "memory_hugepages_total_bytes",
+                "Size of the huge page pool in bytes.",
+                hugepages.total_bytes(),
+            ),
+            (
+                "memory_hugepages_used_bytes",
+                "Allocated huge pages in bytes.",
+                hugepages.used_bytes(),
+            ),
+        ] {
+            families.push(Family::new(name, MetricType::Gauge, help).value(bytes as f64));
+        }
+    }
+
+    if let Some(cpu_time) = &snapshot.cpu_time {
+        let mut modes = Family::new(
+            "cpu_mode_ratio",
+            MetricType::Gauge,
+            "Share of CPU time spent in a mode across all cores (0-1).",
+        );
+        for mode in CpuMode::ALL {
+            modes.sample(
+                vec![("mode", mode.name().to_string())],
+                f64::from(cpu_time.total.get(mode)) / 100.0,
+            );
+        }
+        families.push(modes);
+    }
+
+    if let Some(pressure) = &snapshot.pressure {
+        let mut stalled = Family::new(
+            "pressure_stalled_seconds_total",
+            MetricType::Counter,
+            "Time in which tasks were stalled on a resource in seconds.",
+        );
+        let mut ratio = Family::new(
+            "pressure_ratio",
+            MetricType::Gauge,
+            "Share of time in which tasks were stalled on a resource, averaged over a window (0-1).",
+        );
+        for resource in Resource::ALL {
+            let Some(stalls) = pressure.get(resource) else {
+                continue;
+            };
+            for (kind, stats) in [("some", Some(&stalls.some)), ("full", stalls.full.as_ref())] {
+                let Some(stats) = stats else {
+                    continue;
+                };
+                let labels = vec![
+                    ("resource", resource.name().to_string()),
+                    ("kind", kind.to_string()),
+                ];
+                stalled.sample(labels.clone(), stats.total_usec as f64 / 1e6);
+                for (window, average) in [
+                    ("10s", stats.avg10),
+                    ("60s", stats.avg60),
+                    ("300s", stats.avg300),
+                ] {
+                    let mut labels = labels.clone();
+                    labels.push(("window", window.to_string()));
+                    ratio.sample(labels, f64::from(average) / 100.0);
+                }
+            }
+        }
+        families.extend([stalled, ratio]);
+    }
+
+    if let Ok(since_epoch) = snapshot.timestamp.duration_since(UNIX_EPOCH) {
+        families.push(
+            Family::new(
+                "last_refresh_timestamp_seconds",
+                MetricType::Gauge,
+                "Unix time at which the exported metrics were collected.",
+            )
+            .value(since_epoch.as_secs_f64()),
+        );
+    }
+
+    let mut out = String::new();
+    for family in &families {
+        family.write(&mut out);
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cgroup::{ContainerUsage, CpuStat};
+    use crate::cputime::{CpuShares, CpuTimeUsage};
+    use crate::disk::DiskUsage;
+    use crate::meminfo::{HugePages, MemInfo};
+    use crate::network::{InterfaceUsage, NetworkCounters};
+    use crate::pressure::{Pressure, ResourcePressure, StallStats};
+    use crate::snapshot::CoreUsage;
+    use std::time::{Duration, SystemTime};
+
+    fn snapshot() -> MetricsSnapshot {
+        MetricsSnapshot {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000),
+            cores: vec![
+                CoreUsage {
+                    id: 0,
+                    name: "cpu0".to_string(),
+                    usage: 25.0,
+                },
+                CoreUsage {
+                    id: 1,
+                    name: "cpu1".to_string(),
+                    usage: 75.0,
+                },
+            ],
+            total_memory: 8192,
+            used_memory: 2048,
+            disks: vec![
+                DiskUsage {
+                    name: "/dev/sda1".to_string(),
+                    mount_point: "/".to_string(),
+                    file_system: "ext4".to_string(),
+                    total: 1000,
+                    available: 400,
+                },
+                DiskUsage {
+                    name: "tmpfs".to_string(),
+                    mount_point: "/run".to_string(),
+                    file_system: "tmpfs".to_string(),
+                    total: 100,
+                    available: 100,
+                },
+            ],
+            networks: vec![InterfaceUsage {
+                name: "eth0".to_string(),
+                rx_bytes_per_sec: 0.0,
+                tx_bytes_per_sec: 0.0,
+                rx_packets_per_sec: 0.0,
+                tx_packets_per_sec: 0.0,
+                total: NetworkCounters {
+                    rx_bytes: 5000,
+                    tx_bytes: 3000,
+                    rx_packets: 50,
+                    tx_packets: 30,
+                },
+            }],
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    #[test]
+    fn test_cpu_metrics_are_ratios() {
+        let text = encode(&snapshot());
+        assert!(text.contains("system_monitor_cpu_usage_ratio 0.5\n"));
+        assert!(text.contains("system_monitor_cpu_core_usage_ratio{core=\"0\"} 0.25\n"));
+        assert!(text.contains("system_monitor_cpu_core_usage_ratio{core=\"1\"} 0.75\n"));
+    }
+
+    #[test]
+    fn test_core_label_follows_kernel_numbering() {
+        // cpu1 is offline: sysinfo reports cpu2 at index 1
+        let mut snapshot = snapshot();
+        snapshot.cores[1].name = "cpu2".to_string();
+        let text = encode(&snapshot);
+        assert!(text.contains("system_monitor_cpu_core_usage_ratio{core=\"2\"} 0.75\n"));
+        assert!(!text.contains("core=\"1\""));
+    }
+
+    #[test]
+    fn test_help_and_type_precede_samples() {
+        let text = encode(&snapshot());
+        let lines: Vec<&str> = text.lines().collect();
+        let index = lines
+            .iter()
+            .position(|l| l.starts_with("system_monitor_memory_used_bytes "))
+            .unwrap();
+        assert_eq!(
+            lines[index - 2],
+            "# HELP system_monitor_memory_used_bytes Used physical memory in bytes."
+        );
+        assert_eq!(
+            lines[index - 1],
+            "# TYPE system_monitor_memory_used_bytes gauge"
+        );
+    }
+
+    #[test]
+    fn test_network_counters() {
+        let text = encode(&snapshot());
+        assert!(text.contains("# TYPE system_monitor_network_receive_bytes_total counter"));
+        assert!(
+            text.contains("system_monitor_network_receive_bytes_total{interface=\"eth0\"} 5000\n")
+        );
+        assert!(
+            text.contains("system_monitor_network_transmit_packets_total{interface=\"eth0\"} 30\n")
+        );
+    }
+
+    #[test]
+    fn test_disk_labels_and_pseudo_filter() {
+        let text = encode(&snapshot());
+        assert!(text.contains(
+            "system_monitor_disk_available_bytes{mount=\"/\",device=\"/dev/sda1\",fstype=\"ext4\"} 400\n"
+        ));
+        assert!(
+            !text.contains("tmpfs"),
+            "Pseudo filesystems are not exported"
+        );
+    }
+
+    #[test]
+    fn test_unavailable_metrics_are_omitted() {
+        let mut snapshot = snapshot();
+        snapshot.cores.clear();
+        snapshot.disks.clear();
+        let text = encode(&snapshot);
+        assert!(!text.contains("system_monitor_cpu_usage_ratio"));
+        assert!(!text.contains("system_monitor_disk_total_bytes"));
+        assert!(!text.contains("NaN"));
+    }
+
+    #[test]
+    fn test_container_metrics() {
+        assert!(!encode(&snapshot()).contains("container"));
+
+        let mut snapshot = snapshot();
+        snapshot.container = Some(ContainerUsage {
+            cgroup: "/docker/abc".to_string(),
+            memory_used: 256,
+            memory_limit: Some(1024),
+            cpu: CpuStat {
+                usage_usec: 1_500_000,
+                ..CpuStat::default()
+            },
+            cpu_usage: Some(50.0),
+            ..ContainerUsage::default()
+        });
+        let text = encode(&snapshot);
+        assert!(text.contains(
+            "system_monitor_container_memory_limit_bytes{cgroup=\"/docker/abc\"} 1024\n"
+        ));
+        assert!(
+            text.contains("system_monitor_container_cpu_usage_ratio{cgroup=\"/docker/abc\"} 0.5\n")
+        );
+        assert!(text.contains(
+            "system_monitor_container_cpu_usage_seconds_total{cgroup=\"/docker/abc\"} 1.5\n"
+        ));
+        assert!(
+            !text.contains("container_cpu_limit_cpus"),
+            "No quota is exported without a limit"
+        );
+    }
+
+    #[test]
+    fn test_pressure_metrics() {
+        assert!(!encode(&snapshot()).contains("pressure"));
+
+        let mut snapshot = snapshot();
+        snapshot.pressure = Some(Pressure {
+            cpu: Some(ResourcePressure {
+                some: StallStats {
+                    avg10: 25.0,
+                    avg60: 10.0,
+                    avg300: 5.0,
+                    total_usec: 2_500_000,
+                },
+                full: None,
+            }),
+            memory: None,
+            io: None,
+        });
+        let text = encode(&snapshot);
+        assert!(text.contains(
+            "system_monitor_pressure_stalled_seconds_total{resource=\"cpu\",kind=\"some\"} 2.5\n"
+        ));
+        assert!(text.contains(
+            "system_monitor_pressure_ratio{resource=\"cpu\",kind=\"some\",window=\"10s\"} 0.25\n"
+        ));
+        assert!(!text.contains("kind=\"full\""));
+    }
+
+    #[test]
+    fn test_meminfo_metrics() {
+        assert!(!encode(&snapshot()).contains("memory_available_bytes"));
+
+        let mut snapshot = snapshot();
+        snapshot.meminfo = Some(MemInfo {
+            available: 600,
+            dirty: 20,
+            hugepages: HugePages {
+                total: 4,
+                free: 1,
+                page_size: 2048,
+                ..HugePages::default()
+            },
+            ..MemInfo::default()
+        });
+        let text = encode(&snapshot);
+        assert!(text.contains("system_monitor_memory_available_bytes 600\n"));
+        assert!(text.contains("system_monitor_memory_dirty_bytes 20\n"));
+        assert!(text.contains("system_monitor_memory_hugepages_total_bytes 8192\n"));
+        assert!(text.contains("system_monitor_memory_hugepages_used_bytes 6144\n"));
+    }
+
+    #[test]
+    fn test_cpu_mode_metrics() {
+        assert!(!encode(&snapshot()).contains("cpu_mode_ratio"));
+
+        let mut snapshot = snapshot();
+        snapshot.cpu_time = Some(CpuTimeUsage {
+            total: CpuShares {
+                user: 50.0,
+                steal: 25.0,
+                idle: 25.0,
+                ..CpuShares::default()
+            },
+            cores: Vec::new(),
+        });
+        let text = encode(&snapshot);
+        assert!(text.contains("system_monitor_cpu_mode_ratio{mode=\"steal\"} 0.25\n"));
+        assert!(text.contains("system_monitor_cpu_mode_ratio{mode=\"iowait\"} 0\n"));
+        assert_eq!(text.matches("system_monitor_cpu_mode_ratio{").count(), 9);
+    }
+
+    #[test]
+    fn test_timestamp() {
+        let text = encode(&snapshot());
+        assert!(text.contains("system_monitor_last_refresh_timestamp_seconds 1700000000\n"));
+    }
+
+    #[test]
+    fn test_label_escaping() {
+        assert_eq!(escape_label(r#"a"b\c"#), r#"a\"b\\c"#);
+        assert_eq!(escape_label("line\nbreak"), "line\\nbreak");
+    }
+
+    #[test]
+    fn test_output_ends_with_newline() {
+        assert!(encode(&snapshot()).ends_with('\n'));
+    }
+}
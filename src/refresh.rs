@@ -0,0 +1,266 @@
+//! Selective refresh of the metric collectors.
+//!
+//! Refreshing every collector on every tick rescans all processes, disks
+//! and interfaces even when only CPU and memory are displayed. A
+//! [`RefreshPlan`] enables collectors individually and gives each its own
+//! refresh interval, so expensive collectors such as processes can run
+//! slower than CPU. [`RefreshScheduler`] decides which collectors are due
+//! on a given tick.
+
+use std::time::{Duration, Instant};
+
+/// Group of metrics refreshed together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Collector {
+    /// Per-core and aggregate CPU usage
+    Cpu,
+    /// Physical memory and swap usage
+    Memory,
+    /// Process table
+    Processes,
+    /// Filesystem usage per mount point
+    Disks,
+    /// Interface traffic counters
+    Networks,
+}
+
+impl Collector {
+    /// All collectors, cheapest first.
+    pub const ALL: [Collector; 5] = [
+        Collector::Cpu,
+        Collector::Memory,
+        Collector::Networks,
+        Collector::Disks,
+        Collector::Processes,
+    ];
+
+    /// Returns the lowercase identifier of the collector.
+    pub fn name(self) -> &'static str {
+        match self {
+            Collector::Cpu => "cpu",
+            Collector::Memory => "memory",
+            Collector::Processes => "processes",
+            Collector::Disks => "disks",
+            Collector::Networks => "networks",
+        }
+    }
+
+    /// Returns the position of the collector in [`Collector::ALL`].
+    fn index(self) -> usize {
+        Self::ALL.iter().position(|&c| c == self).unwrap_or(0)
+    }
+}
+
+/// Default interval between two process table refreshes.
+pub const DEFAULT_PROCESS_INTERVAL: Duration = Duration::from_secs(2);
+
+/// Default interval between two disk usage refreshes.
+pub const DEFAULT_DISK_INTERVAL: Duration = Duration::from_secs(10);
+
+/// Which collectors are enabled and how often each one is refreshed.
+///
+/// An interval of zero refreshes the collector on every read. A disabled
+/// collector is never refreshed and reports no data.
+///
+/// # Example
+/// ```
+/// use std::time::Duration;
+/// use system_monitor::refresh::{Collector, RefreshPlan};
+///
+/// // CPU and memory only, e.g. for a minimal status line
+/// let plan = RefreshPlan::none()
+///     .with_interval(Collector::Cpu, Duration::ZERO)
+///     .with_interval(Collector::Memory, Duration::from_secs(5));
+/// assert!(!plan.is_enabled(Collector::Processes));
+/// assert_eq!(plan.interval(Collector::Memory), Some(Duration::from_secs(5)));
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RefreshPlan {
+    intervals: [Option<Duration>; 5],
+}
+
+impl RefreshPlan {
+    /// Creates a plan with every collector disabled.
+    pub fn none() -> Self {
+        Self {
+            intervals: [None; 5],
+        }
+    }
+
+    /// Creates a plan refreshing every collector on every read.
+    ///
+    /// This matches the cost of `refresh_all` and is mostly useful as a
+    /// baseline for comparison.
+    pub fn every_tick() -> Self {
+        Self {
+            intervals: [Some(Duration::ZERO); 5],
+        }
+    }
+
+    /// Enables `collector` with the given refresh interval.
+    pub fn with_interval(mut self, collector: Collector, interval: Duration) -> Self {
+        self.intervals[collector.index()] = Some(interval);
+        self
+    }
+
+    /// Disables `collector`.
+    pub fn without(mut self, collector: Collector) -> Self {
+        self.intervals[collector.index()] = None;
+        self
+    }
+
+    /// Returns the refresh interval of `collector`, or `None` if disabled.
+    pub fn interval(&self, collector: Collector) -> Option<Duration> {
+        self.intervals[collector.index()]
+    }
+
+    /// Returns `true` if `collector` is refreshed at all.
+    pub fn is_enabled(&self, collector: Collector) -> bool {
+        self.interval(collector).is_some()
+    }
+}
+
+impl Default for RefreshPlan {
+    /// Refreshes CPU, memory and networks on every read, processes every
+    /// `DEFAULT_PROCESS_INTERVAL` and disks every `DEFAULT_DISK_INTERVAL`.
+    fn default() -> Self {
+        Self::every_tick()
+            .with_interval(Collector::Processes, DEFAULT_PROCESS_INTERVAL)
+            .with_interval(Collector::Disks, DEFAULT_DISK_INTERVAL)
+    }
+}
+
+/// Tracks when each collector last ran and which ones are due.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, Instant};
+/// use system_monitor::refresh::{Collector, RefreshPlan, RefreshScheduler};
+///
+/// let plan = RefreshPlan::none().with_interval(Collector::Disks, Duration::from_secs(10));
+/// let mut scheduler = RefreshScheduler::new(plan);
+/// let start = Instant::now();
+///
+/// assert_eq!(scheduler.due(start), vec![Collector::Disks]);
+/// assert!(scheduler.due(start + Duration::from_secs(1)).is_empty());
+/// assert_eq!(scheduler.due(start + Duration::from_secs(10)), vec![Collector::Disks]);
+/// ```
+#[derive(Debug, Clone)]
+pub struct RefreshScheduler {
+    plan: RefreshPlan,
+    last_run: [Option<Instant>; 5],
+}
+
+impl RefreshScheduler {
+    /// Creates a scheduler for which every enabled collector is due.
+    pub fn new(plan: RefreshPlan) -> Self {
+        Self {
+            plan,
+            last_run: [None; 5],
+        }
+    }
+
+    /// Returns the plan being scheduled.
+    pub fn plan(&self) -> &RefreshPlan {
+        &self.plan
+    }
+
+    /// Returns the collectors due at `now` and marks them as run.
+    ///
+    /// # Arguments
+    /// * `now` - Time of the refresh
+    ///
+    /// # Returns
+    /// * `Vec<Collector>` - Due collectors, in [`Collector::ALL`] order
+    pub fn due(&mut self, now: Instant) -> Vec<Collector> {
+        let mut due = Vec::new();
+        for collector in Collector::ALL {
+            let Some(interval) = self.plan.interval(collector) else {
+                continue;
+            };
+            let last_run = &mut self.last_run[collector.index()];
+            let is_due = match *last_run {
+                Some(then) => now.saturating_duration_since(then) >= interval,
+                None => true,
+            };
+            if is_due {
+                *last_run = Some(now);
+                due.push(collector);
+            }
+        }
+        due
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_default_plan_refreshes_processes_slower_than_cpu() {
+        let plan = RefreshPlan::default();
+        assert_eq!(plan.interval(Collector::Cpu), Some(Duration::ZERO));
+        assert_eq!(
+            plan.interval(Collector::Processes),
+            Some(DEFAULT_PROCESS_INTERVAL)
+        );
+        assert!(Collector::ALL.iter().all(|&c| plan.is_enabled(c)));
+    }
+
+    #[test]
+    fn test_without_disables_collector() {
+        let plan = RefreshPlan::every_tick().without(Collector::Processes);
+        assert!(!plan.is_enabled(Collector::Processes));
+        assert!(plan.is_enabled(Collector::Disks));
+    }
+
+    #[test]
+    fn test_everything_due_on_first_tick() {
+        let mut scheduler = RefreshScheduler::new(RefreshPlan::default());
+        assert_eq!(scheduler.due(Instant::now()), Collector::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_every_tick_refreshes_everything_on_every_tick() {
+        let mut scheduler = RefreshScheduler::new(RefreshPlan::every_tick());
+        let now = Instant::now();
+        assert_eq!(scheduler.due(now), Collector::ALL.to_vec());
+        assert_eq!(scheduler.due(now), Collector::ALL.to_vec());
+    }
+
+    #[test]
+    fn test_disabled_collectors_never_due() {
+        let mut scheduler = RefreshScheduler::new(RefreshPlan::none());
+        assert!(scheduler.due(Instant::now()).is_empty());
+    }
+
+    #[test]
+    fn test_independent_intervals() {
+        let plan = RefreshPlan::none()
+            .with_interval(Collector::Cpu, Duration::ZERO)
+            .with_interval(Collector::Processes, Duration::from_secs(2));
+        let mut scheduler = RefreshScheduler::new(plan);
+        let start = Instant::now();
+        let at = |seconds: u64| start + Duration::from_secs(seconds);
+
+        assert_eq!(
+            scheduler.due(at(0)),
+            vec![Collector::Cpu, Collector::Processes]
+        );
+        assert_eq!(scheduler.due(at(1)), vec![Collector::Cpu]);
+        assert_eq!(
+            scheduler.due(at(2)),
+            vec![Collector::Cpu, Collector::Processes]
+        );
+        assert_eq!(scheduler.due(at(3)), vec![Collector::Cpu]);
+    }
+
+    #[test]
+    fn test_earlier_time_is_not_due() {
+        let plan = RefreshPlan::none().with_interval(Collector::Disks, Duration::from_secs(5));
+        let mut scheduler = RefreshScheduler::new(plan);
+        let start = Instant::now() + Duration::from_secs(60);
+        scheduler.due(start);
+        assert!(scheduler.due(start - Duration::from_secs(30)).is_empty());
+    }
+}
@@ -0,0 +1,658 @@
+//! Recording and replaying monitoring sessions.
+//!
+//! A session is a newline-delimited JSON file holding one snapshot per
+//! line, in the format written by the [`ndjson`](crate::ndjson) module, so
+//! the output of the `json` mode can be replayed too. [`SessionWriter`]
+//! appends snapshots to such a file while monitoring; [`Session`] loads
+//! one back and [`Player`] steps through it, rebuilding an [`App`] whose
+//! histories and statistics are computed from the recorded data only.
+
+use std::error::Error;
+use std::fmt;
+use std::fs::File;
+use std::io::{self, BufRead, BufReader, Write};
+use std::path::Path;
+use std::time::Duration;
+
+use crate::alert::AlertRule;
+use crate::ndjson;
+use crate::snapshot::MetricsSnapshot;
+use crate::App;
+
+/// Playback speeds selectable with [`Player::faster`] and
+/// [`Player::slower`], as multiples of the recorded pace.
+pub const SPEEDS: [f32; 7] = [0.25, 0.5, 1.0, 2.0, 4.0, 8.0, 16.0];
+
+/// Index of the real-time speed in [`SPEEDS`].
+const REAL_TIME: usize = 2;
+
+/// Number of snapshots between two app states saved by [`Player::seek`].
+///
+/// Each checkpoint holds one copy of the histories, so a day recorded at
+/// one-second intervals keeps 86 of them.
+const CHECKPOINT_INTERVAL: usize = 1000;
+
+/// Appends snapshots to a session file.
+///
+/// # Example
+/// ```no_run
+/// use system_monitor::session::SessionWriter;
+/// use system_monitor::App;
+///
+/// let mut writer = SessionWriter::create("incident.ndjson").unwrap();
+/// let mut app = App::new();
+/// for _ in 0..60 {
+///     std::thread::sleep(std::time::Duration::from_secs(1));
+///     writer.record(&app.update()).unwrap();
+/// }
+/// ```
+#[derive(Debug)]
+pub struct SessionWriter {
+    file: File,
+}
+
+impl SessionWriter {
+    /// Creates (or truncates) the session file at `path`.
+    pub fn create(path: impl AsRef<Path>) -> io::Result<Self> {
+        Ok(Self {
+            file: File::create(path)?,
+        })
+    }
+
+    /// Appends one snapshot as a line of JSON.
+    pub fn record(&mut self, snapshot: &MetricsSnapshot) -> io::Result<()> {
+        let line = format!("{}\n", ndjson::encode(snapshot));
+        self.file.write_all(line.as_bytes())
+    }
+}
+
+/// Error raised while loading a session.
+#[derive(Debug)]
+pub enum SessionError {
+    /// The file could not be read
+    Io(io::Error),
+    /// A line is not a valid snapshot
+    Parse {
+        /// One-based line number
+        line: usize,
+        /// Description of the problem
+        message: String,
+    },
+    /// The file holds no snapshot
+    Empty,
+}
+
+impl fmt::Display for SessionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SessionError::Io(err) => write!(f, "cannot read session: {}", err),
+            SessionError::Parse { line, message } => {
+                write!(f, "invalid snapshot on line {}: {}", line, message)
+            }
+            SessionError::Empty => write!(f, "session contains no snapshot"),
+        }
+    }
+}
+
+impl Error for SessionError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            SessionError::Io(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for SessionError {
+    fn from(err: io::Error) -> Self {
+        SessionError::Io(err)
+    }
+}
+
+/// Recorded snapshots, oldest first.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Session {
+    snapshots: Vec<MetricsSnapshot>,
+}
+
+impl Session {
+    /// Loads the session file at `path`.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, SessionError> {
+        Self::from_reader(BufReader::new(File::open(path)?))
+    }
+
+    /// Reads a session from newline-delimited JSON.
+    ///
+    /// Blank lines are skipped and fields other than the snapshot's (such
+    /// as the derived usages written by the `json` mode) are ignored.
+    ///
+    /// # Example
+    /// ```
+    /// use system_monitor::session::Session;
+    ///
+    /// let ndjson = r#"{"timestamp":0,"cores":[],"total_memory":100,"used_memory":25,"total_swap":0,"used_swap":0,"processes":[],"disks":[],"networks":[]}"#;
+    /// let session = Session::from_reader(ndjson.as_bytes()).unwrap();
+    /// assert_eq!(session.snapshots()[0].memory_usage(), Ok(25.0));
+    /// ```
+    pub fn from_reader(reader: impl BufRead) -> Result<Self, SessionError> {
+        let mut snapshots = Vec::new();
+        for (index, line) in reader.lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let snapshot = serde_json::from_str(&line).map_err(|err| SessionError::Parse {
+                line: index + 1,
+                message: err.to_string(),
+            })?;
+            snapshots.push(snapshot);
+        }
+        Self::new(snapshots)
+    }
+
+    /// Creates a session from snapshots, oldest first.
+    pub fn new(snapshots: Vec<MetricsSnapshot>) -> Result<Self, SessionError> {
+        if snapshots.is_empty() {
+            return Err(SessionError::Empty);
+        }
+        Ok(Self { snapshots })
+    }
+
+    /// Returns the recorded snapshots, oldest first.
+    pub fn snapshots(&self) -> &[MetricsSnapshot] {
+        &self.snapshots
+    }
+
+    /// Returns the number of snapshots.
+    pub fn len(&self) -> usize {
+        self.snapshots.len()
+    }
+
+    /// Returns `false`; sessions always hold at least one snapshot.
+    pub fn is_empty(&self) -> bool {
+        self.snapshots.is_empty()
+    }
+}
+
+/// Playback position and settings, e.g. for a status line.
+///
+/// # Fields
+/// * `position` - Zero-based index of the displayed snapshot
+/// * `len` - Number of snapshots in the session
+/// * `speed` - Playback speed as a multiple of the recorded pace
+/// * `paused` - Whether playback is suspended
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct PlaybackStatus {
+    pub position: usize,
+    pub len: usize,
+    pub speed: f32,
+    pub paused: bool,
+}
+
+impl PlaybackStatus {
+    /// Returns `true` if the last snapshot is displayed.
+    pub fn at_end(&self) -> bool {
+        self.position + 1 >= self.len
+    }
+}
+
+/// Steps through a session, keeping an [`App`] in sync with the position.
+///
+/// The app holds the snapshot at the current position, and its histories
+/// and statistics cover the snapshots up to that position, exactly as if
+/// they had been collected live.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use system_monitor::session::{Player, Session};
+/// use system_monitor::{App, Metric};
+///
+/// let snapshots = (0..3)
+///     .map(|i| {
+///         let mut snapshot = App::without_source(10).snapshot().clone();
+///         snapshot.timestamp = UNIX_EPOCH + Duration::from_secs(i);
+///         snapshot.total_memory = 100;
+///         snapshot.used_memory = 10 * i;
+///         snapshot
+///     })
+///     .collect();
+/// let mut player = Player::new(Session::new(snapshots).unwrap(), 10);
+/// assert_eq!(player.app().get_memory_usage(), Ok(0.0));
+///
+/// // One recorded second elapses in half a second at twice the speed
+/// player.faster();
+/// assert_eq!(player.advance(Duration::from_millis(500)), 1);
+/// assert_eq!(player.app().get_memory_usage(), Ok(10.0));
+///
+/// player.step_backward();
+/// assert_eq!(player.position(), 0);
+/// assert_eq!(player.app().history(Metric::Memory).len(), 1);
+/// ```
+#[derive(Debug)]
+pub struct Player {
+    session: Session,
+    history_len: usize,
+    alert_rules: Vec<AlertRule>,
+    app: App,
+    position: usize,
+    speed: usize,
+    paused: bool,
+    /// Playback time elapsed since the current snapshot was shown
+    clock: Duration,
+    /// App states before ingesting every `CHECKPOINT_INTERVAL`-th
+    /// snapshot, in order, recorded while seeking
+    checkpoints: Vec<App>,
+}
+
+impl Player {
+    /// Creates a player positioned on the first snapshot.
+    ///
+    /// # Arguments
+    /// * `session` - Session to replay
+    /// * `history_len` - Number of samples kept per metric by the app
+    pub fn new(session: Session, history_len: usize) -> Self {
+        let mut player = Self {
+            session,
+            history_len,
+            alert_rules: Vec::new(),
+            app: App::without_source(history_len),
+            position: 0,
+            speed: REAL_TIME,
+            paused: false,
+            clock: Duration::ZERO,
+            checkpoints: Vec::new(),
+        };
+        player.seek(0);
+        player
+    }
+
+    /// Evaluates `rules` against the session, up to the current position
+    /// and then at every step.
+    pub fn set_alert_rules(&mut self, rules: Vec<AlertRule>) {
+        self.alert_rules = rules;
+        self.checkpoints.clear();
+        self.seek(self.position);
+    }
+
+    /// Returns the app reflecting the current position.
+    pub fn app(&self) -> &App {
+        &self.app
+    }
+
+    /// Returns the app reflecting the current position, e.g. to reset its
+    /// statistics.
+    pub fn app_mut(&mut self) -> &mut App {
+        &mut self.app
+    }
+
+    /// Returns the session being replayed.
+    pub fn session(&self) -> &Session {
+        &self.session
+    }
+
+    /// Returns the zero-based index of the displayed snapshot.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the playback speed as a multiple of the recorded pace.
+    pub fn speed(&self) -> f32 {
+        SPEEDS[self.speed]
+    }
+
+    /// Returns `true` if playback is suspended.
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Suspends or resumes playback.
+    pub fn set_paused(&mut self, paused: bool) {
+        self.paused = paused;
+    }
+
+    /// Switches to the next faster speed, if any.
+    pub fn faster(&mut self) {
+        self.speed = (self.speed + 1).min(SPEEDS.len() - 1);
+    }
+
+    /// Switches to the next slower speed, if any.
+    pub fn slower(&mut self) {
+        self.speed = self.speed.saturating_sub(1);
+    }
+
+    /// Returns the playback position and settings.
+    pub fn status(&self) -> PlaybackStatus {
+        PlaybackStatus {
+            position: self.position,
+            len: self.session.len(),
+            speed: self.speed(),
+            paused: self.paused,
+        }
+    }
+
+    /// Shows the next snapshot.
+    ///
+    /// # Returns
+    /// * `bool` - `false` if the last snapshot was already displayed
+    pub fn step_forward(&mut self) -> bool {
+        let Some(next) = self.session.snapshots.get(self.position + 1) else {
+            return false;
+        };
+        self.app.ingest_snapshot(next.clone());
+        self.position += 1;
+        self.clock = Duration::ZERO;
+        true
+    }
+
+    /// Shows the previous snapshot.
+    ///
+    /// # Returns
+    /// * `bool` - `false` if the first snapshot was already displayed
+    pub fn step_backward(&mut self) -> bool {
+        if self.position == 0 {
+            return false;
+        }
+        self.seek(self.position - 1);
+        true
+    }
+
+    /// Shows the snapshot at `position`, clamped to the session.
+    ///
+    /// The app is rebuilt so that its histories, statistics and alerts end
+    /// at `position`: from the start of the session the first time, and
+    /// afterwards from the app state saved every `CHECKPOINT_INTERVAL`
+    /// snapshots on the way, so stepping backward replays at most that
+    /// many snapshots. Process lists are only copied for the displayed
+    /// snapshot.
+    pub fn seek(&mut self, position: usize) {
+        let position = position.min(self.session.len() - 1);
+        let checkpoint = (position / CHECKPOINT_INTERVAL).min(self.checkpoints.len());
+        let mut app = match checkpoint.checked_sub(1) {
+            Some(index) => self.checkpoints[index].clone_state(),
+            None => {
+                let mut app = App::without_source(self.history_len);
+                app.set_alert_rules(self.alert_rules.clone());
+                app
+            }
+        };
+        let start = checkpoint * CHECKPOINT_INTERVAL;
+        for (index, snapshot) in self.session.snapshots[..position]
+            .iter()
+            .enumerate()
+            .skip(start)
+        {
+            if index % CHECKPOINT_INTERVAL == 0
+                && self.checkpoints.len() < index / CHECKPOINT_INTERVAL
+            {
+                self.checkpoints.push(app.clone_state());
+            }
+            app.ingest_snapshot(MetricsSnapshot {
+                processes: Vec::new(),
+                ..snapshot.clone()
+            });
+        }
+        app.ingest_snapshot(self.session.snapshots[position].clone());
+        self.app = app;
+        self.position = position;
+        self.clock = Duration::ZERO;
+    }
+
+    /// Advances playback by `elapsed` wall-clock time.
+    ///
+    /// Snapshots are shown at the pace they were recorded, scaled by the
+    /// playback speed. Nothing happens while paused or once the last
+    /// snapshot is displayed.
+    ///
+    /// # Returns
+    /// * `usize` - Number of snapshots stepped over
+    pub fn advance(&mut self, elapsed: Duration) -> usize {
+        if self.paused || self.status().at_end() {
+            return 0;
+        }
+        self.clock += elapsed.mul_f32(self.speed());
+        let mut steps = 0;
+        while let Some(gap) = self.gap_to_next() {
+            if self.clock < gap {
+                break;
+            }
+            let remaining = self.clock - gap;
+            self.step_forward();
+            self.clock = remaining;
+            steps += 1;
+        }
+        if self.status().at_end() {
+            self.clock = Duration::ZERO;
+        }
+        steps
+    }
+
+    /// Returns the recorded time between the current and next snapshots.
+    fn gap_to_next(&self) -> Option<Duration> {
+        let snapshots = &self.session.snapshots;
+        let next = snapshots.get(self.position + 1)?;
+        Some(
+            next.timestamp
+                .duration_since(snapshots[self.position].timestamp)
+                .unwrap_or_default(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::network::{InterfaceUsage, NetworkCounters};
+    use crate::process::ProcessInfo;
+    use crate::snapshot::{CoreUsage, Metric};
+    use std::time::{SystemTime, UNIX_EPOCH};
+
+    fn snapshot(seconds: u64, usage: f32) -> MetricsSnapshot {
+        MetricsSnapshot {
+            timestamp: UNIX_EPOCH + Duration::from_secs(seconds),
+            cores: vec![CoreUsage {
+                id: 0,
+                name: "cpu0".to_string(),
+                usage,
+            }],
+            total_memory: 100,
+            used_memory: 50,
+            processes: vec![ProcessInfo {
+                pid: seconds as u32,
+                name: "worker".to_string(),
+                user: None,
+                cpu_usage: usage,
+                memory: 1024,
+                state: "Runnable".to_string(),
+                command: "worker --busy".to_string(),
+            }],
+            networks: vec![InterfaceUsage {
+                name: "eth0".to_string(),
+                rx_bytes_per_sec: 100.0 * seconds as f64,
+                tx_bytes_per_sec: 0.0,
+                rx_packets_per_sec: 0.0,
+                tx_packets_per_sec: 0.0,
+                total: NetworkCounters::default(),
+            }],
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    fn player(usages: &[f32]) -> Player {
+        let snapshots = usages
+            .iter()
+            .enumerate()
+            .map(|(i, &usage)| snapshot(i as u64, usage))
+            .collect();
+        Player::new(Session::new(snapshots).unwrap(), 10)
+    }
+
+    #[test]
+    fn test_writer_output_loads_back() {
+        let path = std::env::temp_dir().join(format!(
+            "system-monitor-session-{}.ndjson",
+            std::process::id()
+        ));
+        let recorded = vec![snapshot(1, 10.0), snapshot(2, 20.0)];
+        let mut writer = SessionWriter::create(&path).unwrap();
+        for snapshot in &recorded {
+            writer.record(snapshot).unwrap();
+        }
+        drop(writer);
+
+        let session = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(session.snapshots(), &recorded[..]);
+    }
+
+    #[test]
+    fn test_unavailable_usage_loads_back_as_nan() {
+        let path = std::env::temp_dir().join(format!(
+            "system-monitor-session-nan-{}.ndjson",
+            std::process::id()
+        ));
+        let mut writer = SessionWriter::create(&path).unwrap();
+        writer.record(&snapshot(1, f32::NAN)).unwrap();
+        writer.record(&snapshot(2, 20.0)).unwrap();
+        drop(writer);
+
+        let session = Session::load(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        let first = &session.snapshots()[0];
+        assert!(first.cores[0].usage.is_nan());
+        assert!(first.processes[0].cpu_usage.is_nan());
+        assert_eq!(session.snapshots()[1], snapshot(2, 20.0));
+    }
+
+    #[test]
+    fn test_parse_error_reports_line() {
+        let input = format!("{}\n\nnot json\n", ndjson::encode(&snapshot(0, 0.0)));
+        match Session::from_reader(input.as_bytes()) {
+            Err(SessionError::Parse { line, .. }) => assert_eq!(line, 3),
+            other => panic!("expected a parse error, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_empty_session_is_an_error() {
+        assert!(matches!(
+            Session::from_reader("\n".as_bytes()),
+            Err(SessionError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_app_renders_recorded_data() {
+        let mut player = player(&[10.0, 20.0, 30.0]);
+        player.step_forward();
+        let app = player.app();
+        assert_eq!(app.get_cpu_usage(), Ok(20.0));
+        assert_eq!(app.snapshot().processes[0].pid, 1);
+        assert_eq!(app.cpu_stats().max(), Some(20.0));
+        assert_eq!(app.get_network_history("eth0"), Some(vec![0, 100]));
+    }
+
+    #[test]
+    fn test_step_backward_rebuilds_history_and_stats() {
+        let mut player = player(&[10.0, 20.0, 30.0]);
+        player.seek(2);
+        assert_eq!(player.app().cpu_stats().max(), Some(30.0));
+
+        assert!(player.step_backward());
+        let app = player.app();
+        assert_eq!(player.position(), 1);
+        assert_eq!(app.get_cpu_usage(), Ok(20.0));
+        assert_eq!(app.cpu_stats().max(), Some(20.0));
+        assert_eq!(app.history(Metric::Cpu).len(), 2);
+        assert_eq!(app.snapshot().processes.len(), 1);
+    }
+
+    #[test]
+    fn test_seeking_from_checkpoints_matches_full_rebuild() {
+        let usages: Vec<f32> = (0..2 * CHECKPOINT_INTERVAL + 5)
+            .map(|i| (i % 100) as f32)
+            .collect();
+        let rules = vec!["cpu > 90% for 3s".parse::<AlertRule>().unwrap()];
+        let mut player = player(&usages);
+        player.set_alert_rules(rules.clone());
+        player.seek(usages.len() - 1);
+        assert_eq!(player.checkpoints.len(), 2);
+        for _ in 0..6 {
+            player.step_backward();
+        }
+
+        // A fresh player has no checkpoints and replays from the start
+        let mut rebuilt = self::player(&usages);
+        rebuilt.set_alert_rules(rules);
+        rebuilt.seek(player.position());
+
+        let (app, expected) = (player.app(), rebuilt.app());
+        assert_eq!(player.position(), 2 * CHECKPOINT_INTERVAL - 2);
+        assert_eq!(app.snapshot(), expected.snapshot());
+        assert_eq!(app.history(Metric::Cpu), expected.history(Metric::Cpu));
+        assert_eq!(app.cpu_stats(), expected.cpu_stats());
+        assert_eq!(app.alerts(), expected.alerts());
+        assert_eq!(app.alert_events(), expected.alert_events());
+    }
+
+    #[test]
+    fn test_alerts_follow_the_position() {
+        let mut player = player(&[95.0, 95.0, 95.0, 10.0]);
+        player.set_alert_rules(vec!["cpu > 90% for 1s".parse().unwrap()]);
+        assert_eq!(player.app().alerts().alerts()[0].state.name(), "pending");
+
+        player.seek(2);
+        assert_eq!(player.app().alerts().firing().count(), 1);
+        assert!(player.step_forward());
+        assert_eq!(player.app().alerts().alerts()[0].state.name(), "resolved");
+
+        assert!(player.step_backward());
+        assert_eq!(player.app().alerts().firing().count(), 1);
+        assert_eq!(player.app().alert_events().len(), 2);
+    }
+
+    #[test]
+    fn test_steps_stop_at_the_ends() {
+        let mut player = player(&[10.0, 20.0]);
+        assert!(!player.step_backward());
+        assert!(player.step_forward());
+        assert!(!player.step_forward());
+        assert!(player.status().at_end());
+    }
+
+    #[test]
+    fn test_advance_follows_recorded_pace() {
+        let mut player = player(&[0.0, 0.0, 0.0, 0.0]);
+        assert_eq!(player.advance(Duration::from_millis(999)), 0);
+        assert_eq!(player.advance(Duration::from_millis(1)), 1);
+        assert_eq!(player.advance(Duration::from_secs(5)), 2);
+        assert_eq!(player.position(), 3);
+        assert_eq!(player.advance(Duration::from_secs(5)), 0);
+    }
+
+    #[test]
+    fn test_speed_and_pause() {
+        let mut player = player(&[0.0, 0.0, 0.0, 0.0]);
+        player.slower();
+        assert_eq!(player.speed(), 0.5);
+        assert_eq!(player.advance(Duration::from_secs(1)), 0);
+        assert_eq!(player.advance(Duration::from_secs(1)), 1);
+
+        player.set_paused(true);
+        assert_eq!(player.advance(Duration::from_secs(60)), 0);
+        player.set_paused(false);
+        for _ in 0..SPEEDS.len() {
+            player.faster();
+        }
+        assert_eq!(player.speed(), 16.0);
+        assert_eq!(player.advance(Duration::from_millis(125)), 2);
+    }
+
+    #[test]
+    fn test_clock_going_backwards_does_not_stall() {
+        let snapshots = vec![snapshot(10, 0.0), snapshot(5, 0.0)];
+        let mut player = Player::new(Session::new(snapshots).unwrap(), 10);
+        assert_eq!(player.advance(Duration::ZERO), 1);
+        assert!(SystemTime::UNIX_EPOCH < player.app().snapshot().timestamp);
+    }
+}
@@ -0,0 +1,352 @@
+//! Point-in-time view of collected system metrics.
+//!
+//! A [`MetricsSnapshot`] is produced by every call to [`App::update`] and
+//! holds all values collected during that refresh. Because every field is
+//! read from the same refresh, the values within one snapshot are mutually
+//! consistent. Snapshots are plain data: they can be cloned, stored,
+//! compared, sent across threads and serialized (timestamps as fractional
+//! Unix seconds).
+//!
+//! [`App::update`]: crate::App::update
+
+use std::time::SystemTime;
+
+use serde::{Deserialize, Serialize};
+
+use crate::cgroup::ContainerUsage;
+use crate::cputime::CpuTimeUsage;
+use crate::disk::DiskUsage;
+use crate::error::MonitorError;
+use crate::meminfo::MemInfo;
+use crate::network::InterfaceUsage;
+use crate::pressure::Pressure;
+use crate::process::ProcessInfo;
+
+/// Usage of a single logical CPU core.
+///
+/// # Fields
+/// * `id` - Zero-based index of the core as reported by the OS
+/// * `name` - Core name as reported by the OS (e.g. `cpu0`)
+/// * `usage` - Usage as a percentage between 0.0 and 100.0
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct CoreUsage {
+    pub id: usize,
+    pub name: String,
+    #[serde(deserialize_with = "nan_as_null::deserialize")]
+    pub usage: f32,
+}
+
+/// Immutable, timestamped set of metrics collected in one refresh.
+///
+/// # Fields
+/// * `timestamp` - Wall-clock time at which the metrics were collected
+/// * `cores` - Usage of every logical core, ordered by core id
+/// * `total_memory` - Total physical memory in bytes
+/// * `used_memory` - Used physical memory in bytes
+/// * `total_swap` - Total swap space in bytes (0 if no swap is configured)
+/// * `used_swap` - Used swap space in bytes
+/// * `processes` - Every running process, in no particular order
+/// * `disks` - Every mounted filesystem, including pseudo filesystems
+/// * `networks` - Throughput of every network interface, ordered by name
+/// * `container` - Usage and limits of the monitor's cgroup, `None` outside
+///   a cgroup v2 hierarchy (absent from data recorded before it existed)
+/// * `pressure` - Pressure stall information, `None` if the kernel does not
+///   report it (absent from data recorded before it existed)
+/// * `cpu_time` - Share of time spent in each CPU mode, overall and per
+///   core, `None` until two readings of `/proc/stat` were taken or on other
+///   platforms (absent from data recorded before it existed)
+/// * `meminfo` - Memory breakdown from `/proc/meminfo`, `None` on other
+///   platforms (absent from data recorded before it existed); when present,
+///   `total_memory` and `used_memory` are based on its `MemAvailable`
+///
+/// # Example
+/// ```no_run
+/// use system_monitor::App;
+///
+/// let mut app = App::new();
+/// let snapshot = app.update();
+/// match (snapshot.cpu_usage(), snapshot.memory_usage()) {
+///     (Ok(cpu), Ok(memory)) => {
+///         println!("CPU {:.1}% / Memory {:.1}% at {:?}", cpu, memory, snapshot.timestamp)
+///     }
+///     (cpu, memory) => eprintln!("Incomplete reading: {:?} / {:?}", cpu, memory),
+/// }
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MetricsSnapshot {
+    #[serde(with = "unix_seconds")]
+    pub timestamp: SystemTime,
+    pub cores: Vec<CoreUsage>,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    pub processes: Vec<ProcessInfo>,
+    pub disks: Vec<DiskUsage>,
+    pub networks: Vec<InterfaceUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub container: Option<ContainerUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pressure: Option<Pressure>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cpu_time: Option<CpuTimeUsage>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub meminfo: Option<MemInfo>,
+}
+
+impl Default for MetricsSnapshot {
+    /// An empty snapshot taken at the Unix epoch.
+    fn default() -> Self {
+        Self {
+            timestamp: SystemTime::UNIX_EPOCH,
+            cores: Vec::new(),
+            total_memory: 0,
+            used_memory: 0,
+            total_swap: 0,
+            used_swap: 0,
+            processes: Vec::new(),
+            disks: Vec::new(),
+            networks: Vec::new(),
+            container: None,
+            pressure: None,
+            cpu_time: None,
+            meminfo: None,
+        }
+    }
+}
+
+/// Scalar percentage metric that can be read from a snapshot.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Metric {
+    /// Average CPU usage across all cores
+    Cpu,
+    /// Physical memory usage
+    Memory,
+    /// Swap usage
+    Swap,
+}
+
+impl Metric {
+    /// All metrics in display order.
+    pub const ALL: [Metric; 3] = [Metric::Cpu, Metric::Memory, Metric::Swap];
+
+    /// Returns the lowercase identifier of the metric.
+    pub fn name(self) -> &'static str {
+        match self {
+            Metric::Cpu => "cpu",
+            Metric::Memory => "memory",
+            Metric::Swap => "swap",
+        }
+    }
+
+    /// Reads the metric from a snapshot.
+    ///
+    /// # Returns
+    /// * `Option<f32>` - The value as a percentage, or `None` if the metric
+    ///   is not available on this host (e.g. swap without swap space, or
+    ///   memory when total memory is reported as zero)
+    pub fn value(self, snapshot: &MetricsSnapshot) -> Option<f32> {
+        match self {
+            Metric::Cpu => snapshot.cpu_usage().ok(),
+            Metric::Memory => snapshot.memory_usage().ok(),
+            Metric::Swap => snapshot.swap_usage(),
+        }
+    }
+}
+
+impl MetricsSnapshot {
+    /// Returns the average usage of all cores.
+    ///
+    /// # Returns
+    /// * `Ok(f32)` - Average core usage as a percentage
+    /// * `Err(MonitorError::NoCpuCores)` - No core was reported
+    /// * `Err(MonitorError::NonFinite)` - A core reported a NaN or infinite usage
+    pub fn cpu_usage(&self) -> Result<f32, MonitorError> {
+        if self.cores.is_empty() {
+            return Err(MonitorError::NoCpuCores);
+        }
+        let usage = self.cores.iter().map(|core| core.usage).sum::<f32>() / self.cores.len() as f32;
+        finite(usage, Metric::Cpu)
+    }
+
+    /// Returns the memory usage percentage.
+    ///
+    /// # Returns
+    /// * `Ok(f32)` - Used memory as a percentage of total memory
+    /// * `Err(MonitorError::NoMemory)` - Total memory was reported as zero
+    pub fn memory_usage(&self) -> Result<f32, MonitorError> {
+        if self.total_memory == 0 {
+            return Err(MonitorError::NoMemory);
+        }
+        Ok((self.used_memory as f32 / self.total_memory as f32) * 100.0)
+    }
+
+    /// Returns `true` if the host has swap space configured.
+    pub fn has_swap(&self) -> bool {
+        self.total_swap > 0
+    }
+
+    /// Returns the swap usage percentage.
+    ///
+    /// # Returns
+    /// * `Option<f32>` - Used swap as a percentage of total swap, or `None`
+    ///   if the host has no swap configured
+    pub fn swap_usage(&self) -> Option<f32> {
+        self.has_swap()
+            .then(|| (self.used_swap as f32 / self.total_swap as f32) * 100.0)
+    }
+}
+
+/// Passes `value` through if it is finite.
+fn finite(value: f32, metric: Metric) -> Result<f32, MonitorError> {
+    if value.is_finite() {
+        Ok(value)
+    } else {
+        Err(MonitorError::NonFinite(metric))
+    }
+}
+
+/// Reads back an `f32` that may be NaN.
+///
+/// JSON has no NaN, so serde_json writes a NaN usage (unavailable) as
+/// `null`. Reading `null` as NaN lets such snapshots be replayed instead of
+/// rejecting the whole recording.
+pub(crate) mod nan_as_null {
+    use serde::{Deserialize, Deserializer};
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<f32, D::Error> {
+        Ok(Option::<f32>::deserialize(deserializer)?.unwrap_or(f32::NAN))
+    }
+}
+
+/// Serializes a `SystemTime` as fractional seconds since the Unix epoch.
+///
+/// Plain numbers are easier to consume from `jq` or a spreadsheet than
+/// serde's default `{secs_since_epoch, nanos_since_epoch}` structure.
+mod unix_seconds {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        let seconds = match time.duration_since(UNIX_EPOCH) {
+            Ok(after) => after.as_secs_f64(),
+            Err(before) => -before.duration().as_secs_f64(),
+        };
+        serializer.serialize_f64(seconds)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let seconds = f64::deserialize(deserializer)?;
+        let offset = Duration::try_from_secs_f64(seconds.abs()).map_err(de::Error::custom)?;
+        let time = if seconds >= 0.0 {
+            UNIX_EPOCH.checked_add(offset)
+        } else {
+            UNIX_EPOCH.checked_sub(offset)
+        };
+        time.ok_or_else(|| de::Error::custom("timestamp out of range"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn snapshot(total_memory: u64, used_memory: u64) -> MetricsSnapshot {
+        MetricsSnapshot {
+            total_memory,
+            used_memory,
+            ..MetricsSnapshot::default()
+        }
+    }
+
+    #[test]
+    fn test_memory_usage_percentage() {
+        assert_eq!(snapshot(1000, 250).memory_usage(), Ok(25.0));
+        assert_eq!(snapshot(1000, 1000).memory_usage(), Ok(100.0));
+    }
+
+    #[test]
+    fn test_zero_total_memory_is_an_error() {
+        assert_eq!(snapshot(0, 0).memory_usage(), Err(MonitorError::NoMemory));
+        assert_eq!(Metric::Memory.value(&snapshot(0, 0)), None);
+    }
+
+    #[test]
+    fn test_cpu_usage_averages_cores() {
+        let mut snapshot = snapshot(1000, 0);
+        snapshot.cores = [10.0, 20.0, 60.0]
+            .iter()
+            .enumerate()
+            .map(|(id, &usage)| CoreUsage {
+                id,
+                name: format!("cpu{}", id),
+                usage,
+            })
+            .collect();
+        assert_eq!(snapshot.cpu_usage(), Ok(30.0));
+
+        snapshot.cores[1].usage = f32::NAN;
+        assert_eq!(
+            snapshot.cpu_usage(),
+            Err(MonitorError::NonFinite(Metric::Cpu))
+        );
+    }
+
+    #[test]
+    fn test_no_cores_is_an_error() {
+        assert_eq!(snapshot(1000, 0).cpu_usage(), Err(MonitorError::NoCpuCores));
+    }
+
+    #[test]
+    fn test_swap_usage_percentage() {
+        let mut with_swap = snapshot(1000, 0);
+        with_swap.total_swap = 400;
+        with_swap.used_swap = 100;
+        assert!(with_swap.has_swap());
+        assert_eq!(with_swap.swap_usage(), Some(25.0));
+    }
+
+    #[test]
+    fn test_no_swap_configured() {
+        let without_swap = snapshot(1000, 0);
+        assert!(!without_swap.has_swap());
+        assert_eq!(without_swap.swap_usage(), None);
+    }
+
+    #[test]
+    fn test_metric_values() {
+        let mut snapshot = snapshot(1000, 500);
+        snapshot.cores = vec![CoreUsage {
+            id: 0,
+            name: "cpu0".to_string(),
+            usage: 12.5,
+        }];
+        assert_eq!(Metric::Cpu.value(&snapshot), Some(12.5));
+        assert_eq!(Metric::Memory.value(&snapshot), Some(50.0));
+        assert_eq!(Metric::Swap.value(&snapshot), None);
+    }
+
+    #[test]
+    fn test_snapshot_is_send_and_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<MetricsSnapshot>();
+    }
+
+    #[test]
+    fn test_clone_compares_equal() {
+        let original = snapshot(2048, 1024);
+        let copy = original.clone();
+        assert_eq!(original, copy);
+    }
+
+    #[test]
+    fn test_timestamp_serializes_as_unix_seconds() {
+        let mut original = snapshot(2048, 1024);
+        original.timestamp = SystemTime::UNIX_EPOCH + std::time::Duration::from_millis(1_500);
+        let json = serde_json::to_string(&original).unwrap();
+        assert!(json.starts_with("{\"timestamp\":1.5,"));
+        let parsed: MetricsSnapshot = serde_json::from_str(&json).unwrap();
+        assert_eq!(parsed, original);
+    }
+}
@@ -0,0 +1,546 @@
+//! Pluggable providers of raw system metrics.
+//!
+//! [`App`](crate::App) does not talk to the operating system directly.
+//! Instead it asks a [`MetricSource`] for a [`Reading`] on every update and
+//! derives everything else (averages, rates, history, statistics) from it.
+//!
+//! Two sources are provided:
+//! * [`SysinfoSource`] - Reads the live machine through `sysinfo` (default),
+//!   refreshing only the collectors due according to a [`RefreshPlan`]
+//! * [`ScriptedSource`] - Replays a fixed list of readings, which makes
+//!   computations and rendering deterministic in tests
+
+use std::collections::VecDeque;
+use std::fmt::Debug;
+use std::path::{Path, PathBuf};
+use std::time::{Instant, SystemTime};
+
+use sysinfo::{
+    CpuExt, CpuRefreshKind, DiskExt, NetworkExt, NetworksExt, PidExt, ProcessExt,
+    ProcessRefreshKind, System, SystemExt, UserExt,
+};
+
+use crate::cgroup::{Cgroup, ContainerUsage};
+use crate::cputime::ProcStat;
+use crate::disk::DiskUsage;
+use crate::meminfo::MemInfo;
+use crate::network::NetworkCounters;
+use crate::pressure::Pressure;
+use crate::process::ProcessInfo;
+use crate::refresh::{Collector, RefreshPlan, RefreshScheduler};
+use crate::snapshot::CoreUsage;
+
+/// Mount point of procfs on Linux.
+pub const DEFAULT_PROC_ROOT: &str = "/proc";
+
+/// Raw metrics as reported by a source, before any derivation.
+///
+/// The process, disk and network fields are `None` when the corresponding
+/// collector was not refreshed for this reading; [`App`](crate::App) then
+/// keeps the values from the previous snapshot.
+///
+/// # Fields
+/// * `timestamp` - Wall-clock time at which the metrics were read
+/// * `cores` - Usage of every logical core, ordered by core id
+/// * `total_memory` - Total physical memory in bytes
+/// * `used_memory` - Used physical memory in bytes
+/// * `total_swap` - Total swap space in bytes
+/// * `used_swap` - Used swap space in bytes
+/// * `processes` - Every running process, if refreshed
+/// * `disks` - Every mounted filesystem, if refreshed
+/// * `network_counters` - Cumulative traffic counters per interface, if
+///   refreshed
+/// * `container` - Usage and limits of the monitor's cgroup, if one was
+///   detected and read
+/// * `pressure` - Pressure stall information, if the kernel reports it and
+///   it was read
+/// * `cpu_times` - Cumulative time per CPU mode from `/proc/stat`, if
+///   refreshed on Linux
+/// * `meminfo` - Memory breakdown from `/proc/meminfo`, if refreshed on
+///   Linux; [`App`](crate::App) then bases the used memory on it
+#[derive(Debug, Clone, PartialEq)]
+pub struct Reading {
+    pub timestamp: SystemTime,
+    pub cores: Vec<CoreUsage>,
+    pub total_memory: u64,
+    pub used_memory: u64,
+    pub total_swap: u64,
+    pub used_swap: u64,
+    pub processes: Option<Vec<ProcessInfo>>,
+    pub disks: Option<Vec<DiskUsage>>,
+    pub network_counters: Option<Vec<(String, NetworkCounters)>>,
+    pub container: Option<ContainerUsage>,
+    pub pressure: Option<Pressure>,
+    pub cpu_times: Option<ProcStat>,
+    pub meminfo: Option<MemInfo>,
+}
+
+impl Default for Reading {
+    /// An empty reading taken at the Unix epoch.
+    fn default() -> Self {
+        Self {
+            timestamp: SystemTime::UNIX_EPOCH,
+            cores: Vec::new(),
+            total_memory: 0,
+            used_memory: 0,
+            total_swap: 0,
+            used_swap: 0,
+            processes: None,
+            disks: None,
+            network_counters: None,
+            container: None,
+            pressure: None,
+            cpu_times: None,
+            meminfo: None,
+        }
+    }
+}
+
+/// Provider of raw system metrics.
+///
+/// Implementations must be `Send` so an `App` can be moved to a
+/// collector thread.
+///
+/// # Example
+/// ```
+/// use system_monitor::source::{MetricSource, Reading};
+///
+/// /// A source reporting a constant memory usage of 50%.
+/// #[derive(Debug)]
+/// struct HalfFull;
+///
+/// impl MetricSource for HalfFull {
+///     fn read(&mut self) -> Reading {
+///         Reading {
+///             total_memory: 100,
+///             used_memory: 50,
+///             ..Reading::default()
+///         }
+///     }
+/// }
+/// ```
+pub trait MetricSource: Debug + Send {
+    /// Refreshes the underlying data and returns the current metrics.
+    fn read(&mut self) -> Reading;
+}
+
+/// Source reading the live machine through `sysinfo`.
+///
+/// Each read refreshes only the collectors that are enabled and due
+/// according to the source's [`RefreshPlan`]; the values of the other
+/// collectors are left out of the reading. When the process runs in a
+/// cgroup v2 hierarchy, the cgroup's usage and limits are read along with
+/// the CPU and memory collectors, and so is the pressure stall information
+/// under the proc root. The time per CPU mode is read from `stat` under the
+/// proc root along with the CPU collector, and the memory breakdown from
+/// `meminfo` along with the memory collector.
+///
+/// # Example
+/// ```no_run
+/// use std::time::Duration;
+/// use system_monitor::refresh::{Collector, RefreshPlan};
+/// use system_monitor::{App, SysinfoSource};
+///
+/// let plan = RefreshPlan::default().with_interval(Collector::Processes, Duration::from_secs(5));
+/// let mut app = App::with_source(SysinfoSource::with_plan(plan), 300);
+/// app.update();
+/// ```
+#[derive(Debug)]
+pub struct SysinfoSource {
+    system: System,
+    scheduler: RefreshScheduler,
+    cgroup: Option<Cgroup>,
+    proc_root: PathBuf,
+}
+
+impl SysinfoSource {
+    /// Creates a source using the default [`RefreshPlan`].
+    pub fn new() -> Self {
+        Self::with_plan(RefreshPlan::default())
+    }
+
+    /// Creates a source refreshing collectors according to `plan`.
+    ///
+    /// Only the data needed by the enabled collectors is loaded. Nothing is
+    /// refreshed until the first read, which refreshes every enabled
+    /// collector and is the baseline for usage and rate computations, so
+    /// the usage of the next refresh covers the whole time in between.
+    pub fn with_plan(plan: RefreshPlan) -> Self {
+        let mut system = System::new();
+        if plan.is_enabled(Collector::Processes) {
+            system.refresh_users_list();
+        }
+        if plan.is_enabled(Collector::Disks) {
+            system.refresh_disks_list();
+        }
+        if plan.is_enabled(Collector::Networks) {
+            system.refresh_networks_list();
+        }
+
+        Self {
+            system,
+            scheduler: RefreshScheduler::new(plan),
+            cgroup: Cgroup::detect(),
+            proc_root: PathBuf::from(DEFAULT_PROC_ROOT),
+        }
+    }
+
+    /// Returns the plan driving this source's refreshes.
+    pub fn plan(&self) -> &RefreshPlan {
+        self.scheduler.plan()
+    }
+
+    /// Replaces the detected cgroup, e.g. with one found under another
+    /// sysfs root through [`Cgroup::detect_in`], or `None` to report host
+    /// figures only.
+    pub fn with_cgroup(mut self, cgroup: Option<Cgroup>) -> Self {
+        self.cgroup = cgroup;
+        self
+    }
+
+    /// Returns the cgroup whose limits are reported, if any.
+    pub fn cgroup(&self) -> Option<&Cgroup> {
+        self.cgroup.as_ref()
+    }
+
+    /// Reads the Linux `/proc` files (`stat`, `meminfo` and `pressure/*`)
+    /// under `root` instead of [`DEFAULT_PROC_ROOT`].
+    ///
+    /// Pointing the root at a directory of fixture files drives the
+    /// parsing of those files without a Linux host.
+    pub fn with_proc_root(mut self, root: impl Into<PathBuf>) -> Self {
+        self.proc_root = root.into();
+        self
+    }
+
+    /// Returns the root the Linux `/proc` files are read under.
+    pub fn proc_root(&self) -> &Path {
+        &self.proc_root
+    }
+
+    /// Refreshes the sysinfo data backing one collector.
+    fn refresh(&mut self, collector: Collector) {
+        match collector {
+            Collector::Cpu => self
+                .system
+                .refresh_cpu_specifics(CpuRefreshKind::new().with_cpu_usage()),
+            Collector::Memory => self.system.refresh_memory(),
+            Collector::Processes => self
+                .system
+                .refresh_processes_specifics(ProcessRefreshKind::new().with_cpu().with_user()),
+            Collector::Disks => self.system.refresh_disks(),
+            Collector::Networks => self.system.refresh_networks(),
+        }
+    }
+
+    /// Reads the usage of every logical core.
+    fn read_cores(&self) -> Vec<CoreUsage> {
+        self.system
+            .cpus()
+            .iter()
+            .enumerate()
+            .map(|(id, cpu)| CoreUsage {
+                id,
+                name: cpu.name().to_string(),
+                usage: cpu.cpu_usage(),
+            })
+            .collect()
+    }
+
+    /// Reads the cumulative traffic counters of every interface.
+    fn read_network_counters(&self) -> Vec<(String, NetworkCounters)> {
+        self.system
+            .networks()
+            .iter()
+            .map(|(name, data)| {
+                let counters = NetworkCounters {
+                    rx_bytes: data.total_received(),
+                    tx_bytes: data.total_transmitted(),
+                    rx_packets: data.total_packets_received(),
+                    tx_packets: data.total_packets_transmitted(),
+                };
+                (name.clone(), counters)
+            })
+            .collect()
+    }
+
+    /// Reads every mounted filesystem.
+    fn read_disks(&self) -> Vec<DiskUsage> {
+        self.system
+            .disks()
+            .iter()
+            .map(|disk| DiskUsage {
+                name: disk.name().to_string_lossy().into_owned(),
+                mount_point: disk.mount_point().to_string_lossy().into_owned(),
+                file_system: String::from_utf8_lossy(disk.file_system()).into_owned(),
+                total: disk.total_space(),
+                available: disk.available_space(),
+            })
+            .collect()
+    }
+
+    /// Reads every running process.
+    fn read_processes(&self) -> Vec<ProcessInfo> {
+        self.system
+            .processes()
+            .values()
+            .map(|process| ProcessInfo {
+                pid: process.pid().as_u32(),
+                name: process.name().to_string(),
+                user: process
+                    .user_id()
+                    .and_then(|uid| self.system.get_user_by_id(uid))
+                    .map(|user| user.name().to_string()),
+                cpu_usage: process.cpu_usage(),
+                memory: process.memory(),
+                state: process.status().to_string(),
+                command: process.cmd().join(" "),
+            })
+            .collect()
+    }
+
+    /// Builds a reading from the last refresh.
+    ///
+    /// CPU and memory values are cheap to copy and always included; the
+    /// process, disk and network lists only if their collector is in
+    /// `refreshed`. The cgroup and the pressure stall information are read
+    /// when the CPU or memory collector was refreshed; a cgroup that can no
+    /// longer be read is left out. The time per CPU mode is read when the
+    /// CPU collector was refreshed, the memory breakdown when the memory
+    /// collector was.
+    fn reading(&self, refreshed: &[Collector]) -> Reading {
+        let included = |collector| refreshed.contains(&collector);
+        let resources = included(Collector::Cpu) || included(Collector::Memory);
+        let container = resources
+            .then_some(self.cgroup.as_ref())
+            .flatten()
+            .and_then(|cgroup| cgroup.read().ok());
+        let pressure = resources.then(|| Pressure::read(&self.proc_root)).flatten();
+        let cpu_times = included(Collector::Cpu)
+            .then(|| ProcStat::read(&self.proc_root))
+            .flatten();
+        let meminfo = included(Collector::Memory)
+            .then(|| MemInfo::read(&self.proc_root))
+            .flatten();
+        Reading {
+            timestamp: SystemTime::now(),
+            cores: self.read_cores(),
+            total_memory: self.system.total_memory(),
+            used_memory: self.system.used_memory(),
+            total_swap: self.system.total_swap(),
+            used_swap: self.system.used_swap(),
+            processes: included(Collector::Processes).then(|| self.read_processes()),
+            disks: included(Collector::Disks).then(|| self.read_disks()),
+            network_counters: included(Collector::Networks).then(|| self.read_network_counters()),
+            container,
+            pressure,
+            cpu_times,
+            meminfo,
+        }
+    }
+}
+
+impl Default for SysinfoSource {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl MetricSource for SysinfoSource {
+    fn read(&mut self) -> Reading {
+        let due = self.scheduler.due(Instant::now());
+        for &collector in &due {
+            self.refresh(collector);
+        }
+        self.reading(&due)
+    }
+}
+
+/// Source replaying a fixed sequence of readings.
+///
+/// Each call to [`MetricSource::read`] returns the next reading. Once the
+/// script is exhausted, the last reading is repeated.
+///
+/// # Example
+/// ```
+/// use system_monitor::source::{Reading, ScriptedSource};
+/// use system_monitor::App;
+///
+/// let reading = |used_memory| Reading {
+///     total_memory: 1000,
+///     used_memory,
+///     ..Reading::default()
+/// };
+/// let source = ScriptedSource::new(vec![reading(0), reading(250), reading(500)]);
+/// let mut app = App::with_source(source, 10);
+///
+/// assert_eq!(app.update().memory_usage(), Ok(25.0));
+/// assert_eq!(app.update().memory_usage(), Ok(50.0));
+/// assert_eq!(app.update().memory_usage(), Ok(50.0));
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ScriptedSource {
+    readings: VecDeque<Reading>,
+    last: Reading,
+}
+
+impl ScriptedSource {
+    /// Creates a source returning `readings` in order.
+    pub fn new(readings: Vec<Reading>) -> Self {
+        Self {
+            readings: readings.into(),
+            last: Reading::default(),
+        }
+    }
+
+    /// Appends a reading to the end of the script.
+    pub fn push(&mut self, reading: Reading) {
+        self.readings.push_back(reading);
+    }
+
+    /// Returns the number of readings not yet returned.
+    pub fn remaining(&self) -> usize {
+        self.readings.len()
+    }
+}
+
+impl MetricSource for ScriptedSource {
+    fn read(&mut self) -> Reading {
+        if let Some(reading) = self.readings.pop_front() {
+            self.last = reading;
+        }
+        self.last.clone()
+    }
+}
+
+/// Proc root holding the fixture files under `tests/fixtures`.
+#[cfg(test)]
+pub(crate) fn fixture_proc_root() -> PathBuf {
+    Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    fn at(seconds: u64) -> Reading {
+        Reading {
+            timestamp: SystemTime::UNIX_EPOCH + Duration::from_secs(seconds),
+            ..Reading::default()
+        }
+    }
+
+    #[test]
+    fn test_scripted_source_replays_in_order() {
+        let mut source = ScriptedSource::new(vec![at(1), at(2)]);
+        assert_eq!(source.remaining(), 2);
+        assert_eq!(source.read(), at(1));
+        assert_eq!(source.read(), at(2));
+        assert_eq!(source.remaining(), 0);
+    }
+
+    #[test]
+    fn test_scripted_source_repeats_last_reading() {
+        let mut source = ScriptedSource::new(vec![at(1)]);
+        source.read();
+        assert_eq!(source.read(), at(1));
+        source.push(at(5));
+        assert_eq!(source.read(), at(5));
+    }
+
+    #[test]
+    fn test_empty_script_returns_default() {
+        let mut source = ScriptedSource::default();
+        assert_eq!(source.read(), Reading::default());
+    }
+
+    #[test]
+    fn test_sysinfo_source_reads_live_machine() {
+        let mut source = SysinfoSource::new();
+        let reading = source.read();
+        assert!(!reading.cores.is_empty(), "Should detect at least one CPU");
+        assert!(reading.used_memory <= reading.total_memory);
+        assert!(
+            reading.processes.is_some(),
+            "First read refreshes everything"
+        );
+    }
+
+    #[test]
+    fn test_sysinfo_source_skips_collectors_not_due() {
+        let plan =
+            RefreshPlan::default().with_interval(Collector::Processes, Duration::from_secs(3600));
+        let mut source = SysinfoSource::with_plan(plan);
+        assert!(source.read().processes.is_some());
+
+        let reading = source.read();
+        assert!(reading.processes.is_none(), "Processes are not due yet");
+        assert!(reading.network_counters.is_some());
+        assert!(!reading.cores.is_empty());
+    }
+
+    #[test]
+    fn test_sysinfo_source_disabled_collectors() {
+        let plan = RefreshPlan::none().with_interval(Collector::Memory, Duration::ZERO);
+        let mut source = SysinfoSource::with_plan(plan);
+        let reading = source.read();
+        assert!(reading.total_memory > 0);
+        assert!(reading.cores.is_empty(), "CPU collector is disabled");
+        assert_eq!(reading.processes, None);
+        assert_eq!(reading.disks, None);
+        assert_eq!(reading.network_counters, None);
+    }
+
+    #[test]
+    fn test_sysinfo_source_reads_cgroup() {
+        let root = std::env::temp_dir().join(format!(
+            "system-monitor-source-cgroup-{}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&root).unwrap();
+        std::fs::write(root.join("cgroup.controllers"), "cpu memory\n").unwrap();
+        std::fs::write(root.join("memory.max"), "1048576\n").unwrap();
+        let cgroup = Cgroup::detect_in(&root, "0::/\n");
+
+        let plan = RefreshPlan::none()
+            .with_interval(Collector::Memory, Duration::ZERO)
+            .with_interval(Collector::Disks, Duration::from_secs(3600));
+        let mut source = SysinfoSource::with_plan(plan).with_cgroup(cgroup);
+        let reading = source.read();
+        std::fs::remove_dir_all(&root).unwrap();
+
+        let container = reading.container.expect("Memory collector was due");
+        assert_eq!(container.memory_limit, Some(1_048_576));
+
+        let mut source = SysinfoSource::with_plan(plan).with_cgroup(None);
+        assert_eq!(source.read().container, None);
+    }
+
+    #[test]
+    fn test_sysinfo_source_reads_proc_files_with_their_collectors() {
+        // Collector due, then whether pressure, CPU times and meminfo are read
+        let cases = [
+            (Collector::Cpu, true, true, false),
+            (Collector::Memory, true, false, true),
+            (Collector::Disks, false, false, false),
+        ];
+        for (collector, pressure, cpu_times, meminfo) in cases {
+            let plan = RefreshPlan::none().with_interval(collector, Duration::ZERO);
+            let mut source = SysinfoSource::with_plan(plan).with_proc_root(fixture_proc_root());
+            assert_eq!(source.proc_root(), fixture_proc_root());
+            let reading = source.read();
+            assert_eq!(reading.pressure.is_some(), pressure, "{:?}", collector);
+            assert_eq!(reading.cpu_times.is_some(), cpu_times, "{:?}", collector);
+            assert_eq!(reading.meminfo.is_some(), meminfo, "{:?}", collector);
+        }
+
+        let plan = RefreshPlan::every_tick();
+        let reading = SysinfoSource::with_plan(plan)
+            .with_proc_root("/nonexistent")
+            .read();
+        assert_eq!(reading.pressure, None);
+        assert_eq!(reading.cpu_times, None);
+        assert_eq!(reading.meminfo, None);
+    }
+}
@@ -0,0 +1,88 @@
+//! Timestamp formatting.
+//!
+//! Recorded files use ISO-8601 / RFC 3339 timestamps in UTC so that they
+//! sort lexically and parse in spreadsheets and log tools without a time
+//! zone database.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Formats `time` as an ISO-8601 UTC timestamp with millisecond precision.
+///
+/// Times before the Unix epoch are clamped to the epoch.
+///
+/// # Example
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use system_monitor::timefmt::iso8601;
+///
+/// let time = UNIX_EPOCH + Duration::from_millis(1_700_000_000_123);
+/// assert_eq!(iso8601(time), "2023-11-14T22:13:20.123Z");
+/// ```
+pub fn iso8601(time: SystemTime) -> String {
+    let since_epoch = time.duration_since(UNIX_EPOCH).unwrap_or_default();
+    let seconds = since_epoch.as_secs();
+    let (year, month, day) = civil_from_days((seconds / 86_400) as i64);
+    let second_of_day = seconds % 86_400;
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}.{:03}Z",
+        year,
+        month,
+        day,
+        second_of_day / 3600,
+        second_of_day / 60 % 60,
+        second_of_day % 60,
+        since_epoch.subsec_millis()
+    )
+}
+
+/// Converts days since 1970-01-01 into a proleptic Gregorian date.
+///
+/// Howard Hinnant's `civil_from_days` algorithm.
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let z = days + 719_468;
+    let era = z.div_euclid(146_097);
+    let day_of_era = z.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * shifted_month + 2) / 5 + 1) as u32;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    } as u32;
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn test_epoch() {
+        assert_eq!(iso8601(UNIX_EPOCH), "1970-01-01T00:00:00.000Z");
+    }
+
+    #[test]
+    fn test_leap_day() {
+        // 2024-02-29T12:00:00Z
+        let time = UNIX_EPOCH + Duration::from_secs(1_709_208_000);
+        assert_eq!(iso8601(time), "2024-02-29T12:00:00.000Z");
+    }
+
+    #[test]
+    fn test_end_of_year() {
+        // 1999-12-31T23:59:59Z
+        let time = UNIX_EPOCH + Duration::from_secs(946_684_799);
+        assert_eq!(iso8601(time), "1999-12-31T23:59:59.000Z");
+    }
+
+    #[test]
+    fn test_before_epoch_is_clamped() {
+        let time = UNIX_EPOCH - Duration::from_secs(10);
+        assert_eq!(iso8601(time), "1970-01-01T00:00:00.000Z");
+    }
+}
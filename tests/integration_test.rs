@@ -13,8 +13,25 @@
 //! 3. Updates values consistently
 //! 4. Handles resource calculations properly
 
+#![allow(clippy::manual_range_contains)]
+
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::thread;
 use std::time::Duration;
+use system_monitor::alert::Transition;
+use system_monitor::cgroup::Cgroup;
+use system_monitor::cputime::{parse_stat, ProcStat};
+use system_monitor::csv::{CsvRecorder, MetricGroup, Rotation};
+use system_monitor::meminfo::MemInfo;
+use system_monitor::notify::ActionKind;
+use system_monitor::session::{Player, Session, SessionWriter};
 use system_monitor::App; // This assumes we'll make our App public
+use system_monitor::{
+    ndjson, AlertAction, AlertEngine, AlertRule, BackgroundCollector, BackgroundNotifier,
+    Collector, CoreUsage, DiskUsage, Metric, MetricsServer, NetworkCounters, Notifier, Reading,
+    RefreshPlan, ScriptedSource, SysinfoSource,
+};
 
 /// Tests the system monitoring flow.
 ///
@@ -28,15 +45,15 @@ fn test_system_monitoring_flow() {
     let mut app = App::new();
 
     // Initial readings
-    let initial_cpu = app.get_cpu_usage();
-    let initial_memory = app.get_memory_usage();
+    let initial_cpu = app.get_cpu_usage().unwrap();
+    let initial_memory = app.get_memory_usage().unwrap();
 
     // Wait a bit and take new readings
     std::thread::sleep(Duration::from_secs(1));
     app.update();
 
-    let updated_cpu = app.get_cpu_usage();
-    let updated_memory = app.get_memory_usage();
+    let updated_cpu = app.get_cpu_usage().unwrap();
+    let updated_memory = app.get_memory_usage().unwrap();
 
     // Verify readings are within valid ranges
     assert!(initial_cpu >= 0.0 && initial_cpu <= 100.0);
@@ -62,8 +79,8 @@ fn test_multiple_updates() {
     // Test multiple consecutive updates
     for _ in 0..5 {
         app.update();
-        let cpu = app.get_cpu_usage();
-        let memory = app.get_memory_usage();
+        let cpu = app.get_cpu_usage().unwrap();
+        let memory = app.get_memory_usage().unwrap();
 
         assert!(cpu >= 0.0 && cpu <= 100.0);
         assert!(memory >= 0.0 && memory <= 100.0);
@@ -71,3 +88,582 @@ fn test_multiple_updates() {
         std::thread::sleep(Duration::from_millis(100));
     }
 }
+
+/// Tests that snapshots can be handed to other threads.
+///
+/// Verifies that:
+/// * `App::update` returns the snapshot it stores
+/// * A snapshot moved to another thread keeps its values
+/// * Values within a snapshot are consistent with each other
+#[test]
+fn test_snapshot_across_threads() {
+    let mut app = App::new();
+    std::thread::sleep(Duration::from_millis(100));
+    let snapshot = app.update();
+    assert_eq!(&snapshot, app.snapshot());
+
+    let expected = snapshot.clone();
+    let received = std::thread::spawn(move || snapshot).join().unwrap();
+    assert_eq!(received, expected);
+
+    assert!(received.used_memory <= received.total_memory);
+    let average =
+        received.cores.iter().map(|core| core.usage).sum::<f32>() / received.cores.len() as f32;
+    assert!((received.cpu_usage().unwrap() - average).abs() < 0.01);
+}
+
+/// Tests the monitoring flow against a scripted source.
+///
+/// Verifies that:
+/// * Every update consumes exactly one scripted reading
+/// * Derived values match the script exactly
+/// * Statistics cover every update
+#[test]
+fn test_scripted_monitoring_flow() {
+    let reading = |usage: f32, used_memory: u64| Reading {
+        cores: vec![CoreUsage {
+            id: 0,
+            name: "cpu0".to_string(),
+            usage,
+        }],
+        total_memory: 200,
+        used_memory,
+        ..Reading::default()
+    };
+    let source = ScriptedSource::new(vec![reading(0.0, 0), reading(10.0, 50), reading(90.0, 150)]);
+    let mut app = App::with_source(source, 10);
+
+    app.update();
+    assert_eq!(app.get_cpu_usage(), Ok(10.0));
+    assert_eq!(app.get_memory_usage(), Ok(25.0));
+
+    app.update();
+    assert_eq!(app.get_cpu_usage(), Ok(90.0));
+    assert_eq!(app.get_memory_usage(), Ok(75.0));
+    assert_eq!(app.cpu_stats().average(), Some(50.0));
+    assert_eq!(app.history(Metric::Memory).len(), 2);
+}
+
+/// Tests collecting live metrics on a background thread.
+///
+/// Verifies that:
+/// * Snapshots are delivered without the caller updating the app
+/// * The latest snapshot matches the last one delivered
+/// * The app handed back on stop has recorded the samples
+#[test]
+fn test_background_collection() {
+    let collector = BackgroundCollector::spawn(App::new(), Duration::from_millis(50)).unwrap();
+
+    let mut received = Vec::new();
+    while received.len() < 3 {
+        let snapshot = collector
+            .recv_timeout(Duration::from_secs(5))
+            .expect("Collector should deliver snapshots on its own");
+        received.push(snapshot);
+    }
+    assert!(received.windows(2).all(|w| w[0].timestamp < w[1].timestamp));
+
+    let app = collector.stop();
+    assert!(app.history(Metric::Cpu).len() >= 3);
+    let cpu = app.get_cpu_usage().unwrap();
+    assert!((0.0..=100.0).contains(&cpu));
+}
+
+/// Sends a `GET` request to `addr` and returns the raw response.
+fn http_get(addr: std::net::SocketAddr, path: &str) -> String {
+    let mut stream = TcpStream::connect(addr).unwrap();
+    stream
+        .set_read_timeout(Some(Duration::from_secs(5)))
+        .unwrap();
+    write!(
+        stream,
+        "GET {} HTTP/1.1\r\nHost: localhost\r\nAccept: text/plain\r\n\r\n",
+        path
+    )
+    .unwrap();
+    let mut response = String::new();
+    stream.read_to_string(&mut response).unwrap();
+    response
+}
+
+/// Tests scraping the Prometheus endpoint over localhost.
+///
+/// Verifies that:
+/// * The endpoint answers `/metrics` with the text exposition content type
+/// * Every family has `# HELP` and `# TYPE` lines with the right type
+/// * Per-core, per-mount and per-interface samples carry their labels
+/// * Unknown paths get a 404
+#[test]
+fn test_prometheus_scrape() {
+    let reading = Reading {
+        cores: vec![CoreUsage {
+            id: 0,
+            name: "cpu0".to_string(),
+            usage: 50.0,
+        }],
+        total_memory: 4096,
+        used_memory: 1024,
+        disks: Some(vec![DiskUsage {
+            name: "/dev/sda1".to_string(),
+            mount_point: "/".to_string(),
+            file_system: "ext4".to_string(),
+            total: 1000,
+            available: 250,
+        }]),
+        network_counters: Some(vec![(
+            "eth0".to_string(),
+            NetworkCounters {
+                rx_bytes: 100,
+                tx_bytes: 200,
+                rx_packets: 1,
+                tx_packets: 2,
+            },
+        )]),
+        ..Reading::default()
+    };
+    let app = App::with_source(ScriptedSource::new(vec![reading]), 10);
+    let collector = BackgroundCollector::spawn(app, Duration::from_secs(3600)).unwrap();
+    let server = MetricsServer::bind("127.0.0.1:0", collector).unwrap();
+    let addr = server.local_addr().unwrap();
+    thread::spawn(move || server.serve());
+
+    let response = http_get(addr, "/metrics");
+    let (head, body) = response.split_once("\r\n\r\n").unwrap();
+    assert!(head.starts_with("HTTP/1.1 200 OK"));
+    assert!(head.contains("Content-Type: text/plain; version=0.0.4"));
+
+    assert!(body.contains("# TYPE system_monitor_cpu_usage_ratio gauge"));
+    assert!(body.contains("system_monitor_cpu_usage_ratio 0.5\n"));
+    assert!(body.contains("system_monitor_cpu_core_usage_ratio{core=\"0\"} 0.5\n"));
+    assert!(body.contains("system_monitor_memory_used_bytes 1024\n"));
+    assert!(body.contains(
+        "system_monitor_disk_available_bytes{mount=\"/\",device=\"/dev/sda1\",fstype=\"ext4\"} 250\n"
+    ));
+    assert!(body.contains("# TYPE system_monitor_network_transmit_bytes_total counter"));
+    assert!(body.contains("system_monitor_network_transmit_bytes_total{interface=\"eth0\"}"));
+    for line in body.lines().filter(|l| !l.starts_with('#')) {
+        let name = line.split(['{', ' ']).next().unwrap();
+        assert!(
+            body.contains(&format!("# HELP {} ", name)),
+            "{} has no HELP line",
+            name
+        );
+        assert!(body.contains(&format!("# TYPE {} ", name)));
+    }
+
+    assert!(http_get(addr, "/other").starts_with("HTTP/1.1 404 Not Found"));
+}
+
+/// Tests streaming samples as newline-delimited JSON.
+///
+/// Verifies that:
+/// * Exactly the requested number of lines is written
+/// * Every line is a standalone JSON object
+/// * Lines carry the snapshot fields and the derived percentages
+#[test]
+fn test_ndjson_stream() {
+    let reading = |seconds: u64, used_memory: u64| Reading {
+        timestamp: std::time::UNIX_EPOCH + Duration::from_secs(seconds),
+        cores: vec![CoreUsage {
+            id: 0,
+            name: "cpu0".to_string(),
+            usage: 20.0,
+        }],
+        total_memory: 200,
+        used_memory,
+        ..Reading::default()
+    };
+    let source = ScriptedSource::new(vec![reading(0, 0), reading(1, 50), reading(2, 100)]);
+    let app = App::with_source(source, 10);
+    let collector = BackgroundCollector::spawn(app, Duration::from_millis(1)).unwrap();
+
+    let mut out = Vec::new();
+    assert_eq!(ndjson::stream(&collector, &mut out, Some(2)).unwrap(), 2);
+    let out = String::from_utf8(out).unwrap();
+    let lines: Vec<serde_json::Value> = out
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+
+    assert_eq!(lines.len(), 2);
+    assert_eq!(lines[0]["timestamp"], 1.0);
+    assert_eq!(lines[0]["memory_usage"], 25.0);
+    assert_eq!(lines[1]["memory_usage"], 50.0);
+    assert_eq!(lines[1]["cpu_usage"], 20.0);
+    assert_eq!(lines[1]["cores"][0]["usage"], 20.0);
+}
+
+/// Tests recording collected snapshots to a rotating CSV file.
+///
+/// Verifies that:
+/// * Every file starts with the same header, in stable column order
+/// * Rows carry ISO-8601 timestamps and the selected groups only
+/// * The file is rotated once the time limit is reached
+#[test]
+fn test_csv_recording() {
+    let dir = std::env::temp_dir().join(format!("system-monitor-it-csv-{}", std::process::id()));
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).unwrap();
+    let path = dir.join("metrics.csv");
+
+    let reading = |seconds: u64| Reading {
+        timestamp: std::time::UNIX_EPOCH + Duration::from_secs(seconds),
+        cores: vec![CoreUsage {
+            id: 0,
+            name: "cpu0".to_string(),
+            usage: 50.0,
+        }],
+        total_memory: 100,
+        used_memory: 40,
+        ..Reading::default()
+    };
+    let source = ScriptedSource::new((0..=4).map(|i| reading(i * 30)).collect());
+    let app = App::with_source(source, 10);
+    let collector = BackgroundCollector::spawn(app, Duration::from_millis(1)).unwrap();
+
+    let rotation = Rotation {
+        max_age: Some(Duration::from_secs(60)),
+        ..Rotation::default()
+    };
+    let groups = [MetricGroup::Memory, MetricGroup::Cpu];
+    let mut recorder = CsvRecorder::new(&path, &groups, rotation).unwrap();
+    for _ in 0..4 {
+        let snapshot = collector.recv_timeout(Duration::from_secs(5)).unwrap();
+        recorder.record(&snapshot).unwrap();
+    }
+
+    let header = "timestamp,cpu_usage,memory_total_bytes,memory_used_bytes,memory_usage";
+    let rotated = std::fs::read_to_string(recorder.rotated_path(1)).unwrap();
+    let current = std::fs::read_to_string(&path).unwrap();
+    assert_eq!(
+        rotated,
+        format!(
+            "{}\n1970-01-01T00:00:30.000Z,50.00,100,40,40.00\n\
+             1970-01-01T00:01:00.000Z,50.00,100,40,40.00\n",
+            header
+        )
+    );
+    assert_eq!(current.lines().next(), Some(header));
+    assert_eq!(current.lines().count(), 3);
+
+    std::fs::remove_dir_all(&dir).unwrap();
+}
+
+/// Tests recording a session and playing it back.
+///
+/// Verifies that:
+/// * Collected snapshots written by the session writer load back unchanged
+/// * The player shows the recorded values, not freshly collected ones
+/// * Playback follows the recorded pace scaled by the playback speed
+#[test]
+fn test_session_record_and_replay() {
+    let path = std::env::temp_dir().join(format!(
+        "system-monitor-it-session-{}.ndjson",
+        std::process::id()
+    ));
+    let reading = |seconds: u64, usage: f32| Reading {
+        timestamp: std::time::UNIX_EPOCH + Duration::from_secs(seconds),
+        cores: vec![CoreUsage {
+            id: 0,
+            name: "cpu0".to_string(),
+            usage,
+        }],
+        total_memory: 100,
+        used_memory: 40,
+        ..Reading::default()
+    };
+    // The first reading is taken when the app is created
+    let source = ScriptedSource::new(vec![
+        reading(0, 0.0),
+        reading(10, 10.0),
+        reading(12, 20.0),
+        reading(14, 30.0),
+    ]);
+    let collector =
+        BackgroundCollector::spawn(App::with_source(source, 10), Duration::from_millis(1)).unwrap();
+
+    let mut writer = SessionWriter::create(&path).unwrap();
+    let mut recorded = Vec::new();
+    for _ in 0..3 {
+        let snapshot = collector.recv_timeout(Duration::from_secs(5)).unwrap();
+        writer.record(&snapshot).unwrap();
+        recorded.push(snapshot);
+    }
+    drop(writer);
+
+    let session = Session::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(session.snapshots(), &recorded[..]);
+
+    let mut player = Player::new(session, 10);
+    assert_eq!(player.app().get_cpu_usage(), Ok(10.0));
+    player.faster();
+    assert_eq!(player.advance(Duration::from_millis(1100)), 1);
+    assert_eq!(player.app().get_cpu_usage(), Ok(20.0));
+    assert_eq!(player.app().cpu_stats().max(), Some(20.0));
+}
+
+/// Tests evaluating alert rules against a recorded session.
+///
+/// Verifies that:
+/// * A rule with a duration is pending before it fires
+/// * A value just below the threshold keeps the alert firing (hysteresis)
+/// * The alert resolves once the value drops past the hysteresis margin
+/// * Events carry the timestamps of the recorded snapshots
+#[test]
+fn test_alerts_on_recorded_session() {
+    let path = std::env::temp_dir().join(format!(
+        "system-monitor-it-alerts-{}.ndjson",
+        std::process::id()
+    ));
+    let at = |seconds| std::time::UNIX_EPOCH + Duration::from_secs(seconds);
+    let mut app = App::with_source(ScriptedSource::default(), 10);
+    let mut writer = SessionWriter::create(&path).unwrap();
+    for (seconds, usage) in [
+        (0, 50.0),
+        (10, 95.0),
+        (20, 96.0),
+        (40, 97.0),
+        (50, 88.0),
+        (60, 80.0),
+    ] {
+        let snapshot = app.ingest(Reading {
+            timestamp: at(seconds),
+            cores: vec![CoreUsage {
+                id: 0,
+                name: "cpu0".to_string(),
+                usage,
+            }],
+            total_memory: 100,
+            used_memory: 40,
+            ..Reading::default()
+        });
+        writer.record(&snapshot).unwrap();
+    }
+    drop(writer);
+
+    let session = Session::load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    let rule: AlertRule = "cpu > 90% for 20s".parse().unwrap();
+    let mut engine = AlertEngine::new(vec![rule.named("cpu saturated")]);
+    let events: Vec<_> = engine
+        .evaluate_all(session.snapshots())
+        .into_iter()
+        .map(|event| (event.transition, event.timestamp, event.rule.name))
+        .collect();
+    let name = || "cpu saturated".to_string();
+    assert_eq!(
+        events,
+        vec![
+            (Transition::Pending, at(10), name()),
+            (Transition::Firing, at(40), name()),
+            (Transition::Resolved, at(60), name()),
+        ]
+    );
+    assert_eq!(engine.firing().count(), 0);
+}
+
+/// Tests notifying alert state changes to a webhook stand-in over
+/// localhost.
+///
+/// Verifies that:
+/// * A firing and a resolved alert are each posted once, in order
+/// * A rejected delivery is retried until the webhook accepts it
+/// * Pending alerts are not posted
+/// * The payload carries the alert name, state and value
+#[test]
+fn test_alert_webhook_delivery() {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    let url = format!("http://{}/alerts", listener.local_addr().unwrap());
+    let server = thread::spawn(move || {
+        let mut bodies = Vec::new();
+        for status in ["503 Service Unavailable", "200 OK", "200 OK"] {
+            let (mut stream, _) = listener.accept().unwrap();
+            let mut reader = BufReader::new(stream.try_clone().unwrap());
+            let mut length = 0;
+            loop {
+                let mut line = String::new();
+                reader.read_line(&mut line).unwrap();
+                if let Some(value) = line.strip_prefix("Content-Length: ") {
+                    length = value.trim().parse().unwrap();
+                }
+                if line == "\r\n" {
+                    break;
+                }
+            }
+            let mut body = vec![0; length];
+            reader.read_exact(&mut body).unwrap();
+            write!(stream, "HTTP/1.1 {}\r\nContent-Length: 0\r\n\r\n", status).unwrap();
+            if status == "200 OK" {
+                bodies.push(String::from_utf8(body).unwrap());
+            }
+        }
+        bodies
+    });
+
+    let action = AlertAction::new(ActionKind::Webhook(url.parse().unwrap()))
+        .with_retry_delay(Duration::from_millis(1));
+    let notifier = BackgroundNotifier::spawn(Notifier::new(vec![action]), |_| {}).unwrap();
+    let mut app = App::with_source(ScriptedSource::default(), 10);
+    app.set_alert_rules(vec!["memory > 90% for 10s".parse().unwrap()]);
+    app.set_notifier(notifier);
+    for (seconds, used_memory) in [(0, 95), (10, 96), (20, 50)] {
+        app.ingest(Reading {
+            timestamp: std::time::UNIX_EPOCH + Duration::from_secs(seconds),
+            total_memory: 100,
+            used_memory,
+            ..Reading::default()
+        });
+    }
+    assert!(app.take_notifier().unwrap().finish().is_empty());
+    assert!(app.delivery_failures().is_empty());
+
+    let bodies = server.join().unwrap();
+    assert_eq!(bodies.len(), 2);
+    assert!(bodies[0].contains("\"alert\":\"memory > 90% for 10s\""));
+    assert!(bodies[0].contains("\"state\":\"firing\""));
+    assert!(bodies[0].contains("\"value\":96.0"));
+    assert!(bodies[1].contains("\"state\":\"resolved\""));
+}
+
+/// Tests reading container limits from a cgroup v2 fixture directory.
+///
+/// Verifies that:
+/// * The cgroup is found under a custom sysfs root
+/// * Snapshots carry the cgroup's memory usage and limits
+/// * CPU usage is derived once the CPU time counter has advanced
+/// * The container object is part of the JSON output
+#[test]
+fn test_container_limits_from_cgroup_fixture() {
+    let root =
+        std::env::temp_dir().join(format!("system-monitor-it-cgroup-{}", std::process::id()));
+    let dir = root.join("ci/job");
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(root.join("cgroup.controllers"), "cpu memory\n").unwrap();
+    std::fs::write(dir.join("memory.current"), "268435456\n").unwrap();
+    std::fs::write(dir.join("memory.max"), "1073741824\n").unwrap();
+    std::fs::write(dir.join("cpu.max"), "100000 100000\n").unwrap();
+    std::fs::write(dir.join("cpu.stat"), "usage_usec 1000\n").unwrap();
+
+    let cgroup = Cgroup::detect_in(&root, "0::/ci/job\n").expect("Fixture is a cgroup v2 root");
+    let plan = RefreshPlan::none().with_interval(Collector::Memory, Duration::ZERO);
+    let mut app = App::with_source(SysinfoSource::with_plan(plan).with_cgroup(Some(cgroup)), 10);
+
+    thread::sleep(Duration::from_millis(20));
+    std::fs::write(dir.join("cpu.stat"), "usage_usec 6000\n").unwrap();
+    let snapshot = app.update();
+    std::fs::remove_dir_all(&root).unwrap();
+
+    let container = snapshot.container.as_ref().expect("Cgroup should be read");
+    assert_eq!(container.cgroup, "/ci/job");
+    assert_eq!(container.memory_usage(), Some(25.0));
+    assert_eq!(container.cpu_limit(), Some(1.0));
+    assert!(container.cpu_usage.is_some_and(|usage| usage > 0.0));
+
+    let line: serde_json::Value = serde_json::from_str(&ndjson::encode(&snapshot)).unwrap();
+    assert_eq!(line["container"]["memory_limit"], 1_073_741_824u64);
+}
+
+/// Proc root holding the fixture files under `tests/fixtures`.
+fn fixture_proc_root() -> std::path::PathBuf {
+    std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/proc")
+}
+
+/// Tests pressure stall information read from fixture files.
+///
+/// Verifies that:
+/// * Pressure files are read under a custom proc root
+/// * Snapshots carry the averages and totals of every resource
+/// * Pressure alert rules fire on the `some` 10-second average
+#[test]
+fn test_pressure_from_proc_fixture() {
+    let plan = RefreshPlan::none().with_interval(Collector::Cpu, Duration::ZERO);
+    let source = SysinfoSource::with_plan(plan)
+        .with_cgroup(None)
+        .with_proc_root(fixture_proc_root());
+    let mut app = App::with_source(source, 10);
+    app.set_alert_rules(vec![
+        "io_pressure > 20%".parse().unwrap(),
+        "memory_pressure > 20%".parse().unwrap(),
+    ]);
+
+    let snapshot = app.update();
+    let pressure = snapshot.pressure.expect("Fixtures report pressure");
+    assert_eq!(pressure.cpu.unwrap().some.total_usec, 181_637_552);
+    assert_eq!(pressure.memory.unwrap().full.unwrap().avg10, 1.05);
+
+    let firing: Vec<&str> = app
+        .alerts()
+        .firing()
+        .map(|alert| alert.rule.name.as_str())
+        .collect();
+    assert_eq!(firing, vec!["io_pressure > 20%"]);
+}
+
+/// Tests the CPU time breakdown computed from `/proc/stat` counters.
+///
+/// Verifies that:
+/// * The fixture's counters are read under a custom proc root
+/// * Shares are computed from the deltas, overall and per core
+/// * Steal time reaches the JSON and Prometheus outputs
+#[test]
+fn test_cpu_time_breakdown_from_proc_fixture() {
+    let first = ProcStat::read(&fixture_proc_root()).expect("Fixture reports CPU times");
+    let later = parse_stat(
+        "cpu  2300 20 800 15900 200 10 70 400 50 0\n\
+         cpu0 1050 20 300 8000 50 5 25 150 0 0\n\
+         cpu1 1250 0 500 7900 150 5 45 250 50 0\n",
+    )
+    .unwrap();
+    let reading = |cpu_times| Reading {
+        cpu_times: Some(cpu_times),
+        ..Reading::default()
+    };
+    let mut app = App::with_source(ScriptedSource::new(vec![reading(first)]), 10);
+
+    let snapshot = app.ingest(reading(later));
+    let cpu_time = snapshot.cpu_time.clone().expect("Two readings give shares");
+    assert_eq!(cpu_time.total.steal, 25.0);
+    assert_eq!(cpu_time.total.busy(), 75.0);
+    assert_eq!(cpu_time.cores[0].shares.steal, 50.0);
+
+    let line: serde_json::Value = serde_json::from_str(&ndjson::encode(&snapshot)).unwrap();
+    assert_eq!(line["cpu_time"]["total"]["steal"], 25.0);
+    assert_eq!(line["cpu_time"]["cores"][1]["id"], 1);
+    assert!(system_monitor::prometheus::encode(&snapshot)
+        .contains("system_monitor_cpu_mode_ratio{mode=\"steal\"} 0.25\n"));
+}
+
+/// Tests the memory breakdown read from the `/proc/meminfo` fixture.
+///
+/// Verifies that:
+/// * `meminfo` is read under a custom proc root with the memory collector
+/// * The headline memory usage is based on `MemAvailable`
+/// * The breakdown reaches the JSON and Prometheus outputs
+#[test]
+fn test_memory_breakdown_from_proc_fixture() {
+    let plan = RefreshPlan::none().with_interval(Collector::Memory, Duration::ZERO);
+    let source = SysinfoSource::with_plan(plan)
+        .with_cgroup(None)
+        .with_proc_root(fixture_proc_root());
+    let mut app = App::with_source(source, 10);
+    app.set_alert_rules(vec!["memory > 50%".parse().unwrap()]);
+
+    let snapshot = app.update();
+    let meminfo: MemInfo = snapshot.meminfo.expect("Fixture reports meminfo");
+    assert_eq!(snapshot.total_memory, 16_318_480 * 1024);
+    assert_eq!(snapshot.used_memory, meminfo.used());
+    assert_eq!(snapshot.memory_usage(), Ok(40.0));
+    assert_eq!(
+        app.alerts().firing().count(),
+        0,
+        "Caches do not count as used"
+    );
+
+    let line: serde_json::Value = serde_json::from_str(&ndjson::encode(&snapshot)).unwrap();
+    assert_eq!(line["memory_usage"], 40.0);
+    assert_eq!(line["meminfo"]["hugepages"]["total"], 16);
+    assert!(system_monitor::prometheus::encode(&snapshot)
+        .contains("system_monitor_memory_dirty_bytes 2097152\n"));
+}